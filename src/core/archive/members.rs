@@ -0,0 +1,271 @@
+//! Members of zip and tar files inside an item
+//!
+//! Archive.org's data servers list the contents of a zip or tar file through
+//! `view_archive.php` and serve a single member out of it, so one file can be
+//! taken from a multi-gigabyte archive without fetching the rest. Selected
+//! members are written where extracting the whole archive after download
+//! would put them (see [`member_path`]).
+
+use crate::{
+    IaGetError, Result,
+    core::download::{StreamOutcome, candidate_servers, mirror_path, stream_from_urls},
+    core::session::{ArchiveFile, ArchiveMetadata, wildcard_match},
+    infrastructure::http::http_status_error,
+    utilities::compression::CompressionFormat,
+};
+use reqwest::Client;
+use std::path::{Component, Path, PathBuf};
+
+/// One file inside a remote archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMember {
+    /// Path inside the archive, `/`-separated
+    pub name: String,
+    pub size: Option<u64>,
+    /// Modification time as listed by the server (`YYYY-MM-DD HH:MM`)
+    pub modified: Option<String>,
+}
+
+/// Which members to download
+///
+/// Patterns use `*` and `?` and match the full member path; formats are
+/// compared with the member's extension, as for item files.
+#[derive(Debug, Clone, Default)]
+pub struct MemberFilter {
+    pub patterns: Vec<String>,
+    pub include_formats: Vec<String>,
+    pub exclude_formats: Vec<String>,
+    pub max_size: Option<u64>,
+}
+
+impl MemberFilter {
+    pub fn matches(&self, member: &ArchiveMember) -> bool {
+        let extension = Path::new(&member.name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("");
+
+        (self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| wildcard_match(pattern, &member.name)))
+            && (self.include_formats.is_empty()
+                || self
+                    .include_formats
+                    .iter()
+                    .any(|fmt| fmt.eq_ignore_ascii_case(extension)))
+            && !self
+                .exclude_formats
+                .iter()
+                .any(|fmt| fmt.eq_ignore_ascii_case(extension))
+            && match (self.max_size, member.size) {
+                (Some(max), Some(size)) => size <= max,
+                _ => true,
+            }
+    }
+}
+
+/// Whether the server can list and serve members of the file `name`
+pub fn is_browsable_archive(name: &str) -> bool {
+    matches!(
+        CompressionFormat::from_filename(name),
+        Some(
+            CompressionFormat::Zip
+                | CompressionFormat::Tar
+                | CompressionFormat::TarGz
+                | CompressionFormat::TarBz2
+                | CompressionFormat::TarXz
+        )
+    )
+}
+
+/// `view_archive.php` URL listing `archive`, or serving `member` from it
+pub fn view_archive_url(server: &str, dir: &str, archive: &str, member: Option<&str>) -> String {
+    let mut url = format!(
+        "https://{}/view_archive.php?archive={}",
+        server,
+        urlencoding::encode(&format!("{}/{}", dir, archive))
+    );
+    if let Some(member) = member {
+        url.push_str("&file=");
+        url.push_str(&urlencoding::encode(member));
+    }
+    url
+}
+
+/// Members in a `view_archive.php` listing page, directories left out
+///
+/// Each row links to the member with a `file=` query parameter and carries
+/// cells with `id="timestamp"` and `id="size"`.
+pub fn parse_member_listing(html: &str) -> Vec<ArchiveMember> {
+    html.split("<tr")
+        .skip(1)
+        .filter_map(|row| {
+            let name = row
+                .split("href=\"")
+                .skip(1)
+                .filter_map(|rest| rest.split('"').next())
+                .find_map(|href| {
+                    let href = unescape_html(href);
+                    let (_, query) = href.split_once('?')?;
+                    let value = query
+                        .split('&')
+                        .find_map(|param| param.strip_prefix("file="))?;
+                    urlencoding::decode(value)
+                        .ok()
+                        .map(|name| name.into_owned())
+                })?;
+            if name.is_empty() || name.ends_with('/') {
+                return None;
+            }
+            Some(ArchiveMember {
+                name,
+                size: cell(row, "size").and_then(|size| size.parse().ok()),
+                modified: cell(row, "timestamp").filter(|time| !time.is_empty()),
+            })
+        })
+        .collect()
+}
+
+fn cell(row: &str, id: &str) -> Option<String> {
+    let start = row.find(&format!("id=\"{}\"", id))?;
+    let content = &row[start..];
+    let content = &content[content.find('>')? + 1..];
+    let end = content.find('<').unwrap_or(content.len());
+    Some(unescape_html(content[..end].trim()))
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Where `member` of `archive_name` lands when the archive is extracted in `output_dir`
+///
+/// Matches automatic decompression: a zip or tar becomes a directory named
+/// after it next to where the archive itself would be saved. Members whose
+/// path would escape that directory get `None`.
+pub fn member_path(output_dir: &Path, archive_name: &str, member: &str) -> Option<PathBuf> {
+    let format = CompressionFormat::from_filename(archive_name)?;
+    let archive_path = mirror_path(output_dir, archive_name);
+    let archive_file = archive_path.file_name()?.to_str()?;
+    let mut path = output_dir.join(format.get_decompressed_name(archive_file));
+
+    let mut has_name = false;
+    for component in Path::new(member).components() {
+        match component {
+            Component::Normal(part) => {
+                path.push(part);
+                has_name = true;
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    has_name.then_some(path)
+}
+
+/// List the members of `archive`, asking each server that holds the item in turn
+pub async fn list_members(
+    client: &Client,
+    metadata: &ArchiveMetadata,
+    archive: &ArchiveFile,
+) -> Result<Vec<ArchiveMember>> {
+    if !is_browsable_archive(&archive.name) {
+        return Err(IaGetError::Parse(format!(
+            "{} is not a zip or tar file",
+            archive.name
+        )));
+    }
+
+    let mut last_error = None;
+    for server in candidate_servers(metadata) {
+        let url = view_archive_url(&server, &metadata.dir, &archive.name, None);
+        let result = match client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => response
+                .text()
+                .await
+                .map(|html| parse_member_listing(&html))
+                .map_err(IaGetError::from),
+            Ok(response) => Err(http_status_error(response.status(), &url)),
+            Err(e) => Err(IaGetError::from(e)),
+        };
+        match result {
+            Ok(members) => return Ok(members),
+            Err(e) if e.is_permanent() => return Err(e),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        IaGetError::Network(format!("No servers available for {}", archive.name))
+    }))
+}
+
+/// Download one member of `archive` into its extracted location under `output_dir`
+///
+/// The member is written to a `.tmp` file that replaces the target once the
+/// last byte has arrived. Returns the path of the written file.
+pub async fn download_member(
+    client: &Client,
+    metadata: &ArchiveMetadata,
+    archive: &ArchiveFile,
+    member: &ArchiveMember,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    let path = member_path(output_dir, &archive.name, &member.name).ok_or_else(|| {
+        IaGetError::FileSystem(format!(
+            "Refusing to write {} outside the output directory",
+            member.name
+        ))
+    })?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let urls: Vec<String> = candidate_servers(metadata)
+        .iter()
+        .map(|server| view_archive_url(server, &metadata.dir, &archive.name, Some(&member.name)))
+        .collect();
+    // The listing publishes no checksums, so only the size is checked
+    let file_info = ArchiveFile {
+        name: member.name.clone(),
+        source: archive.source.clone(),
+        format: None,
+        mtime: None,
+        size: member.size,
+        md5: None,
+        crc32: None,
+        sha1: None,
+        btih: None,
+        summation: None,
+        original: None,
+        rotation: None,
+    };
+
+    let temp_path = path.with_extension("tmp");
+    let mut file = tokio::fs::File::create(&temp_path).await?;
+    let result = stream_from_urls(client, &urls, &file_info, false, &mut file).await;
+    drop(file);
+    match result {
+        Ok(StreamOutcome::Complete(_)) => {
+            tokio::fs::rename(&temp_path, &path).await?;
+            Ok(path)
+        }
+        Ok(StreamOutcome::ReaderClosed { .. }) => {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            Err(IaGetError::FileSystem(format!(
+                "Output closed while writing {}",
+                path.display()
+            )))
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            Err(e)
+        }
+    }
+}
@@ -40,8 +40,13 @@
 //! - **Error Context**: Detailed error messages with context
 
 use crate::{
-    Result, core::session::ArchiveMetadata, error::IaGetError,
-    infrastructure::http::is_transient_error,
+    Result,
+    core::session::ArchiveMetadata,
+    error::IaGetError,
+    infrastructure::http::{
+        RetryPolicy, http_status_error, is_retryable_status, is_transient_reqwest_error,
+        retry_policy,
+    },
 };
 use colored::*;
 use indicatif::ProgressBar;
@@ -114,6 +119,17 @@ pub async fn fetch_json_metadata(
     client: &Client,
     progress: &ProgressBar,
     cache_dir: Option<&Path>,
+) -> Result<(ArchiveMetadata, reqwest::Url)> {
+    fetch_json_metadata_with_policy(details_url, client, progress, cache_dir, retry_policy()).await
+}
+
+/// [`fetch_json_metadata`] retrying under `policy` instead of the process-wide policy
+pub async fn fetch_json_metadata_with_policy(
+    details_url: &str,
+    client: &Client,
+    progress: &ProgressBar,
+    cache_dir: Option<&Path>,
+    policy: RetryPolicy,
 ) -> Result<(ArchiveMetadata, reqwest::Url)> {
     // Generate JSON metadata URL
     let json_url = get_json_url(details_url);
@@ -184,58 +200,48 @@ pub async fn fetch_json_metadata(
     let base_url = reqwest::Url::parse(&json_url)
         .map_err(|e| IaGetError::Network(format!("URL parse failed: {}", e)))?;
     let mut retries = 0;
-    let max_retries = 3;
-    let mut delay = std::time::Duration::from_secs(30); // Conservative initial delay for metadata
-    let max_delay = std::time::Duration::from_secs(600); // 10 minutes max
 
     let json_content = loop {
-        let result = client
-            .get(&json_url)
-            .send()
-            .await
-            .map_err(|e| IaGetError::Network(format!("GET request failed: {}", e)));
+        let result = client.get(&json_url).send().await;
         match result {
             Ok(response) => {
-                // Check for HTTP 429 and Retry-After header
-                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                    let wait_time = response
-                        .headers()
-                        .get(reqwest::header::RETRY_AFTER)
-                        .and_then(|h| h.to_str().ok())
-                        .and_then(|s| s.parse::<u64>().ok())
-                        .unwrap_or(60);
-
-                    let wait_reason = format!(
-                        "Rate limited during JSON fetch (HTTP 429) - waiting {}s as requested",
-                        wait_time
-                    );
+                let status = response.status();
+                if is_retryable_status(status) && policy.can_retry(retries) {
+                    let delay = policy.delay_for_response(retries, status, response.headers());
+                    retries += 1;
+                    let wait_reason = if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        format!(
+                            "Rate limited during JSON fetch (HTTP 429) - waiting {}s",
+                            delay.as_secs()
+                        )
+                    } else {
+                        format!(
+                            "HTTP {} (attempt {}/{}) - retrying in {}s",
+                            status.as_u16(),
+                            retries,
+                            policy.max_attempts(),
+                            delay.as_secs()
+                        )
+                    };
                     progress.set_message(format!("{} {}", "⏳".yellow(), wait_reason));
-
-                    tokio::time::sleep(std::time::Duration::from_secs(wait_time)).await;
+                    tokio::time::sleep(delay).await;
                     continue;
                 }
 
-                if !response.status().is_success() {
-                    let status = response.status();
-                    let body_text = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Unable to read response body".to_string());
-                    return Err(IaGetError::Network(format!(
-                        "HTTP {}: {}",
-                        status, body_text
-                    )));
+                if !status.is_success() {
+                    return Err(http_status_error(status, &json_url));
                 }
 
                 match response.text().await {
                     Ok(text) => break text,
                     Err(e) => {
-                        if is_transient_error(&e) && retries < max_retries {
+                        if is_transient_reqwest_error(&e) && policy.can_retry(retries) {
+                            let delay = policy.backoff(retries);
                             retries += 1;
                             let wait_reason = format!(
                                 "Response read failed (attempt {}/{}): {}",
                                 retries,
-                                max_retries + 1,
+                                policy.max_attempts(),
                                 e
                             );
                             progress.set_message(format!(
@@ -245,7 +251,6 @@ pub async fn fetch_json_metadata(
                                 delay.as_secs()
                             ));
                             tokio::time::sleep(delay).await;
-                            delay = std::cmp::min(delay * 2, max_delay);
                             continue;
                         } else {
                             return Err(IaGetError::Network(format!(
@@ -257,20 +262,13 @@ pub async fn fetch_json_metadata(
                 }
             }
             Err(e) => {
-                // For IaGetError, check if it's a network error that might be transient
-                let is_transient = match &e {
-                    IaGetError::Network(msg) => {
-                        msg.contains("timeout") || msg.contains("connection")
-                    }
-                    _ => false,
-                };
-
-                if is_transient && retries < max_retries {
+                if is_transient_reqwest_error(&e) && policy.can_retry(retries) {
+                    let delay = policy.backoff(retries);
                     retries += 1;
                     let wait_reason = format!(
                         "Request failed (attempt {}/{}): {}",
                         retries,
-                        max_retries + 1,
+                        policy.max_attempts(),
                         e
                     );
                     progress.set_message(format!(
@@ -280,15 +278,22 @@ pub async fn fetch_json_metadata(
                         delay.as_secs()
                     ));
                     tokio::time::sleep(delay).await;
-                    delay = std::cmp::min(delay * 2, max_delay);
                     continue;
                 } else {
-                    return Err(e);
+                    return Err(IaGetError::Network(format!("GET request failed: {}", e)));
                 }
             }
         }
     };
 
+    // The metadata API answers an empty object for identifiers that do not exist
+    if json_content.trim() == "{}" {
+        return Err(IaGetError::NotFound(format!(
+            "No archive item at {}",
+            json_url
+        )));
+    }
+
     // Parse the JSON response
     // Cache the response if cache_dir is provided
     if let Some(dir) = cache_dir {
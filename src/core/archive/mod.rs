@@ -3,9 +3,13 @@
 //! Contains functionality for working with Internet Archive metadata and archive operations.
 
 pub use archive_metadata::*;
+pub use members::*;
 pub use metadata::*;
 pub use metadata_new::*;
+pub use torrent::*;
 
 pub mod archive_metadata;
+pub mod members;
 pub mod metadata;
 pub mod metadata_new;
+pub mod torrent;
@@ -0,0 +1,440 @@
+//! BitTorrent piece verification for archive items
+//!
+//! Every item ships an `<identifier>_archive.torrent` whose SHA1 piece hashes
+//! cover all of the item's files laid end to end. Checking local files against
+//! those hashes pinpoints corruption to piece-sized byte ranges, which can then
+//! be re-fetched with HTTP range requests instead of downloading whole files.
+//! Once the torrent is on disk the check needs no network access.
+
+use crate::{
+    IaGetError, Result, core::download::mirror_path, infrastructure::api::endpoints,
+    utilities::common::Bencode,
+};
+use indicatif::ProgressBar;
+use reqwest::Client;
+use sha1::{Digest, Sha1};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Largest piece length accepted; pieces are buffered whole while hashing
+pub const MAX_PIECE_LENGTH: u64 = 64 * 1024 * 1024;
+
+/// A file covered by the torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentFile {
+    /// Path relative to the item root, `/`-separated (matches `ArchiveFile::name`)
+    pub path: String,
+    pub length: u64,
+    /// Offset of the file's first byte in the concatenated torrent data
+    pub offset: u64,
+    /// BEP 47 padding file (all zeros, never stored locally)
+    pub padding: bool,
+}
+
+/// The parts of a `.torrent` needed to verify pieces
+#[derive(Debug, Clone)]
+pub struct TorrentInfo {
+    pub name: String,
+    pub piece_length: u64,
+    pub pieces: Vec<[u8; 20]>,
+    pub files: Vec<TorrentFile>,
+    /// Hex SHA1 of the bencoded info dictionary (`btih` in item metadata)
+    pub info_hash: String,
+}
+
+/// A contiguous corrupt byte range inside one local file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptRange {
+    pub file: String,
+    /// First corrupt byte
+    pub start: u64,
+    /// One past the last corrupt byte
+    pub end: u64,
+    pub first_piece: usize,
+    pub last_piece: usize,
+}
+
+/// Result of checking local files against the torrent's piece hashes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PieceReport {
+    pub pieces_total: usize,
+    pub pieces_ok: usize,
+    /// Pieces whose data is present but hashes wrong
+    pub corrupt_pieces: Vec<usize>,
+    /// Pieces that overlap a missing file and could not be checked
+    pub unverifiable_pieces: usize,
+    /// Byte ranges covered by corrupt pieces, merged per file
+    pub corrupt_ranges: Vec<CorruptRange>,
+    /// Torrent files not present locally
+    pub missing_files: Vec<String>,
+}
+
+impl PieceReport {
+    /// Whether every checkable piece matched
+    pub fn is_clean(&self) -> bool {
+        self.corrupt_pieces.is_empty()
+    }
+}
+
+/// Name of the torrent Archive.org generates for an item
+pub fn torrent_file_name(identifier: &str) -> String {
+    format!("{}_archive.torrent", identifier)
+}
+
+impl TorrentInfo {
+    /// Parse a `.torrent` file
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let invalid = |what: &str| IaGetError::Parse(format!("Invalid torrent: {}", what));
+
+        let root = Bencode::decode(data)?;
+        let info = root.get("info").ok_or_else(|| invalid("missing info"))?;
+        let info_hash = format!("{:x}", Sha1::digest(info.encode()));
+
+        let name = info
+            .get("name")
+            .and_then(Bencode::as_str)
+            .ok_or_else(|| invalid("missing name"))?;
+        let piece_length = info
+            .get("piece length")
+            .and_then(Bencode::as_int)
+            .filter(|len| *len > 0)
+            .ok_or_else(|| invalid("missing piece length"))? as u64;
+        if piece_length > MAX_PIECE_LENGTH {
+            return Err(invalid(&format!(
+                "piece length {} exceeds the {} MiB limit",
+                piece_length,
+                MAX_PIECE_LENGTH / (1024 * 1024)
+            )));
+        }
+        let pieces = info
+            .get("pieces")
+            .and_then(Bencode::as_bytes)
+            .filter(|p| p.len() % 20 == 0)
+            .ok_or_else(|| invalid("malformed pieces"))?
+            .chunks_exact(20)
+            .map(|chunk| chunk.try_into().expect("chunks are 20 bytes"))
+            .collect::<Vec<[u8; 20]>>();
+
+        let mut files = Vec::new();
+        let mut offset = 0u64;
+        match info.get("files").and_then(Bencode::as_list) {
+            Some(entries) => {
+                for entry in entries {
+                    let length = entry
+                        .get("length")
+                        .and_then(Bencode::as_int)
+                        .filter(|len| *len >= 0)
+                        .ok_or_else(|| invalid("file without length"))?
+                        as u64;
+                    let path = entry
+                        .get("path")
+                        .and_then(Bencode::as_list)
+                        .ok_or_else(|| invalid("file without path"))?
+                        .iter()
+                        .map(|part| part.as_str().ok_or_else(|| invalid("malformed path")))
+                        .collect::<Result<Vec<_>>>()?
+                        .join("/");
+                    let padding = entry
+                        .get("attr")
+                        .and_then(Bencode::as_bytes)
+                        .is_some_and(|attr| attr.contains(&b'p'));
+                    files.push(TorrentFile {
+                        path,
+                        length,
+                        offset,
+                        padding,
+                    });
+                    offset += length;
+                }
+            }
+            None => {
+                let length = info
+                    .get("length")
+                    .and_then(Bencode::as_int)
+                    .filter(|len| *len >= 0)
+                    .ok_or_else(|| invalid("missing length"))? as u64;
+                files.push(TorrentFile {
+                    path: name.clone(),
+                    length,
+                    offset: 0,
+                    padding: false,
+                });
+                offset = length;
+            }
+        }
+
+        if (pieces.len() as u64) != offset.div_ceil(piece_length) {
+            return Err(invalid("piece count does not match total length"));
+        }
+
+        Ok(Self {
+            name,
+            piece_length,
+            pieces,
+            files,
+            info_hash,
+        })
+    }
+
+    /// Total bytes covered by the torrent
+    pub fn total_length(&self) -> u64 {
+        self.files.last().map(|f| f.offset + f.length).unwrap_or(0)
+    }
+
+    /// Byte range `[start, end)` of a piece in the concatenated data
+    pub fn piece_bounds(&self, index: usize) -> (u64, u64) {
+        let start = index as u64 * self.piece_length;
+        (start, (start + self.piece_length).min(self.total_length()))
+    }
+
+    /// Parts of files making up a piece as (file index, offset in file, length)
+    pub fn piece_segments(&self, index: usize) -> Vec<(usize, u64, u64)> {
+        let (start, end) = self.piece_bounds(index);
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.length > 0 && f.offset < end && f.offset + f.length > start)
+            .map(|(i, f)| {
+                let from = start.max(f.offset);
+                let to = end.min(f.offset + f.length);
+                (i, from - f.offset, to - from)
+            })
+            .collect()
+    }
+}
+
+/// Check the files of an item downloaded to `dir` against the torrent's pieces
+///
+/// `progress` advances by the number of bytes checked.
+pub fn verify_pieces(
+    info: &TorrentInfo,
+    dir: &Path,
+    progress: &ProgressBar,
+) -> Result<PieceReport> {
+    let mut report = PieceReport {
+        pieces_total: info.pieces.len(),
+        ..Default::default()
+    };
+
+    let mut handles: Vec<Option<File>> = Vec::with_capacity(info.files.len());
+    for file in &info.files {
+        if file.padding {
+            handles.push(None);
+            continue;
+        }
+        match File::open(mirror_path(dir, &file.path)) {
+            Ok(handle) => handles.push(Some(handle)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                report.missing_files.push(file.path.clone());
+                handles.push(None);
+            }
+            Err(e) => {
+                return Err(IaGetError::FileSystem(format!(
+                    "Failed to open {}: {}",
+                    file.path, e
+                )));
+            }
+        }
+    }
+
+    let mut buffer = Vec::with_capacity(info.piece_length as usize);
+    for index in 0..info.pieces.len() {
+        let segments = info.piece_segments(index);
+        if segments
+            .iter()
+            .any(|(file, _, _)| !info.files[*file].padding && handles[*file].is_none())
+        {
+            report.unverifiable_pieces += 1;
+            continue;
+        }
+
+        let intact = read_piece(info, &mut handles, &segments, &mut buffer)
+            && Sha1::digest(&buffer).as_slice() == info.pieces[index];
+        if intact {
+            report.pieces_ok += 1;
+        } else {
+            report.corrupt_pieces.push(index);
+            for (file, offset, length) in segments {
+                if !info.files[file].padding {
+                    add_corrupt_range(
+                        &mut report.corrupt_ranges,
+                        &info.files[file].path,
+                        offset,
+                        offset + length,
+                        index,
+                    );
+                }
+            }
+        }
+        let (start, end) = info.piece_bounds(index);
+        progress.inc(end - start);
+    }
+
+    Ok(report)
+}
+
+/// Read a piece into `buffer`; false when a file is too short or unreadable
+fn read_piece(
+    info: &TorrentInfo,
+    handles: &mut [Option<File>],
+    segments: &[(usize, u64, u64)],
+    buffer: &mut Vec<u8>,
+) -> bool {
+    buffer.clear();
+    for &(file, offset, length) in segments {
+        let start = buffer.len();
+        buffer.resize(start + length as usize, 0);
+        if info.files[file].padding {
+            continue;
+        }
+        let Some(handle) = handles[file].as_mut() else {
+            return false;
+        };
+        if handle.seek(SeekFrom::Start(offset)).is_err()
+            || handle.read_exact(&mut buffer[start..]).is_err()
+        {
+            return false;
+        }
+    }
+    true
+}
+
+fn add_corrupt_range(
+    ranges: &mut Vec<CorruptRange>,
+    file: &str,
+    start: u64,
+    end: u64,
+    piece: usize,
+) {
+    if let Some(last) = ranges.last_mut() {
+        if last.file == file && last.end == start {
+            last.end = end;
+            last.last_piece = piece;
+            return;
+        }
+    }
+    ranges.push(CorruptRange {
+        file: file.to_string(),
+        start,
+        end,
+        first_piece: piece,
+        last_piece: piece,
+    });
+}
+
+/// Re-fetch corrupt pieces of `identifier` with HTTP range requests
+///
+/// Each piece is re-hashed after writing; the pieces that are still bad are
+/// returned.
+pub async fn repair_pieces(
+    client: &Client,
+    identifier: &str,
+    info: &TorrentInfo,
+    dir: &Path,
+    pieces: &[usize],
+) -> Result<Vec<usize>> {
+    let mut still_bad = Vec::new();
+
+    for &index in pieces {
+        let mut data = Vec::with_capacity(info.piece_length as usize);
+        let segments = info.piece_segments(index);
+        for &(file, offset, length) in &segments {
+            let torrent_file = &info.files[file];
+            if torrent_file.padding {
+                data.resize(data.len() + length as usize, 0);
+                continue;
+            }
+            let bytes = fetch_range(client, identifier, &torrent_file.path, offset, length).await?;
+            data.extend_from_slice(&bytes);
+        }
+
+        if Sha1::digest(&data).as_slice() != info.pieces[index] {
+            still_bad.push(index);
+            continue;
+        }
+
+        let mut written = 0usize;
+        for &(file, offset, length) in &segments {
+            let torrent_file = &info.files[file];
+            let chunk = &data[written..written + length as usize];
+            written += length as usize;
+            if !torrent_file.padding {
+                write_at(
+                    &mirror_path(dir, &torrent_file.path),
+                    offset,
+                    chunk,
+                    torrent_file.length,
+                )?;
+            }
+        }
+    }
+
+    Ok(still_bad)
+}
+
+/// Download `length` bytes of an item file starting at `offset`
+async fn fetch_range(
+    client: &Client,
+    identifier: &str,
+    path: &str,
+    offset: u64,
+    length: u64,
+) -> Result<Vec<u8>> {
+    let encoded_path = path
+        .split('/')
+        .map(|part| urlencoding::encode(part).into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    let url = format!("{}/{}", endpoints::download(identifier), encoded_path);
+
+    let response = client
+        .get(&url)
+        .header("Accept-Encoding", "identity")
+        .header("Range", format!("bytes={}-{}", offset, offset + length - 1))
+        .send()
+        .await
+        .map_err(|e| IaGetError::Network(format!("Failed to fetch {}: {}", path, e)))?;
+
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(IaGetError::Network(format!(
+            "Server did not honour byte range for {} (HTTP {})",
+            path,
+            response.status()
+        )));
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| IaGetError::Network(format!("Failed to read {}: {}", path, e)))?;
+    crate::infrastructure::http::throttle(bytes.len()).await;
+
+    if bytes.len() as u64 != length {
+        return Err(IaGetError::Network(format!(
+            "Short range response for {}: {} of {} bytes",
+            path,
+            bytes.len(),
+            length
+        )));
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Overwrite part of a local file, trimming it if it grew past `expected_len`
+fn write_at(path: &Path, offset: u64, data: &[u8], expected_len: u64) -> Result<()> {
+    let io_error = |e: std::io::Error| {
+        IaGetError::FileSystem(format!("Failed to repair {}: {}", path.display(), e))
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(io_error)?;
+    file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+    file.write_all(data).map_err(io_error)?;
+    if file.metadata().map_err(io_error)?.len() > expected_len {
+        file.set_len(expected_len).map_err(io_error)?;
+    }
+    file.sync_data().map_err(io_error)
+}
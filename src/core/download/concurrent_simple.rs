@@ -36,7 +36,7 @@
 use crate::{
     IaGetError, Result,
     core::session::{ArchiveFile, ArchiveMetadata, DownloadSession, DownloadState},
-    utilities::common::get_user_agent,
+    infrastructure::http::HttpClientFactory,
 };
 use reqwest::Client;
 use std::sync::Arc;
@@ -120,8 +120,7 @@ pub struct FileDownloadResult {
 impl SimpleConcurrentDownloader {
     /// Create a new enhanced concurrent downloader
     pub fn new(max_concurrent: usize) -> Result<Self> {
-        let client = Client::builder()
-            .user_agent(get_user_agent())
+        let client = HttpClientFactory::builder()?
             .timeout(std::time::Duration::from_secs(300))
             .build()
             .map_err(IaGetError::from)?;
@@ -331,6 +330,7 @@ impl SimpleConcurrentDownloader {
             let chunk = chunk.map_err(IaGetError::from)?;
             bytes_downloaded += chunk.len() as u64;
             std::io::Write::write_all(&mut file, &chunk).map_err(IaGetError::Io)?;
+            crate::infrastructure::http::throttle(chunk.len()).await;
         }
 
         Ok(bytes_downloaded)
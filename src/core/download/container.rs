@@ -0,0 +1,563 @@
+//! Tar and zip output containers
+//!
+//! Lets a download write every file straight into a single `.tar`, `.tar.gz`,
+//! `.tar.zst` or `.zip` archive instead of a directory tree. Each file is staged
+//! on disk only until it has been verified and appended, so disk usage stays
+//! close to the size of the container itself.
+//!
+//! Containers are resumable: opening an existing container lists the members it
+//! already holds so they can be skipped, and drops anything left half-written by
+//! an interrupted run.
+//!
+//! - Tar variants are written one member at a time. Compressed tars use one gzip
+//!   member or zstd frame per file, which standard tools decode as a single
+//!   stream, so every completed file stays readable even if the run is killed.
+//! - Zip files get their central directory on [`ContainerWriter::finish`]. A zip
+//!   without one (from a killed run) is rebuilt from its complete local entries.
+//!   Members are deflated unless they are already compressed.
+
+use crate::{IaGetError, Result};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of a tar block
+const TAR_BLOCK: u64 = 512;
+/// Longest member name that fits in a plain tar header
+const TAR_NAME_LEN: usize = 100;
+/// zstd compression level for `.tar.zst` members
+const ZSTD_LEVEL: i32 = 3;
+/// Bytes deflated to decide whether a zip member is worth compressing
+const DEFLATE_PROBE_LEN: u64 = 256 * 1024;
+/// Extensions of formats that are already compressed; zip stores them as-is
+const COMPRESSED_EXTENSIONS: &[&str] = &[
+    "7z", "aac", "avi", "bz2", "cbr", "cbz", "epub", "flac", "gif", "gz", "jp2", "jpeg", "jpg",
+    "m4a", "m4v", "mkv", "mov", "mp3", "mp4", "ogg", "ogv", "opus", "pdf", "png", "rar", "tgz",
+    "webm", "webp", "xz", "zip", "zst",
+];
+
+/// Container format, chosen from the output file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl ContainerFormat {
+    /// Detect the format from a file name such as `dataset.tar.zst`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::TarZst)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+enum Backend {
+    Tar(File),
+    Zip(Box<zip::ZipWriter<File>>),
+}
+
+/// Writer that appends downloaded files to a tar or zip container
+pub struct ContainerWriter {
+    path: PathBuf,
+    format: ContainerFormat,
+    members: HashSet<String>,
+    backend: Backend,
+}
+
+impl ContainerWriter {
+    /// Create a container, or reopen an existing one to append further members
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let format = ContainerFormat::from_path(&path).ok_or_else(|| {
+            IaGetError::Config(format!(
+                "Unsupported container '{}': use .tar, .tar.gz, .tar.zst or .zip",
+                path.display()
+            ))
+        })?;
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to create container directory: {}", e))
+            })?;
+        }
+
+        let existing_len = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let (members, backend) = match format {
+            ContainerFormat::Zip => open_zip(&path, existing_len)?,
+            _ => open_tar(&path, format, existing_len)?,
+        };
+
+        Ok(Self {
+            path,
+            format,
+            members,
+            backend,
+        })
+    }
+
+    /// Path of the container file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Format of the container
+    pub fn format(&self) -> ContainerFormat {
+        self.format
+    }
+
+    /// Whether a member with this name is already in the container
+    pub fn contains(&self, name: &str) -> bool {
+        self.members.contains(&member_name(name))
+    }
+
+    /// Names of all members in the container
+    pub fn members(&self) -> &HashSet<String> {
+        &self.members
+    }
+
+    /// Append the file at `source` as member `name`, stamped with `mtime` (Unix seconds)
+    pub fn add_file<P: AsRef<Path>>(
+        &mut self,
+        name: &str,
+        source: P,
+        mtime: Option<u64>,
+    ) -> Result<()> {
+        let name = member_name(name);
+        let io_error = |e: io::Error| {
+            IaGetError::FileSystem(format!("Failed to add {} to container: {}", name, e))
+        };
+
+        let mut input = File::open(source.as_ref()).map_err(io_error)?;
+        let size = input.metadata().map_err(io_error)?.len();
+        let mtime = mtime.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+        match &mut self.backend {
+            Backend::Tar(file) => {
+                match self.format {
+                    ContainerFormat::TarGz => {
+                        let mut encoder = flate2::write::GzEncoder::new(
+                            &mut *file,
+                            flate2::Compression::default(),
+                        );
+                        write_tar_member(&mut encoder, &name, size, mtime, &mut input)
+                            .map_err(io_error)?;
+                        encoder.finish().map_err(io_error)?;
+                    }
+                    ContainerFormat::TarZst => {
+                        let mut encoder =
+                            zstd::Encoder::new(&mut *file, ZSTD_LEVEL).map_err(io_error)?;
+                        write_tar_member(&mut encoder, &name, size, mtime, &mut input)
+                            .map_err(io_error)?;
+                        encoder.finish().map_err(io_error)?;
+                    }
+                    _ => write_tar_member(&mut *file, &name, size, mtime, &mut input)
+                        .map_err(io_error)?,
+                }
+                file.sync_data().map_err(io_error)?;
+            }
+            Backend::Zip(writer) => {
+                let method = zip_compression_method(&name, &mut input).map_err(io_error)?;
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(method)
+                    .last_modified_time(zip_datetime(mtime))
+                    .large_file(size >= u32::MAX as u64);
+                writer
+                    .start_file(name.as_str(), options)
+                    .map_err(|e| io_error(io::Error::other(e)))?;
+                io::copy(&mut input, writer).map_err(io_error)?;
+            }
+        }
+
+        self.members.insert(name);
+        Ok(())
+    }
+
+    /// Write the end-of-archive marker (tar) or central directory (zip)
+    pub fn finish(self) -> Result<()> {
+        let io_error =
+            |e: io::Error| IaGetError::FileSystem(format!("Failed to finish container: {}", e));
+
+        match self.backend {
+            Backend::Tar(mut file) => {
+                let terminator = [0u8; 2 * TAR_BLOCK as usize];
+                match self.format {
+                    ContainerFormat::TarGz => {
+                        let mut encoder = flate2::write::GzEncoder::new(
+                            &mut file,
+                            flate2::Compression::default(),
+                        );
+                        encoder.write_all(&terminator).map_err(io_error)?;
+                        encoder.finish().map_err(io_error)?;
+                    }
+                    ContainerFormat::TarZst => {
+                        let mut encoder =
+                            zstd::Encoder::new(&mut file, ZSTD_LEVEL).map_err(io_error)?;
+                        encoder.write_all(&terminator).map_err(io_error)?;
+                        encoder.finish().map_err(io_error)?;
+                    }
+                    _ => file.write_all(&terminator).map_err(io_error)?,
+                }
+                file.sync_all().map_err(io_error)?;
+            }
+            Backend::Zip(writer) => {
+                (*writer)
+                    .finish()
+                    .map_err(|e| io_error(io::Error::other(e)))?
+                    .sync_all()
+                    .map_err(io_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Normalize an archive file name into a relative member path
+fn member_name(name: &str) -> String {
+    name.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+fn round_up_to_block(len: u64) -> u64 {
+    len.div_ceil(TAR_BLOCK) * TAR_BLOCK
+}
+
+fn write_padding<W: Write>(out: &mut W, len: u64) -> io::Result<()> {
+    let padding = round_up_to_block(len) - len;
+    out.write_all(&vec![0u8; padding as usize])
+}
+
+/// Write one regular-file tar member (without the end-of-archive marker)
+fn write_tar_member<W: Write, R: Read>(
+    out: &mut W,
+    name: &str,
+    size: u64,
+    mtime: u64,
+    data: &mut R,
+) -> io::Result<()> {
+    let name_bytes = name.as_bytes();
+
+    // Names over 100 bytes are carried in a preceding GNU long-name entry
+    if name_bytes.len() > TAR_NAME_LEN {
+        let mut long_name = tar::Header::new_gnu();
+        long_name.as_old_mut().name[..13].copy_from_slice(b"././@LongLink");
+        long_name.set_entry_type(tar::EntryType::GNULongName);
+        long_name.set_mode(0o644);
+        long_name.set_size(name_bytes.len() as u64 + 1);
+        long_name.set_cksum();
+        out.write_all(long_name.as_bytes())?;
+        out.write_all(name_bytes)?;
+        out.write_all(&[0])?;
+        write_padding(out, name_bytes.len() as u64 + 1)?;
+    }
+
+    let mut header = tar::Header::new_gnu();
+    let stored = name_bytes.len().min(TAR_NAME_LEN);
+    header.as_old_mut().name[..stored].copy_from_slice(&name_bytes[..stored]);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_size(size);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    out.write_all(header.as_bytes())?;
+
+    let copied = io::copy(&mut data.take(size), out)?;
+    if copied != size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "{} shrank while being added ({} of {} bytes)",
+                name, copied, size
+            ),
+        ));
+    }
+    write_padding(out, size)
+}
+
+/// Open a tar container, returning its members and a file positioned for appending
+fn open_tar(
+    path: &Path,
+    format: ContainerFormat,
+    existing_len: u64,
+) -> Result<(HashSet<String>, Backend)> {
+    let io_error = |e: io::Error| {
+        IaGetError::FileSystem(format!(
+            "Failed to open container {}: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let (members, committed) = if existing_len == 0 {
+        (HashSet::new(), 0)
+    } else {
+        let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+        match format {
+            ContainerFormat::Tar => scan_plain_tar(&mut reader, existing_len),
+            _ => scan_tar_members(&mut reader, format),
+        }
+    };
+
+    // Drop the end marker and anything half-written so new members follow the last good one
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(io_error)?;
+    file.set_len(committed).map_err(io_error)?;
+    file.seek(SeekFrom::Start(committed)).map_err(io_error)?;
+
+    Ok((members, Backend::Tar(file)))
+}
+
+/// List members of an uncompressed tar, returning them with the end offset of the last one
+fn scan_plain_tar<R: Read>(reader: &mut R, file_len: u64) -> (HashSet<String>, u64) {
+    let mut members = HashSet::new();
+    let mut committed = 0;
+
+    let mut archive = tar::Archive::new(reader);
+    let Ok(entries) = archive.entries() else {
+        return (members, 0);
+    };
+    for entry in entries {
+        let Ok(entry) = entry else { break };
+        let end = entry.raw_file_position() + round_up_to_block(entry.size());
+        if end > file_len {
+            break;
+        }
+        if let Ok(path) = entry.path() {
+            members.insert(path.to_string_lossy().to_string());
+        }
+        committed = end;
+    }
+
+    (members, committed)
+}
+
+/// List members of a compressed tar written as one gzip member / zstd frame per file
+fn scan_tar_members<R: Read + Seek>(
+    reader: &mut BufReader<R>,
+    format: ContainerFormat,
+) -> (HashSet<String>, u64) {
+    let mut members = HashSet::new();
+    let mut committed = 0;
+
+    loop {
+        if reader.fill_buf().map_or(true, |buf| buf.is_empty()) {
+            break;
+        }
+
+        let names = match format {
+            ContainerFormat::TarGz => {
+                read_compressed_member(flate2::bufread::GzDecoder::new(&mut *reader))
+            }
+            _ => match zstd::stream::read::Decoder::with_buffer(&mut *reader) {
+                Ok(decoder) => read_compressed_member(decoder.single_frame()),
+                Err(_) => None,
+            },
+        };
+
+        // A member that fails to decode is the tail of an interrupted write
+        let (Some(names), Ok(position)) = (names, reader.stream_position()) else {
+            break;
+        };
+        if names.is_empty() {
+            // End-of-archive marker from a finished run; new members replace it
+            break;
+        }
+        members.extend(names);
+        committed = position;
+    }
+
+    (members, committed)
+}
+
+/// Decode one compressed member, returning the tar member names it holds
+fn read_compressed_member<R: Read>(mut decoder: R) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    {
+        let mut archive = tar::Archive::new(&mut decoder);
+        for entry in archive.entries().ok()? {
+            let mut entry = entry.ok()?;
+            io::copy(&mut entry, &mut io::sink()).ok()?;
+            names.push(entry.path().ok()?.to_string_lossy().to_string());
+        }
+    }
+    // Consume the rest of the member (terminator blocks and compression trailer)
+    io::copy(&mut decoder, &mut io::sink()).ok()?;
+    Some(names)
+}
+
+/// Open a zip container for appending, rebuilding it first if a run was interrupted
+fn open_zip(path: &Path, existing_len: u64) -> Result<(HashSet<String>, Backend)> {
+    let zip_error = |e: zip::result::ZipError| {
+        IaGetError::FileSystem(format!(
+            "Failed to open container {}: {}",
+            path.display(),
+            e
+        ))
+    };
+    let io_error = |e: io::Error| {
+        IaGetError::FileSystem(format!(
+            "Failed to open container {}: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    if existing_len == 0 {
+        let file = File::create(path).map_err(io_error)?;
+        return Ok((
+            HashSet::new(),
+            Backend::Zip(Box::new(zip::ZipWriter::new(file))),
+        ));
+    }
+
+    let readable = File::open(path)
+        .ok()
+        .and_then(|file| zip::ZipArchive::new(file).ok())
+        .is_some();
+    if !readable {
+        recover_zip(path)?;
+    }
+
+    let members = zip::ZipArchive::new(File::open(path).map_err(io_error)?)
+        .map_err(zip_error)?
+        .file_names()
+        .map(|name| name.to_string())
+        .collect();
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(io_error)?;
+    let writer = zip::ZipWriter::new_append(file).map_err(zip_error)?;
+
+    Ok((members, Backend::Zip(Box::new(writer))))
+}
+
+/// Rebuild a zip that has no central directory from its complete local entries
+fn recover_zip(path: &Path) -> Result<()> {
+    let io_error = |e: io::Error| {
+        IaGetError::FileSystem(format!(
+            "Failed to recover container {}: {}",
+            path.display(),
+            e
+        ))
+    };
+    let zip_error = |e: zip::result::ZipError| io_error(io::Error::other(e));
+
+    // First pass: count entries that were completely written
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    let mut complete = 0;
+    let mut last_was_empty = false;
+    loop {
+        match zip::read::read_zipfile_from_stream(&mut reader) {
+            Ok(Some(mut entry)) => {
+                if io::copy(&mut entry, &mut io::sink()).is_err() {
+                    last_was_empty = false;
+                    break;
+                }
+                last_was_empty = entry.compressed_size() == 0;
+                complete += 1;
+            }
+            Ok(None) => {
+                last_was_empty = false;
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+    // An entry whose sizes were never filled in looks empty; re-fetch it to be safe
+    if last_was_empty {
+        complete -= 1;
+    }
+
+    // Second pass: copy those entries into a fresh zip
+    let mut recovered_path = path.as_os_str().to_owned();
+    recovered_path.push(".recover");
+    let recovered_path = PathBuf::from(recovered_path);
+
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    let mut writer = zip::ZipWriter::new(File::create(&recovered_path).map_err(io_error)?);
+    for _ in 0..complete {
+        match zip::read::read_zipfile_from_stream(&mut reader).map_err(zip_error)? {
+            Some(entry) => writer.raw_copy_file(entry).map_err(zip_error)?,
+            None => break,
+        }
+    }
+    writer.finish().map_err(zip_error)?;
+
+    std::fs::rename(&recovered_path, path).map_err(io_error)?;
+    Ok(())
+}
+
+/// Pick Stored for already-compressed data and Deflated for everything else
+///
+/// Known compressed extensions are stored right away; other files are stored
+/// when deflating their first [`DEFLATE_PROBE_LEN`] bytes does not shrink them.
+/// Leaves `input` positioned at its start.
+fn zip_compression_method(name: &str, input: &mut File) -> io::Result<zip::CompressionMethod> {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+    if extension.is_some_and(|e| COMPRESSED_EXTENSIONS.contains(&e.as_str())) {
+        return Ok(zip::CompressionMethod::Stored);
+    }
+
+    let mut probe = Vec::new();
+    (&mut *input)
+        .take(DEFLATE_PROBE_LEN)
+        .read_to_end(&mut probe)?;
+    input.seek(SeekFrom::Start(0))?;
+
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&probe)?;
+    let deflated = encoder.finish()?;
+    Ok(if deflated.len() < probe.len() {
+        zip::CompressionMethod::Deflated
+    } else {
+        zip::CompressionMethod::Stored
+    })
+}
+
+/// Convert Unix seconds to a zip timestamp (zip cannot represent dates before 1980)
+fn zip_datetime(mtime: u64) -> zip::DateTime {
+    use chrono::{Datelike, Timelike};
+
+    chrono::DateTime::from_timestamp(mtime as i64, 0)
+        .and_then(|t| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(t.year()).ok()?,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
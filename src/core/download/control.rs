@@ -0,0 +1,230 @@
+//! Runtime control of running downloads
+//!
+//! A [`DownloadControl`] is shared between whoever drives a download (the CLI,
+//! the GUI, a signal handler) and the per-file tasks doing the work. Pausing
+//! stops every transfer at the next chunk, flushes its `.tmp` file and saves
+//! the session; resuming continues the same run from those partial files.
+//! Cancelling stops the run for good, leaving a session a later run resumes.
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// What running downloads have been asked to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlState {
+    #[default]
+    Running,
+    Paused,
+    Cancelled,
+}
+
+/// Cloneable pause/resume/cancel handle for a download run
+#[derive(Debug, Clone)]
+pub struct DownloadControl {
+    state: Arc<watch::Sender<ControlState>>,
+}
+
+impl Default for DownloadControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownloadControl {
+    pub fn new() -> Self {
+        let (state, _) = watch::channel(ControlState::Running);
+        Self {
+            state: Arc::new(state),
+        }
+    }
+
+    /// Ask running transfers to stop at the next chunk and wait for `resume`
+    pub fn pause(&self) {
+        self.state.send_if_modified(|state| {
+            let pause = *state == ControlState::Running;
+            if pause {
+                *state = ControlState::Paused;
+            }
+            pause
+        });
+    }
+
+    /// Continue a paused run
+    pub fn resume(&self) {
+        self.state.send_if_modified(|state| {
+            let resume = *state == ControlState::Paused;
+            if resume {
+                *state = ControlState::Running;
+            }
+            resume
+        });
+    }
+
+    /// Stop the run; files not yet finished are left to resume later
+    pub fn cancel(&self) {
+        self.state.send_replace(ControlState::Cancelled);
+    }
+
+    pub fn state(&self) -> ControlState {
+        *self.state.borrow()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state() == ControlState::Paused
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state() == ControlState::Cancelled
+    }
+
+    /// Resolves as soon as transfers should stop (paused or cancelled)
+    pub async fn interrupted(&self) {
+        let mut state = self.state.subscribe();
+        let _ = state.wait_for(|s| *s != ControlState::Running).await;
+    }
+
+    /// Resolves once the run has been cancelled
+    pub async fn cancelled(&self) {
+        let mut state = self.state.subscribe();
+        let _ = state.wait_for(|s| *s == ControlState::Cancelled).await;
+    }
+
+    /// Mirror `parent`'s pauses, resumes and cancellation until this control
+    /// is cancelled, which leaves `parent` running
+    ///
+    /// Lets one item of a larger run be stopped on its own.
+    pub fn follow(&self, parent: &DownloadControl) {
+        let child = self.clone();
+        let mut parent_state = parent.state.subscribe();
+        let mut child_state = self.state.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let state = *parent_state.borrow_and_update();
+                match state {
+                    ControlState::Running => child.resume(),
+                    ControlState::Paused => child.pause(),
+                    ControlState::Cancelled => {
+                        child.cancel();
+                        return;
+                    }
+                }
+                if child.is_cancelled() {
+                    return;
+                }
+                tokio::select! {
+                    changed = parent_state.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                    }
+                    _ = child_state.wait_for(|s| *s == ControlState::Cancelled) => return,
+                }
+            }
+        });
+    }
+
+    /// Wait out a pause; false once the run has been cancelled
+    pub async fn wait_while_paused(&self) -> bool {
+        let mut state = self.state.subscribe();
+        match state.wait_for(|s| *s != ControlState::Paused).await {
+            Ok(s) => *s == ControlState::Running,
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_state_transitions() {
+        let control = DownloadControl::new();
+        assert_eq!(control.state(), ControlState::Running);
+
+        control.resume();
+        assert_eq!(control.state(), ControlState::Running);
+
+        control.pause();
+        assert!(control.is_paused());
+        control.resume();
+        assert_eq!(control.state(), ControlState::Running);
+
+        control.pause();
+        control.cancel();
+        assert!(control.is_cancelled());
+
+        // Cancellation is final
+        control.resume();
+        control.pause();
+        assert!(control.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_waiters_follow_state() {
+        let control = DownloadControl::new();
+        let watcher = control.clone();
+        let interrupted = tokio::spawn(async move { watcher.interrupted().await });
+
+        control.pause();
+        timeout(Duration::from_secs(1), interrupted)
+            .await
+            .expect("pause should interrupt transfers")
+            .unwrap();
+
+        let waiter = control.clone();
+        let paused = tokio::spawn(async move { waiter.wait_while_paused().await });
+        control.resume();
+        assert!(
+            timeout(Duration::from_secs(1), paused)
+                .await
+                .unwrap()
+                .unwrap()
+        );
+
+        control.pause();
+        let waiter = control.clone();
+        let paused = tokio::spawn(async move { waiter.wait_while_paused().await });
+        control.cancel();
+        assert!(
+            !timeout(Duration::from_secs(1), paused)
+                .await
+                .unwrap()
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_follower_mirrors_parent_but_stops_alone() {
+        let parent = DownloadControl::new();
+        let child = DownloadControl::new();
+        child.follow(&parent);
+
+        parent.pause();
+        timeout(Duration::from_secs(1), child.interrupted())
+            .await
+            .expect("pause should reach the follower");
+        parent.resume();
+        assert!(
+            timeout(Duration::from_secs(1), child.wait_while_paused())
+                .await
+                .unwrap()
+        );
+
+        child.cancel();
+        assert_eq!(parent.state(), ControlState::Running);
+
+        let other = DownloadControl::new();
+        other.follow(&parent);
+        parent.cancel();
+        timeout(Duration::from_secs(1), async {
+            while !other.is_cancelled() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("cancelling the parent should cancel the follower");
+    }
+}
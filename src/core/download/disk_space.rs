@@ -0,0 +1,191 @@
+//! Disk space preflight
+//!
+//! Before any bytes are fetched the download service estimates how much room
+//! a run needs on every filesystem it writes to and compares that with what
+//! the filesystem actually holding each path reports, so output on a mounted
+//! data volume is checked against that volume rather than the root disk.
+//!
+//! The estimate counts the bytes still to fetch (finished files and partial
+//! `.tmp` files already on disk are subtracted), the expected output of
+//! automatic decompression, and room for files that briefly exist twice:
+//! segment parts being joined and downloads staged for an output container.
+
+use crate::{
+    core::download::mirror_path,
+    core::session::ArchiveFile,
+    utilities::common::{get_available_disk_space, same_filesystem},
+    utilities::compression::{CompressionFormat, should_decompress},
+};
+use std::path::{Path, PathBuf};
+
+/// Assumed expansion of automatically decompressed files
+///
+/// The real ratio is only known after decompressing; 3x is typical for the
+/// text and disc images that make up most compressed uploads.
+pub const DECOMPRESSION_RATIO: u64 = 3;
+
+/// Smallest safety margin added on top of an estimate
+const MIN_SAFETY_MARGIN: u64 = 100 * 1024 * 1024;
+
+/// What a download writes and where
+#[derive(Debug, Clone, Copy)]
+pub struct PreflightOptions<'a> {
+    pub output_dir: &'a Path,
+    /// Archive that receives every file instead of `output_dir`
+    pub output_container: Option<&'a Path>,
+    /// Formats decompressed after download; `None` when decompression is off
+    pub decompress_formats: Option<&'a [String]>,
+    pub concurrent_downloads: usize,
+}
+
+/// Room a download needs on one filesystem
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// Bytes still to be written
+    pub download_bytes: u64,
+    /// Expected output of automatic decompression
+    pub decompressed_bytes: u64,
+    /// Copies that exist only while a file is joined or moved into a container
+    pub temp_bytes: u64,
+}
+
+impl SpaceEstimate {
+    pub fn total(&self) -> u64 {
+        self.download_bytes + self.decompressed_bytes + self.temp_bytes
+    }
+
+    /// 100MB or 5% of the estimate, whichever is larger
+    pub fn safety_margin(&self) -> u64 {
+        MIN_SAFETY_MARGIN.max(self.total() / 20)
+    }
+
+    pub fn required(&self) -> u64 {
+        self.total() + self.safety_margin()
+    }
+
+    fn add(&mut self, other: &SpaceEstimate) {
+        self.download_bytes += other.download_bytes;
+        self.decompressed_bytes += other.decompressed_bytes;
+        self.temp_bytes += other.temp_bytes;
+    }
+}
+
+/// Estimated need and free space of one filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceCheck {
+    /// Path the filesystem was queried through
+    pub path: PathBuf,
+    pub estimate: SpaceEstimate,
+    /// Free space, if the platform could report it
+    pub available: Option<u64>,
+}
+
+impl SpaceCheck {
+    /// Bytes missing for the download to fit; 0 when it fits or is unknown
+    pub fn shortage(&self) -> u64 {
+        self.available.map_or(0, |available| {
+            self.estimate.required().saturating_sub(available)
+        })
+    }
+}
+
+/// Estimate the space `files` need, grouped by filesystem
+///
+/// Returns one estimate per distinct target path; paths on the same
+/// filesystem are merged into the first of them.
+pub fn estimate_disk_space(
+    files: &[ArchiveFile],
+    options: &PreflightOptions,
+) -> Vec<(PathBuf, SpaceEstimate)> {
+    let mut remaining = Vec::with_capacity(files.len());
+    let mut output = SpaceEstimate::default();
+    for file in files {
+        let size = file.size.unwrap_or(0);
+        let left = size.saturating_sub(bytes_on_disk(options.output_dir, file, size));
+        if left == 0 {
+            continue;
+        }
+        remaining.push(left);
+
+        let decompressed = options.decompress_formats.is_some_and(|formats| {
+            CompressionFormat::from_filename(&file.name)
+                .is_some_and(|format| should_decompress(&format, formats))
+        });
+        if decompressed {
+            output.decompressed_bytes += size.saturating_mul(DECOMPRESSION_RATIO);
+        }
+    }
+
+    // The largest files that can be in flight at once may briefly exist twice
+    remaining.sort_unstable_by(|a, b| b.cmp(a));
+    let in_flight: u64 = remaining
+        .iter()
+        .take(options.concurrent_downloads.max(1))
+        .sum();
+    let left_total: u64 = remaining.iter().sum();
+
+    let mut targets = Vec::new();
+    match options.output_container {
+        Some(container) => {
+            // Files are staged in the output directory, then appended to the archive
+            output.temp_bytes = in_flight;
+            let written = std::fs::metadata(container).map_or(0, |m| m.len());
+            let total: u64 = files.iter().map(|f| f.size.unwrap_or(0)).sum();
+            let container_dir = container
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            targets.push((options.output_dir.to_path_buf(), output));
+            targets.push((
+                container_dir.to_path_buf(),
+                SpaceEstimate {
+                    download_bytes: total.saturating_sub(written),
+                    ..Default::default()
+                },
+            ));
+        }
+        None => {
+            // A segmented file holds one part twice while the parts are joined
+            output.download_bytes = left_total;
+            output.temp_bytes = in_flight;
+            targets.push((options.output_dir.to_path_buf(), output));
+        }
+    }
+
+    merge_by_filesystem(targets)
+}
+
+/// Estimate the space `files` need and query each filesystem's free space
+pub fn check_disk_space(files: &[ArchiveFile], options: &PreflightOptions) -> Vec<SpaceCheck> {
+    estimate_disk_space(files, options)
+        .into_iter()
+        .map(|(path, estimate)| SpaceCheck {
+            available: get_available_disk_space(&path),
+            path,
+            estimate,
+        })
+        .collect()
+}
+
+/// Bytes of `file` already downloaded, either complete or as a partial `.tmp`
+fn bytes_on_disk(output_dir: &Path, file: &ArchiveFile, size: u64) -> u64 {
+    let path = mirror_path(output_dir, &file.name);
+    match std::fs::metadata(&path) {
+        Ok(meta) if meta.len() == size => size,
+        _ => std::fs::metadata(path.with_extension("tmp")).map_or(0, |m| m.len().min(size)),
+    }
+}
+
+fn merge_by_filesystem(targets: Vec<(PathBuf, SpaceEstimate)>) -> Vec<(PathBuf, SpaceEstimate)> {
+    let mut merged: Vec<(PathBuf, SpaceEstimate)> = Vec::new();
+    for (path, estimate) in targets {
+        let existing = merged.iter_mut().find(|(other, _)| {
+            *other == path || same_filesystem(other.as_path(), &path).unwrap_or(false)
+        });
+        match existing {
+            Some((_, total)) => total.add(&estimate),
+            None => merged.push((path, estimate)),
+        }
+    }
+    merged
+}
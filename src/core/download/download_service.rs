@@ -5,13 +5,19 @@
 
 use crate::{
     IaGetError, Result,
-    core::archive::fetch_json_metadata,
-    core::download::ArchiveDownloader,
+    core::archive::fetch_json_metadata_with_policy,
+    core::download::{
+        ArchiveDownloader, ContainerFormat, DownloadControl, HookContext, HookEvent, HookOutcome,
+        HookRunner, HookSettings, PreflightOptions, ProgressEvent, check_disk_space,
+        emit_progress_event, hook_settings,
+    },
     core::session::{
-        ArchiveFile, DownloadConfig, DownloadSession, ProgressCallback, ProgressUpdate,
+        ArchiveFile, ChecksumPolicy, DownloadConfig, DownloadOrder, DownloadSession,
+        ProgressCallback, ProgressUpdate,
     },
     infrastructure::api::{ApiStats, ArchiveOrgApiClient, validate_identifier},
     infrastructure::config::Config,
+    infrastructure::http::{HttpClientFactory, RetryPolicy, retry_policy},
     interface::cli::SourceType,
     utilities::common::extract_identifier_from_url,
     utilities::common::get_user_agent,
@@ -19,10 +25,15 @@ use crate::{
 };
 use colored::Colorize;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Unified download request parameters used by both CLI and GUI
-#[derive(Debug, Clone)]
+///
+/// Serialized as JSON by the daemon API; fields left out take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DownloadRequest {
     /// Archive identifier or URL
     pub identifier: String,
@@ -56,6 +67,23 @@ pub struct DownloadRequest {
     pub resume: bool,
     /// Source types to include in download (original, derivative, metadata)
     pub source_types: Vec<SourceType>,
+    /// Number of byte-range segments to fetch concurrently per large file (1 disables)
+    pub segments_per_file: usize,
+    /// Minimum file size before segmented downloading is used (None uses the default threshold)
+    pub segment_min_size: Option<String>,
+    /// Which published checksums to verify when `verify_md5` is enabled
+    pub checksum_policy: ChecksumPolicy,
+    /// Write files into this .tar, .tar.gz, .tar.zst or .zip instead of `output_dir`
+    pub output_container: Option<PathBuf>,
+    /// Content-addressed store used to deduplicate identical files across items
+    pub store_dir: Option<PathBuf>,
+    /// Order in which files are downloaded
+    pub download_order: DownloadOrder,
+    /// Retries per request; `None` keeps the process-wide retry policy
+    pub max_retries: Option<usize>,
+    /// Files fetched again even where a local copy exists; each copy is
+    /// replaced only once its new download is complete
+    pub redownload: Vec<String>,
 }
 
 impl Default for DownloadRequest {
@@ -77,6 +105,14 @@ impl Default for DownloadRequest {
             verbose: false,
             resume: true,
             source_types: Vec::new(), // Default to no filtering - all source types allowed
+            segments_per_file: 1,
+            segment_min_size: None,
+            checksum_policy: ChecksumPolicy::default(),
+            output_container: None,
+            store_dir: None,
+            download_order: DownloadOrder::default(),
+            max_retries: None,
+            redownload: Vec::new(),
         }
     }
 }
@@ -98,6 +134,8 @@ impl DownloadRequest {
             dry_run: config.default_dry_run,
             verbose: config.default_verbose,
             resume: config.default_resume,
+            store_dir: config.store_dir.as_ref().map(PathBuf::from),
+            max_retries: Some(config.max_retries),
             ..Default::default()
         }
     }
@@ -118,25 +156,38 @@ impl DownloadRequest {
 
         Ok((min_size, max_size))
     }
+
+    /// Parse the segmented download threshold into bytes
+    pub fn get_segment_min_size(&self) -> Result<Option<u64>> {
+        self.segment_min_size
+            .as_ref()
+            .map(|s| parse_size_string(s))
+            .transpose()
+    }
 }
 
 /// Download operation result
 #[derive(Debug)]
 pub enum DownloadResult {
     Success(Box<DownloadSession>, Option<ApiStats>, bool), // Third field is dry_run flag
+    /// Stopped through a `DownloadControl`; unfinished files are left to resume
+    Cancelled(Box<DownloadSession>),
     Error(String),
 }
 
 /// Unified download service that both CLI and GUI use
 pub struct DownloadService {
     client: Client,
+    /// Download history file; the config directory's by default
+    history_path: Option<PathBuf>,
+    /// Hook commands; the process-wide settings by default
+    hook_settings: Option<HookSettings>,
 }
 
 impl DownloadService {
     /// Create a new download service
     pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .user_agent(get_user_agent())
+        let client = HttpClientFactory::builder()?
             // No global timeout for downloads, as large files can take a long time
             // We rely on connect timeouts and TCP keepalive instead
             .connect_timeout(std::time::Duration::from_secs(30))
@@ -147,7 +198,23 @@ impl DownloadService {
             .build()
             .map_err(|e| IaGetError::Network(format!("Failed to create HTTP client: {}", e)))?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            history_path: None,
+            hook_settings: None,
+        })
+    }
+
+    /// Keep the download history in `path` instead of the config directory
+    pub fn with_history_path(mut self, path: PathBuf) -> Self {
+        self.history_path = Some(path);
+        self
+    }
+
+    /// Run `settings` instead of the process-wide hook commands
+    pub fn with_hook_settings(mut self, settings: HookSettings) -> Self {
+        self.hook_settings = Some(settings);
+        self
     }
 
     /// Execute a download request with Archive.org API compliance
@@ -155,30 +222,78 @@ impl DownloadService {
         &self,
         request: DownloadRequest,
         progress_callback: Option<ProgressCallback>,
+    ) -> Result<DownloadResult> {
+        self.download_with_control(request, progress_callback, DownloadControl::new())
+            .await
+    }
+
+    /// Execute a download request that `control` can pause, resume or cancel
+    pub async fn download_with_control(
+        &self,
+        request: DownloadRequest,
+        progress_callback: Option<ProgressCallback>,
+        control: DownloadControl,
     ) -> Result<DownloadResult> {
         use crate::infrastructure::persistence::download_history::{
             DownloadHistory, DownloadHistoryEntry, get_default_history_db_path,
         };
 
+        // Hooks hear about every outcome, including items that fail before
+        // any file is downloaded
+        let hook_settings = self.hook_settings.clone().unwrap_or_else(hook_settings);
+        let hooks = (!hook_settings.is_empty()).then(|| Arc::new(HookRunner::new(hook_settings)));
+        let mut hook_context = HookContext {
+            identifier: request.identifier.clone(),
+            path: request.output_dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let hooks_ref = hooks.as_deref();
+
         // Validate identifier for Archive.org compliance
         let identifier = if request.identifier.starts_with("http") {
             // Extract identifier from URL
-            extract_identifier_from_url(&request.identifier)?
+            match extract_identifier_from_url(&request.identifier) {
+                Ok(identifier) => identifier,
+                Err(e) => {
+                    let message = e.to_string();
+                    return Ok(Self::fail_early(hooks_ref, hook_context, &e, message).await);
+                }
+            }
         } else {
             request.identifier.clone()
         };
+        hook_context.identifier = identifier.clone();
 
         // Validate identifier format
         if let Err(e) = validate_identifier(&identifier) {
-            return Ok(DownloadResult::Error(format!(
-                "Invalid Archive.org identifier: {}",
-                e
-            )));
+            let message = format!("Invalid Archive.org identifier: {}", e);
+            return Ok(Self::fail_early(hooks_ref, hook_context, &e, message).await);
         }
 
+        if let Some(ref container) = request.output_container {
+            if ContainerFormat::from_path(container).is_none() {
+                let error = IaGetError::Config(format!(
+                    "Unsupported output archive '{}': use .tar, .tar.gz, .tar.zst or .zip",
+                    container.display()
+                ));
+                let message = error.to_string();
+                return Ok(Self::fail_early(hooks_ref, hook_context, &error, message).await);
+            }
+        }
+
+        // Items run side by side may ask for different retry counts, so the
+        // policy travels with this request rather than replacing the global one
+        let retry = request
+            .max_retries
+            .map_or_else(retry_policy, |max_retries| {
+                RetryPolicy::new(u32::try_from(max_retries).unwrap_or(u32::MAX))
+            });
+
         // Load or create download history
-        let history_path = get_default_history_db_path()?;
-        let mut download_history = DownloadHistory::load_or_create(&history_path)?;
+        let history_path = match &self.history_path {
+            Some(path) => path.clone(),
+            None => get_default_history_db_path()?,
+        };
 
         // Send initial status
         if let Some(ref callback) = progress_callback {
@@ -223,11 +338,12 @@ impl DownloadService {
         let progress = indicatif::ProgressBar::new_spinner();
         progress.enable_steady_tick(std::time::Duration::from_millis(100));
 
-        let (metadata, _base_url) = match fetch_json_metadata(
+        let (metadata, _base_url) = match fetch_json_metadata_with_policy(
             &archive_url,
             api_client.client(),
             &progress,
             Some(&session_dir),
+            retry,
         )
         .await
         {
@@ -237,10 +353,8 @@ impl DownloadService {
             }
             Err(e) => {
                 progress.finish_and_clear();
-                return Ok(DownloadResult::Error(format!(
-                    "Failed to fetch metadata: {}",
-                    e
-                )));
+                let message = format!("Failed to fetch metadata: {}", e);
+                return Ok(Self::fail_early(hooks_ref, hook_context, &e, message).await);
             }
         };
 
@@ -252,6 +366,7 @@ impl DownloadService {
 
         // Parse file size filters
         let (min_size, max_size) = request.get_parsed_sizes()?;
+        let segment_min_size = request.get_segment_min_size()?;
 
         // Apply file filters
         let filtered_files = self.apply_file_filters(
@@ -264,9 +379,10 @@ impl DownloadService {
         );
 
         if filtered_files.is_empty() {
-            return Ok(DownloadResult::Error(
-                "No files match the specified filters".to_string(),
-            ));
+            let error =
+                IaGetError::NoFilesFound("No files match the specified filters".to_string());
+            let message = error.to_string();
+            return Ok(Self::fail_early(hooks_ref, hook_context, &error, message).await);
         }
 
         // Send file count update
@@ -282,49 +398,65 @@ impl DownloadService {
             });
         }
 
-        // Calculate total download size
-        let total_download_size: u64 = filtered_files.iter().map(|f| f.size.unwrap_or(0)).sum();
-
-        // Check available disk space
-        if let Some(available_space) =
-            crate::utilities::common::get_available_disk_space(&request.output_dir)
-        {
-            // Add a safety margin of 100MB or 5% of download size (whichever is larger)
-            let safety_margin = std::cmp::max(
-                100 * 1024 * 1024,
-                (total_download_size as f64 * 0.05) as u64,
-            );
-            let required_space = total_download_size + safety_margin;
+        // Check available disk space on every filesystem the download writes to
+        let preflight = PreflightOptions {
+            output_dir: &request.output_dir,
+            output_container: request.output_container.as_deref(),
+            decompress_formats: request
+                .auto_decompress
+                .then_some(request.decompress_formats.as_slice()),
+            concurrent_downloads: request.concurrent_downloads,
+        };
+        for check in check_disk_space(&filtered_files, &preflight) {
+            let required_space = check.estimate.required();
+            let Some(available_space) = check.available else {
+                if request.verbose {
+                    eprintln!(
+                        "⚠️  Warning: Unable to determine available disk space for {}",
+                        check.path.display()
+                    );
+                }
+                continue;
+            };
 
-            if available_space < required_space {
-                let warning_msg = format!(
-                    "⚠️  Warning: Insufficient disk space!\n\
+            if check.shortage() > 0 {
+                let mut warning_msg = format!(
+                    "⚠️  Warning: Insufficient disk space on the filesystem holding {}!\n\
                      Required: {} (including safety margin)\n\
                      Available: {}\n\
                      Shortage: {}",
+                    check.path.display(),
                     format_size(required_space),
                     format_size(available_space),
-                    format_size(required_space.saturating_sub(available_space))
+                    format_size(check.shortage())
                 );
+                if check.estimate.decompressed_bytes > 0 {
+                    warning_msg.push_str(&format!(
+                        "\nIncludes {} for decompressed output",
+                        format_size(check.estimate.decompressed_bytes)
+                    ));
+                }
 
                 eprintln!("{}", warning_msg.yellow());
 
                 if !request.dry_run {
-                    return Ok(DownloadResult::Error(format!(
-                        "Insufficient disk space. Required: {}, Available: {}",
+                    let message = format!(
+                        "Insufficient disk space for {}. Required: {}, Available: {}",
+                        check.path.display(),
                         format_size(required_space),
                         format_size(available_space)
-                    )));
+                    );
+                    let error = IaGetError::FileSystem(message.clone());
+                    return Ok(Self::fail_early(hooks_ref, hook_context, &error, message).await);
                 }
             } else if request.verbose {
                 eprintln!(
-                    "✓ Disk space check passed: {} available, {} required",
+                    "✓ Disk space check passed for {}: {} available, {} required",
+                    check.path.display(),
                     format_size(available_space).bright_green(),
                     format_size(required_space).bright_blue()
                 );
             }
-        } else if request.verbose {
-            eprintln!("⚠️  Warning: Unable to determine available disk space");
         }
 
         if request.dry_run {
@@ -347,6 +479,18 @@ impl DownloadService {
                         enable_compression: request.enable_compression,
                         auto_decompress: request.auto_decompress,
                         decompress_formats: request.decompress_formats.clone(),
+                        segments_per_file: request.segments_per_file as u32,
+                        segment_min_size,
+                        checksum_policy: request.checksum_policy,
+                        output_container: request
+                            .output_container
+                            .as_ref()
+                            .map(|p| p.to_string_lossy().to_string()),
+                        store_dir: request
+                            .store_dir
+                            .as_ref()
+                            .map(|p| p.to_string_lossy().to_string()),
+                        download_order: request.download_order.clone(),
                     },
                     requested_files: filtered_files.iter().map(|f| f.name.clone()).collect(),
                     file_status: std::collections::HashMap::new(),
@@ -358,6 +502,7 @@ impl DownloadService {
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap()
                         .as_secs(),
+                    journal_seq: 0,
                 }),
                 Some(api_stats),
                 true, // This is a dry run
@@ -377,6 +522,18 @@ impl DownloadService {
             enable_compression: request.enable_compression,
             auto_decompress: request.auto_decompress,
             decompress_formats: request.decompress_formats.clone(),
+            segments_per_file: request.segments_per_file as u32,
+            segment_min_size,
+            checksum_policy: request.checksum_policy,
+            output_container: request
+                .output_container
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+            store_dir: request
+                .store_dir
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+            download_order: request.download_order.clone(),
         };
 
         // Create history entry for this download
@@ -391,16 +548,15 @@ impl DownloadService {
         history_entry.total_files = filtered_files.len();
         history_entry.total_bytes = filtered_files.iter().map(|f| f.size.unwrap_or(0)).sum();
 
-        // Add to history and save
-        let entry_id = history_entry.id.clone();
-        download_history.add_entry(history_entry);
-        download_history.save_to_file(&history_path)?;
+        // Add to history; other downloads may be writing it at the same time
+        let entry_id =
+            DownloadHistory::update(&history_path, |history| history.add_entry(history_entry))?;
 
         // Create session directory
         let session_dir = request.output_dir.join(".ia-get-sessions");
 
         // Initialize the archive downloader
-        let downloader = ArchiveDownloader::new(
+        let mut downloader = ArchiveDownloader::new(
             self.client.clone(),
             request.concurrent_downloads,
             request.verify_md5,
@@ -408,7 +564,14 @@ impl DownloadService {
             session_dir,
             request.enable_compression,
             request.auto_decompress,
-        );
+        )
+        .with_control(control.clone())
+        .with_retry_policy(retry)
+        .with_redownload(request.redownload.clone());
+
+        if let Some(ref hooks) = hooks {
+            downloader = downloader.with_hooks(hooks.clone());
+        }
 
         // Get list of file names to download
         let requested_files: Vec<String> = filtered_files.iter().map(|f| f.name.clone()).collect();
@@ -443,20 +606,46 @@ impl DownloadService {
             .await
         {
             Ok(session) => {
+                if request.verbose {
+                    Self::display_server_health(&downloader).await;
+                }
+
                 // Update history with successful completion
                 let progress_summary = session.get_progress_summary();
-                if let Err(e) = download_history.update_entry(&entry_id, |entry| {
-                    entry.mark_completed();
-                    entry.update_progress(
-                        progress_summary.completed_files,
-                        progress_summary.failed_files,
-                        progress_summary.downloaded_bytes,
-                    );
+                let cancelled = control.is_cancelled();
+                // A stopped download gets no item hook; it is finished by a later run
+                let mut item_context = hook_context;
+                let item_hook = if cancelled {
+                    None
+                } else if progress_summary.failed_files == 0 {
+                    Some(HookEvent::ItemSuccess)
+                } else {
+                    item_context.error =
+                        Some(format!("{} file(s) failed", progress_summary.failed_files));
+                    Some(HookEvent::ItemFailure)
+                };
+                let hook_outcomes =
+                    Self::finish_hooks(hooks.as_deref(), item_hook, item_context).await;
+                if let Err(e) = DownloadHistory::try_update(&history_path, |history| {
+                    history.update_entry(&entry_id, |entry| {
+                        if cancelled {
+                            entry.mark_cancelled();
+                        } else {
+                            entry.mark_completed();
+                        }
+                        entry.update_progress(
+                            progress_summary.completed_files,
+                            progress_summary.failed_files,
+                            progress_summary.downloaded_bytes,
+                        );
+                        Self::record_hook_outcomes(&mut entry.metadata, &hook_outcomes);
+                    })
                 }) {
                     eprintln!("{} Failed to update download history: {}", "⚠️".yellow(), e);
                 }
-                if let Err(e) = download_history.save_to_file(&history_path) {
-                    eprintln!("{} Failed to save download history: {}", "⚠️".yellow(), e);
+
+                if cancelled {
+                    return Ok(DownloadResult::Cancelled(Box::new(session)));
                 }
 
                 let final_api_stats = api_client.get_stats();
@@ -468,21 +657,85 @@ impl DownloadService {
             }
             Err(e) => {
                 // Update history with failure
+                emit_progress_event(ProgressEvent::session_failed(&identifier, &e));
                 let error_message = format!("Download failed: {}", e);
-                if let Err(e) = download_history.update_entry(&entry_id, |entry| {
-                    entry.mark_failed(e.to_string());
+                let mut item_context = hook_context;
+                item_context.error = Some(e.to_string());
+                let hook_outcomes = Self::finish_hooks(
+                    hooks.as_deref(),
+                    Some(HookEvent::ItemFailure),
+                    item_context,
+                )
+                .await;
+                if let Err(e) = DownloadHistory::try_update(&history_path, |history| {
+                    history.update_entry(&entry_id, |entry| {
+                        entry.mark_failed(e.to_string());
+                        Self::record_hook_outcomes(&mut entry.metadata, &hook_outcomes);
+                    })
                 }) {
                     eprintln!("{} Failed to update download history: {}", "⚠️".yellow(), e);
                 }
-                if let Err(e) = download_history.save_to_file(&history_path) {
-                    eprintln!("{} Failed to save download history: {}", "⚠️".yellow(), e);
-                }
 
                 Ok(DownloadResult::Error(error_message))
             }
         }
     }
 
+    /// Report an item that failed before any file was downloaded and run its
+    /// `item_failure` hook
+    async fn fail_early(
+        hooks: Option<&HookRunner>,
+        mut context: HookContext,
+        error: &IaGetError,
+        message: String,
+    ) -> DownloadResult {
+        emit_progress_event(ProgressEvent::session_failed(&context.identifier, error));
+        context.error = Some(message.clone());
+        Self::finish_hooks(hooks, Some(HookEvent::ItemFailure), context).await;
+        DownloadResult::Error(message)
+    }
+
+    /// Start the item hook for `event` and wait for every hook of the item
+    async fn finish_hooks(
+        hooks: Option<&HookRunner>,
+        event: Option<HookEvent>,
+        context: HookContext,
+    ) -> Vec<HookOutcome> {
+        let Some(hooks) = hooks else {
+            return Vec::new();
+        };
+        if let Some(event) = event {
+            hooks.run(event, context);
+        }
+        let outcomes = hooks.finish().await;
+
+        let failed = outcomes
+            .iter()
+            .filter(|outcome| !outcome.succeeded())
+            .count();
+        if failed > 0 {
+            eprintln!(
+                "{} {} of {} hook(s) failed or timed out",
+                "⚠️".yellow(),
+                failed,
+                outcomes.len()
+            );
+        }
+        outcomes
+    }
+
+    /// Keep how each hook ended under `hooks` in a history entry's metadata
+    fn record_hook_outcomes(metadata: &mut serde_json::Value, outcomes: &[HookOutcome]) {
+        if outcomes.is_empty() {
+            return;
+        }
+        if let (Some(metadata), Ok(outcomes)) =
+            (metadata.as_object_mut(), serde_json::to_value(outcomes))
+        {
+            metadata.insert("hooks".to_string(), outcomes);
+        }
+    }
+
     /// Apply file filters to the list of archive files
     fn apply_file_filters(
         &self,
@@ -624,4 +877,46 @@ impl DownloadService {
             );
         }
     }
+
+    /// Print per-server health gathered during a download
+    async fn display_server_health(downloader: &ArchiveDownloader) {
+        let mut servers: Vec<_> = downloader
+            .server_health()
+            .snapshot()
+            .await
+            .into_iter()
+            .collect();
+        if servers.is_empty() {
+            return;
+        }
+        servers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        eprintln!("\n{} Server health:", "🩺".blue());
+        for (server, health) in servers {
+            let throughput = health
+                .avg_throughput
+                .map(|t| format!("{}/s", format_size(t as u64)))
+                .unwrap_or_else(|| "-".to_string());
+            let latency = health
+                .avg_latency
+                .map(|l| format!("{}ms", l.as_millis()))
+                .unwrap_or_else(|| "-".to_string());
+            let circuit = if health.is_circuit_open() {
+                " (circuit open)".red().to_string()
+            } else {
+                String::new()
+            };
+            eprintln!(
+                "  {} ok {} / failed {} (5xx {}, 429 {}), {} at {}{}",
+                server.bright_cyan(),
+                health.successes,
+                health.failures,
+                health.server_errors,
+                health.rate_limited,
+                throughput,
+                latency,
+                circuit
+            );
+        }
+    }
 }
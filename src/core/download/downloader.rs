@@ -13,6 +13,7 @@ use std::collections::HashSet;
 
 use crate::Result;
 use crate::error::IaGetError;
+use crate::infrastructure::http::retry_policy;
 
 /// Buffer size for file operations (8KB)
 const BUFFER_SIZE: usize = 8192;
@@ -161,10 +162,10 @@ async fn download_file_content_simple(
         file.write_all(&chunk)?;
         downloaded_bytes += chunk.len() as u64;
         total_bytes += chunk.len() as u64;
+        crate::infrastructure::http::throttle(chunk.len()).await;
 
         // Update progress bar with download percentage and speed
-        if total_expected_size > 0 {
-            let percentage = (total_bytes * 100) / total_expected_size;
+        if let Some(percentage) = (total_bytes * 100).checked_div(total_expected_size) {
             let elapsed = start_time.elapsed().as_secs_f64();
             if elapsed > 1.0 {
                 // Only show speed after 1 second
@@ -367,7 +368,6 @@ where
         let mut attempt = 0;
         let max_attempts = 2;
         let mut success = false;
-        let mut delay = std::time::Duration::from_secs(60);
 
         while attempt < max_attempts {
             // Update progress to show we're checking existing file
@@ -434,10 +434,9 @@ where
                 }
                 Err(e) => {
                     // Only pause if it's a transient network error
-                    let is_transient = matches!(e, IaGetError::Network(_));
+                    let is_transient = e.is_transient();
                     if is_transient && attempt + 1 < max_attempts {
-                        tokio::time::sleep(delay).await;
-                        delay *= 2;
+                        tokio::time::sleep(retry_policy().delay(attempt, e.retry_after())).await;
                     } else if !is_transient {
                         // For non-transient errors, break immediately
                         break;
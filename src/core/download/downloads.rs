@@ -2,7 +2,9 @@
 //!
 //! Handles file download orchestration with retry logic.
 
-use crate::{Result, core::download::downloader, error::IaGetError};
+use crate::{
+    Result, core::download::downloader, error::IaGetError, infrastructure::http::retry_policy,
+};
 use colored::*;
 use reqwest::Client;
 use std::sync::{Arc, atomic::AtomicBool};
@@ -15,10 +17,8 @@ pub async fn download_files_with_retries(
     log_hash_errors: bool,
     running: Arc<AtomicBool>,
 ) -> Result<()> {
-    let max_retries = 3;
+    let policy = retry_policy();
     let mut retries = 0;
-    let mut delay = std::time::Duration::from_secs(60);
-    let max_delay = std::time::Duration::from_secs(900); // 15 minutes
     loop {
         // Extract output directory from the first file path
         let result = downloader::download_files(
@@ -38,7 +38,8 @@ pub async fn download_files_with_retries(
             Err(e) => {
                 // Only retry on transient network errors
                 let is_transient = matches!(e, IaGetError::Network(_));
-                if is_transient && retries < max_retries {
+                if is_transient && policy.can_retry(retries) {
+                    let delay = policy.backoff(retries);
                     eprintln!(
                         "{} Network error: {}. Retrying in {}s...",
                         "▲".yellow(),
@@ -47,7 +48,6 @@ pub async fn download_files_with_retries(
                     );
                     retries += 1;
                     tokio::time::sleep(delay).await;
-                    delay = std::cmp::min(delay * 2, max_delay);
                     continue;
                 } else {
                     return Err(e);
@@ -12,6 +12,8 @@
 //! - **Filtering**: Advanced file filtering by format, size, and patterns
 //! - **Compression**: Automatic decompression of downloaded archives
 //! - **Verification**: MD5 hash verification for data integrity
+//! - **Segmented Downloads**: Large files fetched as parallel byte ranges across mirrors
+//! - **Mirror Selection**: Transfers go to the healthiest server, failing ones are skipped
 //!
 //! ## Usage
 //!
@@ -32,10 +34,22 @@
 
 use crate::{
     IaGetError, Result,
+    core::download::container::ContainerWriter,
+    core::download::control::{ControlState, DownloadControl},
+    core::download::hooks::{HookContext, HookEvent, HookRunner},
+    core::download::metrics::download_metrics,
+    core::download::progress_events::{
+        PROGRESS_EVENT_INTERVAL, ProgressEvent, emit_progress_event, progress_events_enabled,
+    },
+    core::download::server_health::{ServerFailure, ServerHealthTracker, server_from_url},
+    core::download::store::ContentStore,
     core::session::{
-        ArchiveFile, ArchiveMetadata, DownloadConfig, DownloadSession, DownloadState,
-        FileDownloadStatus, ProgressCallback, ProgressUpdate,
+        ArchiveFile, ArchiveMetadata, ChecksumPolicy, ChecksumResult, DownloadConfig,
+        DownloadSession, DownloadState, FileDownloadStatus, ProgressCallback, ProgressUpdate,
+        ResumeValidator, SegmentStatus, plan_segments,
     },
+    infrastructure::http::{RetryPolicy, http_status_error, retry_after, retry_policy},
+    utilities::common::{FileDigests, StreamingHasher},
 };
 use colored::*;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
@@ -44,12 +58,179 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio::sync::{Mutex, Semaphore, mpsc, oneshot};
+
+/// Default minimum file size for segmented downloads (100MB)
+const DEFAULT_SEGMENT_MIN_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Shared per-segment progress for a file, copied into its `FileDownloadStatus`
+type SegmentTracker = Arc<std::sync::Mutex<Vec<SegmentStatus>>>;
+
+/// Segmented download settings for a single file
+#[derive(Clone)]
+struct SegmentOptions {
+    count: usize,
+    min_size: u64,
+    tracker: SegmentTracker,
+}
+
+impl SegmentOptions {
+    /// Whether a file is large enough to be split into byte ranges
+    fn applies_to(&self, file_info: &ArchiveFile) -> bool {
+        self.count > 1 && file_info.size.is_some_and(|size| size >= self.min_size)
+    }
+}
+
+/// Journal entries written before the session snapshot is compacted
+const JOURNAL_COMPACT_ENTRIES: usize = 1000;
+
+/// Copy of the session that journals file states while downloads are in flight
+///
+/// The session file is otherwise only written before and after a batch, so an
+/// interrupted run would lose the validators needed to resume partial files safely.
+/// Each change is appended to the session journal; the snapshot is rewritten only
+/// every `JOURNAL_COMPACT_ENTRIES` entries.
+struct SessionCheckpoint {
+    path: PathBuf,
+    state: std::sync::Mutex<CheckpointState>,
+}
+
+struct CheckpointState {
+    session: DownloadSession,
+    /// Entries appended since the snapshot was last written
+    journal_entries: usize,
+}
+
+impl SessionCheckpoint {
+    fn new(path: PathBuf, session: DownloadSession) -> Self {
+        Self {
+            path,
+            state: std::sync::Mutex::new(CheckpointState {
+                session,
+                journal_entries: 0,
+            }),
+        }
+    }
+
+    /// Number of the last journal entry written
+    fn journal_seq(&self) -> u64 {
+        self.state
+            .lock()
+            .map(|state| state.session.journal_seq)
+            .unwrap_or(0)
+    }
+
+    /// Journal the current status of `file_name`, compacting when the journal is long
+    fn persist(&self, state: &mut CheckpointState, file_name: &str) {
+        // Best effort: the final session save still records the state
+        let journaled = state
+            .session
+            .journal_file_state(&self.path, file_name)
+            .is_ok();
+        if !journaled || state.journal_entries + 1 >= JOURNAL_COMPACT_ENTRIES {
+            if state.session.save_to_file(&self.path).is_ok() {
+                state.journal_entries = 0;
+            }
+        } else {
+            state.journal_entries += 1;
+        }
+    }
+
+    /// Validator recorded for a file, if any
+    fn validator(&self, file_name: &str) -> Option<ResumeValidator> {
+        let state = self.state.lock().ok()?;
+        state
+            .session
+            .file_status
+            .get(file_name)
+            .and_then(|status| status.resume_validator.clone())
+    }
+
+    /// Record and journal a file's state
+    fn record_state(&self, file_name: &str, download_state: DownloadState) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some(status) = state.session.file_status.get_mut(file_name) else {
+            return;
+        };
+        if status.status == download_state {
+            return;
+        }
+        status.status = download_state;
+        self.persist(&mut state, file_name);
+    }
+
+    /// Record and journal a file's validator
+    fn record_validator(&self, file_name: &str, validator: Option<ResumeValidator>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some(status) = state.session.file_status.get_mut(file_name) else {
+            return;
+        };
+        if status.resume_validator == validator {
+            return;
+        }
+        status.resume_validator = validator;
+        self.persist(&mut state, file_name);
+    }
+}
+
+/// Resume validator of a single file, backed by the session checkpoint
+#[derive(Clone)]
+struct ResumeTracker {
+    file_name: String,
+    checkpoint: Arc<SessionCheckpoint>,
+    /// Whether the finished file is verified against a published checksum,
+    /// which is all that vouches for partial data kept without a validator
+    checked: bool,
+    /// Whether an existing local copy is out of date and must be fetched again
+    replace_local_copy: bool,
+}
+
+impl ResumeTracker {
+    fn get(&self) -> Option<ResumeValidator> {
+        self.checkpoint.validator(&self.file_name)
+    }
+
+    fn set(&self, validator: Option<ResumeValidator>) {
+        self.checkpoint.record_validator(&self.file_name, validator);
+    }
 
-/// Maximum number of server mirrors to try before failing
-const MAX_SERVER_ATTEMPTS: usize = 5;
-/// Maximum number of resume attempts for a single file download
-const MAX_RESUME_ATTEMPTS: u32 = 3;
+    fn set_state(&self, state: DownloadState) {
+        self.checkpoint.record_state(&self.file_name, state);
+    }
+}
+
+/// Whether `name` is one of the item's own metadata files, which archive.org
+/// rewrites whenever the item changes, so its listed size and checksums
+/// are often stale
+fn is_volatile_metadata(name: &str) -> bool {
+    name.ends_with("_meta.xml")
+        || name.ends_with("_reviews.xml")
+        || name.ends_with("_files.xml")
+        || name.ends_with("_meta.sqlite")
+        || name.ends_with("_archive.torrent")
+        || name == "__ia_thumb.jpg"
+}
+
+/// Error for a file lacking the checksum a single-algorithm policy asks for;
+/// fetching it again cannot help
+fn checksum_unavailable(file_info: &ArchiveFile, missing: &ChecksumResult) -> IaGetError {
+    let algorithm = missing.algorithm.as_str().to_uppercase();
+    IaGetError::ChecksumUnavailable(format!(
+        "{} verification failed for {}: the item publishes no {} checksum for it",
+        algorithm, file_info.name, algorithm
+    ))
+}
+
+/// Path of the part file holding a single segment of `output_path`
+fn segment_part_path(output_path: &Path, index: usize) -> PathBuf {
+    let mut part = output_path.as_os_str().to_owned();
+    part.push(format!(".seg{}", index));
+    PathBuf::from(part)
+}
 
 /// Download context to avoid too many function arguments
 struct DownloadContext<'a> {
@@ -60,6 +241,10 @@ struct DownloadContext<'a> {
     file_info: &'a ArchiveFile,
     progress_bar: &'a ProgressBar,
     resume_from: u64,
+    resume: &'a ResumeTracker,
+    server_health: &'a ServerHealthTracker,
+    control: &'a DownloadControl,
+    hasher: StreamingHasher,
 }
 
 /// Enhanced downloader that uses full Archive.org metadata
@@ -71,6 +256,13 @@ pub struct ArchiveDownloader {
     session_dir: PathBuf,
     enable_compression: bool,
     auto_decompress: bool,
+    server_health: Arc<ServerHealthTracker>,
+    control: DownloadControl,
+    hooks: Option<Arc<HookRunner>>,
+    /// Retry policy of this downloader; the process-wide one when None
+    retry_policy: Option<RetryPolicy>,
+    /// Files fetched again even where a local copy exists
+    redownload: Vec<String>,
 }
 
 impl ArchiveDownloader {
@@ -92,9 +284,44 @@ impl ArchiveDownloader {
             session_dir,
             enable_compression,
             auto_decompress,
+            server_health: Arc::new(ServerHealthTracker::new()),
+            control: DownloadControl::new(),
+            hooks: None,
+            retry_policy: None,
+            redownload: Vec::new(),
         }
     }
 
+    /// Let `control` pause, resume or cancel this downloader's transfers
+    pub fn with_control(mut self, control: DownloadControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Run the per-file hooks of `hooks` as each file finishes
+    pub fn with_hooks(mut self, hooks: Arc<HookRunner>) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// Retry failed transfers under `policy` instead of the process-wide one
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Fetch `files` again even where a local copy exists; each copy is
+    /// replaced only once its new download is complete
+    pub fn with_redownload(mut self, files: Vec<String>) -> Self {
+        self.redownload = files;
+        self
+    }
+
+    /// Health statistics gathered for each server during this downloader's session
+    pub fn server_health(&self) -> Arc<ServerHealthTracker> {
+        self.server_health.clone()
+    }
+
     /// Download files using comprehensive metadata and session management
     #[allow(clippy::too_many_arguments)]
     pub async fn download_with_metadata(
@@ -117,6 +344,36 @@ impl ArchiveDownloader {
                 requested_files,
             )
             .await?;
+        self.mark_redownloads(&mut session).await;
+
+        // Files already stored in the output container count as done; anything
+        // marked complete but missing from it has to be fetched again
+        let container = match session.download_config.output_container.clone() {
+            Some(path) => {
+                let writer = tokio::task::spawn_blocking(move || ContainerWriter::open(path))
+                    .await
+                    .map_err(|e| IaGetError::FileSystem(format!("Task join error: {}", e)))??;
+                for (name, status) in session.file_status.iter_mut() {
+                    if writer.contains(name) {
+                        status.status = DownloadState::Completed;
+                        status.bytes_downloaded = status.file_info.size.unwrap_or(0);
+                    } else if status.status == DownloadState::Completed {
+                        status.status = DownloadState::Pending;
+                        status.bytes_downloaded = 0;
+                    }
+                }
+                Some(Arc::new(std::sync::Mutex::new(writer)))
+            }
+            None => None,
+        };
+
+        let store = session
+            .download_config
+            .store_dir
+            .as_ref()
+            .map(ContentStore::open)
+            .transpose()?
+            .map(Arc::new);
 
         // Create session directory if it doesn't exist
         tokio::fs::create_dir_all(&self.session_dir)
@@ -130,6 +387,10 @@ impl ArchiveDownloader {
             .session_dir
             .join(crate::core::session::generate_session_filename(&identifier));
         session.save_to_file(&session_file)?;
+        let checkpoint = Arc::new(SessionCheckpoint::new(
+            session_file.clone(),
+            session.clone(),
+        ));
 
         progress_bar.set_message("Initializing downloads...".to_string());
 
@@ -140,7 +401,24 @@ impl ArchiveDownloader {
             .map(|s| s.to_string())
             .collect();
 
+        emit_progress_event(ProgressEvent::SessionStarted {
+            identifier: identifier.clone(),
+            files: pending_files.len(),
+            bytes: pending_files
+                .iter()
+                .filter_map(|name| session.file_status.get(name))
+                .filter_map(|status| status.file_info.size)
+                .sum(),
+        });
+
         if pending_files.is_empty() {
+            Self::finish_container(container).await?;
+            emit_progress_event(ProgressEvent::SessionCompleted {
+                identifier,
+                completed: 0,
+                failed: 0,
+                interrupted: 0,
+            });
             progress_bar.finish_with_message("All files already downloaded".green().to_string());
             return Ok(session);
         }
@@ -189,25 +467,76 @@ impl ArchiveDownloader {
             (None, None, Vec::new())
         };
 
+        // Every datanode that can serve the item, in Archive.org's preferred order
+        let mut candidate_servers = session.archive_metadata.workable_servers.clone();
+        for datanode in [&session.archive_metadata.d1, &session.archive_metadata.d2] {
+            if !datanode.is_empty() && !candidate_servers.contains(datanode) {
+                candidate_servers.push(datanode.clone());
+            }
+        }
+
         // Create semaphore for concurrency control
         let semaphore = Arc::new(Semaphore::new(self.max_concurrent));
         let mut handles = Vec::new();
 
+        // Each task queues for a permit only once the file before it has one, so
+        // files start in the session's download order
+        let (first_tx, mut previous_turn) = oneshot::channel::<()>();
+        let _ = first_tx.send(());
+
         // Start downloads for pending files
         for file_name in pending_files {
             if let Some(file_status) = session.file_status.get(&file_name) {
                 let file_info = file_status.file_info.clone();
-                let servers = session.archive_metadata.workable_servers.clone();
+                let servers = candidate_servers.clone();
+                let server_health = self.server_health.clone();
                 let dir = session.archive_metadata.dir.clone();
                 let output_path = PathBuf::from(&file_status.local_path);
+                let mtime = file_status.file_info.mtime;
+                let staged_path = output_path.clone();
 
                 let client = self.client.clone();
                 let semaphore_clone = semaphore.clone();
                 let verify_md5 = self.verify_md5;
+                let checksum_policy = session.download_config.checksum_policy;
                 let preserve_mtime = self.preserve_mtime;
                 let _enable_compression = self.enable_compression; // Compression now always enabled per IA docs
                 let auto_decompress = self.auto_decompress;
                 let decompress_formats = session.download_config.decompress_formats.clone();
+                let segment_tracker: SegmentTracker = Arc::default();
+                let segment_options = SegmentOptions {
+                    count: session.download_config.segments_per_file as usize,
+                    min_size: session
+                        .download_config
+                        .segment_min_size
+                        .unwrap_or(DEFAULT_SEGMENT_MIN_SIZE),
+                    tracker: segment_tracker.clone(),
+                };
+                let resume = ResumeTracker {
+                    file_name: file_name.clone(),
+                    checkpoint: checkpoint.clone(),
+                    checked: verify_md5
+                        && !file_info.available_checksums().is_empty()
+                        && !is_volatile_metadata(&file_info.name),
+                    replace_local_copy: session
+                        .file_status
+                        .get(&file_name)
+                        .is_some_and(|status| status.replace_local_copy),
+                };
+                let container = container.clone();
+                let member_name = file_name.clone();
+                let store = store.clone();
+                let hooks = self.hooks.clone();
+                let hook_identifier = identifier.clone();
+                // Files in an output container no longer exist on their own
+                let hook_path = session
+                    .download_config
+                    .output_container
+                    .clone()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| output_path.clone());
+                let control = self.control.clone();
+                let retry = self.retry_policy.unwrap_or_else(retry_policy);
 
                 let multi_progress_clone = multi_progress.clone();
                 // use_hidden_bars removed as it is implied by pool_tx check
@@ -215,11 +544,25 @@ impl ArchiveDownloader {
                 let pool_tx = pool_tx.clone();
                 let pool_rx = pool_rx.clone();
 
+                let (turn_tx, turn_rx) = oneshot::channel::<()>();
+                let my_turn = std::mem::replace(&mut previous_turn, turn_rx);
+                let metrics = download_metrics();
+                let waiting = metrics.as_ref().map(|metrics| metrics.wait_for_slot());
+
                 let handle = tokio::spawn(async move {
+                    let _ = my_turn.await;
                     let _permit = semaphore_clone
                         .acquire()
                         .await
                         .expect("Semaphore closed unexpectedly");
+                    let _ = turn_tx.send(());
+                    drop(waiting);
+
+                    // Files still queued when a pause comes in wait here
+                    if !control.wait_while_paused().await {
+                        return Err(IaGetError::Interrupted);
+                    }
+                    let _active = metrics.as_ref().map(|metrics| metrics.start_transfer());
 
                     // Get progress bar from pool or create new hidden one
                     let file_progress = if let (Some(_), Some(rx)) = (&pool_tx, &pool_rx) {
@@ -238,19 +581,108 @@ impl ArchiveDownloader {
                         multi_progress_clone.add(ProgressBar::hidden())
                     };
 
-                    let result = Self::download_single_file(
-                        client,
-                        file_info,
-                        servers,
-                        dir,
-                        output_path,
-                        verify_md5,
-                        preserve_mtime,
-                        auto_decompress,
-                        decompress_formats,
-                        file_progress.clone(),
-                    )
-                    .await;
+                    emit_progress_event(ProgressEvent::FileStarted {
+                        file: member_name.clone(),
+                        size: file_info.size,
+                    });
+                    let progress_events = progress_events_enabled().then(|| {
+                        Self::report_file_progress(&member_name, &file_progress, file_info.size)
+                    });
+
+                    let stored = store
+                        .as_deref()
+                        .and_then(|store| Self::link_from_store(store, &file_info, &output_path));
+
+                    let result = if let Some(evidence) = stored {
+                        file_progress.set_message(format!("✓ Linked {} from store", member_name));
+                        Ok(vec![evidence])
+                    } else {
+                        let stored_info = file_info.clone();
+                        let result = loop {
+                            let result = Self::download_single_file(
+                                client.clone(),
+                                file_info.clone(),
+                                servers.clone(),
+                                dir.clone(),
+                                output_path.clone(),
+                                verify_md5,
+                                checksum_policy,
+                                preserve_mtime,
+                                auto_decompress,
+                                decompress_formats.clone(),
+                                segment_options.clone(),
+                                resume.clone(),
+                                server_health.clone(),
+                                control.clone(),
+                                retry,
+                                file_progress.clone(),
+                            )
+                            .await;
+
+                            // A paused transfer has flushed its partial file; continue
+                            // from it once the run is resumed
+                            if matches!(result, Err(IaGetError::Interrupted)) && control.is_paused()
+                            {
+                                file_progress.set_message(format!("⏸ Paused {}", member_name));
+                                resume.set_state(DownloadState::Paused);
+                                if control.wait_while_paused().await {
+                                    resume.set_state(DownloadState::InProgress);
+                                    continue;
+                                }
+                            }
+                            break result;
+                        };
+                        if result.is_ok() {
+                            resume.set_state(DownloadState::Completed);
+                        }
+                        if let (Ok(checksum_results), Some(store)) = (&result, &store) {
+                            Self::add_to_store(store, &stored_info, checksum_results, &staged_path);
+                        }
+                        result
+                    };
+
+                    let result = match (result, container) {
+                        (Ok(checksum_results), Some(container)) => {
+                            file_progress
+                                .set_message(format!("Adding {} to container", member_name));
+                            Self::add_to_container(container, member_name, staged_path, mtime)
+                                .await
+                                .map(|_| checksum_results)
+                        }
+                        (result, _) => result,
+                    };
+
+                    if let Some(reporter) = progress_events {
+                        reporter.abort();
+                    }
+                    match &result {
+                        Ok(checksum_results) => emit_progress_event(ProgressEvent::FileVerified {
+                            file: file_info.name.clone(),
+                            bytes: file_info.size.unwrap_or_else(|| file_progress.position()),
+                            checksums: checksum_results
+                                .iter()
+                                .filter(|check| check.matched)
+                                .map(|check| check.algorithm.as_str().to_string())
+                                .collect(),
+                        }),
+                        Err(IaGetError::Interrupted) => {}
+                        Err(e) => {
+                            emit_progress_event(ProgressEvent::file_failed(&file_info.name, e))
+                        }
+                    }
+
+                    if let Some(hooks) = hooks {
+                        let mut context =
+                            HookContext::for_file(&hook_identifier, &file_info, &hook_path);
+                        match &result {
+                            Ok(_) => hooks.run(HookEvent::FileSuccess, context),
+                            Err(IaGetError::Interrupted) => {}
+                            Err(e) => {
+                                context.error = Some(e.to_string());
+                                hooks.run(HookEvent::FileFailure, context);
+                            }
+                        }
+                    }
 
                     // Return bar to pool or clear it
                     if let Some(tx) = pool_tx {
@@ -264,22 +696,48 @@ impl ArchiveDownloader {
                     result
                 });
 
-                handles.push((file_name, handle));
+                handles.push((file_name, segment_tracker, handle));
             }
         }
 
         // Wait for all downloads to complete and update session
         let mut completed = 0;
         let mut failed = 0;
+        let mut interrupted = 0;
         let total_files = handles.len();
 
         // Update progress message less frequently to reduce screen spam
         let mut last_update = std::time::Instant::now();
         const UPDATE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
 
-        for (file_name, handle) in handles {
-            match handle.await {
-                Ok(Ok(_)) => {
+        for (file_name, segment_tracker, handle) in handles {
+            let result = handle.await;
+
+            // Record per-segment progress so interrupted segmented downloads can be inspected
+            let segments = segment_tracker
+                .lock()
+                .map(|segments| segments.clone())
+                .unwrap_or_default();
+            if !segments.is_empty() {
+                if let Some(file_status) = session.file_status.get_mut(&file_name) {
+                    file_status.segments = segments;
+                }
+            }
+
+            // Keep validators of unfinished files so the next run can resume them safely
+            if let Some(file_status) = session.file_status.get_mut(&file_name) {
+                file_status.resume_validator = match result {
+                    Ok(Ok(_)) => None,
+                    _ => checkpoint.validator(&file_name),
+                };
+            }
+
+            match result {
+                Ok(Ok(checksum_results)) => {
+                    if let Some(file_status) = session.file_status.get_mut(&file_name) {
+                        file_status.checksum_results = checksum_results;
+                        file_status.replace_local_copy = false;
+                    }
                     session.update_file_status(&file_name, DownloadState::Completed);
                     completed += 1;
                     main_progress.inc(1);
@@ -311,6 +769,11 @@ impl ArchiveDownloader {
                         last_update = now;
                     }
                 }
+                Ok(Err(IaGetError::Interrupted)) => {
+                    // Left as partial files for a later run to resume
+                    session.update_file_status(&file_name, DownloadState::Paused);
+                    interrupted += 1;
+                }
                 Ok(Err(e)) => {
                     session.update_file_status(&file_name, DownloadState::Failed);
                     if let Some(file_status) = session.file_status.get_mut(&file_name) {
@@ -355,6 +818,11 @@ impl ArchiveDownloader {
                     if let Some(file_status) = session.file_status.get_mut(&file_name) {
                         file_status.error_message = Some(format!("Task join error: {}", e));
                     }
+                    emit_progress_event(ProgressEvent::FileFailed {
+                        file: file_name.clone(),
+                        error_kind: "internal".to_string(),
+                        error: format!("Task join error: {}", e),
+                    });
                     failed += 1;
                     main_progress.inc(1);
 
@@ -397,10 +865,29 @@ impl ArchiveDownloader {
             pb.finish_and_clear();
         }
 
-        // Save final session state
+        Self::finish_container(container).await?;
+
+        // Save final session state, superseding everything journaled during the run
+        session.journal_seq = session.journal_seq.max(checkpoint.journal_seq());
         session.save_to_file(&session_file)?;
 
-        if failed == 0 {
+        emit_progress_event(ProgressEvent::SessionCompleted {
+            identifier,
+            completed,
+            failed,
+            interrupted,
+        });
+
+        if interrupted > 0 {
+            main_progress.finish_with_message(
+                format!(
+                    "⏸ Stopped after {} files, {} left to resume",
+                    completed, interrupted
+                )
+                .yellow()
+                .to_string(),
+            );
+        } else if failed == 0 {
             main_progress.finish_with_message(
                 format!("✓ Successfully downloaded {} files", completed)
                     .green()
@@ -417,6 +904,111 @@ impl ArchiveDownloader {
         Ok(session)
     }
 
+    /// Emit `file_progress` events from `progress_bar` until the returned task is aborted
+    fn report_file_progress(
+        file_name: &str,
+        progress_bar: &ProgressBar,
+        size: Option<u64>,
+    ) -> tokio::task::JoinHandle<()> {
+        let file = file_name.to_string();
+        let progress_bar = progress_bar.clone();
+        tokio::spawn(async move {
+            let mut last_reported = None;
+            let mut interval = tokio::time::interval(PROGRESS_EVENT_INTERVAL);
+            loop {
+                interval.tick().await;
+                let bytes = progress_bar.position();
+                if last_reported != Some(bytes) {
+                    last_reported = Some(bytes);
+                    emit_progress_event(ProgressEvent::FileProgress {
+                        file: file.clone(),
+                        bytes,
+                        size,
+                    });
+                }
+            }
+        })
+    }
+
+    /// Hardlink (or copy) a stored copy of `file_info` to `output_path`,
+    /// returning the checksum the stored copy was verified against
+    ///
+    /// Store failures never fail the download; the file is fetched instead.
+    fn link_from_store(
+        store: &ContentStore,
+        file_info: &ArchiveFile,
+        output_path: &Path,
+    ) -> Option<ChecksumResult> {
+        if output_path.exists() {
+            return None;
+        }
+        store.link_into(file_info, output_path).unwrap_or_else(|e| {
+            eprintln!(
+                "⚠️  Content store lookup failed for {}: {}",
+                file_info.name, e
+            );
+            None
+        })
+    }
+
+    /// Add a download to the store once every checksum it was verified against matched
+    fn add_to_store(
+        store: &ContentStore,
+        file_info: &ArchiveFile,
+        checksum_results: &[ChecksumResult],
+        output_path: &Path,
+    ) {
+        if checksum_results.is_empty() || !checksum_results.iter().all(|r| r.matched) {
+            return;
+        }
+        if let Err(e) = store.insert(file_info, output_path) {
+            eprintln!(
+                "⚠️  Failed to add {} to content store: {}",
+                file_info.name, e
+            );
+        }
+    }
+
+    /// Move a verified download from the staging directory into the output container
+    async fn add_to_container(
+        container: Arc<std::sync::Mutex<ContainerWriter>>,
+        file_name: String,
+        staged_path: PathBuf,
+        mtime: Option<u64>,
+    ) -> Result<()> {
+        tokio::task::spawn_blocking(move || {
+            let mut writer = container.lock().map_err(|_| {
+                IaGetError::FileSystem("Output container lock poisoned".to_string())
+            })?;
+            writer.add_file(&file_name, &staged_path, mtime)?;
+            std::fs::remove_file(&staged_path).map_err(|e| {
+                IaGetError::FileSystem(format!(
+                    "Failed to remove staged file {}: {}",
+                    staged_path.display(),
+                    e
+                ))
+            })
+        })
+        .await
+        .map_err(|e| IaGetError::FileSystem(format!("Task join error: {}", e)))?
+    }
+
+    /// Write the container trailer once every download task has finished
+    async fn finish_container(
+        container: Option<Arc<std::sync::Mutex<ContainerWriter>>>,
+    ) -> Result<()> {
+        let Some(container) = container else {
+            return Ok(());
+        };
+        let writer = Arc::try_unwrap(container)
+            .map_err(|_| IaGetError::FileSystem("Output container still in use".to_string()))?
+            .into_inner()
+            .map_err(|_| IaGetError::FileSystem("Output container lock poisoned".to_string()))?;
+        tokio::task::spawn_blocking(move || writer.finish())
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Task join error: {}", e)))?
+    }
+
     /// Download a single file with retry logic and proper Archive.org server usage
     #[allow(clippy::too_many_arguments)]
     async fn download_single_file(
@@ -426,11 +1018,17 @@ impl ArchiveDownloader {
         dir: String,
         output_path: PathBuf,
         verify_md5: bool,
+        checksum_policy: ChecksumPolicy,
         preserve_mtime: bool,
         auto_decompress: bool,
         decompress_formats: Vec<String>,
+        segment_options: SegmentOptions,
+        resume: ResumeTracker,
+        server_health: Arc<ServerHealthTracker>,
+        control: DownloadControl,
+        policy: RetryPolicy,
         progress_bar: ProgressBar,
-    ) -> Result<()> {
+    ) -> Result<Vec<ChecksumResult>> {
         // Create output directory if it doesn't exist
         if let Some(parent) = output_path.parent() {
             tokio::fs::create_dir_all(parent).await.map_err(|e| {
@@ -438,31 +1036,42 @@ impl ArchiveDownloader {
             })?;
         }
 
-        // Check if file already exists and is valid
-        if output_path.exists() {
-            if verify_md5 && file_info.md5.is_some() {
+        // Check if file already exists and is valid; an outdated copy stays
+        // in place until its replacement has downloaded
+        if output_path.exists() && !resume.replace_local_copy {
+            if verify_md5 && !file_info.available_checksums().is_empty() {
                 progress_bar.set_message(format!("Verifying existing {}", file_info.name));
 
-                // Convert async path to sync for MD5 calculation
-                let path_str = output_path.to_string_lossy().to_string();
+                // Hash on a blocking thread; large files take a while
+                let path = output_path.clone();
                 let file_info_clone = file_info.clone();
-                let validation_result =
-                    tokio::task::spawn_blocking(move || file_info_clone.validate_md5(&path_str))
-                        .await
-                        .map_err(|e| {
-                            IaGetError::Network(format!("MD5 validation task failed: {}", e))
-                        })??;
+                let checksum_results = tokio::task::spawn_blocking(move || {
+                    file_info_clone.validate_checksums(&path, checksum_policy)
+                })
+                .await
+                .map_err(|e| {
+                    IaGetError::Network(format!("Checksum validation task failed: {}", e))
+                })??;
 
-                if validation_result {
+                let missing = checksum_results.iter().find(|r| r.is_missing());
+                if checksum_results.iter().all(|r| r.matched) {
                     progress_bar.set_message(
                         format!("✓ {} already exists and is valid", file_info.name)
                             .green()
                             .to_string(),
                     );
-                    return Ok(());
+                    return Ok(checksum_results);
+                } else if let Some(missing) = missing
+                    && !is_volatile_metadata(&file_info.name)
+                {
+                    let error = checksum_unavailable(&file_info, missing);
+                    progress_bar.set_message(format!("✘ {}", error).red().to_string());
+                    return Err(error);
                 } else {
-                    progress_bar
-                        .set_message(format!("MD5 mismatch, re-downloading {}", file_info.name));
+                    progress_bar.set_message(format!(
+                        "Checksum mismatch, re-downloading {}",
+                        file_info.name
+                    ));
                 }
             } else {
                 progress_bar.set_message(
@@ -473,71 +1082,135 @@ impl ArchiveDownloader {
                     .yellow()
                     .to_string(),
                 );
-                return Ok(());
+                return Ok(Vec::new());
             }
         }
 
         let mut last_error = None;
+        let mut use_segments = segment_options.applies_to(&file_info);
+
+        let mut tried: Vec<String> = Vec::new();
 
-        // Try each server in order (following Archive.org recommendations)
-        for (attempt, server) in servers.iter().enumerate() {
-            if attempt >= MAX_SERVER_ATTEMPTS {
+        // Try the healthiest untried server on each attempt; ties keep Archive.org's order
+        let attempts = servers.len().min(policy.max_attempts() as usize);
+        for attempt in 0..attempts {
+            let ranked = server_health.ranked_servers(&servers).await;
+            let Some(server) = ranked.iter().find(|s| !tried.contains(s)).cloned() else {
                 break;
+            };
+            tried.push(server.clone());
+            let server = &server;
+            if attempt > 0 {
+                server_health.performance_monitor().record_retry().await;
+            }
+            let attempt_started = std::time::Instant::now();
+            let segmented_attempt = use_segments;
+            let mut transferred = 0;
+
+            let download_result = if use_segments {
+                progress_bar.set_message(format!(
+                    "Downloading {} in {} segments",
+                    file_info.name, segment_options.count
+                ));
+
+                // Lead with the chosen server, then spread segments over the rest by health
+                let urls: Vec<String> = std::iter::once(server)
+                    .chain(ranked.iter().filter(|s| *s != server))
+                    .map(|server| file_info.get_download_url(server, &dir))
+                    .collect();
+
+                Self::download_segmented(
+                    &client,
+                    urls,
+                    &output_path,
+                    &file_info,
+                    &segment_options,
+                    &resume,
+                    &server_health,
+                    &control,
+                    policy,
+                    &progress_bar,
+                )
+                .await
+            } else {
+                let download_url = file_info.get_download_url(server, &dir);
+                progress_bar.set_message(format!("Downloading {} from {}", file_info.name, server));
+
+                Self::download_from_url(
+                    &client,
+                    &download_url,
+                    &output_path,
+                    &file_info,
+                    &resume,
+                    &server_health,
+                    &control,
+                    policy,
+                    &progress_bar,
+                )
+                .await
+                .map(|(digests, bytes)| {
+                    transferred = bytes;
+                    digests
+                })
+            };
+
+            // Segmented attempts report health per segment
+            if !segmented_attempt {
+                match &download_result {
+                    Ok(_) => {
+                        server_health
+                            .record_success(server, transferred, attempt_started.elapsed())
+                            .await
+                    }
+                    // Stopping on request says nothing about the server
+                    Err(IaGetError::Interrupted) => {}
+                    Err(e) => {
+                        server_health
+                            .record_failure(server, ServerFailure::from_error(e))
+                            .await
+                    }
+                }
             }
 
-            let download_url = file_info.get_download_url(server, &dir);
-            progress_bar.set_message(format!("Downloading {} from {}", file_info.name, server));
+            match download_result {
+                Ok(digests) => {
+                    // Checksums were computed while streaming; compare them against the
+                    // published values selected by the policy
+                    let checksum_results = if verify_md5 {
+                        file_info.verify_checksums(&digests, checksum_policy)
+                    } else {
+                        Vec::new()
+                    };
 
-            match Self::download_from_url(
-                &client,
-                &download_url,
-                &output_path,
-                &file_info,
-                &progress_bar,
-            )
-            .await
-            {
-                Ok(_) => {
-                    // Verify MD5 if required and available
-                    if verify_md5 && file_info.md5.is_some() {
-                        progress_bar.set_message(format!("Verifying {}", file_info.name));
+                    if let Some(mismatch) = checksum_results.iter().find(|r| !r.matched) {
+                        let algorithm = mismatch.algorithm.as_str().to_uppercase();
 
-                        let path_str = output_path.to_string_lossy().to_string();
-                        let file_info_clone = file_info.clone();
-                        let validation_result = tokio::task::spawn_blocking(move || {
-                            file_info_clone.validate_md5(&path_str)
-                        })
-                        .await
-                        .map_err(|e| {
-                            IaGetError::Network(format!("MD5 validation task failed: {}", e))
-                        })??;
-
-                        if !validation_result {
-                            // Check for metadata files that frequently change
-                            let is_metadata = file_info.name.ends_with("_meta.xml")
-                                || file_info.name.ends_with("_reviews.xml")
-                                || file_info.name.ends_with("_files.xml")
-                                || file_info.name.ends_with("_meta.sqlite")
-                                || file_info.name.ends_with("_archive.torrent")
-                                || file_info.name == "__ia_thumb.jpg";
-
-                            if is_metadata {
-                                progress_bar.set_message(format!(
-                                    "⚠ MD5 mismatch for {} (likely updated). Accepting.",
-                                    file_info.name
-                                ));
-                                // Allow execution to proceed to success (skip the failure block)
-                            } else {
-                                let error_msg =
-                                    format!("MD5 verification failed for {}", file_info.name);
-                                progress_bar
-                                    .set_message(format!("✘ {}", error_msg).red().to_string());
-
-                                // Remove invalid file
-                                let _ = tokio::fs::remove_file(&output_path).await;
-                                last_error = Some(IaGetError::HashMismatch(error_msg));
-                                continue;
+                        if is_volatile_metadata(&file_info.name) {
+                            progress_bar.set_message(format!(
+                                "⚠ {} mismatch for {} (likely updated). Accepting.",
+                                algorithm, file_info.name
+                            ));
+                            // Allow execution to proceed to success; the mismatch stays recorded
+                        } else if mismatch.is_missing() {
+                            // Not the file's fault, so it is kept and no other server is tried
+                            let error = checksum_unavailable(&file_info, mismatch);
+                            progress_bar.set_message(format!("✘ {}", error).red().to_string());
+                            return Err(error);
+                        } else {
+                            let error_msg = format!(
+                                "{} verification failed for {} (expected {}, got {})",
+                                algorithm, file_info.name, mismatch.expected, mismatch.actual
+                            );
+                            progress_bar.set_message(format!("✘ {}", error_msg).red().to_string());
+
+                            if let Some(metrics) = download_metrics() {
+                                metrics.record_checksum_failure();
                             }
+
+                            // Remove invalid file
+                            let _ = tokio::fs::remove_file(&output_path).await;
+                            last_error = Some(IaGetError::HashMismatch(error_msg));
+                            continue;
                         }
                     }
 
@@ -626,48 +1299,42 @@ impl ArchiveDownloader {
                             .green()
                             .to_string(),
                     );
-                    return Ok(());
+                    return Ok(checksum_results);
                 }
+                Err(IaGetError::Interrupted) => return Err(IaGetError::Interrupted),
+                // Other servers hold the same item; logging in or fixing the name is needed
+                Err(e) if e.is_permanent() => return Err(e),
                 Err(e) => {
                     let error_str = e.to_string();
-                    let should_retry_server = error_str.contains("500")
-                        || error_str.contains("502")
-                        || error_str.contains("503")
-                        || error_str.contains("504")
-                        || error_str.contains("Service Unavailable")
-                        || error_str.contains("Bad Gateway")
-                        || error_str.contains("Gateway Timeout")
-                        || error_str.contains("connection")
-                        || error_str.contains("timeout");
-
-                    let should_backoff_rate_limit =
-                        error_str.contains("429") || error_str.contains("Rate limited");
+
+                    // Servers that ignore byte ranges get a single stream on the next attempt
+                    if use_segments && error_str.contains("byte range") {
+                        use_segments = false;
+                        Self::remove_segment_parts(&output_path, segment_options.count).await;
+                    }
+
+                    let failure = ServerFailure::from_error(&e);
+                    let should_retry_server =
+                        matches!(failure, ServerFailure::ServerError | ServerFailure::Timeout)
+                            || matches!(e, IaGetError::Network(_));
+                    let should_backoff_rate_limit = failure == ServerFailure::RateLimited;
 
                     if should_backoff_rate_limit {
-                        // For rate limiting, wait longer before trying next server
                         progress_bar.set_message(format!(
-                            "Rate limited by IA server {}, backing off before trying next server...", 
+                            "Rate limited by IA server {}, backing off before trying next server...",
                             server
                         ));
-                        let backoff_delay = std::cmp::min(60, 2_u64.pow(attempt as u32)); // Max 60 seconds for rate limits
-                        tokio::time::sleep(std::time::Duration::from_secs(backoff_delay)).await;
                     } else if should_retry_server {
                         progress_bar.set_message(format!(
                             "Server {} unavailable (503/timeout), trying next server...",
                             server
                         ));
-                        // Standard exponential backoff for server errors
-                        if attempt < servers.len() - 1 {
-                            let backoff_delay = std::cmp::min(2_u64.pow(attempt as u32), 30); // Max 30 seconds
-                            tokio::time::sleep(std::time::Duration::from_secs(backoff_delay)).await;
-                        }
                     } else {
                         progress_bar
                             .set_message(format!("Failed from {}, trying next server...", server));
-                        // Quick retry for other errors
-                        if attempt < servers.len() - 1 {
-                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                        }
+                    }
+                    if attempt + 1 < attempts {
+                        tokio::time::sleep(policy.delay(attempt as u32, e.retry_after())).await;
                     }
 
                     last_error = Some(e);
@@ -691,18 +1358,26 @@ impl ArchiveDownloader {
     }
 
     /// Download from a specific URL with progress tracking
+    ///
+    /// Returns the checksums of the whole file and the bytes fetched from
+    /// the server, which leaves out data resumed from a partial download.
+    #[allow(clippy::too_many_arguments)]
     async fn download_from_url(
         client: &Client,
         url: &str,
         output_path: &Path,
         file_info: &ArchiveFile,
+        resume: &ResumeTracker,
+        server_health: &ServerHealthTracker,
+        control: &DownloadControl,
+        policy: RetryPolicy,
         progress_bar: &ProgressBar,
-    ) -> Result<()> {
+    ) -> Result<(FileDigests, u64)> {
         let temp_path = output_path.with_extension("tmp");
 
         // Try download with resume capability
-        for attempt in 0..MAX_RESUME_ATTEMPTS {
-            let resume_from = if temp_path.exists() {
+        for attempt in 0..policy.max_attempts() {
+            let mut resume_from = if temp_path.exists() {
                 match tokio::fs::metadata(&temp_path).await {
                     Ok(metadata) => metadata.len(),
                     Err(_) => 0,
@@ -711,6 +1386,39 @@ impl ArchiveDownloader {
                 0
             };
 
+            // Without a validator a partial can only be kept when the final
+            // checksum confirms the result, and only while it is shorter than
+            // the published size
+            if resume_from > 0
+                && resume.get().is_none()
+                && (!resume.checked || file_info.size.is_none_or(|size| resume_from >= size))
+            {
+                progress_bar.set_message(format!(
+                    "Discarding unverifiable partial download of {}",
+                    file_info.name
+                ));
+                resume_from = 0;
+            }
+
+            // Hash the bytes already on disk once so the checksums cover the whole file
+            let hasher = if resume_from > 0 {
+                let prefix_path = temp_path.clone();
+                match tokio::task::spawn_blocking(move || {
+                    StreamingHasher::resume_from_file(prefix_path, resume_from)
+                })
+                .await
+                {
+                    Ok(Ok(hasher)) => hasher,
+                    // Start over if the partial file cannot be read back
+                    _ => {
+                        resume_from = 0;
+                        StreamingHasher::new()
+                    }
+                }
+            } else {
+                StreamingHasher::new()
+            };
+
             let ctx = DownloadContext {
                 client,
                 url,
@@ -719,30 +1427,447 @@ impl ArchiveDownloader {
                 file_info,
                 progress_bar,
                 resume_from,
+                resume,
+                server_health,
+                control,
+                hasher,
             };
 
             match Self::perform_download(ctx).await {
-                Ok(()) => return Ok(()),
+                Ok(transfer) => return Ok(transfer),
+                Err(IaGetError::Interrupted) => return Err(IaGetError::Interrupted),
+                Err(e) if e.is_permanent() => return Err(e),
                 Err(e) => {
                     // For decoding errors, don't retry with compression disabled since we already do that
-                    if attempt == MAX_RESUME_ATTEMPTS - 1 {
+                    if !policy.can_retry(attempt) {
                         return Err(e);
                     }
 
-                    // Short delay before retry
-                    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+                    tokio::time::sleep(policy.delay(attempt, e.retry_after())).await;
                 }
             }
         }
 
         Err(IaGetError::Network(format!(
             "Failed to download {} after {} resume attempts",
-            file_info.name, MAX_RESUME_ATTEMPTS
+            file_info.name,
+            policy.max_attempts()
         )))
     }
 
+    /// Download a file as concurrent byte ranges and stitch them into `output_path`
+    ///
+    /// Each segment is written to its own part file so an interrupted download resumes
+    /// per segment. Segment `i` starts on `urls[i % urls.len()]` and moves to the next
+    /// mirror on retry.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_segmented(
+        client: &Client,
+        urls: Vec<String>,
+        output_path: &Path,
+        file_info: &ArchiveFile,
+        options: &SegmentOptions,
+        resume: &ResumeTracker,
+        server_health: &Arc<ServerHealthTracker>,
+        control: &DownloadControl,
+        policy: RetryPolicy,
+        progress_bar: &ProgressBar,
+    ) -> Result<FileDigests> {
+        let total_size = file_info.size.ok_or_else(|| {
+            IaGetError::Network(format!(
+                "Cannot segment {} without a known file size",
+                file_info.name
+            ))
+        })?;
+
+        // Pick up whatever earlier attempts already fetched. Without a validator
+        // part files are kept only when the final checksum confirms the
+        // reassembled file, and only while they fit their range.
+        let unchecked = resume.get().is_none() && !resume.checked;
+        let mut plan = plan_segments(total_size, options.count);
+        for segment in plan.iter_mut() {
+            let part_path = segment_part_path(output_path, segment.index);
+            if let Ok(metadata) = tokio::fs::metadata(&part_path).await {
+                if unchecked || metadata.len() > segment.len() {
+                    let _ = tokio::fs::remove_file(&part_path).await;
+                    continue;
+                }
+                segment.bytes_downloaded = metadata.len();
+                segment.completed = segment.bytes_downloaded == segment.len();
+            }
+        }
+
+        if let Ok(mut tracked) = options.tracker.lock() {
+            *tracked = plan.clone();
+        }
+
+        progress_bar.set_length(total_size);
+        progress_bar.set_position(plan.iter().map(|s| s.bytes_downloaded).sum());
+
+        let urls = Arc::new(urls);
+        let mut handles = Vec::new();
+        for segment in plan.into_iter().filter(|s| !s.completed) {
+            let client = client.clone();
+            let urls = urls.clone();
+            let part_path = segment_part_path(output_path, segment.index);
+            let file_name = file_info.name.clone();
+            let tracker = options.tracker.clone();
+            let resume = resume.clone();
+            let server_health = server_health.clone();
+            let control = control.clone();
+            let progress_bar = progress_bar.clone();
+
+            handles.push(tokio::spawn(async move {
+                let mut last_error = None;
+                for attempt in 0..policy.max_attempts() {
+                    let url = &urls[(segment.index + attempt as usize) % urls.len()];
+                    match Self::download_segment(
+                        &client,
+                        url,
+                        &part_path,
+                        &file_name,
+                        &segment,
+                        &tracker,
+                        &resume,
+                        &server_health,
+                        &control,
+                        &progress_bar,
+                    )
+                    .await
+                    {
+                        Ok(()) => return Ok(()),
+                        Err(IaGetError::Interrupted) => return Err(IaGetError::Interrupted),
+                        // Retrying elsewhere will not help if ranges are ignored
+                        Err(e) if e.to_string().contains("byte range") => return Err(e),
+                        Err(e) if e.is_permanent() => return Err(e),
+                        Err(e) => {
+                            server_health
+                                .record_failure(
+                                    &server_from_url(url),
+                                    ServerFailure::from_error(&e),
+                                )
+                                .await;
+                            let delay = policy.delay(attempt, e.retry_after());
+                            last_error = Some(e);
+                            if policy.can_retry(attempt) {
+                                tokio::time::sleep(delay).await;
+                            }
+                        }
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| {
+                    IaGetError::Network(format!(
+                        "Failed to download segment {} of {}",
+                        segment.index, file_name
+                    ))
+                }))
+            }));
+        }
+
+        let mut first_error = None;
+        for handle in handles {
+            let result = handle.await.unwrap_or_else(|e| {
+                Err(IaGetError::Network(format!("Segment task failed: {}", e)))
+            });
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+        if control.state() != ControlState::Running {
+            return Err(IaGetError::Interrupted);
+        }
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+
+        progress_bar.set_message(format!("Joining segments of {}", file_info.name));
+        Self::stitch_segments(output_path, options.count, total_size, &file_info.name).await
+    }
+
+    /// Fetch the missing bytes of a single segment into its part file
+    #[allow(clippy::too_many_arguments)]
+    async fn download_segment(
+        client: &Client,
+        url: &str,
+        part_path: &Path,
+        file_name: &str,
+        segment: &SegmentStatus,
+        tracker: &SegmentTracker,
+        resume: &ResumeTracker,
+        server_health: &ServerHealthTracker,
+        control: &DownloadControl,
+        progress_bar: &ProgressBar,
+    ) -> Result<()> {
+        if control.state() != ControlState::Running {
+            return Err(IaGetError::Interrupted);
+        }
+        let server = server_from_url(url);
+        let mut downloaded = match tokio::fs::metadata(part_path).await {
+            Ok(metadata) => metadata.len().min(segment.len()),
+            Err(_) => 0,
+        };
+
+        let update_tracker = |downloaded: u64, completed: bool| {
+            if let Ok(mut segments) = tracker.lock() {
+                if let Some(tracked) = segments.get_mut(segment.index) {
+                    tracked.bytes_downloaded = downloaded;
+                    tracked.completed = completed;
+                    tracked.server_used = Some(server.clone());
+                }
+            }
+        };
+
+        if downloaded == segment.len() {
+            update_tracker(downloaded, true);
+            return Ok(());
+        }
+
+        // Compressed transfer encodings would break byte offsets, so ask for identity
+        let request_started = std::time::Instant::now();
+        let resumed_from = downloaded;
+        let mut request = client
+            .get(url)
+            .header("Accept-Encoding", "identity")
+            .header("X-Accept-Reduced-Priority", "1")
+            .header(
+                "Range",
+                format!("bytes={}-{}", segment.start + downloaded, segment.end),
+            );
+        // Resumed parts must still match the version of the file they started from
+        let validator = resume.get();
+        let if_range = validator
+            .as_ref()
+            .and_then(|v| v.if_range())
+            .filter(|_| downloaded > 0);
+        if let Some(if_range) = if_range {
+            request = request.header("If-Range", if_range);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| IaGetError::Network(format!("Failed to start segment download: {}", e)))?;
+        server_health
+            .record_connection(&server, request_started.elapsed())
+            .await;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::OK && if_range.is_some() {
+            let _ = tokio::fs::remove_file(part_path).await;
+            update_tracker(0, false);
+            progress_bar.set_position(progress_bar.position().saturating_sub(downloaded));
+            return Err(IaGetError::Network(format!(
+                "Segment {} of {} is stale on the server, restarting it",
+                segment.index, file_name
+            )));
+        }
+        if status == reqwest::StatusCode::OK {
+            return Err(IaGetError::Network(format!(
+                "Server ignored byte range request for {}",
+                file_name
+            )));
+        }
+        if validator.is_none() && status == reqwest::StatusCode::PARTIAL_CONTENT {
+            resume.set(ResumeValidator::from_headers(response.headers()));
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(IaGetError::RateLimited {
+                message: format!(
+                    "Internet Archive throttled segment {} of {}",
+                    segment.index, file_name
+                ),
+                retry_after: retry_after(response.headers()),
+            });
+        }
+        if matches!(
+            status,
+            reqwest::StatusCode::UNAUTHORIZED
+                | reqwest::StatusCode::FORBIDDEN
+                | reqwest::StatusCode::NOT_FOUND
+        ) {
+            return Err(http_status_error(status, url));
+        }
+        if status != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(IaGetError::Http {
+                status: status.as_u16(),
+                message: format!("segment {} of {}", segment.index, file_name),
+            });
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(part_path)
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to open segment file: {}", e)))?;
+
+        use futures_util::StreamExt;
+        let mut stream = response.bytes_stream();
+
+        while downloaded < segment.len() {
+            let next = tokio::select! {
+                biased;
+                _ = control.interrupted() => {
+                    Self::flush_partial(&mut file).await?;
+                    return Err(IaGetError::Interrupted);
+                }
+                next = tokio::time::timeout(
+                    std::time::Duration::from_secs(45), // 45s read timeout per chunk
+                    stream.next(),
+                ) => next,
+            };
+            let chunk = match next {
+                Ok(Some(Ok(bytes))) => bytes,
+                Ok(Some(Err(e))) => {
+                    return Err(IaGetError::Network(format!(
+                        "Connection lost for segment {} of {}: {}",
+                        segment.index, file_name, e
+                    )));
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    return Err(IaGetError::Timeout(format!(
+                        "Read stalled for segment {} of {}: No data received for 45s",
+                        segment.index, file_name
+                    )));
+                }
+            };
+
+            // Never write past the end of the segment, even if the server sends extra bytes
+            let wanted = (segment.len() - downloaded).min(chunk.len() as u64) as usize;
+            file.write_all(&chunk[..wanted])
+                .await
+                .map_err(|e| IaGetError::FileSystem(format!("Failed to write segment: {}", e)))?;
+
+            downloaded += wanted as u64;
+            progress_bar.inc(wanted as u64);
+            update_tracker(downloaded, false);
+
+            crate::infrastructure::http::throttle(wanted).await;
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to flush segment: {}", e)))?;
+
+        if downloaded != segment.len() {
+            return Err(IaGetError::Network(format!(
+                "Segment {} of {} incomplete: expected {} bytes, got {} bytes",
+                segment.index,
+                file_name,
+                segment.len(),
+                downloaded
+            )));
+        }
+
+        update_tracker(downloaded, true);
+        server_health
+            .record_success(
+                &server,
+                downloaded - resumed_from,
+                request_started.elapsed(),
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Flush and sync a partial download so a later attempt can resume from it
+    async fn flush_partial(file: &mut File) -> Result<()> {
+        file.flush()
+            .await
+            .and(file.sync_data().await)
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to flush partial file: {}", e)))
+    }
+
+    /// Concatenate segment part files into the final output file, hashing as they are joined
+    async fn stitch_segments(
+        output_path: &Path,
+        count: usize,
+        total_size: u64,
+        file_name: &str,
+    ) -> Result<FileDigests> {
+        use tokio::io::AsyncReadExt;
+
+        let temp_path = output_path.with_extension("tmp");
+        let segments = plan_segments(total_size, count);
+
+        // The first part becomes the temporary file; the rest are appended to it
+        let first_part = segment_part_path(output_path, 0);
+        let first_len = segments.first().map_or(0, |s| s.len());
+        let hash_path = first_part.clone();
+        let mut hasher = tokio::task::spawn_blocking(move || {
+            StreamingHasher::resume_from_file(hash_path, first_len)
+        })
+        .await
+        .map_err(|e| IaGetError::FileSystem(format!("Checksum task failed: {}", e)))??;
+
+        tokio::fs::rename(&first_part, &temp_path)
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to join segments: {}", e)))?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&temp_path)
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to join segments: {}", e)))?;
+
+        for segment in segments.iter().skip(1) {
+            let part_path = segment_part_path(output_path, segment.index);
+            let mut part = File::open(&part_path)
+                .await
+                .map_err(|e| IaGetError::FileSystem(format!("Failed to open segment: {}", e)))?;
+            let mut buffer = vec![0; 64 * 1024];
+            loop {
+                let bytes_read = part.read(&mut buffer).await.map_err(|e| {
+                    IaGetError::FileSystem(format!("Failed to read segment: {}", e))
+                })?;
+                if bytes_read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..bytes_read]);
+                file.write_all(&buffer[..bytes_read]).await.map_err(|e| {
+                    IaGetError::FileSystem(format!("Failed to join segments: {}", e))
+                })?;
+            }
+            let _ = tokio::fs::remove_file(&part_path).await;
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to flush file: {}", e)))?;
+        drop(file);
+
+        let joined_size = tokio::fs::metadata(&temp_path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        if joined_size != total_size {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(IaGetError::Network(format!(
+                "Joined segments of {} have {} bytes, expected {}",
+                file_name, joined_size, total_size
+            )));
+        }
+
+        tokio::fs::rename(&temp_path, output_path)
+            .await
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to finalize file: {}", e)))?;
+
+        Ok(hasher.finalize())
+    }
+
+    /// Remove any leftover segment part files for `output_path`
+    async fn remove_segment_parts(output_path: &Path, count: usize) {
+        for index in 0..count {
+            let _ = tokio::fs::remove_file(segment_part_path(output_path, index)).await;
+        }
+    }
+
     /// Perform a single download attempt with optional resume
-    async fn perform_download(ctx: DownloadContext<'_>) -> Result<()> {
+    async fn perform_download(ctx: DownloadContext<'_>) -> Result<(FileDigests, u64)> {
+        if ctx.control.state() != ControlState::Running {
+            return Err(IaGetError::Interrupted);
+        }
+        let mut hasher = ctx.hasher;
         let mut request = ctx.client.get(ctx.url);
 
         // Apply Internet Archive recommended compression headers
@@ -755,15 +1880,23 @@ impl ArchiveDownloader {
         // a task for execution can avoid rate limiting"
         request = request.header("X-Accept-Reduced-Priority", "1");
 
-        // Add range header for resume - IA supports partial content requests
+        // Add range header for resume - IA supports partial content requests.
+        // If-Range makes the server send the whole file instead if it has changed.
         if ctx.resume_from > 0 {
             request = request.header("Range", format!("bytes={}-", ctx.resume_from));
+            if let Some(if_range) = ctx.resume.get().as_ref().and_then(|v| v.if_range()) {
+                request = request.header("If-Range", if_range);
+            }
         }
 
+        let request_started = std::time::Instant::now();
         let response = request
             .send()
             .await
             .map_err(|e| IaGetError::Network(format!("Failed to start download: {}", e)))?;
+        ctx.server_health
+            .record_connection(&server_from_url(ctx.url), request_started.elapsed())
+            .await;
 
         // Handle Internet Archive specific HTTP status codes
         let status = response.status();
@@ -771,47 +1904,56 @@ impl ArchiveDownloader {
             return match status {
                 reqwest::StatusCode::TOO_MANY_REQUESTS => {
                     // IA API docs: Handle 429 Too Many Requests with backoff
-                    let retry_after = response
-                        .headers()
-                        .get("retry-after")
-                        .and_then(|h| h.to_str().ok())
-                        .and_then(|s| s.parse::<u64>().ok())
-                        .unwrap_or(60); // Default to 60 seconds if no header
-
-                    Err(IaGetError::Network(format!(
-                        "Rate limited by Internet Archive. Retry after {} seconds. File: {}",
-                        retry_after, ctx.file_info.name
-                    )))
+                    Err(IaGetError::RateLimited {
+                        message: format!("Internet Archive throttled {}", ctx.file_info.name),
+                        retry_after: retry_after(response.headers()),
+                    })
                 }
                 reqwest::StatusCode::SERVICE_UNAVAILABLE => {
                     // IA API docs: 503 Service Unavailable - may be temporary
-                    Err(IaGetError::Network(format!(
-                        "Internet Archive server temporarily unavailable (503). File: {}",
-                        ctx.file_info.name
-                    )))
+                    Err(IaGetError::Http {
+                        status: status.as_u16(),
+                        message: format!(
+                            "Internet Archive server temporarily unavailable. File: {}",
+                            ctx.file_info.name
+                        ),
+                    })
                 }
-                reqwest::StatusCode::NOT_FOUND => Err(IaGetError::Network(format!(
-                    "File not found on Internet Archive (404): {}",
-                    ctx.file_info.name
-                ))),
-                reqwest::StatusCode::FORBIDDEN => Err(IaGetError::Network(format!(
-                    "Access denied/Restricted file (403): {}. This file may be lending-only or private.",
-                    ctx.file_info.name
-                ))),
-                _ => Err(IaGetError::Network(format!(
-                    "HTTP error {}: {} for file: {}",
-                    status,
-                    status.canonical_reason().unwrap_or("Unknown error"),
-                    ctx.file_info.name
-                ))),
+                reqwest::StatusCode::UNAUTHORIZED
+                | reqwest::StatusCode::FORBIDDEN
+                | reqwest::StatusCode::NOT_FOUND => Err(http_status_error(status, ctx.url)),
+                _ => Err(IaGetError::Http {
+                    status: status.as_u16(),
+                    message: format!(
+                        "{} for file: {}",
+                        status.canonical_reason().unwrap_or("Unknown error"),
+                        ctx.file_info.name
+                    ),
+                }),
             };
         }
 
+        // A full response to a resume request means the partial data is stale
+        let mut resume_from = ctx.resume_from;
+        if resume_from > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT {
+            ctx.progress_bar.set_message(format!(
+                "{} changed on the server, restarting download",
+                ctx.file_info.name
+            ));
+            resume_from = 0;
+            hasher = StreamingHasher::new();
+            ctx.progress_bar.set_position(0);
+        }
+        if resume_from == 0 {
+            ctx.resume
+                .set(ResumeValidator::from_headers(response.headers()));
+        }
+
         // Verify Content-Length if available
         let content_length = response.content_length();
         if let (Some(expected_size), Some(content_len)) = (ctx.file_info.size, content_length) {
-            let expected_remaining = if ctx.resume_from > 0 {
-                expected_size.saturating_sub(ctx.resume_from)
+            let expected_remaining = if resume_from > 0 {
+                expected_size.saturating_sub(resume_from)
             } else {
                 expected_size
             };
@@ -827,13 +1969,13 @@ impl ArchiveDownloader {
         // Set up progress bar with file size
         if let Some(total_size) = ctx.file_info.size {
             ctx.progress_bar.set_length(total_size);
-            if ctx.resume_from > 0 {
-                ctx.progress_bar.set_position(ctx.resume_from);
+            if resume_from > 0 {
+                ctx.progress_bar.set_position(resume_from);
             }
         }
 
         // Create or open temporary file for writing
-        let mut file = if ctx.resume_from > 0 {
+        let mut file = if resume_from > 0 {
             tokio::fs::OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -852,24 +1994,30 @@ impl ArchiveDownloader {
         };
 
         // Download with progress tracking
-        let mut downloaded = ctx.resume_from;
+        let mut downloaded = resume_from;
 
         use futures_util::StreamExt;
         let mut stream = response.bytes_stream();
 
         loop {
             // Wrap stream read in a timeout to detect stalled connections
-            let chunk_result = match tokio::time::timeout(
-                std::time::Duration::from_secs(45), // 45s read timeout per chunk
-                stream.next(),
-            )
-            .await
-            {
+            let next = tokio::select! {
+                biased;
+                _ = ctx.control.interrupted() => {
+                    Self::flush_partial(&mut file).await?;
+                    return Err(IaGetError::Interrupted);
+                }
+                next = tokio::time::timeout(
+                    std::time::Duration::from_secs(45), // 45s read timeout per chunk
+                    stream.next(),
+                ) => next,
+            };
+            let chunk_result = match next {
                 Ok(Some(res)) => res,
                 Ok(None) => break, // Stream finished
                 Err(_) => {
-                    return Err(IaGetError::Network(format!(
-                        "Read timeout (stall) for {}: No data received for 45s",
+                    return Err(IaGetError::Timeout(format!(
+                        "Read stalled for {}: No data received for 45s",
                         ctx.file_info.name
                     )));
                 }
@@ -886,11 +2034,11 @@ impl ArchiveDownloader {
                             "Compression decode error for {}: {}. Server may have sent corrupted compressed data. Will retry.",
                             ctx.file_info.name, e
                         )
-                    } else if e.to_string().to_lowercase().contains("timeout") {
-                        format!(
+                    } else if e.is_timeout() {
+                        return Err(IaGetError::Timeout(format!(
                             "Download timeout for {}: {}. File may be large or Internet Archive server is busy.",
                             ctx.file_info.name, e
-                        )
+                        )));
                     } else if e.to_string().to_lowercase().contains("connection") {
                         format!(
                             "Connection lost for {}: {}. Will retry with exponential backoff.",
@@ -910,8 +2058,11 @@ impl ArchiveDownloader {
                 .await
                 .map_err(|e| IaGetError::FileSystem(format!("Failed to write to file: {}", e)))?;
 
+            hasher.update(&chunk);
             downloaded += chunk.len() as u64;
             ctx.progress_bar.set_position(downloaded);
+
+            crate::infrastructure::http::throttle(chunk.len()).await;
         }
 
         // Ensure all data is written
@@ -924,12 +2075,7 @@ impl ArchiveDownloader {
         if let Some(expected_size) = ctx.file_info.size {
             if downloaded != expected_size {
                 // Check if this is a dynamic metadata file that often changes
-                let is_metadata = ctx.file_info.name.ends_with("_meta.xml")
-                    || ctx.file_info.name.ends_with("_reviews.xml")
-                    || ctx.file_info.name.ends_with("_files.xml")
-                    || ctx.file_info.name.ends_with("_meta.sqlite")
-                    || ctx.file_info.name.ends_with("_archive.torrent")
-                    || ctx.file_info.name == "__ia_thumb.jpg";
+                let is_metadata = is_volatile_metadata(&ctx.file_info.name);
 
                 // If it's a metadata file and we got a successful download (just different size), accept it
                 // We only do this if downloaded > 0 to ensure we got *something*
@@ -953,7 +2099,7 @@ impl ArchiveDownloader {
             .await
             .map_err(|e| IaGetError::FileSystem(format!("Failed to finalize file: {}", e)))?;
 
-        Ok(())
+        Ok((hasher.finalize(), downloaded - resume_from))
     }
 
     /// Create or resume an existing download session
@@ -971,6 +2117,21 @@ impl ArchiveDownloader {
             &self.session_dir.to_string_lossy(),
         ) {
             if let Ok(mut existing_session) = DownloadSession::load_from_file(&session_file) {
+                // Files re-derived since the session was created must not resume stale data
+                if existing_session.item_changed(&archive_metadata) {
+                    let changed = existing_session.apply_item_update(archive_metadata.clone());
+                    if !changed.is_empty() {
+                        eprintln!(
+                            "⚠️  Warning: {} was updated on archive.org since the last session; restarting {} changed file(s)",
+                            identifier,
+                            changed.len()
+                        );
+                    }
+                    for file_name in &changed {
+                        Self::discard_partial_data(&existing_session, file_name).await;
+                    }
+                }
+
                 // Update with any new files that weren't in the original session
                 for file_name in &requested_files {
                     if !existing_session.file_status.contains_key(file_name) {
@@ -1015,6 +2176,10 @@ impl ArchiveDownloader {
                                     retry_count: 0,
                                     server_used: None,
                                     local_path,
+                                    segments: Vec::new(),
+                                    checksum_results: Vec::new(),
+                                    resume_validator: None,
+                                    replace_local_copy: false,
                                 },
                             );
                         }
@@ -1049,4 +2214,27 @@ impl ArchiveDownloader {
 
         Ok(session)
     }
+
+    /// Reset the files asked for again, whatever an earlier session recorded
+    async fn mark_redownloads(&self, session: &mut DownloadSession) {
+        for file_name in &self.redownload {
+            if let Some(file_status) = session.file_status.get_mut(file_name) {
+                file_status.mark_for_replacement();
+                Self::discard_partial_data(session, file_name).await;
+            }
+        }
+    }
+
+    /// Remove partial data of `file_name` left by an earlier attempt
+    async fn discard_partial_data(session: &DownloadSession, file_name: &str) {
+        if let Some(file_status) = session.file_status.get(file_name) {
+            let local_path = PathBuf::from(&file_status.local_path);
+            let _ = tokio::fs::remove_file(local_path.with_extension("tmp")).await;
+            Self::remove_segment_parts(
+                &local_path,
+                session.download_config.segments_per_file as usize,
+            )
+            .await;
+        }
+    }
 }
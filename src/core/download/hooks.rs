@@ -0,0 +1,306 @@
+//! Post-download hooks
+//!
+//! Commands configured in `Config` run when a file or a whole item finishes,
+//! one command for each of success and failure. They run through the shell
+//! (`sh -c`, or `cmd /C` on Windows) with the details in environment
+//! variables:
+//!
+//! | Variable | Value |
+//! |---|---|
+//! | `IA_GET_HOOK` | `file_success`, `file_failure`, `item_success` or `item_failure` |
+//! | `IA_GET_IDENTIFIER` | Item identifier |
+//! | `IA_GET_FILE` | File name in the item (file hooks only) |
+//! | `IA_GET_PATH` | Local path of the file, or the output directory for item hooks |
+//! | `IA_GET_MD5` | Published MD5 (file hooks, when known) |
+//! | `IA_GET_SIZE` | Size in bytes (file hooks, when known) |
+//! | `IA_GET_FORMAT` | Archive.org format name (file hooks, when known) |
+//! | `IA_GET_STATUS` | `success` or `failed` |
+//! | `IA_GET_ERROR` | Error message (failure hooks only) |
+//!
+//! At most `hook_concurrency` commands run at once and each is killed after
+//! `hook_timeout` seconds. Hooks never fail the download; their outcomes are
+//! recorded under `hooks` in the item's download history metadata.
+
+use crate::{core::session::ArchiveFile, infrastructure::config::Config};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    FileSuccess,
+    FileFailure,
+    ItemSuccess,
+    ItemFailure,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::FileSuccess => "file_success",
+            HookEvent::FileFailure => "file_failure",
+            HookEvent::ItemSuccess => "item_success",
+            HookEvent::ItemFailure => "item_failure",
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match self {
+            HookEvent::FileSuccess | HookEvent::ItemSuccess => "success",
+            HookEvent::FileFailure | HookEvent::ItemFailure => "failed",
+        }
+    }
+}
+
+/// Configured hook commands and their limits
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookSettings {
+    pub file_success: Option<String>,
+    pub file_failure: Option<String>,
+    pub item_success: Option<String>,
+    pub item_failure: Option<String>,
+    /// Longest a command may run before it is killed
+    pub timeout: Duration,
+    /// Commands allowed to run at the same time
+    pub max_concurrent: usize,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+impl HookSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            file_success: config.hook_file_success.clone(),
+            file_failure: config.hook_file_failure.clone(),
+            item_success: config.hook_item_success.clone(),
+            item_failure: config.hook_item_failure.clone(),
+            timeout: Duration::from_secs(config.hook_timeout),
+            max_concurrent: config.hook_concurrency.max(1),
+        }
+    }
+
+    /// Whether no hook is configured
+    pub fn is_empty(&self) -> bool {
+        self.file_success.is_none()
+            && self.file_failure.is_none()
+            && self.item_success.is_none()
+            && self.item_failure.is_none()
+    }
+
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::FileSuccess => self.file_success.as_deref(),
+            HookEvent::FileFailure => self.file_failure.as_deref(),
+            HookEvent::ItemSuccess => self.item_success.as_deref(),
+            HookEvent::ItemFailure => self.item_failure.as_deref(),
+        }
+    }
+}
+
+/// What a hook is told about the finished file or item
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub identifier: String,
+    pub file: Option<String>,
+    pub path: String,
+    pub md5: Option<String>,
+    pub size: Option<u64>,
+    pub format: Option<String>,
+    pub error: Option<String>,
+}
+
+impl HookContext {
+    /// Context for a file of `identifier` saved at `path`
+    pub fn for_file(identifier: &str, file_info: &ArchiveFile, path: &Path) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            file: Some(file_info.name.clone()),
+            path: path.to_string_lossy().into_owned(),
+            md5: file_info.md5.clone(),
+            size: file_info.size,
+            format: file_info.format.clone(),
+            error: None,
+        }
+    }
+
+    /// Environment variables passed to the command
+    pub fn env(&self, event: HookEvent) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("IA_GET_HOOK", event.as_str().to_string()),
+            ("IA_GET_IDENTIFIER", self.identifier.clone()),
+            ("IA_GET_PATH", self.path.clone()),
+            ("IA_GET_STATUS", event.status().to_string()),
+        ];
+        let optional = [
+            ("IA_GET_FILE", self.file.clone()),
+            ("IA_GET_MD5", self.md5.clone()),
+            ("IA_GET_SIZE", self.size.map(|size| size.to_string())),
+            ("IA_GET_FORMAT", self.format.clone()),
+            ("IA_GET_ERROR", self.error.clone()),
+        ];
+        env.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, value)| value.map(|value| (name, value))),
+        );
+        env
+    }
+}
+
+/// How one hook command ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookOutcome {
+    pub hook: HookEvent,
+    /// File the hook ran for; None for item hooks
+    pub file: Option<String>,
+    /// Exit code; None when the command timed out, was killed by a signal or did not start
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Why the command could not be started
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl HookOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs hooks in the background within the configured limits
+pub struct HookRunner {
+    settings: HookSettings,
+    permits: Arc<Semaphore>,
+    outcomes: Arc<Mutex<Vec<HookOutcome>>>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl HookRunner {
+    pub fn new(settings: HookSettings) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(settings.max_concurrent.max(1))),
+            settings,
+            outcomes: Arc::default(),
+            tasks: Mutex::default(),
+        }
+    }
+
+    /// Start the command configured for `event`, if any, without waiting for it
+    pub fn run(&self, event: HookEvent, context: HookContext) {
+        let Some(command) = self.settings.command(event).map(str::to_string) else {
+            return;
+        };
+        let permits = self.permits.clone();
+        let outcomes = self.outcomes.clone();
+        let timeout = self.settings.timeout;
+
+        let task = tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            let outcome = run_hook(&command, event, &context, timeout).await;
+            if let Ok(mut outcomes) = outcomes.lock() {
+                outcomes.push(outcome);
+            }
+        });
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push(task);
+        }
+    }
+
+    /// Wait for every started command and return how each ended, in completion order
+    pub async fn finish(&self) -> Vec<HookOutcome> {
+        let tasks = self
+            .tasks
+            .lock()
+            .map(|mut tasks| std::mem::take(&mut *tasks))
+            .unwrap_or_default();
+        for task in tasks {
+            let _ = task.await;
+        }
+        self.outcomes
+            .lock()
+            .map(|mut outcomes| std::mem::take(&mut *outcomes))
+            .unwrap_or_default()
+    }
+}
+
+/// Run `command` through the shell and wait at most `timeout` for it
+pub async fn run_hook(
+    command: &str,
+    event: HookEvent,
+    context: &HookContext,
+    timeout: Duration,
+) -> HookOutcome {
+    let started = Instant::now();
+    let mut outcome = HookOutcome {
+        hook: event,
+        file: context.file.clone(),
+        exit_code: None,
+        timed_out: false,
+        error: None,
+        duration_ms: 0,
+    };
+
+    #[cfg(windows)]
+    let mut process = {
+        let mut process = tokio::process::Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    };
+    #[cfg(not(windows))]
+    let mut process = {
+        let mut process = tokio::process::Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+    process
+        .envs(context.env(event))
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+
+    match process.spawn() {
+        Ok(mut child) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(Ok(status)) => outcome.exit_code = status.code(),
+            Ok(Err(e)) => outcome.error = Some(e.to_string()),
+            Err(_) => {
+                outcome.timed_out = true;
+                let _ = child.kill().await;
+            }
+        },
+        Err(e) => outcome.error = Some(format!("Failed to start hook: {}", e)),
+    }
+
+    outcome.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    outcome
+}
+
+fn hook_slot() -> &'static RwLock<HookSettings> {
+    static GLOBAL_HOOKS: OnceLock<RwLock<HookSettings>> = OnceLock::new();
+    GLOBAL_HOOKS.get_or_init(|| RwLock::new(HookSettings::default()))
+}
+
+/// Install the process-wide hook commands
+pub fn set_global_hook_settings(settings: HookSettings) {
+    if let Ok(mut slot) = hook_slot().write() {
+        *slot = settings;
+    }
+}
+
+/// The hook commands in effect
+pub fn hook_settings() -> HookSettings {
+    hook_slot()
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
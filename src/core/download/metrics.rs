@@ -0,0 +1,226 @@
+//! Download metrics for monitoring
+//!
+//! With `--metrics-addr` a process-wide [`DownloadMetrics`] is installed and
+//! fed by the downloaders: per-server transfer and error counters from the
+//! [`ServerHealthTracker`](super::ServerHealthTracker), active and waiting
+//! transfers, queue depth and checksum failures, plus process-wide totals
+//! in a [`PerformanceMonitor`] that every tracker feeds next to its own. [`DownloadMetrics::render_openmetrics`]
+//! turns them into OpenMetrics text for a Prometheus scrape. Without an
+//! installed registry nothing is recorded.
+
+use super::ServerFailure;
+use crate::utilities::common::{MetricType, OpenMetricsEncoder, PerformanceMonitor};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+/// Transfer and error counters for one server
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerMetrics {
+    /// Bytes received by completed transfers
+    pub bytes: u64,
+    /// Time spent on completed transfers
+    pub transfer_seconds: f64,
+    pub transfers: u64,
+    pub server_errors: u64,
+    pub rate_limited: u64,
+    pub timeouts: u64,
+    pub other_errors: u64,
+}
+
+/// Counters and gauges exported by `--metrics-addr`
+pub struct DownloadMetrics {
+    performance: Arc<PerformanceMonitor>,
+    servers: Mutex<BTreeMap<String, ServerMetrics>>,
+    active_transfers: AtomicU64,
+    waiting_files: AtomicU64,
+    queue_depth: AtomicU64,
+    checksum_failures: AtomicU64,
+}
+
+/// Keeps a gauge raised until dropped
+pub struct GaugeGuard {
+    metrics: Arc<DownloadMetrics>,
+    gauge: fn(&DownloadMetrics) -> &AtomicU64,
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        (self.gauge)(&self.metrics).fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl DownloadMetrics {
+    pub fn new() -> Self {
+        Self {
+            performance: Arc::new(PerformanceMonitor::new()),
+            servers: Mutex::new(BTreeMap::new()),
+            active_transfers: AtomicU64::new(0),
+            waiting_files: AtomicU64::new(0),
+            queue_depth: AtomicU64::new(0),
+            checksum_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Process-wide monitor fed by every download's server health tracker
+    pub fn performance_monitor(&self) -> Arc<PerformanceMonitor> {
+        self.performance.clone()
+    }
+
+    fn raise(self: &Arc<Self>, gauge: fn(&DownloadMetrics) -> &AtomicU64) -> GaugeGuard {
+        gauge(self).fetch_add(1, Ordering::Relaxed);
+        GaugeGuard {
+            metrics: self.clone(),
+            gauge,
+        }
+    }
+
+    /// Count a transfer as active until the guard is dropped
+    pub fn start_transfer(self: &Arc<Self>) -> GaugeGuard {
+        self.raise(|metrics| &metrics.active_transfers)
+    }
+
+    /// Count a file as waiting for a download slot until the guard is dropped
+    pub fn wait_for_slot(self: &Arc<Self>) -> GaugeGuard {
+        self.raise(|metrics| &metrics.waiting_files)
+    }
+
+    /// Set the number of items waiting in the download queue or daemon
+    pub fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_checksum_failure(&self) {
+        self.checksum_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_server_success(&self, server: &str, bytes: u64, duration: Duration) {
+        if let Ok(mut servers) = self.servers.lock() {
+            let metrics = servers.entry(server.to_string()).or_default();
+            metrics.bytes += bytes;
+            metrics.transfer_seconds += duration.as_secs_f64();
+            metrics.transfers += 1;
+        }
+    }
+
+    pub fn record_server_failure(&self, server: &str, failure: ServerFailure) {
+        if let Ok(mut servers) = self.servers.lock() {
+            let metrics = servers.entry(server.to_string()).or_default();
+            match failure {
+                ServerFailure::ServerError => metrics.server_errors += 1,
+                ServerFailure::RateLimited => metrics.rate_limited += 1,
+                ServerFailure::Timeout => metrics.timeouts += 1,
+                ServerFailure::Other => metrics.other_errors += 1,
+            }
+        }
+    }
+
+    /// Snapshot of every server's counters
+    pub fn servers(&self) -> BTreeMap<String, ServerMetrics> {
+        self.servers
+            .lock()
+            .map(|servers| servers.clone())
+            .unwrap_or_default()
+    }
+
+    /// Everything recorded so far as an OpenMetrics text exposition
+    pub async fn render_openmetrics(&self) -> String {
+        let mut encoder = OpenMetricsEncoder::new();
+        self.performance
+            .get_metrics()
+            .await
+            .encode_openmetrics(&mut encoder);
+
+        let servers = self.servers();
+        encoder.family(
+            "ia_get_server_bytes",
+            MetricType::Counter,
+            "Bytes received from each server by completed transfers",
+        );
+        for (server, metrics) in &servers {
+            encoder.sample(&[("server", server)], metrics.bytes as f64);
+        }
+        encoder.family(
+            "ia_get_server_transfer_seconds",
+            MetricType::Counter,
+            "Time spent on completed transfers from each server; bytes over seconds is its throughput",
+        );
+        for (server, metrics) in &servers {
+            encoder.sample(&[("server", server)], metrics.transfer_seconds);
+        }
+        encoder.family(
+            "ia_get_server_transfers",
+            MetricType::Counter,
+            "Completed transfers from each server",
+        );
+        for (server, metrics) in &servers {
+            encoder.sample(&[("server", server)], metrics.transfers as f64);
+        }
+        encoder.family(
+            "ia_get_server_errors",
+            MetricType::Counter,
+            "Failed transfers from each server by kind",
+        );
+        for (server, metrics) in &servers {
+            for (kind, count) in [
+                ("server_error", metrics.server_errors),
+                ("rate_limited", metrics.rate_limited),
+                ("timeout", metrics.timeouts),
+                ("other", metrics.other_errors),
+            ] {
+                encoder.sample(&[("server", server), ("kind", kind)], count as f64);
+            }
+        }
+
+        encoder
+            .family(
+                "ia_get_active_transfers",
+                MetricType::Gauge,
+                "Files being downloaded right now",
+            )
+            .sample(&[], self.active_transfers.load(Ordering::Relaxed) as f64)
+            .family(
+                "ia_get_waiting_files",
+                MetricType::Gauge,
+                "Files of running downloads waiting for a download slot",
+            )
+            .sample(&[], self.waiting_files.load(Ordering::Relaxed) as f64)
+            .family(
+                "ia_get_queue_depth",
+                MetricType::Gauge,
+                "Items waiting in the download queue or daemon",
+            )
+            .sample(&[], self.queue_depth.load(Ordering::Relaxed) as f64)
+            .family(
+                "ia_get_checksum_failures",
+                MetricType::Counter,
+                "Downloaded files that failed checksum verification",
+            )
+            .sample(&[], self.checksum_failures.load(Ordering::Relaxed) as f64);
+        encoder.finish()
+    }
+}
+
+impl Default for DownloadMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn global_slot() -> &'static RwLock<Option<Arc<DownloadMetrics>>> {
+    static GLOBAL_METRICS: OnceLock<RwLock<Option<Arc<DownloadMetrics>>>> = OnceLock::new();
+    GLOBAL_METRICS.get_or_init(|| RwLock::new(None))
+}
+
+/// Install (or clear, with None) the process-wide metrics registry
+pub fn set_download_metrics(metrics: Option<Arc<DownloadMetrics>>) {
+    if let Ok(mut slot) = global_slot().write() {
+        *slot = metrics;
+    }
+}
+
+/// The process-wide metrics registry, if one is installed
+pub fn download_metrics() -> Option<Arc<DownloadMetrics>> {
+    global_slot().read().ok().and_then(|slot| slot.clone())
+}
@@ -3,13 +3,35 @@
 //! Contains download engines, concurrent downloaders, and download coordination logic.
 
 pub use concurrent_simple::*;
+pub use container::*;
+pub use control::*;
+pub use disk_space::*;
 pub use download_service::*;
 pub use downloader::*;
 pub use downloads::*;
 pub use enhanced_downloader::*;
+pub use hooks::*;
+pub use metrics::*;
+pub use progress_events::*;
+pub use queue_runner::*;
+pub use server_health::*;
+pub use store::*;
+pub use stream::*;
+pub use sync::*;
 
 pub mod concurrent_simple;
+pub mod container;
+pub mod control;
+pub mod disk_space;
 pub mod download_service;
 pub mod downloader;
 pub mod downloads;
 pub mod enhanced_downloader;
+pub mod hooks;
+pub mod metrics;
+pub mod progress_events;
+pub mod queue_runner;
+pub mod server_health;
+pub mod store;
+pub mod stream;
+pub mod sync;
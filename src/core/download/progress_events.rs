@@ -0,0 +1,245 @@
+//! Machine-readable progress events
+//!
+//! With `--progress=json` every download reports what it is doing as
+//! newline-delimited JSON, one [`ProgressEventRecord`] per line, for scripts
+//! that drive ia-get. Each record carries [`PROGRESS_SCHEMA_VERSION`]; fields
+//! may be added within a version, but renaming or removing one, or changing
+//! its meaning, bumps the version.
+//!
+//! Events go to the process-wide writer installed with
+//! [`set_progress_event_writer`]; without one, emitting is a no-op.
+
+use crate::{IaGetError, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+/// Version of the event schema written in every record
+pub const PROGRESS_SCHEMA_VERSION: u32 = 1;
+
+/// Smallest gap between two `file_progress` events for the same file
+pub const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Something that happened during a download
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// Downloading the files of an item begins
+    SessionStarted {
+        identifier: String,
+        /// Files left to download
+        files: usize,
+        /// Their combined size, as far as the metadata lists sizes
+        bytes: u64,
+    },
+    /// A file's transfer begins
+    FileStarted { file: String, size: Option<u64> },
+    /// Bytes of a file on disk so far, reported at most every [`PROGRESS_EVENT_INTERVAL`]
+    FileProgress {
+        file: String,
+        bytes: u64,
+        size: Option<u64>,
+    },
+    /// A file is complete; `checksums` lists the algorithms that matched
+    FileVerified {
+        file: String,
+        bytes: u64,
+        checksums: Vec<String>,
+    },
+    /// A file could not be downloaded
+    FileFailed {
+        file: String,
+        /// Stable category from [`IaGetError::kind`]
+        error_kind: String,
+        error: String,
+    },
+    /// Downloading the files of an item ended
+    SessionCompleted {
+        identifier: String,
+        completed: usize,
+        failed: usize,
+        /// Files stopped by a pause or cancellation, left to resume
+        interrupted: usize,
+    },
+    /// An item failed before any of its files were downloaded, e.g. because
+    /// its metadata could not be fetched or the disk is too small
+    SessionFailed {
+        identifier: String,
+        /// Stable category from [`IaGetError::kind`]
+        error_kind: String,
+        error: String,
+    },
+    /// An entry of the download queue, a job of `ia-get daemon`, changed
+    /// state (`queued`, `running`, `paused`, `completed`, `failed` or
+    /// `cancelled`)
+    JobUpdated {
+        job: u64,
+        identifier: String,
+        state: String,
+    },
+}
+
+impl ProgressEvent {
+    pub fn file_failed(file: &str, error: &IaGetError) -> Self {
+        ProgressEvent::FileFailed {
+            file: file.to_string(),
+            error_kind: error.kind().to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    pub fn session_failed(identifier: &str, error: &IaGetError) -> Self {
+        ProgressEvent::SessionFailed {
+            identifier: identifier.to_string(),
+            error_kind: error.kind().to_string(),
+            error: error.to_string(),
+        }
+    }
+}
+
+/// One line of the event stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressEventRecord {
+    pub schema: u32,
+    /// RFC 3339 time the event was emitted
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub event: ProgressEvent,
+}
+
+impl ProgressEventRecord {
+    pub fn new(event: ProgressEvent) -> Self {
+        Self {
+            schema: PROGRESS_SCHEMA_VERSION,
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            event,
+        }
+    }
+
+    /// The record as one line of JSON, newline included
+    pub fn to_json_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+/// Destination of the event stream
+pub struct ProgressEventWriter {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl ProgressEventWriter {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Mutex::new(out),
+        }
+    }
+
+    /// Write events to stdout, moving everything else printed there to stderr
+    ///
+    /// On Unix the original stdout is kept for events and file descriptor 1
+    /// is pointed at stderr, so status messages printed anywhere in the
+    /// program cannot interleave with the JSON lines. Elsewhere events share
+    /// stdout with other output.
+    pub fn stdout() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::fd::FromRawFd;
+
+            std::io::stdout().flush()?;
+            // SAFETY: plain descriptor calls; the duplicate is owned by the File below.
+            // It is close-on-exec so hook commands and other children never hold
+            // the event stream open.
+            let events_fd = unsafe { libc::fcntl(libc::STDOUT_FILENO, libc::F_DUPFD_CLOEXEC, 0) };
+            if events_fd < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+                let error = std::io::Error::last_os_error();
+                unsafe { libc::close(events_fd) };
+                return Err(error.into());
+            }
+            let file = unsafe { std::fs::File::from_raw_fd(events_fd) };
+            Ok(Self::new(Box::new(file)))
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Self::new(Box::new(std::io::stdout())))
+        }
+    }
+
+    /// Write events to an already open file descriptor, e.g. `3` with `3>events.ndjson`
+    pub fn from_fd(fd: i32) -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::fd::FromRawFd;
+
+            if fd <= libc::STDERR_FILENO {
+                return Err(IaGetError::Config(format!(
+                    "Progress file descriptor must be 3 or higher, got {}",
+                    fd
+                )));
+            }
+            // Descriptors the program opens itself are close-on-exec; one
+            // inherited from the caller never is
+            // SAFETY: fcntl only inspects the descriptor
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+            if flags < 0 || flags & libc::FD_CLOEXEC != 0 {
+                return Err(IaGetError::Config(format!(
+                    "File descriptor {} was not opened for ia-get",
+                    fd
+                )));
+            }
+            // Now that ia-get owns it, keep it out of hook commands and other children
+            // SAFETY: fcntl only changes the descriptor's flags
+            unsafe { libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) };
+            // SAFETY: the descriptor is open and handed to ia-get by the caller
+            let file = unsafe { std::fs::File::from_raw_fd(fd) };
+            Ok(Self::new(Box::new(file)))
+        }
+        #[cfg(not(unix))]
+        {
+            Err(IaGetError::Config(format!(
+                "Writing progress to file descriptor {} is only supported on Unix",
+                fd
+            )))
+        }
+    }
+
+    /// Write `event` as one line and flush it
+    ///
+    /// Failures are ignored: a reader that went away must not stop the download.
+    pub fn emit(&self, event: ProgressEvent) {
+        let line = ProgressEventRecord::new(event).to_json_line();
+        if let Ok(mut out) = self.out.lock() {
+            let _ = out.write_all(line.as_bytes()).and_then(|_| out.flush());
+        }
+    }
+}
+
+fn global_slot() -> &'static RwLock<Option<Arc<ProgressEventWriter>>> {
+    static GLOBAL_WRITER: OnceLock<RwLock<Option<Arc<ProgressEventWriter>>>> = OnceLock::new();
+    GLOBAL_WRITER.get_or_init(|| RwLock::new(None))
+}
+
+/// Install (or clear, with None) the process-wide event writer
+pub fn set_progress_event_writer(writer: Option<ProgressEventWriter>) {
+    if let Ok(mut slot) = global_slot().write() {
+        *slot = writer.map(Arc::new);
+    }
+}
+
+/// Whether progress events are being written
+pub fn progress_events_enabled() -> bool {
+    global_slot().read().is_ok_and(|slot| slot.is_some())
+}
+
+/// Write `event` to the process-wide writer, if one is installed
+pub fn emit_progress_event(event: ProgressEvent) {
+    let writer = global_slot().read().ok().and_then(|slot| slot.clone());
+    if let Some(writer) = writer {
+        writer.emit(event);
+    }
+}
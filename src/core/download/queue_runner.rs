@@ -0,0 +1,291 @@
+//! Working through the persistent download queue
+//!
+//! [`QueueRunner`] takes `queued` entries from the queue file in run order
+//! and downloads up to `parallel` items at once, each through
+//! [`DownloadService`] with the entry's own request. Entries are claimed and
+//! finished with [`DownloadQueue::update`], so items added, removed or
+//! reordered from elsewhere while the queue runs are picked up.
+//!
+//! Each item has its own [`DownloadControl`] following the run's, so an
+//! entry paused, cancelled or removed from the CLI, the GUI or the daemon
+//! API stops on its own while the rest of the run goes on. The queue file is
+//! re-read every [`QUEUE_POLL_INTERVAL`] to notice such edits from other
+//! processes; [`QueueRunner::wake`] makes the runner look at once.
+
+use crate::{
+    IaGetError, Result,
+    core::download::{
+        DownloadControl, DownloadResult, DownloadService, ProgressEvent, download_metrics,
+        emit_progress_event,
+    },
+    infrastructure::persistence::{DownloadQueue, JobState, QueueEntry},
+};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+
+/// How often a run re-reads the queue file for edits made elsewhere
+pub const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How a queue run ended
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueRunSummary {
+    pub completed: usize,
+    pub failed: usize,
+    /// Items stopped by the control, queued again for the next run
+    pub interrupted: usize,
+    /// Items stopped because they were paused, cancelled or removed meanwhile
+    pub withdrawn: usize,
+}
+
+/// Tell progress event readers that entry `id` moved to `state`
+fn announce(id: u64, identifier: &str, state: JobState) {
+    emit_progress_event(ProgressEvent::JobUpdated {
+        job: id,
+        identifier: identifier.to_string(),
+        state: state.as_str().to_string(),
+    });
+}
+
+/// Downloads queued items, several at a time
+pub struct QueueRunner {
+    path: PathBuf,
+    parallel: usize,
+    control: DownloadControl,
+    service: Option<Arc<DownloadService>>,
+    /// Wait for new entries instead of returning once none is queued
+    keep_running: bool,
+    /// Controls of the items downloading now, by entry id
+    running: Mutex<HashMap<u64, DownloadControl>>,
+    wake: Notify,
+}
+
+impl QueueRunner {
+    pub fn new(path: PathBuf, parallel: usize) -> Self {
+        Self {
+            path,
+            parallel: parallel.max(1),
+            control: DownloadControl::new(),
+            service: None,
+            keep_running: false,
+            running: Mutex::new(HashMap::new()),
+            wake: Notify::new(),
+        }
+    }
+
+    /// Stop the run through `control`; cancelling it stops every running item
+    pub fn with_control(mut self, control: DownloadControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Download through `service` instead of one with the default settings
+    pub fn with_service(mut self, service: DownloadService) -> Self {
+        self.service = Some(Arc::new(service));
+        self
+    }
+
+    /// Keep waiting for entries once the queue is done, until the control
+    /// is cancelled; how the daemon runs the queue
+    pub fn keep_running(mut self) -> Self {
+        self.keep_running = true;
+        self
+    }
+
+    /// Queue file this runner works through
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Look at the queue file now rather than at the next poll
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Ids of the entries downloading now
+    pub fn running(&self) -> Vec<u64> {
+        self.running
+            .lock()
+            .map(|running| running.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Claim the next queued entry, marking it running
+    fn claim(&self) -> Result<Option<QueueEntry>> {
+        DownloadQueue::update(&self.path, |queue| {
+            let claimed = queue.next_queued().map(|entry| entry.id).and_then(|id| {
+                let entry = queue.get_mut(id)?;
+                entry.set_state(JobState::Running);
+                Some(entry.clone())
+            });
+            if let Some(metrics) = download_metrics() {
+                metrics.set_queue_depth(
+                    queue
+                        .entries
+                        .iter()
+                        .filter(|entry| entry.state == JobState::Queued)
+                        .count(),
+                );
+            }
+            claimed
+        })
+    }
+
+    /// Stop running items whose entry is no longer `running` in the queue file
+    fn stop_withdrawn(&self) {
+        let Ok(queue) = DownloadQueue::load(&self.path) else {
+            return;
+        };
+        if let Ok(running) = self.running.lock() {
+            for (id, control) in running.iter() {
+                let still_running = queue
+                    .get(*id)
+                    .is_some_and(|entry| entry.state == JobState::Running);
+                if !still_running {
+                    control.cancel();
+                }
+            }
+        }
+    }
+
+    /// Record how entry `id` ended; an entry removed meanwhile stays removed
+    ///
+    /// A cancelled download goes back to `queued` when the run was stopped.
+    /// An entry paused or cancelled while it ran keeps that state unless the
+    /// download completed anyway. Returns the entry's state, `None` when it
+    /// was removed.
+    fn finish(&self, id: u64, result: Result<DownloadResult>) -> Result<Option<JobState>> {
+        let (state, error, progress) = match result {
+            Ok(DownloadResult::Success(session, _, _)) => {
+                let progress = session.get_progress_summary();
+                let (state, error) = if progress.failed_files > 0 {
+                    (
+                        JobState::Failed,
+                        Some(format!("{} file(s) failed", progress.failed_files)),
+                    )
+                } else {
+                    (JobState::Completed, None)
+                };
+                (Some(state), error, Some(progress))
+            }
+            Ok(DownloadResult::Cancelled(session)) => {
+                (None, None, Some(session.get_progress_summary()))
+            }
+            Ok(DownloadResult::Error(error)) => (Some(JobState::Failed), Some(error), None),
+            Err(e) => (Some(JobState::Failed), Some(e.to_string()), None),
+        };
+
+        DownloadQueue::update(&self.path, |queue| {
+            let entry = queue.get_mut(id)?;
+            if let Some(progress) = progress {
+                entry.total_files = progress.total_files;
+                entry.completed_files = progress.completed_files;
+                entry.failed_files = progress.failed_files;
+            }
+            // Paused, cancelled or retried from elsewhere while it ran
+            let withdrawn = entry.state != JobState::Running;
+            match state {
+                Some(JobState::Completed) => {
+                    entry.error = None;
+                    entry.set_state(JobState::Completed);
+                }
+                _ if withdrawn => {}
+                Some(state) => {
+                    entry.error = error;
+                    entry.set_state(state);
+                }
+                None => entry.set_state(JobState::Queued),
+            }
+            Some(entry.state)
+        })
+    }
+
+    /// Run until no queued entry is left or the control is cancelled
+    ///
+    /// Entries a previous run left `running` are queued again first; with
+    /// `retry_failed` failed entries are too.
+    pub async fn run(&self, retry_failed: bool) -> Result<QueueRunSummary> {
+        let service = match self.service {
+            Some(ref service) => service.clone(),
+            None => Arc::new(DownloadService::new()?),
+        };
+        DownloadQueue::update(&self.path, |queue| queue.requeue(retry_failed))?;
+
+        let mut summary = QueueRunSummary::default();
+        let mut tasks = JoinSet::new();
+        let mut entries = HashMap::new();
+        loop {
+            while tasks.len() < self.parallel && !self.control.is_cancelled() {
+                let Some(entry) = self.claim()? else {
+                    break;
+                };
+                println!(
+                    "{} Queue item {}: downloading {}",
+                    "⬇️".blue(),
+                    entry.id,
+                    entry.request.identifier.bold()
+                );
+                announce(entry.id, &entry.request.identifier, JobState::Running);
+                let control = DownloadControl::new();
+                control.follow(&self.control);
+                if let Ok(mut running) = self.running.lock() {
+                    running.insert(entry.id, control.clone());
+                }
+                let service = service.clone();
+                let (id, identifier) = (entry.id, entry.request.identifier.clone());
+                let task = tasks.spawn(async move {
+                    service
+                        .download_with_control(entry.request, None, control)
+                        .await
+                });
+                entries.insert(task.id(), (id, identifier));
+            }
+
+            if tasks.is_empty() && (!self.keep_running || self.control.is_cancelled()) {
+                break;
+            }
+            let joined = tokio::select! {
+                Some(joined) = tasks.join_next_with_id() => Some(joined),
+                _ = self.wake.notified() => None,
+                _ = self.control.cancelled(), if self.keep_running && tasks.is_empty() => None,
+                _ = tokio::time::sleep(QUEUE_POLL_INTERVAL) => None,
+            };
+            let Some(joined) = joined else {
+                self.stop_withdrawn();
+                continue;
+            };
+
+            let (task, result) = match joined {
+                Ok((task, result)) => (task, result),
+                Err(e) => (
+                    e.id(),
+                    Err(IaGetError::FileSystem(format!(
+                        "Download task failed: {}",
+                        e
+                    ))),
+                ),
+            };
+            let Some((id, identifier)) = entries.remove(&task) else {
+                continue;
+            };
+            if let Ok(mut running) = self.running.lock() {
+                running.remove(&id);
+            }
+            let state = self.finish(id, result)?;
+            if let Some(state) = state {
+                announce(id, &identifier, state);
+            }
+            match state {
+                Some(JobState::Completed) => summary.completed += 1,
+                Some(JobState::Failed) => summary.failed += 1,
+                Some(JobState::Queued) => summary.interrupted += 1,
+                _ => summary.withdrawn += 1,
+            }
+        }
+        Ok(summary)
+    }
+}
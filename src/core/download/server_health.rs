@@ -0,0 +1,282 @@
+//! Per-server health scoring for Archive.org datanodes
+//!
+//! Tracks latency, throughput and failures for each mirror (`d1`, `d2` and
+//! `workable_servers`) over a download session. New transfers go to the
+//! best-scoring server, and a server that keeps failing is taken out of rotation
+//! by a circuit breaker until its cooldown expires.
+
+use super::download_metrics;
+use crate::{IaGetError, utilities::common::PerformanceMonitor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Consecutive failures before a server's circuit opens
+const CIRCUIT_BREAKER_THRESHOLD: u32 = 3;
+/// Cooldown after the first circuit trip; doubles on each further trip
+const CIRCUIT_BASE_COOLDOWN: Duration = Duration::from_secs(30);
+/// Longest a circuit stays open
+const CIRCUIT_MAX_COOLDOWN: Duration = Duration::from_secs(300);
+/// Weight of the newest sample in the latency and throughput moving averages
+const EWMA_WEIGHT: f64 = 0.3;
+
+/// Kind of failure reported for a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerFailure {
+    /// HTTP 5xx response
+    ServerError,
+    /// HTTP 429 response
+    RateLimited,
+    /// Connection or read timeout
+    Timeout,
+    /// Any other error (connection reset, short read, ...)
+    Other,
+}
+
+impl ServerFailure {
+    /// Classify a download error by its kind and HTTP status
+    pub fn from_error(error: &IaGetError) -> Self {
+        match error {
+            IaGetError::RateLimited { .. } => Self::RateLimited,
+            IaGetError::Http { status, .. } if *status >= 500 => Self::ServerError,
+            IaGetError::Timeout(_) => Self::Timeout,
+            IaGetError::Io(e) if e.kind() == std::io::ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Health statistics for a single server
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerHealth {
+    /// Moving average of time to first response
+    pub avg_latency: Option<Duration>,
+    /// Moving average of transfer throughput in bytes per second
+    pub avg_throughput: Option<f64>,
+    /// Completed transfers
+    pub successes: u64,
+    /// Failed transfers of any kind
+    pub failures: u64,
+    /// HTTP 5xx responses
+    pub server_errors: u64,
+    /// HTTP 429 responses
+    pub rate_limited: u64,
+    /// Timeouts
+    pub timeouts: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Number of times the circuit breaker has opened
+    pub circuit_trips: u32,
+    #[serde(skip)]
+    circuit_open_until: Option<Instant>,
+}
+
+impl ServerHealth {
+    /// Whether the circuit breaker currently keeps this server out of rotation
+    pub fn is_circuit_open(&self) -> bool {
+        self.circuit_open_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Score used to rank servers; higher is better
+    ///
+    /// Reliability (Laplace-smoothed success ratio, counting rate limits double)
+    /// scaled by throughput. Servers with no throughput sample use `fallback_throughput`
+    /// so untried mirrors still get picked.
+    pub fn score(&self, fallback_throughput: f64) -> f64 {
+        let penalties = self.failures + self.rate_limited;
+        let reliability =
+            (self.successes as f64 + 1.0) / ((self.successes + penalties) as f64 + 2.0);
+        let latency_penalty = 1.0 + self.avg_latency.map_or(0.0, |l| l.as_secs_f64());
+        reliability * self.avg_throughput.unwrap_or(fallback_throughput) / latency_penalty
+    }
+}
+
+fn ewma(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(prev) => prev * (1.0 - EWMA_WEIGHT) + sample * EWMA_WEIGHT,
+        None => sample,
+    }
+}
+
+/// Session-wide server health tracker shared by all download tasks
+pub struct ServerHealthTracker {
+    servers: Mutex<HashMap<String, ServerHealth>>,
+    performance_monitor: Arc<PerformanceMonitor>,
+    /// Process-wide monitor of the installed metrics exporter, if any
+    exported_monitor: Option<Arc<PerformanceMonitor>>,
+}
+
+impl ServerHealthTracker {
+    /// Create a tracker with its own performance monitor
+    ///
+    /// The monitor only sees this session's transfers; the process-wide one
+    /// of the installed [`DownloadMetrics`](super::DownloadMetrics) is fed
+    /// alongside it.
+    pub fn new() -> Self {
+        Self::with_monitor(Arc::new(PerformanceMonitor::new()))
+    }
+
+    /// Create a tracker that feeds an existing performance monitor
+    pub fn with_monitor(performance_monitor: Arc<PerformanceMonitor>) -> Self {
+        let exported_monitor = download_metrics()
+            .map(|metrics| metrics.performance_monitor())
+            .filter(|exported| !Arc::ptr_eq(exported, &performance_monitor));
+        Self {
+            servers: Mutex::new(HashMap::new()),
+            performance_monitor,
+            exported_monitor,
+        }
+    }
+
+    /// This tracker's monitor followed by the exporter's
+    fn monitors(&self) -> impl Iterator<Item = &Arc<PerformanceMonitor>> {
+        std::iter::once(&self.performance_monitor).chain(&self.exported_monitor)
+    }
+
+    /// Aggregate performance monitor fed by this tracker
+    pub fn performance_monitor(&self) -> Arc<PerformanceMonitor> {
+        self.performance_monitor.clone()
+    }
+
+    /// Record the time until a server's response headers arrived
+    pub async fn record_connection(&self, server: &str, latency: Duration) {
+        {
+            let mut servers = self.servers.lock().await;
+            let health = servers.entry(server.to_string()).or_default();
+            let avg = ewma(
+                health.avg_latency.map(|l| l.as_secs_f64()),
+                latency.as_secs_f64(),
+            );
+            health.avg_latency = Some(Duration::from_secs_f64(avg));
+        }
+        for monitor in self.monitors() {
+            monitor.record_connection(latency, false).await;
+        }
+    }
+
+    /// Record a completed transfer of `bytes` from a server
+    pub async fn record_success(&self, server: &str, bytes: u64, duration: Duration) {
+        {
+            let mut servers = self.servers.lock().await;
+            let health = servers.entry(server.to_string()).or_default();
+            health.successes += 1;
+            health.consecutive_failures = 0;
+            health.circuit_open_until = None;
+            if bytes > 0 && !duration.is_zero() {
+                health.avg_throughput = Some(ewma(
+                    health.avg_throughput,
+                    bytes as f64 / duration.as_secs_f64(),
+                ));
+            }
+        }
+        if let Some(metrics) = download_metrics() {
+            metrics.record_server_success(server, bytes, duration);
+        }
+        for monitor in self.monitors() {
+            monitor.record_download(bytes, duration).await;
+        }
+    }
+
+    /// Record a failed transfer from a server, opening its circuit if it keeps failing
+    pub async fn record_failure(&self, server: &str, failure: ServerFailure) {
+        {
+            let mut servers = self.servers.lock().await;
+            let health = servers.entry(server.to_string()).or_default();
+            health.failures += 1;
+            health.consecutive_failures += 1;
+            match failure {
+                ServerFailure::ServerError => health.server_errors += 1,
+                ServerFailure::RateLimited => health.rate_limited += 1,
+                ServerFailure::Timeout => health.timeouts += 1,
+                ServerFailure::Other => {}
+            }
+
+            if health.consecutive_failures >= CIRCUIT_BREAKER_THRESHOLD {
+                let cooldown = CIRCUIT_BASE_COOLDOWN
+                    .saturating_mul(2_u32.saturating_pow(health.circuit_trips))
+                    .min(CIRCUIT_MAX_COOLDOWN);
+                health.circuit_trips += 1;
+                health.consecutive_failures = 0;
+                health.circuit_open_until = Some(Instant::now() + cooldown);
+            }
+        }
+        if let Some(metrics) = download_metrics() {
+            metrics.record_server_failure(server, failure);
+        }
+        for monitor in self.monitors() {
+            if failure == ServerFailure::Timeout {
+                monitor.record_connection_timeout().await;
+            }
+            monitor.record_failure().await;
+        }
+    }
+
+    /// Order servers from best to worst
+    ///
+    /// Servers with an open circuit go last (soonest to reopen first) so a
+    /// transfer still has somewhere to go when every mirror is struggling.
+    /// Ties keep the given order, which preserves Archive.org's preference.
+    pub async fn ranked_servers(&self, candidates: &[String]) -> Vec<String> {
+        let servers = self.servers.lock().await;
+        let best_throughput = servers
+            .values()
+            .filter_map(|h| h.avg_throughput)
+            .fold(1.0_f64, f64::max);
+
+        let mut available = Vec::new();
+        let mut open = Vec::new();
+        for (position, server) in candidates.iter().enumerate() {
+            match servers.get(server) {
+                Some(health) if health.is_circuit_open() => {
+                    open.push((health.circuit_open_until, server.clone()))
+                }
+                Some(health) => available.push((health.score(best_throughput), position, server)),
+                None => available.push((
+                    ServerHealth::default().score(best_throughput),
+                    position,
+                    server,
+                )),
+            }
+        }
+
+        available.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        open.sort_by_key(|(until, _)| *until);
+
+        available
+            .into_iter()
+            .map(|(_, _, server)| server.clone())
+            .chain(open.into_iter().map(|(_, server)| server))
+            .collect()
+    }
+
+    /// Whether a server's circuit breaker is currently open
+    pub async fn is_circuit_open(&self, server: &str) -> bool {
+        self.servers
+            .lock()
+            .await
+            .get(server)
+            .is_some_and(|h| h.is_circuit_open())
+    }
+
+    /// Snapshot of every tracked server's health
+    pub async fn snapshot(&self) -> HashMap<String, ServerHealth> {
+        self.servers.lock().await.clone()
+    }
+}
+
+impl Default for ServerHealthTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Host part of a download URL, used as the server key
+pub fn server_from_url(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_else(|| url.to_string())
+}
@@ -0,0 +1,325 @@
+//! Content-addressed local store
+//!
+//! Identical files (cover images, licence texts, re-uploads) appear in many
+//! items. The store keeps one verified copy of each file keyed by its published
+//! checksum and hardlinks it into every output tree that needs it, falling back
+//! to a copy (reflinked by the kernel where the filesystem supports it) when the
+//! store lives on another device.
+//!
+//! Layout: `<root>/<sha1|md5>/<first two hex digits>/<hash>` for the blob and a
+//! sibling `<hash>.refs` listing the output paths it was materialised at. Paths
+//! that no longer hold the blob are dropped by [`ContentStore::gc`], which then
+//! removes blobs nothing refers to.
+//!
+//! Only downloads whose checksums all matched are stored, so a blob's key is
+//! the hash it was verified against; [`ContentStore::link_into`] hands that
+//! evidence back for the download history.
+//!
+//! Hardlinked outputs share their bytes with the blob, so editing a downloaded
+//! file in place also changes the stored copy.
+
+use crate::{
+    IaGetError, Result,
+    core::session::{ArchiveFile, ChecksumAlgorithm, ChecksumResult},
+};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Checksum families used as store keys, strongest first
+const KEY_ALGORITHMS: [(ChecksumAlgorithm, usize); 2] =
+    [(ChecksumAlgorithm::Sha1, 40), (ChecksumAlgorithm::Md5, 32)];
+
+/// Content-addressed cache of verified downloads
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+/// Outcome of a [`ContentStore::gc`] run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreGcReport {
+    /// Blobs still referenced by at least one output file
+    pub blobs_kept: usize,
+    /// Blobs removed because nothing refers to them any more
+    pub blobs_removed: usize,
+    /// Bytes released by removed blobs and leftover temporary files
+    pub bytes_freed: u64,
+}
+
+impl ContentStore {
+    /// Open (creating if needed) the store rooted at `root`
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).map_err(|e| {
+            IaGetError::FileSystem(format!(
+                "Failed to create store directory {}: {}",
+                root.display(),
+                e
+            ))
+        })?;
+        Ok(Self { root })
+    }
+
+    /// Root directory of the store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Stored blob matching the file's published checksums and size, if any
+    pub fn lookup(&self, file_info: &ArchiveFile) -> Option<PathBuf> {
+        self.find_blob(file_info).map(|(_, _, blob)| blob)
+    }
+
+    /// Materialise the stored copy of `file_info` at `dest`
+    ///
+    /// Returns the checksum the blob was verified against when it was stored,
+    /// or `Ok(None)` when the store has no matching blob.
+    pub fn link_into(
+        &self,
+        file_info: &ArchiveFile,
+        dest: &Path,
+    ) -> Result<Option<ChecksumResult>> {
+        let Some((algorithm, hash, blob)) = self.find_blob(file_info) else {
+            return Ok(None);
+        };
+
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to create output directory: {}", e))
+            })?;
+        }
+        place(&blob, dest)?;
+        // Every key holding this content now has one more user
+        for key in self.blob_paths(file_info) {
+            if key.exists() {
+                self.add_ref(&key, dest)?;
+            }
+        }
+        Ok(Some(ChecksumResult {
+            algorithm,
+            expected: hash.clone(),
+            actual: hash,
+            matched: true,
+            verified_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }))
+    }
+
+    /// Add a verified download at `source` to the store under every published key
+    ///
+    /// Files without a usable SHA1 or MD5 are ignored.
+    pub fn insert(&self, file_info: &ArchiveFile, source: &Path) -> Result<()> {
+        for blob in self.blob_paths(file_info) {
+            if !blob.exists() {
+                if let Some(parent) = blob.parent() {
+                    fs::create_dir_all(parent).map_err(|e| {
+                        IaGetError::FileSystem(format!("Failed to create store directory: {}", e))
+                    })?;
+                }
+                place(source, &blob)?;
+            }
+            self.add_ref(&blob, source)?;
+        }
+        Ok(())
+    }
+
+    /// Drop stale references and remove blobs no output file refers to
+    pub fn gc(&self) -> Result<StoreGcReport> {
+        let mut report = StoreGcReport::default();
+
+        for (algorithm, _) in KEY_ALGORITHMS {
+            for blob in list_files(&self.root.join(algorithm.as_str()))? {
+                let name = blob.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if name.ends_with(".refs") {
+                    // Orphaned reference lists are removed together with their blob
+                    if !blob.with_extension("").exists() {
+                        let _ = fs::remove_file(&blob);
+                    }
+                    continue;
+                }
+                if name.ends_with(".tmp") {
+                    report.bytes_freed += fs::metadata(&blob).map(|m| m.len()).unwrap_or(0);
+                    let _ = fs::remove_file(&blob);
+                    continue;
+                }
+
+                let blob_meta = fs::metadata(&blob).map_err(|e| {
+                    IaGetError::FileSystem(format!("Failed to read {}: {}", blob.display(), e))
+                })?;
+                let refs_path = refs_path(&blob);
+                let live: Vec<String> = fs::read_to_string(&refs_path)
+                    .unwrap_or_default()
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .filter(|line| still_holds_blob(Path::new(line), &blob_meta))
+                    .map(str::to_string)
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+
+                if live.is_empty() {
+                    fs::remove_file(&blob).map_err(|e| {
+                        IaGetError::FileSystem(format!(
+                            "Failed to remove {}: {}",
+                            blob.display(),
+                            e
+                        ))
+                    })?;
+                    let _ = fs::remove_file(&refs_path);
+                    report.blobs_removed += 1;
+                    report.bytes_freed += blob_meta.len();
+                } else {
+                    let mut contents = live.join("\n");
+                    contents.push('\n');
+                    fs::write(&refs_path, contents).map_err(|e| {
+                        IaGetError::FileSystem(format!(
+                            "Failed to update {}: {}",
+                            refs_path.display(),
+                            e
+                        ))
+                    })?;
+                    report.blobs_kept += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// First stored blob matching the file's published checksums and size,
+    /// with the key it was found under
+    fn find_blob(&self, file_info: &ArchiveFile) -> Option<(ChecksumAlgorithm, String, PathBuf)> {
+        self.keys(file_info).into_iter().find(|(_, _, blob)| {
+            fs::metadata(blob)
+                .map(|meta| meta.is_file() && file_info.size.is_none_or(|size| size == meta.len()))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Blob locations for each published checksum, strongest first
+    fn blob_paths(&self, file_info: &ArchiveFile) -> Vec<PathBuf> {
+        self.keys(file_info)
+            .into_iter()
+            .map(|(_, _, blob)| blob)
+            .collect()
+    }
+
+    /// Published checksums usable as keys, with their blob locations
+    fn keys(&self, file_info: &ArchiveFile) -> Vec<(ChecksumAlgorithm, String, PathBuf)> {
+        KEY_ALGORITHMS
+            .iter()
+            .filter_map(|(algorithm, hex_len)| {
+                let hash = match algorithm {
+                    ChecksumAlgorithm::Sha1 => file_info.sha1.as_deref(),
+                    _ => file_info.md5.as_deref(),
+                }?
+                .trim()
+                .to_ascii_lowercase();
+                // Only well-formed digests may become path components
+                if hash.len() != *hex_len || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return None;
+                }
+                let blob = self
+                    .root
+                    .join(algorithm.as_str())
+                    .join(&hash[..2])
+                    .join(&hash);
+                Some((*algorithm, hash, blob))
+            })
+            .collect()
+    }
+
+    /// Record that `path` holds a copy of `blob`
+    fn add_ref(&self, blob: &Path, path: &Path) -> Result<()> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut refs = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(refs_path(blob))
+            .map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to record store reference: {}", e))
+            })?;
+        writeln!(refs, "{}", path.display())
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to record store reference: {}", e)))
+    }
+}
+
+fn refs_path(blob: &Path) -> PathBuf {
+    let mut name = blob.as_os_str().to_os_string();
+    name.push(".refs");
+    PathBuf::from(name)
+}
+
+/// Hardlink `source` to `dest`, copying when a link is not possible
+///
+/// Goes through a temporary name so `dest` never holds a partial file.
+fn place(source: &Path, dest: &Path) -> Result<()> {
+    let mut tmp = dest.as_os_str().to_os_string();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    let _ = fs::remove_file(&tmp);
+
+    if fs::hard_link(source, &tmp).is_err() {
+        // std::fs::copy uses copy_file_range, which reflinks on btrfs/XFS
+        fs::copy(source, &tmp).map_err(|e| {
+            IaGetError::FileSystem(format!(
+                "Failed to copy {} to {}: {}",
+                source.display(),
+                dest.display(),
+                e
+            ))
+        })?;
+    }
+
+    fs::rename(&tmp, dest).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        IaGetError::FileSystem(format!("Failed to place {}: {}", dest.display(), e))
+    })
+}
+
+/// Whether `path` still holds the blob described by `blob_meta`
+fn still_holds_blob(path: &Path, blob_meta: &fs::Metadata) -> bool {
+    let Ok(meta) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // A hardlink is the same inode; a copy only has to keep the same size
+        if meta.dev() == blob_meta.dev() && meta.ino() == blob_meta.ino() {
+            return true;
+        }
+    }
+    meta.is_file() && meta.len() == blob_meta.len()
+}
+
+/// Every regular file below `dir` (empty when `dir` does not exist)
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(IaGetError::FileSystem(format!(
+                    "Failed to read {}: {}",
+                    dir.display(),
+                    e
+                )));
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(path),
+                Ok(t) if t.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,251 @@
+//! Stream a single archive file to any writer
+//!
+//! Used by `ia-get cat` to pipe a file into another program without touching
+//! disk. Bytes are written as they arrive, so a failed transfer cannot be rolled
+//! back; instead the next server is asked for the remaining bytes with a `Range`
+//! request. Checksums are computed on the fly and checked once the stream ends.
+
+use crate::{
+    IaGetError, Result,
+    core::download::metrics::download_metrics,
+    core::session::{ArchiveFile, ArchiveMetadata},
+    infrastructure::http::http_status_error,
+    utilities::common::{FileDigests, StreamingHasher},
+};
+use futures_util::StreamExt;
+use reqwest::Client;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Attempts per server before moving on to the next one
+const ATTEMPTS_PER_SERVER: usize = 2;
+/// Longest wait for the next chunk before a transfer is considered stalled
+const STALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(45);
+
+/// Outcome of streaming a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamOutcome {
+    /// The whole file was written; digests cover every byte
+    Complete(FileDigests),
+    /// The reader closed the pipe early; nothing could be verified
+    ReaderClosed { bytes_written: u64 },
+}
+
+/// Servers able to serve an item, in Archive.org's preferred order
+pub fn candidate_servers(metadata: &ArchiveMetadata) -> Vec<String> {
+    let mut servers = metadata.workable_servers.clone();
+    for datanode in [&metadata.d1, &metadata.d2, &metadata.server] {
+        if !datanode.is_empty() && !servers.contains(datanode) {
+            servers.push(datanode.clone());
+        }
+    }
+    servers
+}
+
+/// Stream `file_info` from the first server that delivers it, writing to `writer`
+///
+/// When `verify_md5` is set and the metadata publishes an MD5, a mismatch is
+/// returned as [`IaGetError::HashMismatch`] after the last byte was written.
+pub async fn stream_file<W: AsyncWrite + Unpin>(
+    client: &Client,
+    file_info: &ArchiveFile,
+    servers: &[String],
+    dir: &str,
+    verify_md5: bool,
+    writer: &mut W,
+) -> Result<StreamOutcome> {
+    let urls: Vec<String> = servers
+        .iter()
+        .map(|server| file_info.get_download_url(server, dir))
+        .collect();
+    stream_from_urls(client, &urls, file_info, verify_md5, writer).await
+}
+
+/// Stream `file_info` from the first of `urls` that delivers it, writing to `writer`
+///
+/// Each URL is one server's copy of the same bytes; [`stream_file`] passes the
+/// item's download URLs, but any URL serving the file works.
+pub async fn stream_from_urls<W: AsyncWrite + Unpin>(
+    client: &Client,
+    urls: &[String],
+    file_info: &ArchiveFile,
+    verify_md5: bool,
+    writer: &mut W,
+) -> Result<StreamOutcome> {
+    if urls.is_empty() {
+        return Err(IaGetError::Network(format!(
+            "No servers available for {}",
+            file_info.name
+        )));
+    }
+
+    let mut hasher = StreamingHasher::new();
+    let mut last_error = None;
+
+    for url in urls
+        .iter()
+        .flat_map(|url| std::iter::repeat_n(url, ATTEMPTS_PER_SERVER))
+    {
+        match stream_from_url(client, url, file_info, &mut hasher, writer).await {
+            Ok(()) => {
+                let digests = hasher.finalize();
+                if let (true, Some(expected)) = (verify_md5, &file_info.md5) {
+                    if !digests.matches_md5(expected) {
+                        if let Some(metrics) = download_metrics() {
+                            metrics.record_checksum_failure();
+                        }
+                        return Err(IaGetError::HashMismatch(format!(
+                            "MD5 verification failed for {} (expected {}, got {})",
+                            file_info.name, expected, digests.md5
+                        )));
+                    }
+                }
+                return Ok(StreamOutcome::Complete(digests));
+            }
+            Err(StreamError::ReaderClosed) => {
+                return Ok(StreamOutcome::ReaderClosed {
+                    bytes_written: hasher.bytes_hashed(),
+                });
+            }
+            Err(StreamError::Fatal(e)) => return Err(e),
+            Err(StreamError::Retry(e)) => {
+                let server = reqwest::Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+                    .unwrap_or_else(|| url.clone());
+                eprintln!("⚠️  {} from {}, trying again", e, server);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| IaGetError::Network(format!("Failed to stream {}", file_info.name))))
+}
+
+enum StreamError {
+    /// The consumer went away (e.g. `| head`)
+    ReaderClosed,
+    /// Another server may succeed
+    Retry(IaGetError),
+    /// Retrying cannot help
+    Fatal(IaGetError),
+}
+
+/// Fetch the bytes not yet written from one URL
+async fn stream_from_url<W: AsyncWrite + Unpin>(
+    client: &Client,
+    url: &str,
+    file_info: &ArchiveFile,
+    hasher: &mut StreamingHasher,
+    writer: &mut W,
+) -> std::result::Result<(), StreamError> {
+    let already_written = hasher.bytes_hashed();
+
+    // Identity encoding keeps byte offsets meaningful for resuming on another server
+    let mut request = client
+        .get(url)
+        .header("Accept-Encoding", "identity")
+        .header("X-Accept-Reduced-Priority", "1");
+    if already_written > 0 {
+        request = request.header("Range", format!("bytes={}-", already_written));
+    }
+
+    let response = request.send().await.map_err(|e| {
+        StreamError::Retry(IaGetError::Network(format!(
+            "Failed to start download: {}",
+            e
+        )))
+    })?;
+
+    let status = response.status();
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED
+        | reqwest::StatusCode::FORBIDDEN
+        | reqwest::StatusCode::NOT_FOUND => {
+            return Err(StreamError::Fatal(http_status_error(status, url)));
+        }
+        _ if !status.is_success() => {
+            return Err(StreamError::Retry(IaGetError::Network(format!(
+                "HTTP error {} for {}",
+                status, file_info.name
+            ))));
+        }
+        _ => {}
+    }
+
+    // A server that ignores the range resends everything; skip what was already written
+    let mut skip = if already_written > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT {
+        already_written
+    } else {
+        0
+    };
+
+    let mut stream = response.bytes_stream();
+    loop {
+        let chunk = match tokio::time::timeout(STALL_TIMEOUT, stream.next()).await {
+            Ok(Some(Ok(bytes))) => bytes,
+            Ok(Some(Err(e))) => {
+                return Err(StreamError::Retry(IaGetError::Network(format!(
+                    "Connection lost while streaming {}: {}",
+                    file_info.name, e
+                ))));
+            }
+            Ok(None) => break,
+            Err(_) => {
+                return Err(StreamError::Retry(IaGetError::Network(format!(
+                    "Read timeout (stall) for {}: No data received for {}s",
+                    file_info.name,
+                    STALL_TIMEOUT.as_secs()
+                ))));
+            }
+        };
+
+        let data = if skip > 0 {
+            let skipped = skip.min(chunk.len() as u64) as usize;
+            skip -= skipped as u64;
+            &chunk[skipped..]
+        } else {
+            &chunk[..]
+        };
+        if data.is_empty() {
+            continue;
+        }
+
+        if let Err(e) = writer.write_all(data).await {
+            return Err(if e.kind() == std::io::ErrorKind::BrokenPipe {
+                StreamError::ReaderClosed
+            } else {
+                StreamError::Fatal(IaGetError::FileSystem(format!(
+                    "Failed to write output: {}",
+                    e
+                )))
+            });
+        }
+        hasher.update(data);
+        crate::infrastructure::http::throttle(data.len()).await;
+    }
+
+    if let Err(e) = writer.flush().await {
+        return Err(if e.kind() == std::io::ErrorKind::BrokenPipe {
+            StreamError::ReaderClosed
+        } else {
+            StreamError::Fatal(IaGetError::FileSystem(format!(
+                "Failed to flush output: {}",
+                e
+            )))
+        });
+    }
+
+    if let Some(expected) = file_info.size {
+        if hasher.bytes_hashed() < expected {
+            return Err(StreamError::Retry(IaGetError::Network(format!(
+                "Stream ended early for {}: {} of {} bytes",
+                file_info.name,
+                hasher.bytes_hashed(),
+                expected
+            ))));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Incremental sync of a local mirror with an archive item
+//!
+//! Compares fresh metadata with the files on disk and works out what has to be
+//! fetched again. Files whose size differs are always re-downloaded. When the
+//! size matches, a local mtime equal to the published one (as left behind by
+//! `preserve_mtime`) is trusted; otherwise the published MD5 decides. Files that
+//! the previous session knew about but the item no longer lists are reported as
+//! removed and can be deleted or moved aside.
+
+use crate::{
+    IaGetError, Result,
+    core::session::{
+        ArchiveMetadata, DownloadSession, find_latest_session_file,
+        sanitize_filename_for_filesystem,
+    },
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directory (inside the mirror) holding session files
+pub const SESSION_DIR_NAME: &str = ".ia-get-sessions";
+/// Directory (inside the mirror) receiving quarantined files
+pub const QUARANTINE_DIR_NAME: &str = ".ia-get-quarantine";
+
+/// What to do with local files that were removed upstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RemovalMode {
+    /// Leave them in place and only report them
+    #[default]
+    Keep,
+    /// Delete them
+    Delete,
+    /// Move them under `.ia-get-quarantine/<timestamp>/`
+    Quarantine,
+}
+
+/// Differences between an item and a local mirror
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// Files listed by the item but missing locally
+    pub added: Vec<String>,
+    /// Files present locally whose size or checksum no longer matches
+    pub changed: Vec<String>,
+    /// Files from the previous sync that the item no longer lists
+    pub removed: Vec<String>,
+    /// Files already up to date
+    pub unchanged: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Files that have to be downloaded
+    pub fn to_download(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(self.changed.iter())
+    }
+
+    /// Whether the mirror is already up to date
+    pub fn is_up_to_date(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Local path of an archive file inside the mirror
+pub fn mirror_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(sanitize_filename_for_filesystem(file_name))
+}
+
+/// Most recent session for `identifier` in the mirror
+pub fn load_mirror_session(identifier: &str, dir: &Path) -> Option<DownloadSession> {
+    let session_dir = dir.join(SESSION_DIR_NAME);
+    let path = find_latest_session_file(identifier, &session_dir.to_string_lossy()).ok()??;
+    DownloadSession::load_from_file(&path).ok()
+}
+
+/// Compare `metadata` with the mirror in `dir`
+///
+/// `previous` is the last session for the mirror, used to spot removed files.
+/// With `verify_all`, MD5s are checked even when size and mtime match.
+pub fn plan_sync(
+    metadata: &ArchiveMetadata,
+    dir: &Path,
+    previous: Option<&DownloadSession>,
+    verify_all: bool,
+) -> Result<SyncPlan> {
+    let mut plan = SyncPlan::default();
+
+    for file_info in &metadata.files {
+        let path = mirror_path(dir, &file_info.name);
+        let Ok(local) = std::fs::metadata(&path) else {
+            plan.added.push(file_info.name.clone());
+            continue;
+        };
+
+        if file_info.size.is_some_and(|size| size != local.len()) {
+            plan.changed.push(file_info.name.clone());
+            continue;
+        }
+
+        let local_mtime = local
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let mtime_matches = file_info.mtime.is_some() && file_info.mtime == local_mtime;
+
+        if (verify_all || !mtime_matches) && !file_info.validate_md5(&path)? {
+            plan.changed.push(file_info.name.clone());
+        } else {
+            plan.unchanged.push(file_info.name.clone());
+        }
+    }
+
+    if let Some(previous) = previous {
+        let current: HashSet<&str> = metadata.files.iter().map(|f| f.name.as_str()).collect();
+        let mut removed: Vec<String> = previous
+            .archive_metadata
+            .files
+            .iter()
+            .map(|f| f.name.clone())
+            .chain(previous.file_status.keys().cloned())
+            .filter(|name| !current.contains(name.as_str()))
+            .filter(|name| mirror_path(dir, name).exists())
+            .collect();
+        removed.sort();
+        removed.dedup();
+        plan.removed = removed;
+    }
+
+    plan.added.sort();
+    plan.changed.sort();
+    plan.unchanged.sort();
+    Ok(plan)
+}
+
+/// Delete or quarantine the files listed in `removed`
+///
+/// Returns where each file ended up (its old path when deleted or kept).
+pub fn apply_removals(dir: &Path, removed: &[String], mode: RemovalMode) -> Result<Vec<PathBuf>> {
+    let quarantine = dir
+        .join(QUARANTINE_DIR_NAME)
+        .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+    let mut results = Vec::new();
+
+    for name in removed {
+        let path = mirror_path(dir, name);
+        match mode {
+            RemovalMode::Keep => results.push(path),
+            RemovalMode::Delete => {
+                std::fs::remove_file(&path).map_err(|e| {
+                    IaGetError::FileSystem(format!("Failed to delete {}: {}", path.display(), e))
+                })?;
+                results.push(path);
+            }
+            RemovalMode::Quarantine => {
+                std::fs::create_dir_all(&quarantine).map_err(|e| {
+                    IaGetError::FileSystem(format!("Failed to create quarantine directory: {}", e))
+                })?;
+                let target = quarantine.join(path.file_name().unwrap_or(name.as_ref()));
+                std::fs::rename(&path, &target).map_err(|e| {
+                    IaGetError::FileSystem(format!(
+                        "Failed to quarantine {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                results.push(target);
+            }
+        }
+    }
+
+    Ok(results)
+}
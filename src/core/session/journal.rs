@@ -0,0 +1,87 @@
+//! Append-only journal of file state transitions
+//!
+//! Rewriting a session file serialises the full item metadata, which is slow
+//! for items with tens of thousands of files and leaves a truncated file when
+//! the process dies mid-write. While downloads run, each file state change is
+//! instead appended as one JSON line to `<session>.journal` and synced.
+//!
+//! [`DownloadSession::save_to_file`] compacts the journal: it writes a fresh
+//! snapshot next to the old one, renames it into place and then drops the
+//! journal. Entries are numbered and the snapshot records the last number it
+//! includes, so a crash between the rename and the removal never replays stale
+//! entries. [`DownloadSession::load_from_file`] replays the newer entries,
+//! skipping a line torn by a crash.
+//!
+//! [`DownloadSession::save_to_file`]: super::DownloadSession::save_to_file
+//! [`DownloadSession::load_from_file`]: super::DownloadSession::load_from_file
+
+use crate::{IaGetError, Result, core::session::FileDownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// One recorded file state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// Position in the journal, counting from the session's first entry
+    pub seq: u64,
+    /// Name of the file in the item
+    pub file: String,
+    /// Full status of the file after the transition
+    pub status: FileDownloadStatus,
+    /// When the transition happened (Unix timestamp)
+    pub recorded_at: u64,
+}
+
+/// Journal belonging to the session file at `session_path`
+pub fn journal_path(session_path: &Path) -> PathBuf {
+    let mut path = session_path.as_os_str().to_owned();
+    path.push(".journal");
+    PathBuf::from(path)
+}
+
+/// Append `entry` to the journal and sync it to disk
+pub fn append_journal_entry(path: &Path, entry: &JournalEntry) -> Result<()> {
+    let mut line = serde_json::to_vec(entry).map_err(|e| {
+        IaGetError::JsonParsing(format!("Failed to serialize journal entry: {}", e))
+    })?;
+    line.push(b'\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| IaGetError::FileSystem(format!("Failed to open session journal: {}", e)))?;
+    file.write_all(&line)
+        .and_then(|_| file.sync_data())
+        .map_err(|e| IaGetError::FileSystem(format!("Failed to write session journal: {}", e)))
+}
+
+/// Entries of the journal at `path`, oldest first
+///
+/// A missing journal has no entries. Lines that do not parse, left by an
+/// append cut short by a crash, are skipped.
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(IaGetError::FileSystem(format!(
+                "Failed to read session journal: {}",
+                e
+            )));
+        }
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).split(b'\n') {
+        let line = line.map_err(|e| {
+            IaGetError::FileSystem(format!("Failed to read session journal: {}", e))
+        })?;
+        if let Ok(entry) = serde_json::from_slice(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
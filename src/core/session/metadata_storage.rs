@@ -4,6 +4,8 @@
 //! for download resumption and comprehensive file management.
 
 use crate::IaGetError;
+use crate::core::session::{JournalEntry, append_journal_entry, journal_path, read_journal};
+use crate::utilities::filters::{FileFormats, FormatCategory};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -93,6 +95,9 @@ pub struct DownloadSession {
     pub session_start: u64,
     /// Last update time
     pub last_updated: u64,
+    /// Number of the last journal entry folded into this snapshot
+    #[serde(default)]
+    pub journal_seq: u64,
 }
 
 /// Configuration used for downloads
@@ -120,6 +125,24 @@ pub struct DownloadConfig {
     pub auto_decompress: bool,
     /// Compression formats to decompress automatically
     pub decompress_formats: Vec<String>,
+    /// Number of byte-range segments to fetch concurrently per large file (0 or 1 disables)
+    #[serde(default)]
+    pub segments_per_file: u32,
+    /// Minimum file size before segmented downloading is used
+    #[serde(default)]
+    pub segment_min_size: Option<u64>,
+    /// Which published checksums to verify when `verify_md5` is enabled
+    #[serde(default)]
+    pub checksum_policy: ChecksumPolicy,
+    /// Tar or zip file that receives downloaded files instead of `output_dir`
+    #[serde(default)]
+    pub output_container: Option<String>,
+    /// Content-addressed store to link verified files from and into
+    #[serde(default)]
+    pub store_dir: Option<String>,
+    /// Order in which pending files are downloaded; kept so resumes use the same order
+    #[serde(default)]
+    pub download_order: DownloadOrder,
 }
 
 /// Status of an individual file download
@@ -143,6 +166,404 @@ pub struct FileDownloadStatus {
     pub server_used: Option<String>,
     /// Local file path
     pub local_path: String,
+    /// Per-segment progress when the file is fetched in byte ranges
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<SegmentStatus>,
+    /// Outcome of each checksum verified for the downloaded file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checksum_results: Vec<ChecksumResult>,
+    /// HTTP validators of the response the partial data came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_validator: Option<ResumeValidator>,
+    /// The local copy is out of date; it is fetched again and replaced only
+    /// once the new copy has downloaded
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replace_local_copy: bool,
+}
+
+impl FileDownloadStatus {
+    /// Reset to pending so the file is fetched again, keeping the local copy
+    /// until the new one has downloaded
+    pub fn mark_for_replacement(&mut self) {
+        self.status = DownloadState::Pending;
+        self.bytes_downloaded = 0;
+        self.completed_at = None;
+        self.error_message = None;
+        self.segments.clear();
+        self.checksum_results.clear();
+        self.resume_validator = None;
+        self.replace_local_copy = true;
+    }
+}
+
+/// HTTP validators identifying the exact version of a file being downloaded
+///
+/// Captured from the first response and sent back as `If-Range` on resume, so a
+/// server holding a newer version returns the whole file instead of appending
+/// new bytes onto stale partial data.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ResumeValidator {
+    /// `ETag` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl ResumeValidator {
+    /// Extract validators from response headers, if the server sent any
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let header = |name: reqwest::header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let validator = Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+        validator.if_range().is_some().then_some(validator)
+    }
+
+    /// Value for an `If-Range` header
+    ///
+    /// `If-Range` requires a strong validator, so weak ETags (`W/"..."`) fall back
+    /// to the Last-Modified date.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+/// Progress of a single byte range in a segmented download
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SegmentStatus {
+    /// Position of the segment within the file
+    pub index: usize,
+    /// First byte offset covered by the segment
+    pub start: u64,
+    /// Last byte offset covered by the segment (inclusive)
+    pub end: u64,
+    /// Bytes fetched so far for this segment
+    pub bytes_downloaded: u64,
+    /// Server the segment was last fetched from
+    pub server_used: Option<String>,
+    /// Whether all bytes of the segment have been fetched
+    pub completed: bool,
+}
+
+impl SegmentStatus {
+    /// Number of bytes covered by this segment
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Whether the segment covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// Bytes still missing for this segment
+    pub fn remaining(&self) -> u64 {
+        self.len().saturating_sub(self.bytes_downloaded)
+    }
+}
+
+/// Split a file of `total_size` bytes into at most `count` contiguous byte ranges
+///
+/// Segments are sized as evenly as possible; the last segment absorbs the remainder.
+/// Returns an empty list for empty files.
+pub fn plan_segments(total_size: u64, count: usize) -> Vec<SegmentStatus> {
+    if total_size == 0 || count == 0 {
+        return Vec::new();
+    }
+
+    let count = (count as u64).min(total_size);
+    let segment_size = total_size / count;
+
+    (0..count)
+        .map(|i| {
+            let start = i * segment_size;
+            let end = if i == count - 1 {
+                total_size - 1
+            } else {
+                start + segment_size - 1
+            };
+            SegmentStatus {
+                index: i as usize,
+                start,
+                end,
+                bytes_downloaded: 0,
+                server_used: None,
+                completed: false,
+            }
+        })
+        .collect()
+}
+
+/// Which published checksums to verify after a download
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumPolicy {
+    /// Verify MD5 only
+    #[default]
+    Md5,
+    /// Verify SHA1 only
+    Sha1,
+    /// Verify CRC32 only
+    Crc32,
+    /// Verify the strongest checksum published for each file (SHA1, then MD5, then CRC32)
+    Strongest,
+    /// Verify every checksum published for each file
+    All,
+}
+
+impl ChecksumPolicy {
+    /// Name as accepted on the command line and in configuration
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChecksumPolicy::Md5 => "md5",
+            ChecksumPolicy::Sha1 => "sha1",
+            ChecksumPolicy::Crc32 => "crc32",
+            ChecksumPolicy::Strongest => "strongest",
+            ChecksumPolicy::All => "all",
+        }
+    }
+}
+
+impl std::str::FromStr for ChecksumPolicy {
+    type Err = IaGetError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "md5" => Ok(ChecksumPolicy::Md5),
+            "sha1" => Ok(ChecksumPolicy::Sha1),
+            "crc32" => Ok(ChecksumPolicy::Crc32),
+            "strongest" => Ok(ChecksumPolicy::Strongest),
+            "all" => Ok(ChecksumPolicy::All),
+            _ => Err(IaGetError::Parse(format!(
+                "Invalid checksum policy '{}'. Valid values: md5, sha1, crc32, strongest, all",
+                s
+            ))),
+        }
+    }
+}
+
+/// Order in which the pending files of a session are downloaded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case", tag = "strategy", content = "priority")]
+pub enum DownloadOrder {
+    /// The order files appear in the item metadata
+    #[default]
+    Metadata,
+    /// Smallest files first, so small metadata and text files are usable quickly
+    SmallestFirst,
+    /// Largest files first, keeping connections busy with long transfers
+    LargestFirst,
+    /// By format category; listed categories come first, in the given order.
+    /// An empty list uses [`DownloadOrder::DEFAULT_CATEGORY_PRIORITY`].
+    FormatPriority(Vec<FormatCategory>),
+    /// Files matching earlier patterns first. Patterns are matched against the
+    /// file name and may use `*` and `?` wildcards.
+    Priority(Vec<String>),
+}
+
+impl DownloadOrder {
+    /// Category ranking used by `format` without an explicit list
+    pub const DEFAULT_CATEGORY_PRIORITY: [FormatCategory; 9] = [
+        FormatCategory::Metadata,
+        FormatCategory::Documents,
+        FormatCategory::Images,
+        FormatCategory::Audio,
+        FormatCategory::Data,
+        FormatCategory::Web,
+        FormatCategory::Software,
+        FormatCategory::Archives,
+        FormatCategory::Video,
+    ];
+
+    /// Stable-sort `files` (already in metadata order) by this strategy
+    pub fn sort(&self, files: &mut [&FileDownloadStatus]) {
+        match self {
+            DownloadOrder::Metadata => {}
+            DownloadOrder::SmallestFirst => {
+                files.sort_by_key(|f| f.file_info.size.unwrap_or(u64::MAX))
+            }
+            DownloadOrder::LargestFirst => {
+                files.sort_by_key(|f| std::cmp::Reverse(f.file_info.size.unwrap_or(0)))
+            }
+            DownloadOrder::FormatPriority(categories) => {
+                let categories = if categories.is_empty() {
+                    &Self::DEFAULT_CATEGORY_PRIORITY[..]
+                } else {
+                    &categories[..]
+                };
+                let formats = FileFormats::new();
+                files.sort_by_cached_key(|f| {
+                    Path::new(&f.file_info.name)
+                        .extension()
+                        .and_then(|ext| formats.find_category(&ext.to_string_lossy()))
+                        .and_then(|category| categories.iter().position(|c| *c == category))
+                        .unwrap_or(categories.len())
+                });
+            }
+            DownloadOrder::Priority(patterns) => {
+                files.sort_by_cached_key(|f| {
+                    patterns
+                        .iter()
+                        .position(|pattern| wildcard_match(pattern, &f.file_info.name))
+                        .unwrap_or(patterns.len())
+                });
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DownloadOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownloadOrder::Metadata => write!(f, "metadata"),
+            DownloadOrder::SmallestFirst => write!(f, "smallest"),
+            DownloadOrder::LargestFirst => write!(f, "largest"),
+            DownloadOrder::FormatPriority(categories) if categories.is_empty() => {
+                write!(f, "format")
+            }
+            DownloadOrder::FormatPriority(categories) => {
+                let names: Vec<String> = categories
+                    .iter()
+                    .map(|c| format!("{:?}", c).to_lowercase())
+                    .collect();
+                write!(f, "format:{}", names.join(","))
+            }
+            DownloadOrder::Priority(patterns) => write!(f, "priority:{}", patterns.join(",")),
+        }
+    }
+}
+
+impl std::str::FromStr for DownloadOrder {
+    type Err = IaGetError;
+
+    /// Parse `metadata`, `smallest`, `largest`, `format[:CATEGORY,...]` or
+    /// `priority:PATTERN,...`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (strategy, list) = match s.trim().split_once(':') {
+            Some((strategy, list)) => (strategy, Some(list)),
+            None => (s.trim(), None),
+        };
+        let items = || -> Vec<String> {
+            list.unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        match (strategy.to_lowercase().as_str(), list) {
+            ("metadata", None) => Ok(DownloadOrder::Metadata),
+            ("smallest", None) => Ok(DownloadOrder::SmallestFirst),
+            ("largest", None) => Ok(DownloadOrder::LargestFirst),
+            ("format", _) => items()
+                .iter()
+                .map(|name| {
+                    FormatCategory::all()
+                        .into_iter()
+                        .find(|c| format!("{:?}", c).eq_ignore_ascii_case(name))
+                        .ok_or_else(|| {
+                            IaGetError::Parse(format!(
+                                "Unknown format category '{}'. Valid categories: documents, images, audio, video, software, data, web, archives, metadata",
+                                name
+                            ))
+                        })
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map(DownloadOrder::FormatPriority),
+            ("priority", Some(_)) if !items().is_empty() => Ok(DownloadOrder::Priority(items())),
+            _ => Err(IaGetError::Parse(format!(
+                "Invalid download order '{}'. Valid values: metadata, smallest, largest, format[:CATEGORY,...], priority:PATTERN,...",
+                s
+            ))),
+        }
+    }
+}
+
+/// Case-insensitive match of `name` against a pattern with `*` and `?` wildcards
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Checksum algorithm published in Archive.org file metadata
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+    Crc32,
+}
+
+impl ChecksumAlgorithm {
+    /// Lowercase algorithm name
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha1 => "sha1",
+            ChecksumAlgorithm::Crc32 => "crc32",
+        }
+    }
+}
+
+/// Result of verifying one checksum of a downloaded file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChecksumResult {
+    /// Algorithm that was checked
+    pub algorithm: ChecksumAlgorithm,
+    /// Value published in the archive metadata
+    pub expected: String,
+    /// Value computed from the local file
+    pub actual: String,
+    /// Whether the values matched
+    pub matched: bool,
+    /// When the check was performed (Unix timestamp)
+    pub verified_at: u64,
+}
+
+impl ChecksumResult {
+    /// Whether the archive metadata published no value for the algorithm,
+    /// so the file could not be checked against it
+    pub fn is_missing(&self) -> bool {
+        self.expected.is_empty()
+    }
 }
 
 /// Download state enumeration
@@ -338,6 +759,10 @@ impl DownloadSession {
                         retry_count: 0,
                         server_used: None,
                         local_path,
+                        segments: Vec::new(),
+                        checksum_results: Vec::new(),
+                        resume_validator: None,
+                        replace_local_copy: false,
                     },
                 );
             }
@@ -352,26 +777,118 @@ impl DownloadSession {
             file_status,
             session_start: now,
             last_updated: now,
+            journal_seq: 0,
         }
     }
 
     /// Save session to disk
+    ///
+    /// The snapshot is written to a temporary file and renamed into place, so a
+    /// crash leaves either the old or the new snapshot. Its journal, now folded
+    /// into the snapshot, is removed afterwards.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| IaGetError::JsonParsing(format!("Failed to serialize session: {}", e)))?;
 
-        std::fs::write(path, json)
+        let tmp = path.with_extension("json.tmp");
+        std::fs::File::create(&tmp)
+            .and_then(|mut file| {
+                std::io::Write::write_all(&mut file, json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&tmp, path))
             .map_err(|e| IaGetError::FileSystem(format!("Failed to write session file: {}", e)))?;
 
+        // Entries up to journal_seq are in the snapshot; a leftover journal is harmless
+        let _ = std::fs::remove_file(journal_path(path));
         Ok(())
     }
 
-    /// Load session from disk
+    /// Load session from disk, replaying its journal
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path)
             .map_err(|e| anyhow!("Failed to read session file: {}", e))?;
 
-        serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse session file: {}", e))
+        let mut session: Self = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse session file: {}", e))?;
+        for entry in read_journal(&journal_path(path))? {
+            session.apply_journal_entry(entry);
+        }
+        Ok(session)
+    }
+
+    /// Append the current status of `file_name` to the journal of the session at `path`
+    ///
+    /// Cheap enough to call on every state change, unlike [`Self::save_to_file`].
+    pub fn journal_file_state<P: AsRef<Path>>(&mut self, path: P, file_name: &str) -> Result<()> {
+        let Some(status) = self.file_status.get(file_name) else {
+            return Ok(());
+        };
+        let entry = JournalEntry {
+            seq: self.journal_seq + 1,
+            file: file_name.to_string(),
+            status: status.clone(),
+            recorded_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        append_journal_entry(&journal_path(path.as_ref()), &entry)?;
+        self.journal_seq = entry.seq;
+        self.last_updated = entry.recorded_at;
+        Ok(())
+    }
+
+    /// Apply a journal entry newer than the snapshot
+    fn apply_journal_entry(&mut self, entry: JournalEntry) {
+        if entry.seq <= self.journal_seq {
+            return;
+        }
+        self.journal_seq = entry.seq;
+        self.last_updated = self.last_updated.max(entry.recorded_at);
+        self.file_status.insert(entry.file, entry.status);
+    }
+
+    /// Whether the item was updated on archive.org after this session was created
+    pub fn item_changed(&self, current: &ArchiveMetadata) -> bool {
+        current.item_last_updated != self.archive_metadata.item_last_updated
+    }
+
+    /// Adopt fresh metadata for an item that changed since the session was created
+    ///
+    /// Files whose size or checksums changed are reset to pending with their partial
+    /// progress and resume validators cleared, and their local copies marked for
+    /// replacement. Returns the names of the reset files; their partial data on
+    /// disk is stale and must be discarded by the caller.
+    pub fn apply_item_update(&mut self, current: ArchiveMetadata) -> Vec<String> {
+        let mut reset = Vec::new();
+
+        for (file_name, file_status) in self.file_status.iter_mut() {
+            let Some(latest) = current.files.iter().find(|f| f.name == *file_name) else {
+                continue;
+            };
+            let old = &file_status.file_info;
+            if old.size == latest.size
+                && old.md5 == latest.md5
+                && old.sha1 == latest.sha1
+                && old.crc32 == latest.crc32
+            {
+                continue;
+            }
+
+            file_status.file_info = latest.clone();
+            file_status.mark_for_replacement();
+            reset.push(file_name.clone());
+        }
+
+        self.archive_metadata = current;
+        self.last_updated = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        reset
     }
 
     /// Update file status
@@ -385,17 +902,42 @@ impl DownloadSession {
         }
     }
 
-    /// Get files that still need to be downloaded
+    /// Get files that still need to be downloaded, in the session's download order
     pub fn get_pending_files(&self) -> Vec<&str> {
-        self.file_status
+        let metadata_position: HashMap<&str, usize> = self
+            .archive_metadata
+            .files
             .iter()
-            .filter(|(_, status)| {
+            .enumerate()
+            .map(|(i, f)| (f.name.as_str(), i))
+            .collect();
+
+        let mut pending: Vec<&FileDownloadStatus> = self
+            .file_status
+            .values()
+            .filter(|status| {
                 matches!(
                     status.status,
-                    DownloadState::Pending | DownloadState::Failed
+                    DownloadState::Pending | DownloadState::Failed | DownloadState::Paused
                 )
             })
-            .map(|(name, _)| name.as_str())
+            .collect();
+        pending.sort_by(|a, b| {
+            let position = |f: &FileDownloadStatus| {
+                metadata_position
+                    .get(f.file_info.name.as_str())
+                    .copied()
+                    .unwrap_or(usize::MAX)
+            };
+            position(a)
+                .cmp(&position(b))
+                .then_with(|| a.file_info.name.cmp(&b.file_info.name))
+        });
+        self.download_config.download_order.sort(&mut pending);
+
+        pending
+            .into_iter()
+            .map(|status| status.file_info.name.as_str())
             .collect()
     }
 
@@ -445,8 +987,15 @@ pub struct DownloadProgress {
 
 impl ArchiveFile {
     /// Get the download URL for this file using the specified server
+    ///
+    /// `server` is a host name; a base URL with a scheme (such as a local
+    /// mirror at `http://127.0.0.1:8080`) is used as given.
     pub fn get_download_url(&self, server: &str, dir: &str) -> String {
-        format!("https://{}{}/{}", server, dir, self.name)
+        if server.contains("://") {
+            format!("{}{}/{}", server.trim_end_matches('/'), dir, self.name)
+        } else {
+            format!("https://{}{}/{}", server, dir, self.name)
+        }
     }
 
     /// Check if this file matches the given format filters
@@ -503,6 +1052,89 @@ impl ArchiveFile {
         }
     }
 
+    /// Checksum algorithms with a published value for this file, strongest first
+    pub fn available_checksums(&self) -> Vec<(ChecksumAlgorithm, &str)> {
+        [
+            (ChecksumAlgorithm::Sha1, &self.sha1),
+            (ChecksumAlgorithm::Md5, &self.md5),
+            (ChecksumAlgorithm::Crc32, &self.crc32),
+        ]
+        .into_iter()
+        .filter_map(|(algorithm, value)| {
+            value
+                .as_deref()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| (algorithm, v))
+        })
+        .collect()
+    }
+
+    /// Compare computed digests against the published checksums selected by `policy`
+    ///
+    /// A single-algorithm policy fails with a [missing](ChecksumResult::is_missing)
+    /// result when the file publishes other checksums but not the requested one.
+    /// Returns an empty list when the file publishes no checksums at all.
+    pub fn verify_checksums(
+        &self,
+        digests: &crate::utilities::common::FileDigests,
+        policy: ChecksumPolicy,
+    ) -> Vec<ChecksumResult> {
+        let available = self.available_checksums();
+        let requested = match policy {
+            ChecksumPolicy::Md5 => Some(ChecksumAlgorithm::Md5),
+            ChecksumPolicy::Sha1 => Some(ChecksumAlgorithm::Sha1),
+            ChecksumPolicy::Crc32 => Some(ChecksumAlgorithm::Crc32),
+            ChecksumPolicy::Strongest | ChecksumPolicy::All => None,
+        };
+
+        if available.is_empty() {
+            return Vec::new();
+        }
+        let selected: Vec<_> = match requested {
+            None if policy == ChecksumPolicy::All => available,
+            None => available.into_iter().take(1).collect(),
+            Some(algorithm) => vec![
+                available
+                    .into_iter()
+                    .find(|(a, _)| *a == algorithm)
+                    .unwrap_or((algorithm, "")),
+            ],
+        };
+
+        let verified_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        selected
+            .into_iter()
+            .map(|(algorithm, expected)| {
+                let (actual, matched) = match algorithm {
+                    ChecksumAlgorithm::Md5 => (&digests.md5, digests.matches_md5(expected)),
+                    ChecksumAlgorithm::Sha1 => (&digests.sha1, digests.matches_sha1(expected)),
+                    ChecksumAlgorithm::Crc32 => (&digests.crc32, digests.matches_crc32(expected)),
+                };
+                ChecksumResult {
+                    algorithm,
+                    expected: expected.trim().to_lowercase(),
+                    actual: actual.clone(),
+                    matched: matched && !expected.trim().is_empty(),
+                    verified_at,
+                }
+            })
+            .collect()
+    }
+
+    /// Hash a local file and verify it against the checksums selected by `policy`
+    pub fn validate_checksums<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        policy: ChecksumPolicy,
+    ) -> Result<Vec<ChecksumResult>> {
+        let digests = crate::utilities::common::StreamingHasher::hash_file(file_path)?;
+        Ok(self.verify_checksums(&digests, policy))
+    }
+
     /// Alternative validation for XML files using size and structure validation
     /// Returns true if the file appears to be a valid XML file
     fn validate_xml_file_alternative<P: AsRef<Path>>(&self, file_path: P) -> Result<bool> {
@@ -981,12 +1613,13 @@ pub fn find_latest_session_file(identifier: &str, session_dir: &str) -> Result<O
         }
     }
 
-    // Sort by modification time, newest first
-    session_files.sort_by_key(|path| {
+    // Sort by modification time, newest first; a journal append counts as a modification
+    let modified = |path: &Path| {
         std::fs::metadata(path)
             .and_then(|m| m.modified())
             .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-    });
+    };
+    session_files.sort_by_key(|path| modified(path).max(modified(&journal_path(path))));
 
     Ok(session_files
         .last()
@@ -2,8 +2,10 @@
 //!
 //! Contains session tracking, metadata storage, and download state management.
 
+pub use journal::*;
 pub use metadata_storage::*;
 
+pub mod journal;
 pub mod metadata_storage;
 
 /// Progress update information for callbacks
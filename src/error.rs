@@ -9,6 +9,7 @@
 //! - **FileSystem**: Local file operations, permission issues, disk space
 //! - **UrlFormat**: Invalid or malformed Internet Archive URLs
 //! - **Parse**: JSON/data parsing failures from API responses
+//! - **AuthRequired** / **NotFound**: restricted and missing items or files
 //! - **Io**: Low-level I/O operations (wraps std::io::Error)
 //! - **ReqwestError**: HTTP client errors (wraps reqwest::Error)
 //!
@@ -53,6 +54,10 @@ pub enum IaGetError {
     #[error("Hash verification failed: {0}")]
     HashMismatch(String),
 
+    /// The checksum policy asks for a checksum the item does not publish
+    #[error("Checksum unavailable: {0}")]
+    ChecksumUnavailable(String),
+
     /// JSON parsing errors
     #[error("Failed to parse JSON: {0}")]
     JsonParsing(String),
@@ -72,15 +77,109 @@ pub enum IaGetError {
     /// IO errors
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// A transfer was stopped because the download was paused or cancelled
+    #[error("Download interrupted")]
+    Interrupted,
+
+    /// The server refused an anonymous or unauthorised request (HTTP 401/403)
+    #[error("Authentication required: {0}")]
+    AuthRequired(String),
+
+    /// The item or file does not exist (HTTP 404)
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// Any other unsuccessful HTTP status
+    #[error("HTTP error {status}: {message}")]
+    Http { status: u16, message: String },
+
+    /// A connection or read that stalled past its deadline
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    /// The server asked for fewer requests (HTTP 429)
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        /// Wait requested through `Retry-After`
+        retry_after: Option<std::time::Duration>,
+    },
+}
+
+impl IaGetError {
+    /// Delay the server asked for before retrying, if any
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            IaGetError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Short, stable name of the error category, e.g. `not_found`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IaGetError::Network(_) => "network",
+            IaGetError::FileSystem(_) => "filesystem",
+            IaGetError::UrlFormat(_) => "url_format",
+            IaGetError::HashMismatch(_) => "hash_mismatch",
+            IaGetError::ChecksumUnavailable(_) => "checksum_unavailable",
+            IaGetError::JsonParsing(_) => "json_parsing",
+            IaGetError::Config(_) => "config",
+            IaGetError::Parse(_) => "parse",
+            IaGetError::NoFilesFound(_) => "no_files_found",
+            IaGetError::Io(_) => "io",
+            IaGetError::Interrupted => "interrupted",
+            IaGetError::AuthRequired(_) => "auth_required",
+            IaGetError::NotFound(_) => "not_found",
+            IaGetError::RateLimited { .. } => "rate_limited",
+            IaGetError::Http { .. } => "http",
+            IaGetError::Timeout(_) => "timeout",
+        }
+    }
+
+    /// HTTP status behind the error, when a server answered
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            IaGetError::Http { status, .. } => Some(*status),
+            IaGetError::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Whether the same request may succeed if repeated
+    pub fn is_transient(&self) -> bool {
+        match self {
+            IaGetError::Network(_) | IaGetError::Timeout(_) | IaGetError::RateLimited { .. } => {
+                true
+            }
+            IaGetError::Http { status, .. } => *status >= 500 || *status == 408,
+            _ => false,
+        }
+    }
+
+    /// Whether repeating the request cannot succeed without user action
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            IaGetError::AuthRequired(_)
+                | IaGetError::NotFound(_)
+                | IaGetError::ChecksumUnavailable(_)
+        )
+    }
 }
 
 impl From<reqwest::Error> for IaGetError {
     fn from(err: reqwest::Error) -> Self {
-        if err.is_connect() || err.is_timeout() {
+        if err.is_timeout() {
+            IaGetError::Timeout(format!("Connection failed: {}", err))
+        } else if err.is_connect() {
             IaGetError::Network(format!("Connection failed: {}", err))
-        } else if err.is_status() {
-            let status = err.status().map(|s| s.to_string()).unwrap_or_default();
-            IaGetError::Network(format!("HTTP error {}: {}", status, err))
+        } else if let Some(status) = err.status() {
+            IaGetError::Http {
+                status: status.as_u16(),
+                message: err.to_string(),
+            }
         } else {
             IaGetError::Network(err.to_string())
         }
@@ -4,7 +4,14 @@
 //! including proper rate limiting, server selection, and request formatting
 //! following the Internet Archive's guidelines and best practices.
 
-use crate::{IaGetError, Result, utilities::common::*};
+use crate::{
+    IaGetError, Result,
+    infrastructure::http::{
+        http_status_error, is_retryable_status, is_transient_reqwest_error, retry_after,
+        retry_policy,
+    },
+    utilities::common::*,
+};
 use reqwest::{Client, Response};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
@@ -30,69 +37,93 @@ impl ArchiveOrgApiClient {
     }
 
     /// Make a rate-limited request to Archive.org with proper compliance
+    ///
+    /// Rate limiting, server errors and transient network failures are retried
+    /// under the process-wide [`RetryPolicy`](crate::infrastructure::http::RetryPolicy).
     pub async fn make_request(&mut self, url: &str) -> Result<Response> {
-        // Implement minimum delay between requests
-        if let Some(last_time) = self.last_request_time {
-            let elapsed = last_time.elapsed();
-            let min_delay = Duration::from_millis(MIN_REQUEST_DELAY_MS);
-
-            if elapsed < min_delay {
-                let wait_time = min_delay - elapsed;
-                sleep(wait_time).await;
+        let policy = retry_policy();
+        let mut retries = 0;
+
+        loop {
+            // Implement minimum delay between requests
+            if let Some(last_time) = self.last_request_time {
+                let elapsed = last_time.elapsed();
+                let min_delay = Duration::from_millis(MIN_REQUEST_DELAY_MS);
+
+                if elapsed < min_delay {
+                    let wait_time = min_delay - elapsed;
+                    sleep(wait_time).await;
+                }
             }
-        }
-
-        // Add Archive.org-specific headers
-        let response = self
-            .client
-            .get(url)
-            .header("Accept", "application/json, text/plain, */*")
-            .header("Accept-Language", "en-US,en;q=0.9")
-            .header("Cache-Control", "no-cache")
-            .header("DNT", "1") // Do Not Track - be respectful
-            .timeout(Duration::from_secs(HTTP_TIMEOUT))
-            .send()
-            .await
-            .map_err(|e| IaGetError::Network(format!("Request to {} failed: {}", url, e)))?;
 
-        // Update request tracking
-        self.last_request_time = Some(Instant::now());
-        self.request_count += 1;
-
-        // Handle Archive.org-specific status codes
-        self.handle_archive_response(&response, url).await?;
-
-        Ok(response)
+            // Add Archive.org-specific headers
+            let result = self
+                .client
+                .get(url)
+                .header("Accept", "application/json, text/plain, */*")
+                .header("Accept-Language", "en-US,en;q=0.9")
+                .header("Cache-Control", "no-cache")
+                .header("DNT", "1") // Do Not Track - be respectful
+                .timeout(Duration::from_secs(HTTP_TIMEOUT))
+                .send()
+                .await;
+
+            // Update request tracking
+            self.last_request_time = Some(Instant::now());
+            self.request_count += 1;
+
+            match result {
+                Ok(response)
+                    if is_retryable_status(response.status()) && policy.can_retry(retries) =>
+                {
+                    let delay =
+                        policy.delay_for_response(retries, response.status(), response.headers());
+                    retries += 1;
+                    sleep(delay).await;
+                }
+                Ok(response) => {
+                    // Handle Archive.org-specific status codes
+                    self.handle_archive_response(&response, url).await?;
+                    return Ok(response);
+                }
+                Err(e) if is_transient_reqwest_error(&e) && policy.can_retry(retries) => {
+                    let delay = policy.backoff(retries);
+                    retries += 1;
+                    sleep(delay).await;
+                }
+                Err(e) => {
+                    return Err(IaGetError::Network(format!(
+                        "Request to {} failed: {}",
+                        url, e
+                    )));
+                }
+            }
+        }
     }
 
     /// Handle Archive.org-specific response status codes and rate limiting
     async fn handle_archive_response(&self, response: &Response, url: &str) -> Result<()> {
         match response.status() {
             reqwest::StatusCode::TOO_MANY_REQUESTS => {
-                let wait_time = response
-                    .headers()
-                    .get(reqwest::header::RETRY_AFTER)
-                    .and_then(|h| h.to_str().ok())
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .unwrap_or(DEFAULT_RETRY_DELAY_SECS);
-
-                Err(IaGetError::Network(format!(
-                    "Rate limited by Archive.org. Please wait {}s before retrying. URL: {}",
-                    wait_time, url
-                )))
+                let wait_time = retry_after(response.headers())
+                    .unwrap_or(Duration::from_secs(DEFAULT_RETRY_DELAY_SECS));
+
+                Err(IaGetError::RateLimited {
+                    message: format!(
+                        "Archive.org asked to wait {}s before retrying. URL: {}",
+                        wait_time.as_secs(),
+                        url
+                    ),
+                    retry_after: Some(wait_time),
+                })
             }
             reqwest::StatusCode::SERVICE_UNAVAILABLE => Err(IaGetError::Network(format!(
                 "Archive.org service temporarily unavailable. URL: {}",
                 url
             ))),
-            reqwest::StatusCode::FORBIDDEN => Err(IaGetError::Network(format!(
-                "Access forbidden. This item may be restricted or require authentication. URL: {}",
-                url
-            ))),
-            reqwest::StatusCode::NOT_FOUND => Err(IaGetError::Network(format!(
-                "Archive item not found. The identifier may be incorrect. URL: {}",
-                url
-            ))),
+            status @ (reqwest::StatusCode::UNAUTHORIZED
+            | reqwest::StatusCode::FORBIDDEN
+            | reqwest::StatusCode::NOT_FOUND) => Err(http_status_error(status, url)),
             status if status.is_server_error() => Err(IaGetError::Network(format!(
                 "Archive.org server error ({}). This is likely temporary. URL: {}",
                 status.as_u16(),
@@ -273,6 +304,12 @@ pub mod endpoints {
         "https://archive.org/advancedsearch.php"
     }
 
+    /// Scrape API endpoint - cursor-based paging through large result sets
+    /// Documentation: https://archive.org/developers/search.html#scraping-api
+    pub fn scrape() -> &'static str {
+        "https://archive.org/services/search/v1/scrape"
+    }
+
     /// Tasks API endpoint - for monitoring long-running operations
     /// Documentation: https://archive.org/developers/tasks.html
     pub fn tasks(identifier: &str) -> String {
@@ -380,6 +417,47 @@ impl EnhancedArchiveApiClient {
         self.search_items(&query, fields, rows, None).await
     }
 
+    /// Fetch one page of identifiers matching `query` from the Scrape API
+    ///
+    /// Unlike `search_items` there is no upper bound on the number of results:
+    /// pass the returned `cursor` to get the next page until it is `None`.
+    /// `count` is clamped to the API's 100-10000 range.
+    pub async fn scrape_items(
+        &mut self,
+        query: &str,
+        count: u32,
+        cursor: Option<&str>,
+    ) -> Result<ScrapePage> {
+        self.scrape_items_at(endpoints::scrape(), query, count, cursor)
+            .await
+    }
+
+    /// [`scrape_items`](Self::scrape_items) against another Scrape API endpoint
+    pub async fn scrape_items_at(
+        &mut self,
+        endpoint: &str,
+        query: &str,
+        count: u32,
+        cursor: Option<&str>,
+    ) -> Result<ScrapePage> {
+        let mut url = format!(
+            "{}?q={}&fields=identifier&count={}",
+            endpoint,
+            urlencoding::encode(query),
+            count.clamp(100, 10000)
+        );
+
+        if let Some(cursor) = cursor {
+            url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+        }
+
+        let response = self.base_client.make_request(&url).await?;
+        response
+            .json::<ScrapePage>()
+            .await
+            .map_err(|e| IaGetError::JsonParsing(format!("Invalid scrape response: {}", e)))
+    }
+
     /// Find related items to a given identifier
     /// Uses subject, creator, and collection fields for similarity
     pub async fn find_related_items(
@@ -517,6 +595,26 @@ pub struct ItemDetails {
     pub related_items: Option<serde_json::Value>,
 }
 
+/// One page of Scrape API results
+#[derive(Debug, serde::Deserialize)]
+pub struct ScrapePage {
+    #[serde(default)]
+    pub items: Vec<ScrapeItem>,
+    /// Number of items in this page
+    #[serde(default)]
+    pub count: u64,
+    /// Total number of matching items (only reported on the first page)
+    pub total: Option<u64>,
+    /// Cursor for the next page; absent on the last page
+    pub cursor: Option<String>,
+}
+
+/// A single Scrape API result
+#[derive(Debug, serde::Deserialize)]
+pub struct ScrapeItem {
+    pub identifier: String,
+}
+
 /// Service status information from Archive.org
 #[derive(Debug, serde::Deserialize)]
 pub struct ServiceStatus {
@@ -0,0 +1,178 @@
+//! Archive.org account credentials
+//!
+//! Restricted items answer 401/403 to anonymous clients. A user signs in with
+//! either their IA S3 keys, sent as `Authorization: LOW <access>:<secret>`, or
+//! the cookies of a logged-in browser session. Both are kept in
+//! `credentials.toml` in the configuration directory, apart from the regular
+//! configuration and readable by the owner only.
+//!
+//! The S3 header goes with every request and is dropped by the HTTP client
+//! when a redirect leaves the host. Cookies go to every host under
+//! `cookie_domains`, so they also reach the data servers that downloads are
+//! redirected to.
+
+use crate::{
+    Result,
+    error::IaGetError,
+    infrastructure::config::{ConfigManager, read_private_file, write_private_file},
+};
+use reqwest::ClientBuilder;
+use reqwest::cookie::CookieStore;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// File holding the credentials, inside the configuration directory
+pub const CREDENTIALS_FILE: &str = "credentials.toml";
+
+/// Signed-in account details attached to every request
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    /// IA S3 access key (https://archive.org/account/s3.php)
+    pub access_key: Option<String>,
+    /// IA S3 secret key
+    pub secret_key: Option<String>,
+    /// `Cookie` header of a logged-in session (`logged-in-user=...; logged-in-sig=...`)
+    pub cookies: Option<String>,
+    /// Domains, subdomains included, that receive the cookies
+    #[serde(default = "default_cookie_domains")]
+    pub cookie_domains: Vec<String>,
+}
+
+fn default_cookie_domains() -> Vec<String> {
+    vec!["archive.org".to_string()]
+}
+
+impl Default for Credentials {
+    fn default() -> Self {
+        Self {
+            access_key: None,
+            secret_key: None,
+            cookies: None,
+            cookie_domains: default_cookie_domains(),
+        }
+    }
+}
+
+// Secrets stay out of logs and panic messages
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redact = |value: &Option<String>| value.as_ref().map(|_| "****");
+        f.debug_struct("Credentials")
+            .field("access_key", &self.access_key)
+            .field("secret_key", &redact(&self.secret_key))
+            .field("cookies", &redact(&self.cookies))
+            .field("cookie_domains", &self.cookie_domains)
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Location of the credentials file
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(ConfigManager::get_config_directory()?.join(CREDENTIALS_FILE))
+    }
+
+    /// Load credentials from `path`; a missing file means signed out
+    ///
+    /// A file other users can read is refused, see [`read_private_file`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let Some(content) = read_private_file(path)? else {
+            return Ok(Self::default());
+        };
+        toml::from_str(&content)
+            .map_err(|e| IaGetError::Config(format!("Failed to parse credentials: {}", e)))
+    }
+
+    /// Write the credentials to `path`, readable and writable by the owner only
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| IaGetError::Config(format!("Failed to serialize credentials: {}", e)))?;
+        write_private_file(path, content.as_bytes())
+    }
+
+    /// Whether any credentials are present
+    pub fn is_configured(&self) -> bool {
+        self.authorization().is_some() || self.cookies.is_some()
+    }
+
+    /// `Authorization` header value for the S3 keys, if both are set
+    pub fn authorization(&self) -> Option<String> {
+        match (&self.access_key, &self.secret_key) {
+            (Some(access), Some(secret)) => Some(format!("LOW {}:{}", access, secret)),
+            _ => None,
+        }
+    }
+
+    /// Configure `builder` to send these credentials
+    pub fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder> {
+        if let Some(authorization) = self.authorization() {
+            let mut value = HeaderValue::from_str(&authorization).map_err(|_| {
+                IaGetError::Config("S3 keys contain invalid characters".to_string())
+            })?;
+            value.set_sensitive(true);
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, value);
+            builder = builder.default_headers(headers);
+        }
+
+        if let Some(ref cookies) = self.cookies {
+            let mut header = HeaderValue::from_str(cookies).map_err(|_| {
+                IaGetError::Config("Cookies contain invalid characters".to_string())
+            })?;
+            header.set_sensitive(true);
+            builder = builder.cookie_provider(Arc::new(ScopedCookies {
+                header,
+                domains: self.cookie_domains.clone(),
+            }));
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Sends a fixed cookie header to a set of domains and ignores `Set-Cookie`
+struct ScopedCookies {
+    header: HeaderValue,
+    domains: Vec<String>,
+}
+
+impl CookieStore for ScopedCookies {
+    fn set_cookies(&self, _: &mut dyn Iterator<Item = &HeaderValue>, _: &url::Url) {}
+
+    fn cookies(&self, url: &url::Url) -> Option<HeaderValue> {
+        let host = url.host_str()?;
+        self.domains
+            .iter()
+            .any(|domain| {
+                host == domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+            .then(|| self.header.clone())
+    }
+}
+
+fn global_slot() -> &'static RwLock<Credentials> {
+    static GLOBAL_CREDENTIALS: OnceLock<RwLock<Credentials>> = OnceLock::new();
+    GLOBAL_CREDENTIALS.get_or_init(|| RwLock::new(Credentials::default()))
+}
+
+/// Install the process-wide credentials
+///
+/// Clients built afterwards send them; existing clients stay as they are.
+pub fn set_global_credentials(credentials: Credentials) {
+    if let Ok(mut slot) = global_slot().write() {
+        *slot = credentials;
+    }
+}
+
+/// The credentials in effect
+pub fn global_credentials() -> Credentials {
+    global_slot()
+        .read()
+        .map(|credentials| credentials.clone())
+        .unwrap_or_default()
+}
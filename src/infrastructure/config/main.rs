@@ -60,6 +60,58 @@ pub struct Config {
     /// User agent string override
     pub user_agent_override: Option<String>,
 
+    /// Bandwidth limit shared by all transfers (e.g. "5MB/s" or "2MB/s 09:00-18:00, unlimited")
+    #[serde(default)]
+    pub bandwidth_limit: Option<String>,
+
+    /// Content-addressed store shared between downloads (None disables deduplication)
+    #[serde(default)]
+    pub store_dir: Option<String>,
+
+    /// Proxy for all requests (http, https or socks5 URL; None uses HTTP_PROXY and friends)
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Hosts that bypass the proxy (comma-separated)
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+
+    /// Extra trusted root CA certificates (PEM or DER files)
+    #[serde(default)]
+    pub ca_certificates: Vec<String>,
+
+    /// Client certificate for TLS client authentication (PEM file)
+    #[serde(default)]
+    pub client_certificate: Option<String>,
+
+    /// Private key of the client certificate (PEM file; None if it is in the certificate file)
+    #[serde(default)]
+    pub client_key: Option<String>,
+
+    /// Command run after each file downloads and verifies
+    #[serde(default)]
+    pub hook_file_success: Option<String>,
+
+    /// Command run after a file fails to download
+    #[serde(default)]
+    pub hook_file_failure: Option<String>,
+
+    /// Command run after every file of an item downloaded
+    #[serde(default)]
+    pub hook_item_success: Option<String>,
+
+    /// Command run after an item finished with failed files or could not be downloaded
+    #[serde(default)]
+    pub hook_item_failure: Option<String>,
+
+    /// Seconds a hook may run before it is killed
+    #[serde(default = "default_hook_timeout")]
+    pub hook_timeout: u64,
+
+    /// Hooks allowed to run at the same time
+    #[serde(default = "default_hook_concurrency")]
+    pub hook_concurrency: usize,
+
     /// Recently used archive URLs (for quick access)
     pub recent_urls: Vec<String>,
 
@@ -99,6 +151,19 @@ impl Default for Config {
             default_decompress_formats: None,
             http_timeout: 30,
             user_agent_override: None,
+            bandwidth_limit: None,
+            store_dir: None,
+            proxy: None,
+            no_proxy: None,
+            ca_certificates: Vec::new(),
+            client_certificate: None,
+            client_key: None,
+            hook_file_success: None,
+            hook_file_failure: None,
+            hook_item_success: None,
+            hook_item_failure: None,
+            hook_timeout: default_hook_timeout(),
+            hook_concurrency: default_hook_concurrency(),
             recent_urls: Vec::new(),
             max_recent_urls: 10,
             filter_presets: vec![
@@ -135,6 +200,14 @@ impl Default for Config {
     }
 }
 
+fn default_hook_timeout() -> u64 {
+    300
+}
+
+fn default_hook_concurrency() -> usize {
+    2
+}
+
 /// Configuration manager with file I/O and validation
 pub struct ConfigManager {
     config_dir: PathBuf,
@@ -251,6 +324,35 @@ impl ConfigManager {
             ));
         }
 
+        if let Some(ref limit) = config.bandwidth_limit {
+            crate::infrastructure::http::BandwidthSchedule::parse(limit).map_err(|e| {
+                IaGetError::Config(format!("Invalid bandwidth limit '{}': {}", limit, e))
+            })?;
+        }
+
+        if let Some(ref proxy) = config.proxy {
+            reqwest::Proxy::all(proxy.as_str())
+                .map_err(|e| IaGetError::Config(format!("Invalid proxy URL: {}", e)))?;
+        }
+
+        if config.client_key.is_some() && config.client_certificate.is_none() {
+            return Err(IaGetError::Config(
+                "client_key requires client_certificate".to_string(),
+            ));
+        }
+
+        if config.hook_timeout == 0 {
+            return Err(IaGetError::Config(
+                "Hook timeout must be at least 1 second".to_string(),
+            ));
+        }
+
+        if config.hook_concurrency == 0 || config.hook_concurrency > 16 {
+            return Err(IaGetError::Config(
+                "Hook concurrency must be between 1 and 16".to_string(),
+            ));
+        }
+
         if config.max_recent_urls > 100 {
             return Err(IaGetError::Config(
                 "Max recent URLs cannot exceed 100".to_string(),
@@ -2,6 +2,10 @@
 //!
 //! Contains configuration loading, validation, and management.
 
+pub use credentials::*;
 pub use main::*;
+pub use private_file::*;
 
+pub mod credentials;
 pub mod main;
+pub mod private_file;
@@ -0,0 +1,62 @@
+//! Files readable by their owner only
+//!
+//! Secrets such as the account credentials and the daemon token are written
+//! with mode 0600 and, on Unix, refused when other users can access them, as
+//! SSH does for keys.
+
+use crate::{Result, error::IaGetError};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Read the private file at `path`; `None` when it does not exist
+pub fn read_private_file(path: &Path) -> Result<Option<String>> {
+    let read_error =
+        |e: std::io::Error| IaGetError::Config(format!("Failed to read {}: {}", path.display(), e));
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(read_error(e)),
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = file.metadata().map_err(read_error)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(IaGetError::Config(format!(
+                "{} is accessible by other users; run 'chmod 600 {}'",
+                path.display(),
+                path.display()
+            )));
+        }
+    }
+
+    let mut content = String::new();
+    file.read_to_string(&mut content).map_err(read_error)?;
+    Ok(Some(content))
+}
+
+/// Replace the file at `path` with `content`, readable and writable by the owner only
+pub fn write_private_file(path: &Path, content: &[u8]) -> Result<()> {
+    let write_error = |e: std::io::Error| {
+        IaGetError::Config(format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    // Created with restricted permissions so the secret is never exposed
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let _ = std::fs::remove_file(&temp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp_path).map_err(write_error)?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(write_error)
+}
@@ -0,0 +1,288 @@
+//! Process-wide bandwidth limiting
+//!
+//! Provides a token-bucket limiter shared by every concurrent transfer in the
+//! process, with optional time-of-day schedules.
+//!
+//! ## Limit syntax
+//!
+//! A limit is a comma-separated list of entries. Each entry is a rate, optionally
+//! followed by a local time window. Entries without a window set the rate used
+//! outside all windows.
+//!
+//! - `5MB/s` - cap at 5 MB/s at all times
+//! - `2MB/s 09:00-18:00, unlimited` - 2 MB/s during office hours, no cap otherwise
+//! - `500KB/s@22:00-06:00` - windows may cross midnight
+//!
+//! Downloaders call [`throttle`] after each chunk; it returns immediately when no
+//! limit is installed.
+//!
+//! There is a single limit per process. The CLI installs it once at startup, from
+//! `--limit-rate` or the saved `bandwidth_limit`; download requests cannot change
+//! it, so items run side by side by the daemon or the queue share the same cap.
+
+use crate::{Result, error::IaGetError, utilities::filters::parse_size_string};
+use chrono::{Local, NaiveTime};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// A rate that applies during a local time-of-day window
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateWindow {
+    /// Window start (inclusive)
+    pub start: NaiveTime,
+    /// Window end (exclusive); earlier than `start` for windows crossing midnight
+    pub end: NaiveTime,
+    /// Bytes per second, or None for unlimited
+    pub rate: Option<u64>,
+}
+
+impl RateWindow {
+    /// Whether `time` falls inside this window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Bandwidth limit with optional time-of-day windows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandwidthSchedule {
+    /// Rate used outside every window (None for unlimited)
+    pub default_rate: Option<u64>,
+    /// Time windows checked in order; the first match wins
+    pub windows: Vec<RateWindow>,
+}
+
+impl BandwidthSchedule {
+    /// Parse a limit such as `5MB/s` or `2MB/s 09:00-18:00, unlimited`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut schedule = Self::default();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let normalized = entry.replace('@', " ").replace('–', "-");
+            let mut tokens: Vec<&str> = normalized.split_whitespace().collect();
+            if tokens.last() == Some(&"otherwise") {
+                tokens.pop();
+            }
+
+            let window = match tokens.last() {
+                Some(token) if token.contains(':') => {
+                    let token = tokens.pop().unwrap_or_default();
+                    Some(parse_window(token)?)
+                }
+                _ => None,
+            };
+
+            let rate = parse_rate(&tokens.concat())?;
+            match window {
+                Some((start, end)) => schedule.windows.push(RateWindow { start, end, rate }),
+                None => schedule.default_rate = rate,
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    /// Rate in bytes per second at the given local time (None for unlimited)
+    pub fn rate_at(&self, time: NaiveTime) -> Option<u64> {
+        self.windows
+            .iter()
+            .find(|w| w.contains(time))
+            .map(|w| w.rate)
+            .unwrap_or(self.default_rate)
+    }
+
+    /// Whether the schedule never limits throughput
+    pub fn is_unlimited(&self) -> bool {
+        self.default_rate.is_none() && self.windows.iter().all(|w| w.rate.is_none())
+    }
+}
+
+/// Parse a rate such as `5MB/s`, `512KB` or `unlimited` into bytes per second
+fn parse_rate(rate: &str) -> Result<Option<u64>> {
+    let rate = rate.trim();
+    if rate.is_empty() {
+        return Err(IaGetError::Parse("Missing bandwidth rate".to_string()));
+    }
+    if matches!(rate.to_lowercase().as_str(), "unlimited" | "none" | "0") {
+        return Ok(None);
+    }
+
+    let size = rate
+        .strip_suffix("/s")
+        .or_else(|| rate.strip_suffix("/S"))
+        .unwrap_or(rate);
+    let bytes = parse_size_string(size)
+        .map_err(|_| IaGetError::Parse(format!("Invalid bandwidth rate: {}", rate)))?;
+
+    Ok(if bytes == 0 { None } else { Some(bytes) })
+}
+
+/// Parse a `HH:MM-HH:MM` window
+fn parse_window(window: &str) -> Result<(NaiveTime, NaiveTime)> {
+    let invalid = || IaGetError::Parse(format!("Invalid time window: {}", window));
+    let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| invalid())?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").map_err(|_| invalid())?;
+    Ok((start, end))
+}
+
+/// Token bucket state shared across transfers
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket bandwidth limiter following a [`BandwidthSchedule`]
+///
+/// The bucket holds at most one second of tokens. Callers that overdraw it sleep
+/// until the debt is repaid, so concurrent transfers share the rate fairly.
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    schedule: BandwidthSchedule,
+    bucket: Mutex<Bucket>,
+}
+
+impl BandwidthLimiter {
+    /// Create a limiter for the given schedule
+    pub fn new(schedule: BandwidthSchedule) -> Self {
+        Self {
+            schedule,
+            bucket: Mutex::new(Bucket {
+                tokens: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Schedule followed by this limiter
+    pub fn schedule(&self) -> &BandwidthSchedule {
+        &self.schedule
+    }
+
+    /// Rate currently in effect (None for unlimited)
+    pub fn current_rate(&self) -> Option<u64> {
+        self.schedule.rate_at(Local::now().time())
+    }
+
+    /// Account for `bytes` transferred, returning how long the caller should pause
+    pub fn reserve(&self, bytes: u64) -> Duration {
+        self.reserve_at_rate(bytes, self.current_rate())
+    }
+
+    fn reserve_at_rate(&self, bytes: u64, rate: Option<u64>) -> Duration {
+        let Ok(mut bucket) = self.bucket.lock() else {
+            return Duration::ZERO;
+        };
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.last_refill = now;
+
+        let Some(rate) = rate else {
+            bucket.tokens = 0.0;
+            return Duration::ZERO;
+        };
+
+        let rate = rate as f64;
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate) - bytes as f64;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+
+    /// Wait until `bytes` may be transferred under the current rate
+    pub async fn acquire(&self, bytes: u64) {
+        let delay = self.reserve(bytes);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn global_slot() -> &'static RwLock<Option<Arc<BandwidthLimiter>>> {
+    static GLOBAL_LIMITER: OnceLock<RwLock<Option<Arc<BandwidthLimiter>>>> = OnceLock::new();
+    GLOBAL_LIMITER.get_or_init(|| RwLock::new(None))
+}
+
+/// Install (or clear, with None) the process-wide bandwidth limit
+pub fn set_global_bandwidth_limit(schedule: Option<BandwidthSchedule>) {
+    let limiter = schedule
+        .filter(|s| !s.is_unlimited())
+        .map(|s| Arc::new(BandwidthLimiter::new(s)));
+    if let Ok(mut slot) = global_slot().write() {
+        *slot = limiter;
+    }
+}
+
+/// The process-wide bandwidth limiter, if one is installed
+pub fn global_bandwidth_limiter() -> Option<Arc<BandwidthLimiter>> {
+    global_slot().read().ok().and_then(|slot| slot.clone())
+}
+
+/// Pause as needed after transferring `bytes` under the process-wide limit
+pub async fn throttle(bytes: usize) {
+    if let Some(limiter) = global_bandwidth_limiter() {
+        limiter.acquire(bytes as u64).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_simple_rate() {
+        let schedule = BandwidthSchedule::parse("5MB/s").unwrap();
+        assert_eq!(schedule.default_rate, Some(5 * 1024 * 1024));
+        assert!(schedule.windows.is_empty());
+
+        let schedule = BandwidthSchedule::parse("unlimited").unwrap();
+        assert!(schedule.is_unlimited());
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let schedule = BandwidthSchedule::parse("2 MB/s 09:00–18:00, unlimited otherwise").unwrap();
+        assert_eq!(schedule.default_rate, None);
+        assert_eq!(schedule.rate_at(time(10, 30)), Some(2 * 1024 * 1024));
+        assert_eq!(schedule.rate_at(time(18, 0)), None);
+        assert_eq!(schedule.rate_at(time(8, 59)), None);
+    }
+
+    #[test]
+    fn test_window_across_midnight() {
+        let schedule = BandwidthSchedule::parse("1MB/s, 100KB/s@22:00-06:00").unwrap();
+        assert_eq!(schedule.rate_at(time(23, 0)), Some(100 * 1024));
+        assert_eq!(schedule.rate_at(time(5, 59)), Some(100 * 1024));
+        assert_eq!(schedule.rate_at(time(12, 0)), Some(1024 * 1024));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(BandwidthSchedule::parse("fast").is_err());
+        assert!(BandwidthSchedule::parse("1MB/s 25:00-26:00").is_err());
+        assert!(BandwidthSchedule::parse("09:00-18:00").is_err());
+    }
+
+    #[test]
+    fn test_bucket_delays_overdraw() {
+        let limiter = BandwidthLimiter::new(BandwidthSchedule::default());
+        assert_eq!(limiter.reserve_at_rate(1_000_000, None), Duration::ZERO);
+
+        // Empty bucket: 1000 bytes at 1000 B/s costs about a second
+        let delay = limiter.reserve_at_rate(1000, Some(1000));
+        assert!(delay > Duration::from_millis(900) && delay <= Duration::from_secs(1));
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Contains HTTP client implementations and networking utilities.
 
+pub use bandwidth::*;
 pub use http_client::*;
 pub use network::*;
+pub use retry::*;
 
+pub mod bandwidth;
 pub mod http_client;
 pub mod network;
+pub mod retry;
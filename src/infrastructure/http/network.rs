@@ -2,20 +2,26 @@
 //!
 //! Handles HTTP requests, retries, and response processing for Internet Archive interactions.
 
-use crate::{Result, error::IaGetError, utilities::common::HTTP_TIMEOUT};
+use crate::{
+    Result,
+    error::IaGetError,
+    infrastructure::config::global_credentials,
+    infrastructure::http::{is_retryable_status, retry_policy},
+    utilities::common::HTTP_TIMEOUT,
+};
 use colored::*;
 use reqwest::Client;
 
 /// Checks if a URL is accessible by sending appropriate request method, with retry logic and dynamic wait reasons
+///
+/// Retries follow the process-wide [`RetryPolicy`](super::RetryPolicy).
 pub async fn is_url_accessible(
     url: &str,
     client: &Client,
     spinner: Option<&indicatif::ProgressBar>,
 ) -> Result<()> {
+    let policy = retry_policy();
     let mut retries = 0;
-    let max_retries = 5;
-    let mut delay = std::time::Duration::from_secs(30); // More conservative initial delay
-    let max_delay = std::time::Duration::from_secs(600); // 10 minutes max (reduced from 15)
 
     loop {
         // Use GET for metadata URLs since Archive.org returns 405 for HEAD requests on metadata endpoints
@@ -37,80 +43,50 @@ pub async fn is_url_accessible(
 
         match result {
             Ok(response) => {
-                // Check for HTTP 429 and Retry-After header BEFORE moving response
-                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                    let wait_time = if let Some(retry_after) =
-                        response.headers().get(reqwest::header::RETRY_AFTER)
-                    {
-                        retry_after
-                            .to_str()
-                            .ok()
-                            .and_then(|s| s.parse::<u64>().ok())
-                            .unwrap_or(60)
+                let status = response.status();
+                if is_retryable_status(status) && policy.can_retry(retries) {
+                    let delay = policy.delay_for_response(retries, status, response.headers());
+                    let wait_reason = if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        format!(
+                            "Rate limited by server (HTTP 429) - waiting {}s (attempt {}/{})",
+                            delay.as_secs(),
+                            retries + 1,
+                            policy.max_retries
+                        )
                     } else {
-                        60 // Default if no Retry-After header
+                        format!(
+                            "Server error (HTTP {}) - retrying in {}s (attempt {}/{})",
+                            status.as_u16(),
+                            delay.as_secs(),
+                            retries + 1,
+                            policy.max_retries
+                        )
                     };
+                    report_wait(spinner, &wait_reason);
 
-                    let wait_reason = format!(
-                        "Rate limited by server (HTTP 429) - waiting {}s as requested",
-                        wait_time
-                    );
-                    if let Some(spinner) = spinner {
-                        spinner.set_message(format!("{} {}", "⏳".yellow(), wait_reason));
-                    } else {
-                        eprintln!("{} {}", "▲".yellow(), wait_reason);
-                    }
-
-                    tokio::time::sleep(std::time::Duration::from_secs(wait_time)).await;
                     retries += 1;
+                    tokio::time::sleep(delay).await;
                     continue;
                 }
 
-                if let Err(e) = response.error_for_status() {
-                    if retries < max_retries && is_transient_error(&e) {
-                        let wait_reason = format!(
-                            "Server error (HTTP {}) - retrying in {}s (attempt {}/{})",
-                            e.status().map(|s| s.as_u16()).unwrap_or(0),
-                            delay.as_secs(),
-                            retries + 1,
-                            max_retries
-                        );
-
-                        if let Some(spinner) = spinner {
-                            spinner.set_message(format!("{} {}", "⏳".yellow(), wait_reason));
-                        } else {
-                            eprintln!("{} {}", "▲".yellow(), wait_reason);
-                        }
-
-                        retries += 1;
-                        tokio::time::sleep(delay).await;
-                        delay = std::cmp::min(delay * 2, max_delay);
-                        continue;
-                    } else {
-                        return Err(e.into());
-                    }
+                if !status.is_success() {
+                    return Err(http_status_error(status, url));
                 }
                 return Ok(());
             }
             Err(e) => {
-                let is_transient = matches!(&e, IaGetError::Network(_));
-                if retries < max_retries && is_transient {
+                if policy.can_retry(retries) {
+                    let delay = policy.backoff(retries);
                     let wait_reason = format!(
                         "Network error - retrying in {}s (attempt {}/{})",
                         delay.as_secs(),
                         retries + 1,
-                        max_retries
+                        policy.max_retries
                     );
-
-                    if let Some(spinner) = spinner {
-                        spinner.set_message(format!("{} {}", "⏳".yellow(), wait_reason));
-                    } else {
-                        eprintln!("{} {}", "▲".yellow(), wait_reason);
-                    }
+                    report_wait(spinner, &wait_reason);
 
                     retries += 1;
                     tokio::time::sleep(delay).await;
-                    delay = std::cmp::min(delay * 2, max_delay);
                     continue;
                 } else {
                     return Err(e);
@@ -120,6 +96,39 @@ pub async fn is_url_accessible(
     }
 }
 
+fn report_wait(spinner: Option<&indicatif::ProgressBar>, wait_reason: &str) {
+    if let Some(spinner) = spinner {
+        spinner.set_message(format!("{} {}", "⏳".yellow(), wait_reason));
+    } else {
+        eprintln!("{} {}", "▲".yellow(), wait_reason);
+    }
+}
+
+/// Error for an unsuccessful response, telling "needs login" apart from "not found"
+pub fn http_status_error(status: reqwest::StatusCode, url: &str) -> IaGetError {
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            let hint = if global_credentials().is_configured() {
+                "the stored credentials do not grant access; check them with 'ia-get auth status'"
+            } else {
+                "the item is restricted; sign in with 'ia-get auth keys' or 'ia-get auth cookies'"
+            };
+            IaGetError::AuthRequired(format!("HTTP {} for {}: {}", status.as_u16(), url, hint))
+        }
+        reqwest::StatusCode::NOT_FOUND => {
+            IaGetError::NotFound(format!("{} does not exist (HTTP 404)", url))
+        }
+        _ => IaGetError::Http {
+            status: status.as_u16(),
+            message: format!(
+                "{} for {}",
+                status.canonical_reason().unwrap_or("Unknown"),
+                url
+            ),
+        },
+    }
+}
+
 /// Determines if a reqwest::Error is transient and should be retried
 pub fn is_transient_reqwest_error(e: &reqwest::Error) -> bool {
     // Check for network-level transient errors
@@ -0,0 +1,222 @@
+//! Retry policy shared by every request path
+//!
+//! Metadata fetches, API calls and file transfers all retry through a
+//! [`RetryPolicy`]: a bounded number of retries with exponential backoff,
+//! jitter so parallel transfers do not retry in lockstep, a delay cap of
+//! [`MAX_RETRY_DELAY_SECS`], and the server's `Retry-After` taking precedence
+//! whenever it sends one.
+//!
+//! The default policy is process-wide, installed from `Config.max_retries`
+//! with [`set_global_retry_policy`]. A download whose request sets
+//! `max_retries` carries its own policy down to its transfers instead, so
+//! items downloaded side by side do not change each other's retries.
+
+use crate::{
+    infrastructure::config::Config,
+    utilities::common::{DEFAULT_RETRY_DELAY_SECS, MAX_RETRY_DELAY_SECS},
+};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// Delay before the first retry; each further retry doubles it
+pub const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How failed requests are retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including `Retry-After`
+    pub max_delay: Duration,
+    /// Fraction of each backoff delay that is randomised away (0.0 to 1.0)
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(Config::default().max_retries as u32)
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: INITIAL_RETRY_DELAY,
+            max_delay: Duration::from_secs(MAX_RETRY_DELAY_SECS),
+            jitter: 0.25,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(u32::try_from(config.max_retries).unwrap_or(u32::MAX))
+    }
+
+    /// Total attempts, the first one included
+    pub fn max_attempts(&self) -> u32 {
+        self.max_retries.saturating_add(1)
+    }
+
+    /// Whether another attempt is allowed after `retries` retries
+    pub fn can_retry(&self, retries: u32) -> bool {
+        retries < self.max_retries
+    }
+
+    /// Jittered exponential delay before retry number `retry` (counting from 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(retry.min(31)))
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        exponential.mul_f64(1.0 - jitter)
+    }
+
+    /// Delay before retry number `retry`, deferring to a server-provided `Retry-After`
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(wait) => wait.min(self.max_delay),
+            None => self.backoff(retry),
+        }
+    }
+
+    /// Delay before retrying a request that got `status` with `headers`
+    ///
+    /// Rate limiting without a `Retry-After` waits at least
+    /// [`DEFAULT_RETRY_DELAY_SECS`], as Archive.org asks clients to back off.
+    pub fn delay_for_response(
+        &self,
+        retry: u32,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Duration {
+        let retry_after = retry_after(headers).or_else(|| {
+            (status == StatusCode::TOO_MANY_REQUESTS)
+                .then(|| Duration::from_secs(DEFAULT_RETRY_DELAY_SECS).max(self.backoff(retry)))
+        });
+        self.delay(retry, retry_after)
+    }
+}
+
+/// Whether a response status is worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// `Retry-After` header as a delay, given in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Uniform value in [0, 1) from the standard library's randomly keyed hasher
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn global_slot() -> &'static RwLock<RetryPolicy> {
+    static GLOBAL_POLICY: OnceLock<RwLock<RetryPolicy>> = OnceLock::new();
+    GLOBAL_POLICY.get_or_init(|| RwLock::new(RetryPolicy::default()))
+}
+
+/// Install the process-wide retry policy
+pub fn set_global_retry_policy(policy: RetryPolicy) {
+    if let Ok(mut slot) = global_slot().write() {
+        *slot = policy;
+    }
+}
+
+/// The retry policy in effect
+pub fn retry_policy() -> RetryPolicy {
+    global_slot()
+        .read()
+        .map(|policy| *policy)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = RetryPolicy {
+            jitter: 0.0,
+            ..RetryPolicy::new(5)
+        };
+        assert_eq!(policy.backoff(0), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(16));
+        assert_eq!(
+            policy.backoff(40),
+            Duration::from_secs(MAX_RETRY_DELAY_SECS)
+        );
+        assert_eq!(policy.max_attempts(), 6);
+        assert!(policy.can_retry(4));
+        assert!(!policy.can_retry(5));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_in_range() {
+        let policy = RetryPolicy::new(3);
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay <= Duration::from_secs(8));
+            assert!(delay >= Duration::from_secs(6));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_takes_precedence() {
+        let policy = RetryPolicy::new(3);
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        assert_eq!(
+            policy.delay_for_response(0, StatusCode::SERVICE_UNAVAILABLE, &headers),
+            Duration::from_secs(120)
+        );
+
+        // Capped like any other delay
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        assert_eq!(
+            policy.delay_for_response(0, StatusCode::TOO_MANY_REQUESTS, &headers),
+            Duration::from_secs(MAX_RETRY_DELAY_SECS)
+        );
+
+        // Dates in the past mean "now"
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_rate_limit_without_header_waits_default() {
+        let policy = RetryPolicy::new(3);
+        let delay = policy.delay_for_response(0, StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new());
+        assert_eq!(delay, Duration::from_secs(DEFAULT_RETRY_DELAY_SECS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
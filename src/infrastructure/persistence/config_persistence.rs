@@ -102,6 +102,19 @@ pub struct ConfigWithSources {
     pub default_decompress_formats: ConfigValue<Option<String>>,
     pub http_timeout: ConfigValue<u64>,
     pub user_agent_override: ConfigValue<Option<String>>,
+    pub bandwidth_limit: ConfigValue<Option<String>>,
+    pub store_dir: ConfigValue<Option<String>>,
+    pub proxy: ConfigValue<Option<String>>,
+    pub no_proxy: ConfigValue<Option<String>>,
+    pub ca_certificates: ConfigValue<Vec<String>>,
+    pub client_certificate: ConfigValue<Option<String>>,
+    pub client_key: ConfigValue<Option<String>>,
+    pub hook_file_success: ConfigValue<Option<String>>,
+    pub hook_file_failure: ConfigValue<Option<String>>,
+    pub hook_item_success: ConfigValue<Option<String>>,
+    pub hook_item_failure: ConfigValue<Option<String>>,
+    pub hook_timeout: ConfigValue<u64>,
+    pub hook_concurrency: ConfigValue<usize>,
 }
 
 impl Default for ConfigWithSources {
@@ -163,6 +176,43 @@ impl Default for ConfigWithSources {
                 default_config.user_agent_override,
                 ConfigSource::Default,
             ),
+            bandwidth_limit: ConfigValue::new(
+                default_config.bandwidth_limit,
+                ConfigSource::Default,
+            ),
+            store_dir: ConfigValue::new(default_config.store_dir, ConfigSource::Default),
+            proxy: ConfigValue::new(default_config.proxy, ConfigSource::Default),
+            no_proxy: ConfigValue::new(default_config.no_proxy, ConfigSource::Default),
+            ca_certificates: ConfigValue::new(
+                default_config.ca_certificates,
+                ConfigSource::Default,
+            ),
+            client_certificate: ConfigValue::new(
+                default_config.client_certificate,
+                ConfigSource::Default,
+            ),
+            client_key: ConfigValue::new(default_config.client_key, ConfigSource::Default),
+            hook_file_success: ConfigValue::new(
+                default_config.hook_file_success,
+                ConfigSource::Default,
+            ),
+            hook_file_failure: ConfigValue::new(
+                default_config.hook_file_failure,
+                ConfigSource::Default,
+            ),
+            hook_item_success: ConfigValue::new(
+                default_config.hook_item_success,
+                ConfigSource::Default,
+            ),
+            hook_item_failure: ConfigValue::new(
+                default_config.hook_item_failure,
+                ConfigSource::Default,
+            ),
+            hook_timeout: ConfigValue::new(default_config.hook_timeout, ConfigSource::Default),
+            hook_concurrency: ConfigValue::new(
+                default_config.hook_concurrency,
+                ConfigSource::Default,
+            ),
         }
     }
 }
@@ -187,6 +237,19 @@ impl ConfigWithSources {
             default_decompress_formats: self.default_decompress_formats.value.clone(),
             http_timeout: self.http_timeout.value,
             user_agent_override: self.user_agent_override.value.clone(),
+            bandwidth_limit: self.bandwidth_limit.value.clone(),
+            store_dir: self.store_dir.value.clone(),
+            proxy: self.proxy.value.clone(),
+            no_proxy: self.no_proxy.value.clone(),
+            ca_certificates: self.ca_certificates.value.clone(),
+            client_certificate: self.client_certificate.value.clone(),
+            client_key: self.client_key.value.clone(),
+            hook_file_success: self.hook_file_success.value.clone(),
+            hook_file_failure: self.hook_file_failure.value.clone(),
+            hook_item_success: self.hook_item_success.value.clone(),
+            hook_item_failure: self.hook_item_failure.value.clone(),
+            hook_timeout: self.hook_timeout.value,
+            hook_concurrency: self.hook_concurrency.value,
             // These fields aren't tracked with sources yet but use defaults
             recent_urls: Vec::new(),
             max_recent_urls: 10,
@@ -220,6 +283,19 @@ impl ConfigWithSources {
         apply_if_higher_priority!(default_decompress_formats);
         apply_if_higher_priority!(http_timeout);
         apply_if_higher_priority!(user_agent_override);
+        apply_if_higher_priority!(bandwidth_limit);
+        apply_if_higher_priority!(store_dir);
+        apply_if_higher_priority!(proxy);
+        apply_if_higher_priority!(no_proxy);
+        apply_if_higher_priority!(ca_certificates);
+        apply_if_higher_priority!(client_certificate);
+        apply_if_higher_priority!(client_key);
+        apply_if_higher_priority!(hook_file_success);
+        apply_if_higher_priority!(hook_file_failure);
+        apply_if_higher_priority!(hook_item_success);
+        apply_if_higher_priority!(hook_item_failure);
+        apply_if_higher_priority!(hook_timeout);
+        apply_if_higher_priority!(hook_concurrency);
     }
 }
 
@@ -374,7 +450,20 @@ impl ConfigPersistence {
                 source.clone(),
             ),
             http_timeout: ConfigValue::new(config.http_timeout, source.clone()),
-            user_agent_override: ConfigValue::new(config.user_agent_override, source),
+            user_agent_override: ConfigValue::new(config.user_agent_override, source.clone()),
+            bandwidth_limit: ConfigValue::new(config.bandwidth_limit, source.clone()),
+            store_dir: ConfigValue::new(config.store_dir, source.clone()),
+            proxy: ConfigValue::new(config.proxy, source.clone()),
+            no_proxy: ConfigValue::new(config.no_proxy, source.clone()),
+            ca_certificates: ConfigValue::new(config.ca_certificates, source.clone()),
+            client_certificate: ConfigValue::new(config.client_certificate, source.clone()),
+            client_key: ConfigValue::new(config.client_key, source.clone()),
+            hook_file_success: ConfigValue::new(config.hook_file_success, source.clone()),
+            hook_file_failure: ConfigValue::new(config.hook_file_failure, source.clone()),
+            hook_item_success: ConfigValue::new(config.hook_item_success, source.clone()),
+            hook_item_failure: ConfigValue::new(config.hook_item_failure, source.clone()),
+            hook_timeout: ConfigValue::new(config.hook_timeout, source.clone()),
+            hook_concurrency: ConfigValue::new(config.hook_concurrency, source),
         }
     }
 }
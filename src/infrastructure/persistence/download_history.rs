@@ -8,6 +8,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Status of a download task
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -111,32 +112,93 @@ impl DownloadHistory {
         Ok(history)
     }
 
-    /// Save download history to file
+    /// Save download history to file, atomically
+    ///
+    /// Use [`update`](Self::update) to change a history that other downloads
+    /// may be writing too.
     pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+
         self.last_updated = Utc::now();
         self.cleanup_old_entries();
-
-        // Ensure parent directory exists
-        if let Some(parent) = path.as_ref().parent() {
-            fs::create_dir_all(parent).map_err(|e| {
-                IaGetError::Config(format!("Failed to create config directory: {}", e))
-            })?;
-        }
+        Self::create_parent(path.as_ref())?;
 
         let content = serde_json::to_string_pretty(self).map_err(|e| {
             IaGetError::Config(format!("Failed to serialize download history: {}", e))
         })?;
 
-        fs::write(path.as_ref(), content)
-            .map_err(|e| IaGetError::Config(format!("Failed to write download history: {}", e)))?;
+        // Every writer gets its own temporary file, so none renames another's half-written one
+        let tmp = path.as_ref().with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, path.as_ref()))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                IaGetError::Config(format!("Failed to write download history: {}", e))
+            })
+    }
 
-        Ok(())
+    fn create_parent(path: &Path) -> Result<()> {
+        match path.parent() {
+            Some(parent) => fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::Config(format!("Failed to create config directory: {}", e))
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the lock serialising changes to the history at `path`; it is
+    /// released when the returned file is dropped
+    fn lock(path: &Path) -> Result<fs::File> {
+        Self::create_parent(path)?;
+        let lock_error = |e: std::io::Error| {
+            IaGetError::Config(format!("Failed to lock download history: {}", e))
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("json.lock"))
+            .map_err(lock_error)?;
+        file.lock().map_err(lock_error)?;
+        Ok(file)
     }
 
-    /// Add a new download entry
-    pub fn add_entry(&mut self, entry: DownloadHistoryEntry) {
+    /// Apply `change` to the history saved at `path` and save the result
+    ///
+    /// The file is re-read under a lock, so entries written meanwhile by
+    /// other downloads are kept.
+    pub fn update<T>(path: &Path, change: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        Self::try_update(path, |history| Ok(change(history)))
+    }
+
+    /// Like [`update`](Self::update), but a change that fails leaves the file untouched
+    pub fn try_update<T>(path: &Path, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let _lock = Self::lock(path)?;
+        let mut history = Self::load_or_create(path)?;
+        let result = change(&mut history)?;
+        history.save_to_file(path)?;
+        Ok(result)
+    }
+
+    /// Add a new download entry, returning its id
+    ///
+    /// Ids are made unique: downloads of the same item started in the same
+    /// second get a numbered suffix.
+    pub fn add_entry(&mut self, mut entry: DownloadHistoryEntry) -> String {
+        let base = entry.id.clone();
+        let mut suffix = 1;
+        while self.get_entry(&entry.id).is_some() {
+            suffix += 1;
+            entry.id = format!("{}-{}", base, suffix);
+        }
+        let id = entry.id.clone();
         self.entries.push(entry);
         self.cleanup_old_entries();
+        id
     }
 
     /// Update an existing entry by ID
@@ -179,7 +241,7 @@ impl DownloadHistory {
     /// Get recent entries (newest first)
     pub fn get_recent_entries(&self, limit: usize) -> Vec<&DownloadHistoryEntry> {
         let mut entries = self.entries.iter().collect::<Vec<_>>();
-        entries.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.started_at));
         entries.into_iter().take(limit).collect()
     }
 
@@ -193,7 +255,8 @@ impl DownloadHistory {
     fn cleanup_old_entries(&mut self) {
         if self.entries.len() > self.max_entries {
             // Sort by start time (newest first) and keep only max_entries
-            self.entries.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+            self.entries
+                .sort_by_key(|e| std::cmp::Reverse(e.started_at));
             self.entries.truncate(self.max_entries);
         }
     }
@@ -370,6 +433,12 @@ mod tests {
             enable_compression: true,
             auto_decompress: false,
             decompress_formats: vec![],
+            segments_per_file: 1,
+            segment_min_size: None,
+            checksum_policy: Default::default(),
+            output_container: None,
+            store_dir: None,
+            download_order: Default::default(),
             verify_md5: true,
             preserve_mtime: true,
             user_agent: "test-agent".to_string(),
@@ -402,6 +471,12 @@ mod tests {
             enable_compression: true,
             auto_decompress: false,
             decompress_formats: vec![],
+            segments_per_file: 1,
+            segment_min_size: None,
+            checksum_policy: Default::default(),
+            output_container: None,
+            store_dir: None,
+            download_order: Default::default(),
             verify_md5: true,
             preserve_mtime: true,
             user_agent: "test-agent".to_string(),
@@ -424,6 +499,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_concurrent_updates_keep_every_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("ia-get-db.json");
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let db_path = db_path.clone();
+                std::thread::spawn(move || {
+                    for item in 0..10 {
+                        let config = DownloadConfig {
+                            output_dir: "/tmp/test".to_string(),
+                            max_concurrent: 1,
+                            format_filters: vec![],
+                            min_size: None,
+                            max_size: None,
+                            enable_compression: false,
+                            auto_decompress: false,
+                            decompress_formats: vec![],
+                            segments_per_file: 1,
+                            segment_min_size: None,
+                            checksum_policy: Default::default(),
+                            output_container: None,
+                            store_dir: None,
+                            download_order: Default::default(),
+                            verify_md5: true,
+                            preserve_mtime: false,
+                            user_agent: "test-agent".to_string(),
+                        };
+                        let entry = DownloadHistoryEntry::new(
+                            format!("item-{}", item),
+                            "test-input".to_string(),
+                            "/tmp/test".to_string(),
+                            config,
+                        );
+                        let id =
+                            DownloadHistory::update(&db_path, |history| history.add_entry(entry))
+                                .unwrap();
+                        DownloadHistory::try_update(&db_path, |history| {
+                            history.update_entry(&id, |entry| entry.mark_completed())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let history = DownloadHistory::load_from_file(&db_path).unwrap();
+        assert_eq!(history.entries.len(), 80);
+        let ids: std::collections::HashSet<_> = history.entries.iter().map(|e| &e.id).collect();
+        assert_eq!(ids.len(), 80);
+        assert!(
+            history
+                .entries
+                .iter()
+                .all(|entry| entry.status == TaskStatus::Success)
+        );
+    }
+
     #[test]
     fn test_entry_progress_tracking() {
         let config = DownloadConfig {
@@ -435,6 +571,12 @@ mod tests {
             enable_compression: true,
             auto_decompress: false,
             decompress_formats: vec![],
+            segments_per_file: 1,
+            segment_min_size: None,
+            checksum_policy: Default::default(),
+            output_container: None,
+            store_dir: None,
+            download_order: Default::default(),
             verify_md5: true,
             preserve_mtime: true,
             user_agent: "test-agent".to_string(),
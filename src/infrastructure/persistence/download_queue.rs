@@ -0,0 +1,283 @@
+//! Persistent download queue
+//!
+//! Items waiting to be downloaded are kept in `ia-get-queue.json` next to the
+//! download history, each with its own `DownloadRequest`. The queue runs in
+//! priority order, highest first, and in the order items were added among
+//! equal priorities; `entries` is always kept in that order.
+//!
+//! The CLI, the GUI and the daemon edit the file while a `queue run` may be
+//! working through it, so every change goes through [`DownloadQueue::update`],
+//! which holds an exclusive lock on `ia-get-queue.json.lock` while it reads
+//! the current file, applies the change and writes it back.
+
+use crate::{Result, core::download::DownloadRequest, error::IaGetError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Where a queue entry is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for its turn
+    Queued,
+    /// Downloading
+    Running,
+    /// Stopped until resumed; finished files and partial ones are kept
+    Paused,
+    /// Every file downloaded
+    Completed,
+    /// Ended with an error or with files that could not be downloaded
+    Failed,
+    /// Stopped for good
+    Cancelled,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Paused => "paused",
+            JobState::Completed => "completed",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the entry will not run again
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+/// One queued item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueEntry {
+    pub id: u64,
+    /// Higher runs first
+    pub priority: i32,
+    pub request: DownloadRequest,
+    /// `paused` entries are held back until set to `queued` again
+    pub state: JobState,
+    pub added_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Why the last run failed
+    pub error: Option<String>,
+    /// Files in the item selected for download, once known
+    pub total_files: usize,
+    pub completed_files: usize,
+    pub failed_files: usize,
+}
+
+impl QueueEntry {
+    /// Move the entry to `state`
+    pub fn set_state(&mut self, state: JobState) {
+        self.state = state;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// The queue, in run order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadQueue {
+    next_id: u64,
+    pub entries: Vec<QueueEntry>,
+}
+
+impl DownloadQueue {
+    /// Load the queue at `path`, or start an empty one
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| IaGetError::FileSystem(format!("Failed to read download queue: {}", e)))?;
+        let mut queue: Self = serde_json::from_str(&content).map_err(|e| {
+            IaGetError::JsonParsing(format!("Failed to parse download queue: {}", e))
+        })?;
+        queue.sort();
+        Ok(queue)
+    }
+
+    /// Write the queue atomically
+    ///
+    /// Use [`update`](Self::update) to change a queue others may be editing.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+
+        Self::create_parent(path)?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            IaGetError::JsonParsing(format!("Failed to serialize download queue: {}", e))
+        })?;
+        // Every writer gets its own temporary file, so none renames another's half-written one
+        let tmp = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                IaGetError::FileSystem(format!("Failed to write download queue: {}", e))
+            })
+    }
+
+    fn create_parent(path: &Path) -> Result<()> {
+        match path.parent() {
+            Some(parent) => fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to create queue directory: {}", e))
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the lock serialising changes to the queue at `path`; it is
+    /// released when the returned file is dropped
+    fn lock(path: &Path) -> Result<fs::File> {
+        Self::create_parent(path)?;
+        let lock_error = |e: std::io::Error| {
+            IaGetError::FileSystem(format!("Failed to lock download queue: {}", e))
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("json.lock"))
+            .map_err(lock_error)?;
+        file.lock().map_err(lock_error)?;
+        Ok(file)
+    }
+
+    /// Apply `change` to the queue saved at `path` and save the result
+    pub fn update<T>(path: &Path, change: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        Self::try_update(path, |queue| Ok::<_, IaGetError>(change(queue)))
+    }
+
+    /// Like [`update`](Self::update), but a change that fails leaves the file untouched
+    pub fn try_update<T, E: From<IaGetError>>(
+        path: &Path,
+        change: impl FnOnce(&mut Self) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let _lock = Self::lock(path)?;
+        let mut queue = Self::load(path)?;
+        let result = change(&mut queue)?;
+        queue.save(path)?;
+        Ok(result)
+    }
+
+    /// Stable sort into run order
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.priority));
+    }
+
+    /// Add `request` behind everything of the same or higher priority
+    pub fn add(&mut self, request: DownloadRequest, priority: i32) -> QueueEntry {
+        self.next_id += 1;
+        let now = Utc::now();
+        let entry = QueueEntry {
+            id: self.next_id,
+            priority,
+            request,
+            state: JobState::Queued,
+            added_at: now,
+            updated_at: now,
+            error: None,
+            total_files: 0,
+            completed_files: 0,
+            failed_files: 0,
+        };
+        self.entries.push(entry.clone());
+        self.sort();
+        entry
+    }
+
+    pub fn get(&self, id: u64) -> Option<&QueueEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut QueueEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<QueueEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Drop completed and cancelled entries, returning how many were removed
+    pub fn remove_finished(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|entry| !matches!(entry.state, JobState::Completed | JobState::Cancelled));
+        before - self.entries.len()
+    }
+
+    pub fn set_priority(&mut self, id: u64, priority: i32) -> bool {
+        let Some(entry) = self.get_mut(id) else {
+            return false;
+        };
+        entry.priority = priority;
+        entry.updated_at = Utc::now();
+        self.sort();
+        true
+    }
+
+    /// Move entry `id` to `position` (0-based) in the run order
+    ///
+    /// The entry takes the priority of the one it lands in front of (or
+    /// behind, at the end), so the order holds when more items are added.
+    pub fn move_to(&mut self, id: u64, position: usize) -> bool {
+        let Some(index) = self.entries.iter().position(|entry| entry.id == id) else {
+            return false;
+        };
+        let mut entry = self.entries.remove(index);
+        let position = position.min(self.entries.len());
+        if let Some(neighbour) = self
+            .entries
+            .get(position)
+            .or_else(|| position.checked_sub(1).and_then(|i| self.entries.get(i)))
+        {
+            entry.priority = neighbour.priority;
+        }
+        entry.updated_at = Utc::now();
+        self.entries.insert(position, entry);
+        true
+    }
+
+    /// The first entry waiting to run
+    pub fn next_queued(&self) -> Option<&QueueEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.state == JobState::Queued)
+    }
+
+    /// Queue again entries a stopped run left `running`, and failed ones
+    /// too when `retry_failed` is set; returns how many were queued
+    pub fn requeue(&mut self, retry_failed: bool) -> usize {
+        let mut requeued = 0;
+        for entry in &mut self.entries {
+            if entry.state == JobState::Running || (retry_failed && entry.state == JobState::Failed)
+            {
+                entry.error = None;
+                entry.set_state(JobState::Queued);
+                requeued += 1;
+            }
+        }
+        requeued
+    }
+}
+
+/// Get the default path of the download queue
+pub fn get_default_queue_path() -> Result<PathBuf> {
+    let config_dir = crate::infrastructure::config::ConfigManager::get_config_directory()?;
+    Ok(config_dir.join("ia-get-queue.json"))
+}
@@ -0,0 +1,166 @@
+//! Collection mirror checkpoints
+//!
+//! A collection mirror walks the Scrape API one page at a time. The checkpoint
+//! records the cursor of the page being worked on and which of its items are
+//! done, so an interrupted mirror picks up where it stopped instead of paging
+//! through the whole collection again.
+
+use crate::{Result, error::IaGetError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the checkpoint inside the mirror's output directory
+pub const MIRROR_CHECKPOINT_FILE: &str = ".ia-get-mirror.json";
+
+/// Progress of a collection mirror
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MirrorCheckpoint {
+    /// Scrape query being mirrored
+    pub query: String,
+    /// Cursor of the page being processed (None for the first page)
+    pub cursor: Option<String>,
+    /// Items of the current page that are already done
+    pub done_in_page: Vec<String>,
+    /// Total matching items reported by the API
+    pub total_items: Option<u64>,
+    /// Items downloaded by this mirror
+    pub mirrored: usize,
+    /// Items skipped because they were already complete
+    pub skipped: usize,
+    /// Items whose download failed
+    pub failed: Vec<String>,
+    /// Whether every page has been processed
+    pub finished: bool,
+    /// When the mirror was started
+    pub started_at: DateTime<Utc>,
+    /// When the checkpoint was last written
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MirrorCheckpoint {
+    /// Start a new mirror of `query`
+    pub fn new(query: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            query: query.into(),
+            cursor: None,
+            done_in_page: Vec::new(),
+            total_items: None,
+            mirrored: 0,
+            skipped: 0,
+            failed: Vec::new(),
+            finished: false,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Checkpoint path for a mirror written to `output_dir`
+    pub fn path_for(output_dir: &Path) -> PathBuf {
+        output_dir.join(MIRROR_CHECKPOINT_FILE)
+    }
+
+    /// Resume the checkpoint at `path` if it mirrors the same query
+    ///
+    /// A missing file or a checkpoint for a different query starts afresh.
+    pub fn load_or_new(path: &Path, query: &str) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(query));
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            IaGetError::FileSystem(format!("Failed to read mirror checkpoint: {}", e))
+        })?;
+        let checkpoint: Self = serde_json::from_str(&content).map_err(|e| {
+            IaGetError::JsonParsing(format!("Failed to parse mirror checkpoint: {}", e))
+        })?;
+
+        if checkpoint.query == query {
+            Ok(checkpoint)
+        } else {
+            Ok(Self::new(query))
+        }
+    }
+
+    /// Write the checkpoint atomically
+    pub fn save_to_file(&mut self, path: &Path) -> Result<()> {
+        self.updated_at = Utc::now();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to create mirror directory: {}", e))
+            })?;
+        }
+
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            IaGetError::JsonParsing(format!("Failed to serialize mirror checkpoint: {}", e))
+        })?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to write mirror checkpoint: {}", e))
+            })
+    }
+
+    /// Whether `identifier` was already handled on the current page
+    pub fn is_done(&self, identifier: &str) -> bool {
+        self.done_in_page.iter().any(|id| id == identifier)
+    }
+
+    /// Record an item of the current page as handled
+    pub fn mark_done(&mut self, identifier: &str) {
+        if !self.is_done(identifier) {
+            self.done_in_page.push(identifier.to_string());
+        }
+    }
+
+    /// Move on to the page at `cursor`, or finish when there is none
+    pub fn advance(&mut self, cursor: Option<String>) {
+        self.done_in_page.clear();
+        self.finished = cursor.is_none();
+        self.cursor = cursor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = MirrorCheckpoint::path_for(temp_dir.path());
+
+        let mut checkpoint = MirrorCheckpoint::new("collection:example");
+        checkpoint.cursor = Some("abc".to_string());
+        checkpoint.mark_done("item1");
+        checkpoint.mark_done("item1");
+        checkpoint.save_to_file(&path).unwrap();
+
+        let loaded = MirrorCheckpoint::load_or_new(&path, "collection:example").unwrap();
+        assert_eq!(loaded.cursor.as_deref(), Some("abc"));
+        assert_eq!(loaded.done_in_page, vec!["item1"]);
+
+        // A different query starts over
+        let other = MirrorCheckpoint::load_or_new(&path, "collection:other").unwrap();
+        assert_eq!(other.cursor, None);
+        assert!(other.done_in_page.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_advance() {
+        let mut checkpoint = MirrorCheckpoint::new("collection:example");
+        checkpoint.mark_done("item1");
+
+        checkpoint.advance(Some("next".to_string()));
+        assert!(!checkpoint.is_done("item1"));
+        assert!(!checkpoint.finished);
+
+        checkpoint.advance(None);
+        assert!(checkpoint.finished);
+    }
+}
@@ -5,6 +5,10 @@
 
 pub mod config_persistence;
 pub mod download_history;
+pub mod download_queue;
+pub mod mirror_checkpoint;
 
 pub use config_persistence::{ConfigPersistence, ConfigPriority, ConfigSource};
 pub use download_history::{DownloadHistory, DownloadHistoryEntry, TaskStatus};
+pub use download_queue::{DownloadQueue, JobState, QueueEntry};
+pub use mirror_checkpoint::MirrorCheckpoint;
@@ -0,0 +1,99 @@
+//! Archive.org sign-in
+//!
+//! `ia-get auth keys` and `ia-get auth cookies` read secrets from standard
+//! input, keeping them out of shell history and the process list, and store
+//! them in the owner-only credentials file. Every later request sends them;
+//! `ia-get auth logout` deletes the file.
+
+use crate::infrastructure::config::Credentials;
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::io::{self, BufRead, Write};
+
+/// What `ia-get auth` should do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthAction {
+    /// Store IA S3 access and secret keys
+    Keys,
+    /// Store the cookies of a logged-in browser session
+    Cookies,
+    /// Show which credentials are stored
+    Status,
+    /// Delete the stored credentials
+    Logout,
+}
+
+/// Run an `ia-get auth` subcommand
+pub fn handle_auth(action: AuthAction) -> Result<()> {
+    let path = Credentials::default_path()?;
+
+    match action {
+        AuthAction::Keys => {
+            let mut credentials = Credentials::load(&path)?;
+            println!(
+                "{} Keys are listed at https://archive.org/account/s3.php",
+                "💡".yellow()
+            );
+            credentials.access_key = Some(prompt("S3 access key: ")?);
+            credentials.secret_key = Some(prompt("S3 secret key: ")?);
+            credentials.save(&path)?;
+            println!("{} S3 keys saved to {}", "✅".green(), path.display());
+        }
+        AuthAction::Cookies => {
+            let mut credentials = Credentials::load(&path)?;
+            println!(
+                "{} Paste the Cookie header of a logged-in archive.org browser session",
+                "💡".yellow()
+            );
+            let cookies = prompt("Cookies: ")?;
+            if !cookies.contains("logged-in-sig=") {
+                println!(
+                    "{} No logged-in-sig cookie found; restricted items may still be refused",
+                    "⚠️".yellow()
+                );
+            }
+            credentials.cookies = Some(cookies);
+            credentials.save(&path)?;
+            println!("{} Cookies saved to {}", "✅".green(), path.display());
+        }
+        AuthAction::Status => {
+            let credentials = Credentials::load(&path)?;
+            println!("{} Credentials file: {}", "📁".cyan(), path.display());
+            match (&credentials.access_key, credentials.authorization()) {
+                (Some(access), Some(_)) => println!("  S3 keys: {}", access.green()),
+                (Some(_), None) => println!("  S3 keys: {}", "secret key missing".yellow()),
+                _ => println!("  S3 keys: {}", "(not set)".dimmed()),
+            }
+            match &credentials.cookies {
+                Some(_) => println!(
+                    "  Cookies: {} (sent to {})",
+                    "set".green(),
+                    credentials.cookie_domains.join(", ")
+                ),
+                None => println!("  Cookies: {}", "(not set)".dimmed()),
+            }
+        }
+        AuthAction::Logout => match std::fs::remove_file(&path) {
+            Ok(()) => println!("{} Stored credentials removed", "✅".green()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                println!("{} No credentials stored", "ℹ️".blue())
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to remove {}", path.display()));
+            }
+        },
+    }
+    Ok(())
+}
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let value = line.trim();
+    if value.is_empty() {
+        bail!("No value entered");
+    }
+    Ok(value.to_string())
+}
@@ -0,0 +1,64 @@
+//! Stream a single archive file to stdout
+//!
+//! `ia-get cat <identifier>/<path>` writes the file body to stdout so it can be
+//! piped straight into another program. All status output goes to stderr.
+
+use crate::{
+    IaGetError,
+    core::archive::fetch_json_metadata,
+    core::download::{StreamOutcome, candidate_servers, stream_file},
+    infrastructure::http::HttpClientFactory,
+    utilities::common::split_file_reference,
+};
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+
+/// Stream `<identifier>/<path>` to stdout, verifying its MD5 unless `verify_md5` is false
+///
+/// Returns an error (and the CLI exits non-zero) when the file cannot be found,
+/// every server fails, or the streamed bytes do not match the published MD5.
+pub async fn cat_file(reference: &str, verify_md5: bool) -> Result<()> {
+    let (identifier, path) = split_file_reference(reference)?;
+
+    // No overall timeout: piped files can be arbitrarily large
+    let client = HttpClientFactory::builder()?
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .tcp_keepalive(std::time::Duration::from_secs(60))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let details_url = format!("https://archive.org/details/{}", identifier);
+    let (metadata, _) = fetch_json_metadata(&details_url, &client, &ProgressBar::hidden(), None)
+        .await
+        .with_context(|| format!("Failed to fetch metadata for {}", identifier))?;
+
+    let file_info = metadata
+        .files
+        .iter()
+        .find(|f| f.name == path)
+        .ok_or_else(|| IaGetError::NotFound(format!("No file '{}' in {}", path, identifier)))?;
+
+    let servers = candidate_servers(&metadata);
+    let mut stdout = tokio::io::stdout();
+
+    match stream_file(
+        &client,
+        file_info,
+        &servers,
+        &metadata.dir,
+        verify_md5,
+        &mut stdout,
+    )
+    .await?
+    {
+        StreamOutcome::Complete(_) => {}
+        StreamOutcome::ReaderClosed { bytes_written } => {
+            eprintln!(
+                "Output closed after {} bytes; {} was not verified",
+                bytes_written, file_info.name
+            );
+        }
+    }
+
+    Ok(())
+}
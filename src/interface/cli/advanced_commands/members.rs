@@ -0,0 +1,117 @@
+//! List and extract members of remote zip and tar files
+//!
+//! `ia-get members list <identifier>/<archive>` shows what a zip or tar file
+//! in an item contains; `ia-get members extract` downloads only the members
+//! that match the given filters, laid out as if the archive had been
+//! downloaded and extracted in the output directory.
+
+use crate::{
+    IaGetError,
+    core::archive::{MemberFilter, download_member, fetch_json_metadata, list_members},
+    core::session::{ArchiveFile, ArchiveMetadata},
+    infrastructure::http::HttpClientFactory,
+    utilities::common::{format_size, split_file_reference},
+};
+use anyhow::{Context, Result};
+use colored::*;
+use indicatif::ProgressBar;
+use reqwest::Client;
+use std::path::Path;
+
+/// Print the members of `<identifier>/<archive>` that pass `filter`
+pub async fn list_archive_members(reference: &str, filter: &MemberFilter) -> Result<()> {
+    let (client, metadata, archive) = resolve_archive(reference).await?;
+    let members = list_members(&client, &metadata, &archive)
+        .await
+        .with_context(|| format!("Failed to list {}", archive.name))?;
+
+    let shown: Vec<_> = members.iter().filter(|m| filter.matches(m)).collect();
+    for member in &shown {
+        println!(
+            "{:>10}  {:16}  {}",
+            member.size.map(format_size).unwrap_or_default(),
+            member.modified.as_deref().unwrap_or(""),
+            member.name
+        );
+    }
+    eprintln!(
+        "{} of {} member(s) in {}",
+        shown.len(),
+        members.len(),
+        archive.name
+    );
+    Ok(())
+}
+
+/// Download the members of `<identifier>/<archive>` that pass `filter` into `output_dir`
+///
+/// Returns the number of members that failed.
+pub async fn extract_archive_members(
+    reference: &str,
+    filter: &MemberFilter,
+    output_dir: &Path,
+) -> Result<usize> {
+    let (client, metadata, archive) = resolve_archive(reference).await?;
+    let members = list_members(&client, &metadata, &archive)
+        .await
+        .with_context(|| format!("Failed to list {}", archive.name))?;
+
+    let selected: Vec<_> = members.iter().filter(|m| filter.matches(m)).collect();
+    if selected.is_empty() {
+        println!(
+            "{} No members of {} match the filters",
+            "ℹ️".cyan(),
+            archive.name
+        );
+        return Ok(0);
+    }
+
+    let mut failed = 0;
+    for (index, member) in selected.iter().enumerate() {
+        let position = format!("[{}/{}]", index + 1, selected.len());
+        match download_member(&client, &metadata, &archive, member, output_dir).await {
+            Ok(path) => println!("{} {} {}", "✓".green(), position, path.display()),
+            Err(e) => {
+                println!("{} {} {}: {}", "❌".red(), position, member.name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "\n{} Extracted {} of {} member(s) from {}",
+        if failed == 0 {
+            "✅".green()
+        } else {
+            "⚠️".yellow()
+        },
+        selected.len() - failed,
+        selected.len(),
+        archive.name
+    );
+    Ok(failed)
+}
+
+/// HTTP client, item metadata and the file `reference` points at
+async fn resolve_archive(reference: &str) -> Result<(Client, ArchiveMetadata, ArchiveFile)> {
+    let (identifier, path) = split_file_reference(reference)?;
+
+    let client = HttpClientFactory::builder()?
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .tcp_keepalive(std::time::Duration::from_secs(60))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let details_url = format!("https://archive.org/details/{}", identifier);
+    let (metadata, _) = fetch_json_metadata(&details_url, &client, &ProgressBar::hidden(), None)
+        .await
+        .with_context(|| format!("Failed to fetch metadata for {}", identifier))?;
+
+    let archive = metadata
+        .files
+        .iter()
+        .find(|f| f.name == path)
+        .cloned()
+        .ok_or_else(|| IaGetError::FileSystem(format!("No file '{}' in {}", path, identifier)))?;
+    Ok((client, metadata, archive))
+}
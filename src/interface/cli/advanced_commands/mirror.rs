@@ -0,0 +1,242 @@
+//! Mirror a whole collection
+//!
+//! `ia-get mirror <collection>` pages through the Scrape API with cursors and
+//! downloads every item into `<output>/<identifier>` through the regular
+//! download service. Progress is checkpointed in `<output>/.ia-get-mirror.json`
+//! after every item, so an interrupted mirror resumes on the page it stopped
+//! at. Items that `DownloadHistory` records as successfully downloaded are
+//! skipped.
+//!
+//! Items are downloaded one at a time, each with `concurrent_downloads`
+//! files in flight.
+
+use crate::{
+    DownloadRequest, DownloadResult, DownloadService,
+    infrastructure::api::{EnhancedArchiveApiClient, ScrapePage},
+    infrastructure::config::Config,
+    infrastructure::http::HttpClientFactory,
+    infrastructure::persistence::{
+        ConfigPersistence, DownloadHistory, MirrorCheckpoint, TaskStatus,
+        download_history::get_default_history_db_path,
+    },
+};
+use anyhow::{Context, Result};
+use colored::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Collection mirror configuration
+pub struct MirrorConfig {
+    pub collection: String,
+    /// Extra query terms ANDed with `collection:<name>`
+    pub query: Option<String>,
+    pub output_dir: PathBuf,
+    pub page_size: u32,
+    pub concurrent_downloads: usize,
+    /// Ignore an existing checkpoint and start from the first page
+    pub restart: bool,
+}
+
+/// Mirror every item of a collection, returning the final checkpoint
+pub async fn mirror_collection(config: MirrorConfig) -> Result<MirrorCheckpoint> {
+    let client = HttpClientFactory::builder()?
+        .build()
+        .context("Failed to create HTTP client")?;
+    let mut api = EnhancedArchiveApiClient::new(client);
+    let service = DownloadService::new()?;
+    // Saved preferences such as the store and retry count apply to every item
+    let settings = ConfigPersistence::new()
+        .and_then(|persistence| persistence.load_config())
+        .unwrap_or_default();
+
+    mirror_pages(
+        &config,
+        &completed_identifiers(),
+        async |query, cursor| api.scrape_items(query, config.page_size, cursor).await,
+        async |identifier, position| {
+            download_item(&service, &config, &settings, identifier, position).await
+        },
+    )
+    .await
+}
+
+/// The mirror loop behind [`mirror_collection`]
+///
+/// `fetch_page` returns the Scrape API page at a cursor for a query and
+/// `download` fetches one item, reporting whether it was mirrored completely.
+/// Items in `completed` are skipped without downloading them.
+pub async fn mirror_pages<F, D>(
+    config: &MirrorConfig,
+    completed: &HashSet<String>,
+    mut fetch_page: F,
+    mut download: D,
+) -> Result<MirrorCheckpoint>
+where
+    F: AsyncFnMut(&str, Option<&str>) -> crate::Result<ScrapePage>,
+    D: AsyncFnMut(&str, &str) -> bool,
+{
+    let query = match &config.query {
+        Some(extra) => format!("collection:({}) AND ({})", config.collection, extra),
+        None => format!("collection:({})", config.collection),
+    };
+
+    let checkpoint_path = MirrorCheckpoint::path_for(&config.output_dir);
+    let mut checkpoint = if config.restart {
+        MirrorCheckpoint::new(&query)
+    } else {
+        MirrorCheckpoint::load_or_new(&checkpoint_path, &query)?
+    };
+
+    if checkpoint.finished {
+        println!(
+            "{} Mirror of {} already finished ({} mirrored, {} failed); use --restart to check for new items",
+            "✓".green(),
+            config.collection.bright_white(),
+            checkpoint.mirrored,
+            checkpoint.failed.len()
+        );
+        return Ok(checkpoint);
+    }
+    if checkpoint.cursor.is_some() || !checkpoint.done_in_page.is_empty() {
+        println!(
+            "{} Resuming mirror of {} ({} mirrored, {} skipped so far)",
+            "🔄".cyan(),
+            config.collection.bright_white(),
+            checkpoint.mirrored,
+            checkpoint.skipped
+        );
+    }
+
+    loop {
+        let page = fetch_page(&query, checkpoint.cursor.as_deref())
+            .await
+            .with_context(|| format!("Failed to list items in {}", config.collection))?;
+        if page.total.is_some() {
+            checkpoint.total_items = page.total;
+        }
+
+        for item in &page.items {
+            let identifier = &item.identifier;
+            if checkpoint.is_done(identifier) {
+                continue;
+            }
+
+            let processed = checkpoint.mirrored + checkpoint.skipped + checkpoint.failed.len() + 1;
+            let position = match checkpoint.total_items {
+                Some(total) => format!("[{}/{}]", processed, total),
+                None => format!("[{}]", processed),
+            };
+
+            if completed.contains(identifier) {
+                // Not worth a checkpoint write; redoing the lookup after a crash is cheap
+                checkpoint.skipped += 1;
+                checkpoint.mark_done(identifier);
+                continue;
+            }
+
+            println!("{} {} {}", "⬇️".cyan(), position, identifier.bright_white());
+            if download(identifier, &position).await {
+                checkpoint.mirrored += 1;
+                checkpoint.failed.retain(|id| id != identifier);
+            } else {
+                record_failure(&mut checkpoint, identifier);
+            }
+
+            checkpoint.mark_done(identifier);
+            checkpoint.save_to_file(&checkpoint_path)?;
+        }
+
+        // An empty page means the cursor ran past the end
+        let next = page.cursor.filter(|_| !page.items.is_empty());
+        checkpoint.advance(next);
+        checkpoint.save_to_file(&checkpoint_path)?;
+        if checkpoint.finished {
+            break;
+        }
+    }
+
+    println!(
+        "\n{} Mirror of {} finished: {} downloaded, {} already complete, {} failed",
+        if checkpoint.failed.is_empty() {
+            "✅".green()
+        } else {
+            "⚠️".yellow()
+        },
+        config.collection.bright_white(),
+        checkpoint.mirrored,
+        checkpoint.skipped,
+        checkpoint.failed.len()
+    );
+    Ok(checkpoint)
+}
+
+/// Download one item into `<output>/<identifier>`, reporting whether every file arrived
+async fn download_item(
+    service: &DownloadService,
+    config: &MirrorConfig,
+    settings: &Config,
+    identifier: &str,
+    position: &str,
+) -> bool {
+    let request = DownloadRequest {
+        concurrent_downloads: config.concurrent_downloads,
+        dry_run: false,
+        resume: true,
+        ..DownloadRequest::from_config(
+            settings,
+            identifier.to_string(),
+            config.output_dir.join(identifier),
+        )
+    };
+    match service.download(request, None).await {
+        Ok(DownloadResult::Success(session, _, _))
+            if session.get_progress_summary().failed_files == 0 =>
+        {
+            true
+        }
+        Ok(DownloadResult::Success(session, _, _)) => {
+            let failed = session.get_progress_summary().failed_files;
+            println!(
+                "{} {} {}: {} file(s) failed",
+                "⚠️".yellow(),
+                position,
+                identifier,
+                failed
+            );
+            false
+        }
+        Ok(DownloadResult::Cancelled(_)) => {
+            println!("{} {} {}: cancelled", "⏸️".yellow(), position, identifier);
+            false
+        }
+        Ok(DownloadResult::Error(e)) => {
+            println!("{} {} {}: {}", "❌".red(), position, identifier, e);
+            false
+        }
+        Err(e) => {
+            println!("{} {} {}: {}", "❌".red(), position, identifier, e);
+            false
+        }
+    }
+}
+
+fn record_failure(checkpoint: &mut MirrorCheckpoint, identifier: &str) {
+    if !checkpoint.failed.iter().any(|id| id == identifier) {
+        checkpoint.failed.push(identifier.to_string());
+    }
+}
+
+/// Identifiers the download history records as fully downloaded
+fn completed_identifiers() -> HashSet<String> {
+    get_default_history_db_path()
+        .and_then(|path| DownloadHistory::load_or_create(&path))
+        .map(|history| {
+            history
+                .entries
+                .into_iter()
+                .filter(|entry| entry.status == TaskStatus::Success && entry.failed_files == 0)
+                .map(|entry| entry.archive_identifier)
+                .collect()
+        })
+        .unwrap_or_default()
+}
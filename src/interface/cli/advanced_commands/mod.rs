@@ -2,9 +2,27 @@
 //!
 //! Contains all subcommand implementations for the ia-get CLI.
 
+pub mod auth;
 pub mod batch;
+pub mod cat;
+pub mod members;
+pub mod mirror;
+pub mod queue;
+pub mod remote;
 pub mod search;
+pub mod store;
+pub mod sync;
+pub mod verify_pieces;
 
 // Re-export commonly used types
+pub use auth::{AuthAction, handle_auth};
 pub use batch::{BatchConfig, BatchItemResult, batch_download};
+pub use cat::cat_file;
+pub use members::{extract_archive_members, list_archive_members};
+pub use mirror::{MirrorConfig, mirror_collection, mirror_pages};
+pub use queue::{QueueAction, queue_command, run_queue};
+pub use remote::{RemoteAction, remote_command};
 pub use search::{SearchResults, display_search_results, search_archive};
+pub use store::store_gc;
+pub use sync::{SyncConfig, SyncSummary, sync_item};
+pub use verify_pieces::{PieceVerifyConfig, verify_item_pieces};
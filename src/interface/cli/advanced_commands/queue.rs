@@ -0,0 +1,146 @@
+//! Persistent download queue commands
+//!
+//! `ia-get queue` adds items to the queue stored next to the download
+//! history, lists, removes and reorders them, and runs the queue.
+
+use super::remote::job_state_label;
+use crate::{
+    core::download::{DownloadControl, DownloadRequest, QueueRunSummary, QueueRunner},
+    infrastructure::persistence::{DownloadQueue, QueueEntry},
+};
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::path::Path;
+
+/// A change to the queue
+#[derive(Debug, Clone)]
+pub enum QueueAction {
+    /// Add requests with a priority
+    Add(Vec<DownloadRequest>, i32),
+    List,
+    Remove(Vec<u64>),
+    /// Drop completed and cancelled entries
+    RemoveFinished,
+    /// Move an entry to a 1-based position in the run order
+    Move(u64, usize),
+    SetPriority(u64, i32),
+}
+
+fn print_entry(position: usize, entry: &QueueEntry) {
+    let files = if entry.total_files > 0 {
+        format!(
+            "{}/{} files",
+            entry.completed_files + entry.failed_files,
+            entry.total_files
+        )
+    } else {
+        String::new()
+    };
+    println!(
+        "{:>3}. #{:<4} {:<10} p{:<4} {}  {}  {}",
+        position,
+        entry.id,
+        job_state_label(entry.state),
+        entry.priority,
+        entry.request.identifier.bold(),
+        entry.request.output_dir.display().to_string().dimmed(),
+        files
+    );
+    if let Some(ref error) = entry.error {
+        println!("      {}", error.red());
+    }
+}
+
+/// Apply `change` to the queue at `path` and save it unless the change failed
+fn edit_queue(path: &Path, change: impl FnOnce(&mut DownloadQueue) -> Result<()>) -> Result<()> {
+    DownloadQueue::try_update(path, change)
+}
+
+/// Apply `action` to the queue at `path`
+pub fn queue_command(path: &Path, action: QueueAction) -> Result<()> {
+    match action {
+        QueueAction::Add(requests, priority) => edit_queue(path, |queue| {
+            for request in requests {
+                let entry = queue.add(request, priority);
+                println!(
+                    "{} Queued {} as #{}",
+                    "✓".green(),
+                    entry.request.identifier.bold(),
+                    entry.id
+                );
+            }
+            Ok(())
+        }),
+        QueueAction::List => {
+            let queue = DownloadQueue::load(path)
+                .with_context(|| format!("Failed to read queue {}", path.display()))?;
+            if queue.entries.is_empty() {
+                println!("The queue is empty");
+            }
+            for (index, entry) in queue.entries.iter().enumerate() {
+                print_entry(index + 1, entry);
+            }
+            Ok(())
+        }
+        QueueAction::Remove(ids) => edit_queue(path, |queue| {
+            if let Some(id) = ids.iter().find(|&&id| queue.get(id).is_none()) {
+                bail!("No queue entry #{}", id);
+            }
+            for id in ids {
+                if let Some(entry) = queue.remove(id) {
+                    println!(
+                        "{} Removed #{} {}",
+                        "✓".green(),
+                        id,
+                        entry.request.identifier
+                    );
+                }
+            }
+            Ok(())
+        }),
+        QueueAction::RemoveFinished => edit_queue(path, |queue| {
+            let removed = queue.remove_finished();
+            println!("{} Removed {} finished entries", "✓".green(), removed);
+            Ok(())
+        }),
+        QueueAction::Move(id, position) => edit_queue(path, |queue| {
+            if !queue.move_to(id, position.saturating_sub(1)) {
+                bail!("No queue entry #{}", id);
+            }
+            println!("{} Moved #{} to position {}", "✓".green(), id, position);
+            Ok(())
+        }),
+        QueueAction::SetPriority(id, priority) => edit_queue(path, |queue| {
+            if !queue.set_priority(id, priority) {
+                bail!("No queue entry #{}", id);
+            }
+            println!("{} Set priority of #{} to {}", "✓".green(), id, priority);
+            Ok(())
+        }),
+    }
+}
+
+/// Download queued items, `parallel` at a time, until the queue is done or
+/// `control` is cancelled
+pub async fn run_queue(
+    path: &Path,
+    parallel: usize,
+    retry_failed: bool,
+    control: DownloadControl,
+) -> Result<QueueRunSummary> {
+    let summary = QueueRunner::new(path.to_path_buf(), parallel)
+        .with_control(control)
+        .run(retry_failed)
+        .await
+        .context("Queue run failed")?;
+
+    println!(
+        "\n{} Queue run finished: {} completed, {} failed, {} left to resume, {} stopped from elsewhere",
+        "📋".bright_blue(),
+        summary.completed.to_string().green(),
+        summary.failed.to_string().red(),
+        summary.interrupted,
+        summary.withdrawn
+    );
+    Ok(summary)
+}
@@ -0,0 +1,96 @@
+//! Controlling a running daemon
+//!
+//! `ia-get remote` queues downloads on an `ia-get daemon` and lists, pauses,
+//! resumes or cancels its jobs.
+
+use crate::{
+    core::download::DownloadRequest,
+    infrastructure::persistence::{JobState, QueueEntry},
+    interface::daemon::DaemonClient,
+};
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+/// What to ask the daemon
+#[derive(Debug, Clone)]
+pub enum RemoteAction {
+    Add(Box<DownloadRequest>),
+    List,
+    Status(u64),
+    Pause(u64),
+    Resume(u64),
+    Cancel(u64),
+    /// Print progress events until the daemon stops
+    Events,
+}
+
+/// A job or queue state, coloured for the terminal
+pub(crate) fn job_state_label(state: JobState) -> colored::ColoredString {
+    match state {
+        JobState::Queued => state.as_str().normal(),
+        JobState::Running => state.as_str().blue(),
+        JobState::Paused => state.as_str().yellow(),
+        JobState::Completed => state.as_str().green(),
+        JobState::Failed => state.as_str().red(),
+        JobState::Cancelled => state.as_str().dimmed(),
+    }
+}
+
+fn print_job(job: &QueueEntry) {
+    let files = if job.total_files > 0 {
+        format!(
+            "{}/{} files",
+            job.completed_files + job.failed_files,
+            job.total_files
+        )
+    } else {
+        String::new()
+    };
+    println!(
+        "{:>4}  {:<10} {}  {}  {}",
+        job.id,
+        job_state_label(job.state),
+        job.request.identifier.bold(),
+        job.request.output_dir.display().to_string().dimmed(),
+        files
+    );
+    if let Some(ref error) = job.error {
+        println!("      {}", error.red());
+    }
+}
+
+/// Carry out `action` against the daemon behind `client`
+pub async fn remote_command(client: &DaemonClient, action: RemoteAction) -> Result<()> {
+    let context = || format!("Request to daemon at {} failed", client.endpoint());
+    match action {
+        RemoteAction::Add(request) => {
+            let job = client.enqueue(&request).await.with_context(context)?;
+            println!(
+                "{} Queued {} as job {}",
+                "✓".green(),
+                job.request.identifier.bold(),
+                job.id
+            );
+        }
+        RemoteAction::List => {
+            let jobs = client.list().await.with_context(context)?;
+            if jobs.is_empty() {
+                println!("No jobs");
+            }
+            for job in &jobs {
+                print_job(job);
+            }
+        }
+        RemoteAction::Status(id) => print_job(&client.get(id).await.with_context(context)?),
+        RemoteAction::Pause(id) => print_job(&client.pause(id).await.with_context(context)?),
+        RemoteAction::Resume(id) => print_job(&client.resume(id).await.with_context(context)?),
+        RemoteAction::Cancel(id) => print_job(&client.cancel(id).await.with_context(context)?),
+        RemoteAction::Events => {
+            client
+                .events(|line| println!("{}", line))
+                .await
+                .with_context(context)?;
+        }
+    }
+    Ok(())
+}
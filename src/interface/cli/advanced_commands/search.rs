@@ -3,7 +3,10 @@
 //! Provides comprehensive search capabilities with filtering, sorting,
 //! and detailed result display.
 
-use crate::utilities::{common::format_number, filters::format_size};
+use crate::{
+    infrastructure::http::HttpClientFactory,
+    utilities::{common::format_number, filters::format_size},
+};
 use anyhow::{Context, Result};
 use colored::*;
 use serde::{Deserialize, Serialize};
@@ -43,7 +46,7 @@ pub async fn search_archive(
     sort: Option<&str>,
     limit: usize,
 ) -> Result<SearchResults> {
-    let client = reqwest::Client::new();
+    let client = HttpClientFactory::builder()?.build()?;
 
     // Build search query
     let mut full_query = query.to_string();
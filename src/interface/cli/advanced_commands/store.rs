@@ -0,0 +1,24 @@
+//! Content store maintenance
+//!
+//! `ia-get store gc` removes blobs from the content-addressed store that no
+//! downloaded file refers to any more.
+
+use crate::{core::download::ContentStore, utilities::filters::format_size};
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+/// Drop unreferenced blobs from the store at `store_dir` and print a summary
+pub fn store_gc(store_dir: &str) -> Result<()> {
+    let store = ContentStore::open(store_dir)
+        .with_context(|| format!("Failed to open store {}", store_dir))?;
+    let report = store.gc().context("Store garbage collection failed")?;
+
+    println!(
+        "{} Removed {} unreferenced file(s), freed {}; {} still in use",
+        "✓".green(),
+        report.blobs_removed,
+        format_size(report.bytes_freed),
+        report.blobs_kept
+    );
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! Keep a local mirror of an item up to date
+//!
+//! `ia-get sync <identifier> <dir>` fetches fresh metadata, compares it with the
+//! files in `<dir>`, downloads new or changed files and optionally deletes or
+//! quarantines files that were removed upstream.
+
+use crate::{
+    DownloadRequest, DownloadResult, DownloadService,
+    core::archive::fetch_json_metadata,
+    core::download::{RemovalMode, SyncPlan, apply_removals, load_mirror_session, plan_sync},
+    infrastructure::http::HttpClientFactory,
+    infrastructure::persistence::ConfigPersistence,
+};
+use anyhow::{Context, Result};
+use colored::*;
+use indicatif::ProgressBar;
+use std::path::PathBuf;
+
+/// Sync configuration
+pub struct SyncConfig {
+    pub identifier: String,
+    pub dir: PathBuf,
+    pub removal: RemovalMode,
+    pub verify_all: bool,
+    pub dry_run: bool,
+    pub concurrent_downloads: usize,
+}
+
+/// Result of a sync run
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    pub plan: SyncPlan,
+    pub downloaded: usize,
+    pub failed: usize,
+}
+
+/// Bring `config.dir` in line with the current state of `config.identifier`
+pub async fn sync_item(config: SyncConfig) -> Result<SyncSummary> {
+    let client = HttpClientFactory::builder()?
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let details_url = format!("https://archive.org/details/{}", config.identifier);
+    let (metadata, _) = fetch_json_metadata(&details_url, &client, &ProgressBar::hidden(), None)
+        .await
+        .with_context(|| format!("Failed to fetch metadata for {}", config.identifier))?;
+
+    std::fs::create_dir_all(&config.dir)
+        .with_context(|| format!("Failed to create {}", config.dir.display()))?;
+
+    let previous = load_mirror_session(&config.identifier, &config.dir);
+    let plan = plan_sync(&metadata, &config.dir, previous.as_ref(), config.verify_all)?;
+
+    print_plan(&config, &plan);
+    let mut summary = SyncSummary {
+        plan,
+        ..Default::default()
+    };
+    if config.dry_run {
+        return Ok(summary);
+    }
+
+    let removed = apply_removals(&config.dir, &summary.plan.removed, config.removal)?;
+    match config.removal {
+        RemovalMode::Keep => {}
+        RemovalMode::Delete => {
+            println!("{} Deleted {} removed file(s)", "🗑".yellow(), removed.len());
+        }
+        RemovalMode::Quarantine => {
+            if let Some(parent) = removed.first().and_then(|p| p.parent()) {
+                println!(
+                    "{} Moved {} removed file(s) to {}",
+                    "📦".yellow(),
+                    removed.len(),
+                    parent.display()
+                );
+            }
+        }
+    }
+
+    if summary.plan.to_download().next().is_none() {
+        return Ok(summary);
+    }
+
+    // Saved preferences such as the store and retry count still apply; changed
+    // files keep their stale copies until the new ones have downloaded
+    let settings = ConfigPersistence::new()
+        .and_then(|persistence| persistence.load_config())
+        .unwrap_or_default();
+    let request = DownloadRequest {
+        concurrent_downloads: config.concurrent_downloads,
+        dry_run: false,
+        resume: true,
+        redownload: summary.plan.changed.clone(),
+        ..DownloadRequest::from_config(&settings, config.identifier.clone(), config.dir.clone())
+    };
+    match DownloadService::new()?.download(request, None).await? {
+        DownloadResult::Success(session, _, _) => {
+            let wanted: Vec<&String> = summary.plan.to_download().collect();
+            for name in wanted {
+                match session.file_status.get(name).map(|s| &s.status) {
+                    Some(crate::core::session::DownloadState::Completed) => summary.downloaded += 1,
+                    _ => summary.failed += 1,
+                }
+            }
+        }
+        DownloadResult::Cancelled(_) => anyhow::bail!("Sync cancelled; run it again to continue"),
+        DownloadResult::Error(e) => anyhow::bail!(e),
+    }
+
+    println!(
+        "{} Sync finished: {} downloaded, {} failed",
+        if summary.failed == 0 {
+            "✅".green()
+        } else {
+            "⚠️".yellow()
+        },
+        summary.downloaded,
+        summary.failed
+    );
+    Ok(summary)
+}
+
+/// Print every difference found, followed by the totals
+fn print_plan(config: &SyncConfig, plan: &SyncPlan) {
+    println!(
+        "{} Comparing {} with {}",
+        "🔄".cyan(),
+        config.identifier.bright_white(),
+        config.dir.display()
+    );
+    for name in &plan.added {
+        println!("  {} {}", "+".green(), name);
+    }
+    for name in &plan.changed {
+        println!("  {} {}", "~".yellow(), name);
+    }
+    for name in &plan.removed {
+        println!("  {} {}", "-".red(), name);
+    }
+    if plan.is_up_to_date() {
+        println!("{} Already up to date", "✓".green());
+    }
+    println!(
+        "{} new, {} changed, {} removed upstream, {} unchanged",
+        plan.added.len(),
+        plan.changed.len(),
+        plan.removed.len(),
+        plan.unchanged.len()
+    );
+}
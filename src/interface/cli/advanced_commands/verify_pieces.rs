@@ -0,0 +1,212 @@
+//! Verify a downloaded item against its torrent piece hashes
+//!
+//! `ia-get verify-pieces <identifier> <dir>` checks the files in `<dir>`
+//! against the SHA1 piece hashes of `<identifier>_archive.torrent` and reports
+//! the exact byte ranges that are corrupt. The torrent is cached in `<dir>`
+//! next to the files, so later checks can run with `--offline`. With
+//! `--repair` only the corrupt pieces are re-fetched, using HTTP range
+//! requests.
+
+use crate::{
+    core::archive::{
+        PieceReport, TorrentInfo, fetch_json_metadata, repair_pieces, torrent_file_name,
+        verify_pieces,
+    },
+    core::download::mirror_path,
+    infrastructure::api::endpoints,
+    infrastructure::http::HttpClientFactory,
+};
+use anyhow::{Context, Result, bail};
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::Client;
+use std::path::{Path, PathBuf};
+
+/// Piece verification configuration
+pub struct PieceVerifyConfig {
+    pub identifier: String,
+    pub dir: PathBuf,
+    /// Re-fetch corrupt pieces
+    pub repair: bool,
+    /// Use only the cached torrent; never touch the network
+    pub offline: bool,
+}
+
+/// Check `config.dir` against the item's torrent, repairing if requested
+///
+/// Returns the report of the last verification pass.
+pub async fn verify_item_pieces(config: PieceVerifyConfig) -> Result<PieceReport> {
+    if config.offline && config.repair {
+        bail!("--repair needs network access and cannot be combined with --offline");
+    }
+
+    let client = HttpClientFactory::builder()?
+        .connect_timeout(std::time::Duration::from_secs(30))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let torrent_path = mirror_path(&config.dir, &torrent_file_name(&config.identifier));
+    let info = if config.offline {
+        let data = std::fs::read(&torrent_path).with_context(|| {
+            format!(
+                "No cached torrent at {}; run once without --offline to fetch it",
+                torrent_path.display()
+            )
+        })?;
+        TorrentInfo::parse(&data)?
+    } else {
+        load_or_fetch_torrent(&client, &config.identifier, &torrent_path).await?
+    };
+
+    println!(
+        "{} Checking {} piece(s) of {} against {}",
+        "🔍".cyan(),
+        info.pieces.len(),
+        config.identifier.bright_white(),
+        torrent_path.display()
+    );
+    let mut report = run_verification(&info, &config.dir).await?;
+    print_report(&report);
+
+    if config.repair && !report.corrupt_pieces.is_empty() {
+        println!(
+            "{} Re-fetching {} corrupt piece(s)",
+            "🔧".cyan(),
+            report.corrupt_pieces.len()
+        );
+        let still_bad = repair_pieces(
+            &client,
+            &config.identifier,
+            &info,
+            &config.dir,
+            &report.corrupt_pieces,
+        )
+        .await?;
+        if !still_bad.is_empty() {
+            println!(
+                "{} {} piece(s) did not match the torrent after re-fetching",
+                "⚠️".yellow(),
+                still_bad.len()
+            );
+        }
+
+        report = run_verification(&info, &config.dir).await?;
+        print_report(&report);
+    }
+
+    Ok(report)
+}
+
+/// Use the cached torrent if it is still current, otherwise download it
+async fn load_or_fetch_torrent(
+    client: &Client,
+    identifier: &str,
+    torrent_path: &Path,
+) -> Result<TorrentInfo> {
+    let details_url = format!("https://archive.org/details/{}", identifier);
+    let (metadata, _) = fetch_json_metadata(&details_url, client, &ProgressBar::hidden(), None)
+        .await
+        .with_context(|| format!("Failed to fetch metadata for {}", identifier))?;
+    let torrent_name = torrent_file_name(identifier);
+    let expected_btih = metadata
+        .files
+        .iter()
+        .find(|f| f.name == torrent_name)
+        .and_then(|f| f.btih.clone());
+
+    if let Ok(data) = std::fs::read(torrent_path) {
+        match TorrentInfo::parse(&data) {
+            Ok(info)
+                if expected_btih
+                    .as_deref()
+                    .is_none_or(|btih| btih.eq_ignore_ascii_case(&info.info_hash)) =>
+            {
+                return Ok(info);
+            }
+            _ => println!("{} Cached torrent is stale, fetching it again", "🔄".cyan()),
+        }
+    }
+
+    let url = format!("{}/{}", endpoints::download(identifier), torrent_name);
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to download {}", torrent_name))?;
+    let data = response
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {}", torrent_name))?;
+    let info = TorrentInfo::parse(&data)?;
+
+    if let Some(btih) = expected_btih.filter(|btih| !btih.eq_ignore_ascii_case(&info.info_hash)) {
+        bail!(
+            "Torrent info hash {} does not match item metadata ({})",
+            info.info_hash,
+            btih
+        );
+    }
+
+    if let Some(parent) = torrent_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(torrent_path, &data)
+        .with_context(|| format!("Failed to cache torrent at {}", torrent_path.display()))?;
+    Ok(info)
+}
+
+async fn run_verification(info: &TorrentInfo, dir: &Path) -> Result<PieceReport> {
+    let progress = ProgressBar::new(info.total_length());
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+            .expect("valid progress template")
+            .progress_chars("#>-"),
+    );
+
+    let info = info.clone();
+    let dir = dir.to_path_buf();
+    let bar = progress.clone();
+    let report = tokio::task::spawn_blocking(move || verify_pieces(&info, &dir, &bar))
+        .await
+        .context("Verification task panicked")??;
+    progress.finish_and_clear();
+    Ok(report)
+}
+
+fn print_report(report: &PieceReport) {
+    for file in &report.missing_files {
+        println!("{} missing: {}", "❓".yellow(), file);
+    }
+    for range in &report.corrupt_ranges {
+        let pieces = if range.first_piece == range.last_piece {
+            format!("piece {}", range.first_piece)
+        } else {
+            format!("pieces {}-{}", range.first_piece, range.last_piece)
+        };
+        println!(
+            "{} corrupt: {} bytes {}-{} ({})",
+            "❌".red(),
+            range.file,
+            range.start,
+            range.end - 1,
+            pieces
+        );
+    }
+
+    let status = if report.is_clean() {
+        "✅".green()
+    } else {
+        "❌".red()
+    };
+    println!(
+        "{} {}/{} piece(s) ok, {} corrupt, {} unverifiable",
+        status,
+        report.pieces_ok,
+        report.pieces_total,
+        report.corrupt_pieces.len(),
+        report.unverifiable_pieces
+    );
+}
@@ -34,6 +34,19 @@ const VALID_CONFIG_KEYS: &[&str] = &[
     "default_decompress",
     "http_timeout",
     "user_agent_override",
+    "bandwidth_limit",
+    "store_dir",
+    "proxy",
+    "no_proxy",
+    "ca_certificates",
+    "client_certificate",
+    "client_key",
+    "hook_file_success",
+    "hook_file_failure",
+    "hook_item_success",
+    "hook_item_failure",
+    "hook_timeout",
+    "hook_concurrency",
 ];
 
 /// Handle configuration commands
@@ -112,6 +125,11 @@ async fn show_config(persistence: &ConfigPersistence) -> Result<()> {
         "  HTTP timeout: {} seconds",
         config.http_timeout.to_string().cyan()
     );
+    println!(
+        "  Bandwidth limit: {}",
+        format_option(&config.bandwidth_limit)
+    );
+    println!("  Content store: {}", format_option(&config.store_dir));
     println!();
 
     // Show filter settings
@@ -169,6 +187,56 @@ async fn show_config(persistence: &ConfigPersistence) -> Result<()> {
     );
     println!();
 
+    // Show network settings
+    println!("{} Network Settings:", "🌐".blue());
+    println!(
+        "  Proxy: {}",
+        format_option(&config.proxy.as_deref().map(redact_proxy_password))
+    );
+    println!("  No proxy: {}", format_option(&config.no_proxy));
+    println!(
+        "  CA certificates: {}",
+        if config.ca_certificates.is_empty() {
+            "(not set)".dimmed()
+        } else {
+            config.ca_certificates.join(", ").green()
+        }
+    );
+    println!(
+        "  Client certificate: {}",
+        format_option(&config.client_certificate)
+    );
+    println!("  Client key: {}", format_option(&config.client_key));
+    println!();
+
+    // Show post-download hooks
+    println!("{} Hooks:", "🪝".blue());
+    println!(
+        "  File success: {}",
+        format_option(&config.hook_file_success)
+    );
+    println!(
+        "  File failure: {}",
+        format_option(&config.hook_file_failure)
+    );
+    println!(
+        "  Item success: {}",
+        format_option(&config.hook_item_success)
+    );
+    println!(
+        "  Item failure: {}",
+        format_option(&config.hook_item_failure)
+    );
+    println!(
+        "  Timeout: {} seconds",
+        config.hook_timeout.to_string().cyan()
+    );
+    println!(
+        "  Concurrency: {}",
+        config.hook_concurrency.to_string().cyan()
+    );
+    println!();
+
     // Show filter presets
     if !config.filter_presets.is_empty() {
         println!("{} Filter Presets:", "📝".magenta());
@@ -310,6 +378,111 @@ async fn set_config(persistence: &ConfigPersistence, key: &str, value: &str) ->
                 Some(value.to_string())
             };
         }
+        "bandwidth_limit" => {
+            config.bandwidth_limit = if value.is_empty() {
+                None
+            } else {
+                crate::infrastructure::http::BandwidthSchedule::parse(value).map_err(|e| {
+                    IaGetError::Config(format!("bandwidth_limit is invalid: {}", e))
+                })?;
+                Some(value.to_string())
+            };
+        }
+        "store_dir" => {
+            config.store_dir = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "proxy" => {
+            config.proxy = if value.is_empty() {
+                None
+            } else {
+                reqwest::Proxy::all(value)
+                    .map_err(|e| IaGetError::Config(format!("proxy is invalid: {}", e)))?;
+                Some(value.to_string())
+            };
+        }
+        "no_proxy" => {
+            config.no_proxy = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "ca_certificates" => {
+            config.ca_certificates = value
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        "client_certificate" => {
+            config.client_certificate = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "client_key" => {
+            config.client_key = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "hook_file_success" => {
+            config.hook_file_success = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "hook_file_failure" => {
+            config.hook_file_failure = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "hook_item_success" => {
+            config.hook_item_success = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "hook_item_failure" => {
+            config.hook_item_failure = if value.is_empty() {
+                None
+            } else {
+                Some(value.to_string())
+            };
+        }
+        "hook_timeout" => {
+            let val: u64 = value
+                .parse()
+                .map_err(|_| IaGetError::Config("hook_timeout must be a number".to_string()))?;
+            if val == 0 {
+                return Err(IaGetError::Config(
+                    "hook_timeout must be at least 1 second".to_string(),
+                ));
+            }
+            config.hook_timeout = val;
+        }
+        "hook_concurrency" => {
+            let val: usize = value
+                .parse()
+                .map_err(|_| IaGetError::Config("hook_concurrency must be a number".to_string()))?;
+            if !(1..=16).contains(&val) {
+                return Err(IaGetError::Config(
+                    "hook_concurrency must be between 1 and 16".to_string(),
+                ));
+            }
+            config.hook_concurrency = val;
+        }
         _ => {
             return Err(IaGetError::Config(format!(
                 "Unknown configuration key: '{}'.\n\n{} Valid keys:\n  {}\n\n{} Use 'ia-get config show' to see current values",
@@ -365,6 +538,19 @@ async fn unset_config(persistence: &ConfigPersistence, key: &str) -> Result<()>
         "default_decompress" => config.default_decompress = default_config.default_decompress,
         "http_timeout" => config.http_timeout = default_config.http_timeout,
         "user_agent_override" => config.user_agent_override = default_config.user_agent_override,
+        "bandwidth_limit" => config.bandwidth_limit = default_config.bandwidth_limit,
+        "store_dir" => config.store_dir = default_config.store_dir,
+        "proxy" => config.proxy = default_config.proxy,
+        "no_proxy" => config.no_proxy = default_config.no_proxy,
+        "ca_certificates" => config.ca_certificates = default_config.ca_certificates,
+        "client_certificate" => config.client_certificate = default_config.client_certificate,
+        "client_key" => config.client_key = default_config.client_key,
+        "hook_file_success" => config.hook_file_success = default_config.hook_file_success,
+        "hook_file_failure" => config.hook_file_failure = default_config.hook_file_failure,
+        "hook_item_success" => config.hook_item_success = default_config.hook_item_success,
+        "hook_item_failure" => config.hook_item_failure = default_config.hook_item_failure,
+        "hook_timeout" => config.hook_timeout = default_config.hook_timeout,
+        "hook_concurrency" => config.hook_concurrency = default_config.hook_concurrency,
         _ => {
             return Err(IaGetError::Config(format!(
                 "Unknown configuration key: '{}'.\n\n{} Valid keys:\n  {}\n\n{} Use 'ia-get config show' to see current values",
@@ -624,10 +810,11 @@ async fn clear_history(history_path: &std::path::Path, force: bool) -> Result<()
         }
     }
 
-    let mut history = DownloadHistory::load_or_create(history_path)?;
-    let count = history.entries.len();
-    history.clear();
-    history.save_to_file(history_path)?;
+    let count = DownloadHistory::update(history_path, |history| {
+        let count = history.entries.len();
+        history.clear();
+        count
+    })?;
 
     println!("{} Cleared {} history entries", "✅".green(), count);
 
@@ -636,10 +823,7 @@ async fn clear_history(history_path: &std::path::Path, force: bool) -> Result<()
 
 /// Remove specific history entry
 async fn remove_history_entry(history_path: &std::path::Path, id: &str) -> Result<()> {
-    let mut history = DownloadHistory::load_or_create(history_path)?;
-
-    if history.remove_entry(id) {
-        history.save_to_file(history_path)?;
+    if DownloadHistory::update(history_path, |history| history.remove_entry(id))? {
         println!("{} Removed history entry: {}", "✅".green(), id.cyan());
     } else {
         return Err(IaGetError::Config(format!(
@@ -746,6 +930,17 @@ fn format_option(opt: &Option<String>) -> colored::ColoredString {
     }
 }
 
+/// Proxy URL with any password masked
+fn redact_proxy_password(proxy: &str) -> String {
+    match reqwest::Url::parse(proxy) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("****"));
+            url.to_string()
+        }
+        _ => proxy.to_string(),
+    }
+}
+
 /// Helper function to format boolean values
 fn format_bool(value: bool) -> colored::ColoredString {
     if value {
@@ -0,0 +1,107 @@
+//! Client for the daemon API, used by `ia-get remote`
+
+use super::http::{DaemonEndpoint, read_response_body, send_request};
+use super::token::DaemonToken;
+use crate::{
+    IaGetError, Result, core::download::DownloadRequest, infrastructure::persistence::QueueEntry,
+};
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncBufReadExt;
+
+/// Talks to a running `ia-get daemon`
+#[derive(Debug, Clone)]
+pub struct DaemonClient {
+    endpoint: DaemonEndpoint,
+    token: DaemonToken,
+}
+
+impl DaemonClient {
+    pub fn new(endpoint: DaemonEndpoint, token: DaemonToken) -> Self {
+        Self { endpoint, token }
+    }
+
+    pub fn endpoint(&self) -> &DaemonEndpoint {
+        &self.endpoint
+    }
+
+    /// Send a request and decode the JSON answer, turning error statuses into errors
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let connection = self.endpoint.connect().await?;
+        let (status, mut reader, length) =
+            send_request(connection, &self.token, method, path, body).await?;
+        let body = read_response_body(&mut reader, length).await?;
+
+        if !(200..300).contains(&status) {
+            let message = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|value| value["error"].as_str().map(str::to_string))
+                .unwrap_or_else(|| String::from_utf8_lossy(&body).into_owned());
+            return Err(match status {
+                401 => IaGetError::Config(format!("Daemon refused the token: {}", message)),
+                404 => IaGetError::NotFound(message),
+                _ => IaGetError::Network(format!("Daemon returned {}: {}", status, message)),
+            });
+        }
+        serde_json::from_slice(&body)
+            .map_err(|e| IaGetError::JsonParsing(format!("Failed to parse daemon response: {}", e)))
+    }
+
+    pub async fn list(&self) -> Result<Vec<QueueEntry>> {
+        self.call("GET", "/jobs", None).await
+    }
+
+    pub async fn get(&self, id: u64) -> Result<QueueEntry> {
+        self.call("GET", &format!("/jobs/{}", id), None).await
+    }
+
+    /// Queue a download; a relative `output_dir` is made absolute here first
+    pub async fn enqueue(&self, request: &DownloadRequest) -> Result<QueueEntry> {
+        let mut request = request.clone();
+        if request.output_dir.is_relative() {
+            request.output_dir = std::path::absolute(&request.output_dir)?;
+        }
+        let body = serde_json::to_value(&request).map_err(|e| {
+            IaGetError::JsonParsing(format!("Failed to serialize download request: {}", e))
+        })?;
+        self.call("POST", "/jobs", Some(&body)).await
+    }
+
+    pub async fn pause(&self, id: u64) -> Result<QueueEntry> {
+        self.call("POST", &format!("/jobs/{}/pause", id), None)
+            .await
+    }
+
+    pub async fn resume(&self, id: u64) -> Result<QueueEntry> {
+        self.call("POST", &format!("/jobs/{}/resume", id), None)
+            .await
+    }
+
+    pub async fn cancel(&self, id: u64) -> Result<QueueEntry> {
+        self.call("POST", &format!("/jobs/{}/cancel", id), None)
+            .await
+    }
+
+    /// Pass each progress event line to `on_line` until the daemon goes away
+    pub async fn events(&self, mut on_line: impl FnMut(&str)) -> Result<()> {
+        let connection = self.endpoint.connect().await?;
+        let (status, mut reader, _) =
+            send_request(connection, &self.token, "GET", "/events", None).await?;
+        if status != 200 {
+            return Err(IaGetError::Network(format!(
+                "Daemon returned {} for the event stream",
+                status
+            )));
+        }
+        let mut line = String::new();
+        while reader.read_line(&mut line).await? > 0 {
+            on_line(line.trim_end());
+            line.clear();
+        }
+        Ok(())
+    }
+}
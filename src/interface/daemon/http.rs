@@ -0,0 +1,320 @@
+//! Minimal HTTP/1.1 framing for the daemon API
+//!
+//! The API only ever exchanges small JSON bodies and one streamed response,
+//! so requests and responses are read and written by hand over any byte
+//! stream, a TCP connection or a Unix socket alike. Every response closes
+//! the connection.
+
+use super::token::DaemonToken;
+use crate::{IaGetError, Result};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+
+/// Address `ia-get daemon` listens on and `ia-get remote` talks to by default
+pub const DEFAULT_DAEMON_ADDR: &str = "127.0.0.1:8765";
+
+/// Largest request line plus headers accepted
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+
+/// Largest body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// A byte stream carrying one HTTP exchange
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// Where the daemon listens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaemonEndpoint {
+    /// A loopback TCP address
+    Tcp(SocketAddr),
+    /// A Unix domain socket
+    Unix(PathBuf),
+}
+
+impl Default for DaemonEndpoint {
+    fn default() -> Self {
+        DaemonEndpoint::Tcp(DEFAULT_DAEMON_ADDR.parse().expect("valid default address"))
+    }
+}
+
+impl fmt::Display for DaemonEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DaemonEndpoint::Tcp(addr) => write!(f, "http://{}", addr),
+            DaemonEndpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl DaemonEndpoint {
+    /// Endpoint from `--listen ADDR` or `--socket PATH`
+    ///
+    /// TCP addresses must be loopback: the bearer token keeps other local
+    /// users out, not the rest of the network.
+    pub fn from_args(addr: Option<&str>, socket: Option<&str>) -> Result<Self> {
+        if let Some(socket) = socket {
+            return Ok(DaemonEndpoint::Unix(PathBuf::from(socket)));
+        }
+        let Some(addr) = addr else {
+            return Ok(Self::default());
+        };
+        let addr: SocketAddr = addr
+            .parse()
+            .map_err(|_| IaGetError::Config(format!("Invalid daemon address '{}'", addr)))?;
+        if !addr.ip().is_loopback() {
+            return Err(IaGetError::Config(format!(
+                "Daemon address {} is not a loopback address; use 127.0.0.1 or ::1",
+                addr
+            )));
+        }
+        Ok(DaemonEndpoint::Tcp(addr))
+    }
+
+    /// Open a connection to the daemon
+    pub async fn connect(&self) -> Result<Box<dyn Connection>> {
+        let unreachable = |e: std::io::Error| {
+            IaGetError::Network(format!("Cannot reach ia-get daemon at {}: {}", self, e))
+        };
+        match self {
+            DaemonEndpoint::Tcp(addr) => {
+                let stream = tokio::net::TcpStream::connect(addr)
+                    .await
+                    .map_err(unreachable)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            DaemonEndpoint::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(unreachable)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            DaemonEndpoint::Unix(_) => Err(IaGetError::Config(
+                "Unix sockets are only supported on Unix".to_string(),
+            )),
+        }
+    }
+}
+
+/// A request received by the daemon
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Header names in lower case, with their values
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Value of header `name`, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Non-empty path segments, e.g. `["jobs", "3", "pause"]`
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+}
+
+/// Read the status line or request line and the headers
+async fn read_head<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> Result<(String, Vec<(String, String)>)> {
+    let mut head = reader.take(MAX_HEAD_BYTES);
+    let mut first = String::new();
+    if head.read_line(&mut first).await? == 0 {
+        return Err(IaGetError::Parse(
+            "Connection closed before a request".to_string(),
+        ));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if head.read_line(&mut line).await? == 0 {
+            return Err(IaGetError::Parse("Incomplete HTTP headers".to_string()));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    Ok((first.trim_end().to_string(), headers))
+}
+
+/// `Content-Length` among `headers`, if given
+fn content_length(headers: &[(String, String)]) -> Result<Option<usize>> {
+    headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .map(|(_, value)| {
+            value
+                .parse::<usize>()
+                .map_err(|_| IaGetError::Parse(format!("Invalid Content-Length '{}'", value)))
+        })
+        .transpose()
+}
+
+async fn read_body<R: AsyncRead + Unpin>(reader: &mut R, length: usize) -> Result<Vec<u8>> {
+    if length > MAX_BODY_BYTES {
+        return Err(IaGetError::Parse(format!(
+            "Body of {} bytes is larger than the {} allowed",
+            length, MAX_BODY_BYTES
+        )));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(body)
+}
+
+/// Read one request from a client
+pub async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<HttpRequest> {
+    let (line, headers) = read_head(reader).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(IaGetError::Parse(format!(
+            "Invalid request line '{}'",
+            line
+        )));
+    };
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    let body = read_body(reader, content_length(&headers)?.unwrap_or(0)).await?;
+    Ok(HttpRequest {
+        method: method.to_ascii_uppercase(),
+        path,
+        headers,
+        body,
+    })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Write a complete response
+pub async fn write_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status),
+        content_type,
+        body.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Write a complete JSON response
+pub async fn write_json_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<()> {
+    let body = serde_json::to_vec(body)
+        .map_err(|e| IaGetError::JsonParsing(format!("Failed to serialize response: {}", e)))?;
+    write_response(writer, status, "application/json", &body).await
+}
+
+/// Start a response whose NDJSON body lasts until the connection closes
+pub async fn write_stream_head<W: AsyncWrite + Unpin>(writer: &mut W) -> Result<()> {
+    writer
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        )
+        .await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Send a request carrying `token` and read the response head, returning the
+/// status and the reader positioned at the body together with its length,
+/// when given
+pub async fn send_request(
+    connection: Box<dyn Connection>,
+    token: &DaemonToken,
+    method: &str,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<(
+    u16,
+    tokio::io::BufReader<Box<dyn Connection>>,
+    Option<usize>,
+)> {
+    let mut connection = connection;
+    let body = match body {
+        Some(body) => serde_json::to_vec(body)
+            .map_err(|e| IaGetError::JsonParsing(format!("Failed to serialize request: {}", e)))?,
+        None => Vec::new(),
+    };
+    let head = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        method,
+        path,
+        token.as_str(),
+        body.len()
+    );
+    connection.write_all(head.as_bytes()).await?;
+    connection.write_all(&body).await?;
+    connection.flush().await?;
+
+    let mut reader = tokio::io::BufReader::new(connection);
+    let (status_line, headers) = read_head(&mut reader).await?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| IaGetError::Parse(format!("Invalid status line '{}'", status_line)))?;
+    Ok((status, reader, content_length(&headers)?))
+}
+
+/// Read a response body of `length` bytes, or up to the end of the stream
+pub async fn read_response_body<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    length: Option<usize>,
+) -> Result<Vec<u8>> {
+    match length {
+        Some(length) => read_body(reader, length).await,
+        None => {
+            let mut body = Vec::new();
+            reader
+                .take(MAX_BODY_BYTES as u64)
+                .read_to_end(&mut body)
+                .await?;
+            Ok(body)
+        }
+    }
+}
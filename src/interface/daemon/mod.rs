@@ -0,0 +1,34 @@
+//! Download daemon and its control API
+//!
+//! `ia-get daemon` runs one shared download queue that the CLI, the GUI and
+//! scripts hand work to over a small JSON HTTP API, on a loopback TCP port or
+//! a Unix socket. Every request must carry the daemon's bearer token (see
+//! [`token`]), `Content-Type: application/json` and a `localhost` `Host`, and
+//! no `Origin`, which keeps browsers from driving the API:
+//!
+//! | Request | Effect |
+//! |---|---|
+//! | `GET /jobs` | List every job |
+//! | `POST /jobs` | Queue the `DownloadRequest` in the body; returns the job |
+//! | `GET /jobs/{id}` | One job |
+//! | `POST /jobs/{id}/pause` | Stop a queued or running job until resumed |
+//! | `POST /jobs/{id}/resume` | Queue a paused or failed job again |
+//! | `POST /jobs/{id}/cancel` | Stop a job for good |
+//! | `GET /events` | Progress events as NDJSON until the client disconnects |
+//!
+//! The jobs are the entries of the download queue shared with `ia-get queue`
+//! and the GUI, worked through by a [`QueueRunner`](crate::core::download::QueueRunner)
+//! `--parallel` at a time in priority order. Every change is saved to the
+//! queue file and the downloads keep their usual session files, so a
+//! restarted daemon resumes where it stopped. `ia-get remote` is the command
+//! line client.
+
+pub mod client;
+pub mod http;
+pub mod server;
+pub mod token;
+
+pub use client::*;
+pub use http::*;
+pub use server::*;
+pub use token::*;
@@ -0,0 +1,455 @@
+//! The daemon: job queue, worker and API server
+
+use super::http::{
+    DaemonEndpoint, HttpRequest, read_request, write_json_response, write_stream_head,
+};
+use super::token::DaemonToken;
+use crate::{
+    IaGetError, Result,
+    core::download::{
+        DownloadRequest, ProgressEvent, ProgressEventWriter, QueueRunner, download_metrics,
+        emit_progress_event,
+    },
+    infrastructure::persistence::{DownloadQueue, JobState, QueueEntry},
+};
+use colored::Colorize;
+use serde_json::json;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::sync::broadcast;
+
+/// Progress lines buffered for each `/events` client before it starts missing some
+const EVENT_BUFFER: usize = 1024;
+
+/// Wait before restarting a queue run that stopped with an error
+const WORKER_RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// Why a job request was refused
+#[derive(Debug, thiserror::Error)]
+pub enum JobError {
+    #[error("No job {0}")]
+    NotFound(u64),
+    #[error("Cannot {action} job {id}: it is {}", .state.as_str())]
+    InvalidState {
+        id: u64,
+        state: JobState,
+        action: &'static str,
+    },
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error(transparent)]
+    Storage(#[from] IaGetError),
+}
+
+impl JobError {
+    fn status(&self) -> u16 {
+        match self {
+            JobError::NotFound(_) => 404,
+            JobError::InvalidState { .. } => 409,
+            JobError::InvalidRequest(_) => 400,
+            JobError::Storage(_) => 500,
+        }
+    }
+}
+
+/// The daemon's jobs: the entries of the download queue, worked through by
+/// a [`QueueRunner`]
+pub struct JobManager {
+    runner: Arc<QueueRunner>,
+    events: broadcast::Sender<String>,
+}
+
+/// Publish how many jobs are waiting, when metrics are exported
+fn report_queue_depth(queue: &DownloadQueue) {
+    if let Some(metrics) = download_metrics() {
+        let queued = queue
+            .entries
+            .iter()
+            .filter(|entry| entry.state == JobState::Queued);
+        metrics.set_queue_depth(queued.count());
+    }
+}
+
+impl JobManager {
+    /// Serve the queue `runner` works through; it should keep running
+    pub fn new(runner: QueueRunner) -> Arc<Self> {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        Arc::new(Self {
+            runner: Arc::new(runner),
+            events,
+        })
+    }
+
+    /// Event writer that hands every line to the `/events` clients
+    pub fn event_writer(&self) -> ProgressEventWriter {
+        ProgressEventWriter::new(Box::new(BroadcastLines {
+            lines: self.events.clone(),
+            pending: Vec::new(),
+        }))
+    }
+
+    /// Receive progress event lines from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.events.subscribe()
+    }
+
+    pub fn list(&self) -> std::result::Result<Vec<QueueEntry>, JobError> {
+        Ok(DownloadQueue::load(self.runner.path())?.entries)
+    }
+
+    pub fn get(&self, id: u64) -> std::result::Result<QueueEntry, JobError> {
+        DownloadQueue::load(self.runner.path())?
+            .get(id)
+            .cloned()
+            .ok_or(JobError::NotFound(id))
+    }
+
+    /// Queue a download behind everything already queued
+    ///
+    /// The request is run as sent, except that it always resumes from its
+    /// session files. `output_dir` must be absolute: the daemon's working
+    /// directory means nothing to the client.
+    pub fn enqueue(
+        &self,
+        mut request: DownloadRequest,
+    ) -> std::result::Result<QueueEntry, JobError> {
+        if request.identifier.trim().is_empty() {
+            return Err(JobError::InvalidRequest(
+                "identifier must not be empty".to_string(),
+            ));
+        }
+        if !request.output_dir.is_absolute() {
+            return Err(JobError::InvalidRequest(format!(
+                "output_dir must be an absolute path, got '{}'",
+                request.output_dir.display()
+            )));
+        }
+        request.resume = true;
+
+        let job = DownloadQueue::update(self.runner.path(), |queue| {
+            let job = queue.add(request, 0);
+            report_queue_depth(queue);
+            job
+        })?;
+        Self::announce(&job);
+        self.runner.wake();
+        Ok(job)
+    }
+
+    /// Hold a queued job back, or stop the running one keeping its partial files
+    pub fn pause(&self, id: u64) -> std::result::Result<QueueEntry, JobError> {
+        self.update(id, |job| match job.state {
+            JobState::Queued | JobState::Running => {
+                job.set_state(JobState::Paused);
+                Ok(true)
+            }
+            JobState::Paused => Ok(false),
+            state => Err(JobError::InvalidState {
+                id,
+                state,
+                action: "pause",
+            }),
+        })
+    }
+
+    /// Queue a paused job, or retry a failed one
+    pub fn resume(&self, id: u64) -> std::result::Result<QueueEntry, JobError> {
+        self.update(id, |job| match job.state {
+            JobState::Paused | JobState::Failed => {
+                job.error = None;
+                job.set_state(JobState::Queued);
+                Ok(true)
+            }
+            JobState::Queued | JobState::Running => Ok(false),
+            state => Err(JobError::InvalidState {
+                id,
+                state,
+                action: "resume",
+            }),
+        })
+    }
+
+    /// Stop a job for good; its session is kept for a later manual resume
+    pub fn cancel(&self, id: u64) -> std::result::Result<QueueEntry, JobError> {
+        self.update(id, |job| match job.state {
+            JobState::Queued | JobState::Running | JobState::Paused => {
+                job.set_state(JobState::Cancelled);
+                Ok(true)
+            }
+            state => Err(JobError::InvalidState {
+                id,
+                state,
+                action: "cancel",
+            }),
+        })
+    }
+
+    /// Apply `change` to job `id` in the queue file; when it reports a
+    /// change the new state is announced and the runner woken, which stops
+    /// the job's download if it no longer should run
+    fn update(
+        &self,
+        id: u64,
+        change: impl FnOnce(&mut QueueEntry) -> std::result::Result<bool, JobError>,
+    ) -> std::result::Result<QueueEntry, JobError> {
+        let (job, changed) = DownloadQueue::try_update(self.runner.path(), |queue| {
+            let job = queue.get_mut(id).ok_or(JobError::NotFound(id))?;
+            let changed = change(job)?;
+            let job = job.clone();
+            report_queue_depth(queue);
+            Ok::<_, JobError>((job, changed))
+        })?;
+        if changed {
+            Self::announce(&job);
+            self.runner.wake();
+        }
+        Ok(job)
+    }
+
+    fn announce(job: &QueueEntry) {
+        emit_progress_event(ProgressEvent::JobUpdated {
+            job: job.id,
+            identifier: job.request.identifier.clone(),
+            state: job.state.as_str().to_string(),
+        });
+    }
+
+    /// Work through the queue until the runner's control is cancelled
+    ///
+    /// Jobs left `running` by a daemon that stopped are queued again.
+    pub async fn run_worker(self: Arc<Self>) {
+        loop {
+            match self.runner.run(false).await {
+                Ok(_) => return,
+                Err(e) => {
+                    eprintln!("{} Queue run stopped: {}", "⚠️".yellow(), e);
+                    tokio::time::sleep(WORKER_RESTART_DELAY).await;
+                }
+            }
+        }
+    }
+}
+
+/// Splits what the event writer writes into lines for the subscribers
+struct BroadcastLines {
+    lines: broadcast::Sender<String>,
+    pending: Vec<u8>,
+}
+
+impl Write for BroadcastLines {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            // No subscribers is not an error
+            let _ = self.lines.send(String::from_utf8_lossy(&line).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Check that a request comes from a client holding `token` and not from a
+/// web page; returns the status and message to refuse it with
+pub fn authorize(
+    request: &HttpRequest,
+    token: &DaemonToken,
+) -> std::result::Result<(), (u16, &'static str)> {
+    // Browsers always send an Origin on cross-site requests
+    if request.header("origin").is_some() {
+        return Err((403, "Cross-origin requests are not allowed"));
+    }
+    // Guards against DNS rebinding, where a page's own host name resolves to 127.0.0.1
+    let host = request.header("host").unwrap_or_default();
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !name.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    if !["localhost", "127.0.0.1", "[::1]"].contains(&host.to_ascii_lowercase().as_str()) {
+        return Err((403, "Host must be localhost"));
+    }
+    let presented = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !token.matches(presented.trim()) {
+        return Err((401, "Missing or invalid bearer token"));
+    }
+    // A form post cannot set this without a CORS preflight
+    let content_type = request.header("content-type").unwrap_or_default();
+    if !content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .eq_ignore_ascii_case("application/json")
+    {
+        return Err((415, "Content-Type must be application/json"));
+    }
+    Ok(())
+}
+
+/// Answer one API request
+pub fn handle_request(manager: &JobManager, request: &HttpRequest) -> (u16, serde_json::Value) {
+    let job_id = |id: &str| {
+        id.parse::<u64>()
+            .map_err(|_| JobError::InvalidRequest(format!("Invalid job id '{}'", id)))
+    };
+    let result = match (request.method.as_str(), request.segments().as_slice()) {
+        ("GET", ["jobs"]) => manager.list().map(|jobs| (200, json!(jobs))),
+        ("POST", ["jobs"]) => match serde_json::from_slice::<DownloadRequest>(&request.body) {
+            Ok(download) => manager.enqueue(download).map(|job| (201, json!(job))),
+            Err(e) => Err(JobError::InvalidRequest(format!(
+                "Invalid download request: {}",
+                e
+            ))),
+        },
+        ("GET", ["jobs", id]) => job_id(id)
+            .and_then(|id| manager.get(id))
+            .map(|job| (200, json!(job))),
+        ("POST", ["jobs", id, action]) => job_id(id)
+            .and_then(|id| match *action {
+                "pause" => manager.pause(id),
+                "resume" => manager.resume(id),
+                "cancel" => manager.cancel(id),
+                _ => Err(JobError::InvalidRequest(format!(
+                    "Unknown action '{}'; use pause, resume or cancel",
+                    action
+                ))),
+            })
+            .map(|job| (200, json!(job))),
+        (_, ["jobs"] | ["jobs", _] | ["jobs", _, _] | ["events"]) => {
+            return (405, json!({ "error": "Method not allowed" }));
+        }
+        _ => return (404, json!({ "error": "No such endpoint" })),
+    };
+
+    match result {
+        Ok((status, body)) => (status, body),
+        Err(e) => (e.status(), json!({ "error": e.to_string() })),
+    }
+}
+
+async fn handle_connection(
+    manager: Arc<JobManager>,
+    token: Arc<DaemonToken>,
+    connection: impl super::http::Connection,
+) {
+    let mut connection = BufReader::new(connection);
+    let request = match read_request(&mut connection).await {
+        Ok(request) => request,
+        Err(e) => {
+            let _ =
+                write_json_response(&mut connection, 400, &json!({ "error": e.to_string() })).await;
+            return;
+        }
+    };
+    if let Err((status, error)) = authorize(&request, &token) {
+        let _ = write_json_response(&mut connection, status, &json!({ "error": error })).await;
+        return;
+    }
+
+    if request.method == "GET" && request.segments() == ["events"] {
+        let mut events = manager.subscribe();
+        if write_stream_head(&mut connection).await.is_err() {
+            return;
+        }
+        loop {
+            match events.recv().await {
+                Ok(line) => {
+                    if connection.write_all(line.as_bytes()).await.is_err()
+                        || connection.flush().await.is_err()
+                    {
+                        return;
+                    }
+                }
+                // A slow reader misses events rather than holding up downloads
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    let (status, body) = handle_request(&manager, &request);
+    let _ = write_json_response(&mut connection, status, &body).await;
+}
+
+/// Bind a Unix socket at `path` that only the owner can connect to
+///
+/// The socket is created inside a fresh owner-only directory and moved into
+/// place once its permissions are set, so no other user can connect in
+/// between.
+#[cfg(unix)]
+fn bind_private_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    // A socket left behind by a daemon that did not shut down cleanly
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let cannot_listen = |e: std::io::Error| {
+        IaGetError::Network(format!("Cannot listen on {}: {}", path.display(), e))
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| IaGetError::Config(format!("Invalid socket path {}", path.display())))?;
+    let staging = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(cannot_listen)?;
+    let staged = staging.join("socket");
+    let bound = tokio::net::UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    bound.map_err(cannot_listen)
+}
+
+/// Run the daemon on `endpoint` until the process is stopped, admitting
+/// clients that present `token`
+pub async fn serve(
+    manager: Arc<JobManager>,
+    endpoint: &DaemonEndpoint,
+    token: DaemonToken,
+) -> Result<()> {
+    tokio::spawn(manager.clone().run_worker());
+    let token = Arc::new(token);
+
+    match endpoint {
+        DaemonEndpoint::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .map_err(|e| IaGetError::Network(format!("Cannot listen on {}: {}", addr, e)))?;
+            println!("{} ia-get daemon listening on {}", "🛰️".green(), endpoint);
+            loop {
+                let (stream, _) = listener.accept().await?;
+                tokio::spawn(handle_connection(manager.clone(), token.clone(), stream));
+            }
+        }
+        #[cfg(unix)]
+        DaemonEndpoint::Unix(path) => {
+            let listener = bind_private_socket(path)?;
+            println!("{} ia-get daemon listening on {}", "🛰️".green(), endpoint);
+            loop {
+                let (stream, _) = listener.accept().await?;
+                tokio::spawn(handle_connection(manager.clone(), token.clone(), stream));
+            }
+        }
+        #[cfg(not(unix))]
+        DaemonEndpoint::Unix(_) => Err(IaGetError::Config(
+            "Unix sockets are only supported on Unix".to_string(),
+        )),
+    }
+}
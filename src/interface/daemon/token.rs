@@ -0,0 +1,118 @@
+//! Bearer token guarding the daemon API
+//!
+//! Each daemon start writes a fresh random token to `ia-get-daemon.token`
+//! in the configuration directory, readable by the owner only. Clients send
+//! it as `Authorization: Bearer <token>`, so other local users and web pages
+//! that can reach the loopback port cannot drive the daemon.
+
+use crate::{
+    IaGetError, Result,
+    infrastructure::config::{read_private_file, write_private_file},
+};
+use std::path::{Path, PathBuf};
+
+/// Random bytes in a token
+const TOKEN_BYTES: usize = 32;
+
+/// Secret shared by the daemon and its clients
+#[derive(Clone, PartialEq, Eq)]
+pub struct DaemonToken(String);
+
+impl std::fmt::Debug for DaemonToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DaemonToken(..)")
+    }
+}
+
+impl DaemonToken {
+    /// A new random token
+    pub fn generate() -> Result<Self> {
+        let bytes = random_bytes()?;
+        Ok(Self(bytes.iter().map(|b| format!("{:02x}", b)).collect()))
+    }
+
+    /// Token from its text form
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `presented` is this token, compared in constant time
+    pub fn matches(&self, presented: &str) -> bool {
+        let (expected, presented) = (self.0.as_bytes(), presented.as_bytes());
+        expected.len() == presented.len()
+            && expected
+                .iter()
+                .zip(presented)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Read the token a running daemon saved at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = read_private_file(path)?.ok_or_else(|| {
+            IaGetError::Config(format!(
+                "Daemon token {} not found; is 'ia-get daemon' running?",
+                path.display()
+            ))
+        })?;
+
+        let token = content.trim();
+        if token.is_empty() {
+            return Err(IaGetError::Config(format!(
+                "Daemon token {} is empty",
+                path.display()
+            )));
+        }
+        Ok(Self(token.to_string()))
+    }
+
+    /// Write the token to `path`, readable and writable by the owner only
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                IaGetError::Config(format!("Failed to create daemon directory: {}", e))
+            })?;
+        }
+        write_private_file(path, self.0.as_bytes())
+    }
+}
+
+#[cfg(unix)]
+fn random_bytes() -> Result<[u8; TOKEN_BYTES]> {
+    use std::io::Read;
+
+    let mut bytes = [0; TOKEN_BYTES];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .map_err(|e| IaGetError::Config(format!("Cannot generate daemon token: {}", e)))?;
+    Ok(bytes)
+}
+
+/// Bytes from the standard library's randomly keyed hasher, which the
+/// operating system seeds
+#[cfg(not(unix))]
+fn random_bytes() -> Result<[u8; TOKEN_BYTES]> {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut bytes = [0; TOKEN_BYTES];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos()),
+        );
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+/// Where the daemon keeps its token
+pub fn get_default_daemon_token_path() -> Result<PathBuf> {
+    let config_dir = crate::infrastructure::config::ConfigManager::get_config_directory()?;
+    Ok(config_dir.join("ia-get-daemon.token"))
+}
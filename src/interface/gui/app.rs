@@ -3,6 +3,7 @@
 //! Provides the main application window and state management for the GUI interface.
 
 use crate::{
+    core::download::DownloadControl,
     core::download::download_service::{
         DownloadRequest, DownloadResult, DownloadService, ProgressUpdate,
     },
@@ -14,7 +15,7 @@ use std::path::PathBuf;
 use tokio::sync::mpsc;
 
 use super::panels::{
-    ArchiveHealthPanel, ConfigPanel, DownloadPanel, FileBrowserPanel, FiltersPanel,
+    ArchiveHealthPanel, ConfigPanel, DownloadPanel, FileBrowserPanel, FiltersPanel, QueuePanel,
 };
 
 /// Main application state
@@ -36,6 +37,9 @@ pub struct IaGetApp {
     download_status: String,
     is_downloading: bool,
 
+    // Pause/cancel handle for the running download
+    download_control: Option<DownloadControl>,
+
     // Progress receiver for updates from download controller
     progress_rx: Option<mpsc::UnboundedReceiver<ProgressUpdate>>,
 
@@ -58,6 +62,7 @@ pub struct IaGetApp {
     download_panel: DownloadPanel,
     file_browser_panel: FileBrowserPanel,
     filters_panel: FiltersPanel,
+    queue_panel: QueuePanel,
 
     // Dialog state
     show_about_dialog: bool,
@@ -73,6 +78,7 @@ enum AppTab {
     Download,
     FileBrowser,
     Filters,
+    Queue,
     Config,
     History,
     ArchiveHealth,
@@ -103,11 +109,13 @@ impl IaGetApp {
                     .to_string()
             }),
             rt_handle,
+            download_control: None,
             archive_health_panel: ArchiveHealthPanel::new(),
             config_panel: ConfigPanel::new(config.clone()),
             download_panel: DownloadPanel::new(),
             file_browser_panel: FileBrowserPanel::new(),
             filters_panel: FiltersPanel::new(),
+            queue_panel: QueuePanel::new(),
             config,
             switch_to_cli: false,
             ..Default::default()
@@ -239,11 +247,16 @@ impl IaGetApp {
             let _ = progress_tx.send(update);
         });
 
+        let control = DownloadControl::new();
+        self.download_control = Some(control.clone());
+
         // Start download in background
         if let Some(handle) = &self.rt_handle {
             let ctx_clone = ctx.clone();
             handle.spawn(async move {
-                let result = service.download(request, Some(progress_callback)).await;
+                let result = service
+                    .download_with_control(request, Some(progress_callback), control)
+                    .await;
 
                 // Send completion result
                 if let Ok(download_result) = result {
@@ -316,6 +329,7 @@ impl IaGetApp {
                 ui.selectable_value(&mut self.current_tab, AppTab::Download, "Download");
                 ui.selectable_value(&mut self.current_tab, AppTab::FileBrowser, "File Browser");
                 ui.selectable_value(&mut self.current_tab, AppTab::Filters, "Filters");
+                ui.selectable_value(&mut self.current_tab, AppTab::Queue, "Queue");
                 ui.selectable_value(&mut self.current_tab, AppTab::Config, "Settings");
                 ui.selectable_value(&mut self.current_tab, AppTab::History, "History");
                 ui.selectable_value(&mut self.current_tab, AppTab::ArchiveHealth, "API Health");
@@ -330,6 +344,7 @@ impl IaGetApp {
                     AppTab::Download => self.render_download_tab(ui, ctx),
                     AppTab::FileBrowser => self.render_file_browser_tab(ui),
                     AppTab::Filters => self.render_filters_tab(ui),
+                    AppTab::Queue => self.render_queue_tab(ui),
                     AppTab::Config => self.render_config_tab(ui),
                     AppTab::History => self.render_history_tab(ui),
                     AppTab::ArchiveHealth => self.render_archive_health_tab(ui),
@@ -469,9 +484,26 @@ impl IaGetApp {
                 self.start_download(ctx);
             }
 
-            if self.is_downloading && ui.button("❌ Cancel").clicked() {
-                self.is_downloading = false;
-                self.download_status = "Cancelled".to_string();
+            if let Some(control) = self
+                .download_control
+                .as_ref()
+                .filter(|_| self.is_downloading)
+            {
+                if control.is_paused() {
+                    if ui.button("▶ Resume").clicked() {
+                        control.resume();
+                        self.download_status = "Resuming...".to_string();
+                    }
+                } else if !control.is_cancelled() && ui.button("⏸ Pause").clicked() {
+                    control.pause();
+                    self.download_status = "Paused".to_string();
+                }
+
+                if !control.is_cancelled() && ui.button("❌ Cancel").clicked() {
+                    // The download task reports back once partial files are flushed
+                    control.cancel();
+                    self.download_status = "Cancelling...".to_string();
+                }
             }
         });
 
@@ -563,6 +595,11 @@ impl IaGetApp {
         }
     }
 
+    fn render_queue_tab(&mut self, ui: &mut Ui) {
+        self.queue_panel
+            .render(ui, &self.config, &self.output_directory);
+    }
+
     fn render_archive_health_tab(&mut self, ui: &mut Ui) {
         self.archive_health_panel.show(ui);
     }
@@ -664,6 +701,7 @@ impl eframe::App for IaGetApp {
         if let Some(rx) = &mut self.completion_rx {
             while let Ok(result) = rx.try_recv() {
                 self.is_downloading = false;
+                self.download_control = None;
                 match result {
                     DownloadResult::Success(session, _api_stats, is_dry_run) => {
                         if is_dry_run {
@@ -705,6 +743,14 @@ impl eframe::App for IaGetApp {
                         // Reset progress panel
                         self.download_panel.reset();
                     }
+                    DownloadResult::Cancelled(session) => {
+                        let summary = session.get_progress_summary();
+                        self.download_status = format!(
+                            "Cancelled: {} of {} files done; download again to resume",
+                            summary.completed_files, summary.total_files
+                        );
+                        self.download_panel.reset();
+                    }
                     DownloadResult::Error(error_msg) => {
                         self.error_message = Some(format!("Download failed: {}", error_msg));
                         self.download_status = "Download failed".to_string();
@@ -4,7 +4,9 @@
 //! and compliance information for responsible usage.
 
 use crate::{
+    IaGetError,
     infrastructure::api::archive_api::{ApiStats, ArchiveOrgApiClient, get_archive_servers},
+    infrastructure::http::HttpClientFactory,
     utilities::common::constants::get_user_agent,
 };
 use egui::{Color32, Context, RichText, Ui};
@@ -64,11 +66,12 @@ impl ArchiveHealthPanel {
     /// Initialize the API client
     fn init_api_client(&mut self) {
         if self.api_client.is_none() {
-            if let Ok(client) = reqwest::Client::builder()
-                .user_agent(get_user_agent())
-                .timeout(Duration::from_secs(10))
-                .build()
-            {
+            if let Ok(client) = HttpClientFactory::builder().and_then(|builder| {
+                builder
+                    .timeout(Duration::from_secs(10))
+                    .build()
+                    .map_err(IaGetError::from)
+            }) {
                 let api_client = ArchiveOrgApiClient::new(client);
                 self.api_client = Some(Arc::new(Mutex::new(api_client)));
             }
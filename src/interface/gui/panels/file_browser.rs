@@ -391,6 +391,9 @@ impl FileBrowserPanel {
             Ok(crate::core::download::download_service::DownloadResult::Success(session, _, _)) => {
                 Ok(session.archive_metadata)
             }
+            Ok(crate::core::download::download_service::DownloadResult::Cancelled(_)) => {
+                Err("Metadata fetch was cancelled".to_string())
+            }
             Ok(crate::core::download::download_service::DownloadResult::Error(e)) => Err(e),
             Err(e) => Err(format!("Download failed: {}", e)),
         }
@@ -7,9 +7,11 @@ pub mod config;
 pub mod download;
 pub mod file_browser;
 pub mod filters;
+pub mod queue;
 
 pub use archive_health::ArchiveHealthPanel;
 pub use config::ConfigPanel;
 pub use download::DownloadPanel;
 pub use file_browser::FileBrowserPanel;
 pub use filters::FiltersPanel;
+pub use queue::QueuePanel;
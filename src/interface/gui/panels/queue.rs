@@ -0,0 +1,227 @@
+//! Download queue panel
+//!
+//! Shows the queue that `ia-get queue run` works through and edits the same
+//! file, so changes made here are picked up by a running queue.
+
+use crate::{
+    core::download::DownloadRequest,
+    infrastructure::config::Config,
+    infrastructure::persistence::{
+        DownloadQueue, JobState, QueueEntry, download_queue::get_default_queue_path,
+    },
+};
+use egui::{Color32, RichText, Ui};
+use std::path::PathBuf;
+
+/// A change requested from the queue list
+enum QueueEdit {
+    Move(u64, usize),
+    SetState(u64, JobState),
+    Remove(u64),
+}
+
+#[derive(Default)]
+pub struct QueuePanel {
+    path: Option<PathBuf>,
+    entries: Vec<QueueEntry>,
+    loaded: bool,
+    error: Option<String>,
+
+    // New entry form
+    new_identifier: String,
+    new_priority: i32,
+}
+
+impl QueuePanel {
+    pub fn new() -> Self {
+        Self {
+            path: get_default_queue_path().ok(),
+            ..Default::default()
+        }
+    }
+
+    /// Re-read the queue file
+    pub fn refresh(&mut self) {
+        self.loaded = true;
+        let Some(ref path) = self.path else {
+            self.error = Some("No configuration directory for the queue".to_string());
+            return;
+        };
+        match DownloadQueue::load(path) {
+            Ok(queue) => {
+                self.entries = queue.entries;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Apply `change` to the queue file and show the result
+    fn edit(&mut self, change: impl FnOnce(&mut DownloadQueue)) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let result = DownloadQueue::update(&path, |queue| {
+            change(queue);
+            queue.entries.clone()
+        });
+        match result {
+            Ok(entries) => {
+                self.entries = entries;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn state_color(state: JobState) -> Color32 {
+        match state {
+            JobState::Queued => Color32::LIGHT_GRAY,
+            JobState::Running => Color32::LIGHT_BLUE,
+            JobState::Paused => Color32::YELLOW,
+            JobState::Completed => Color32::GREEN,
+            JobState::Failed => Color32::RED,
+            JobState::Cancelled => Color32::GRAY,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut Ui, config: &Config, output_dir: &str) {
+        if !self.loaded {
+            self.refresh();
+        }
+
+        ui.heading("Download Queue");
+        ui.label("Run the queue with 'ia-get queue run'. Higher priorities download first.");
+
+        ui.add_space(10.0);
+
+        // Add an item with the current settings and output directory
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Identifier:");
+                ui.text_edit_singleline(&mut self.new_identifier);
+                ui.label("Priority:");
+                ui.add(egui::DragValue::new(&mut self.new_priority));
+
+                if ui.button("➕ Add to Queue").clicked() && !self.new_identifier.trim().is_empty()
+                {
+                    let request = DownloadRequest::from_config(
+                        config,
+                        self.new_identifier.trim().to_string(),
+                        PathBuf::from(output_dir),
+                    );
+                    let priority = self.new_priority;
+                    self.edit(|queue| {
+                        queue.add(request, priority);
+                    });
+                    self.new_identifier.clear();
+                }
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Refresh").clicked() {
+                self.refresh();
+            }
+            if ui.button("🧹 Remove Finished").clicked() {
+                self.edit(|queue| {
+                    queue.remove_finished();
+                });
+            }
+        });
+
+        if let Some(ref error) = self.error {
+            ui.label(RichText::new(format!("❌ {}", error)).color(Color32::RED));
+        }
+
+        ui.add_space(10.0);
+
+        if self.entries.is_empty() {
+            ui.label("The queue is empty");
+            return;
+        }
+
+        let mut requested = None;
+        egui::Grid::new("download_queue")
+            .striped(true)
+            .num_columns(6)
+            .show(ui, |ui| {
+                ui.label(RichText::new("#").strong());
+                ui.label(RichText::new("Identifier").strong());
+                ui.label(RichText::new("State").strong());
+                ui.label(RichText::new("Priority").strong());
+                ui.label(RichText::new("Files").strong());
+                ui.label("");
+                ui.end_row();
+
+                for (index, entry) in self.entries.iter().enumerate() {
+                    ui.label((index + 1).to_string());
+                    ui.label(&entry.request.identifier)
+                        .on_hover_text(entry.request.output_dir.display().to_string());
+                    let state = ui.label(
+                        RichText::new(entry.state.as_str()).color(Self::state_color(entry.state)),
+                    );
+                    if let Some(ref error) = entry.error {
+                        state.on_hover_text(error);
+                    }
+                    ui.label(entry.priority.to_string());
+                    if entry.total_files > 0 {
+                        ui.label(format!(
+                            "{}/{}",
+                            entry.completed_files + entry.failed_files,
+                            entry.total_files
+                        ));
+                    } else {
+                        ui.label("");
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.small_button("⬆").clicked() && index > 0 {
+                            requested = Some(QueueEdit::Move(entry.id, index - 1));
+                        }
+                        if ui.small_button("⬇").clicked() {
+                            requested = Some(QueueEdit::Move(entry.id, index + 1));
+                        }
+                        match entry.state {
+                            JobState::Queued => {
+                                if ui.small_button("⏸ Hold").clicked() {
+                                    requested =
+                                        Some(QueueEdit::SetState(entry.id, JobState::Paused));
+                                }
+                            }
+                            JobState::Paused | JobState::Failed => {
+                                if ui.small_button("▶ Queue").clicked() {
+                                    requested =
+                                        Some(QueueEdit::SetState(entry.id, JobState::Queued));
+                                }
+                            }
+                            _ => {}
+                        }
+                        // A running item belongs to the queue run that claimed it
+                        if entry.state != JobState::Running && ui.small_button("❌").clicked() {
+                            requested = Some(QueueEdit::Remove(entry.id));
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        match requested {
+            Some(QueueEdit::Move(id, position)) => self.edit(|queue| {
+                queue.move_to(id, position);
+            }),
+            Some(QueueEdit::SetState(id, state)) => self.edit(|queue| {
+                if let Some(entry) = queue.get_mut(id) {
+                    entry.error = None;
+                    entry.set_state(state);
+                }
+            }),
+            Some(QueueEdit::Remove(id)) => self.edit(|queue| {
+                queue.remove(id);
+            }),
+            None => {}
+        }
+    }
+}
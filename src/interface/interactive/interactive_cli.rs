@@ -5,7 +5,7 @@
 
 use crate::{
     Result,
-    core::download::{DownloadRequest, DownloadResult, DownloadService},
+    core::download::{DownloadControl, DownloadRequest, DownloadResult, DownloadService},
     core::session::{ArchiveFile, DownloadSession, ProgressUpdate},
     infrastructure::config::{Config, ConfigManager},
     utilities::filters::format_size,
@@ -21,6 +21,43 @@ pub struct InteractiveCli {
     config_manager: ConfigManager,
     config: Config,
     download_service: DownloadService,
+    commands: CommandReader,
+}
+
+/// Reads typed commands from stdin for the whole session
+///
+/// A single thread reads one line per [`CommandReader::request`], so it never
+/// holds stdin while the menus prompt for input themselves. It exits when
+/// stdin closes or the CLI is dropped.
+struct CommandReader {
+    requests: std::sync::mpsc::Sender<()>,
+    lines: tokio::sync::Mutex<tokio::sync::mpsc::UnboundedReceiver<String>>,
+}
+
+impl CommandReader {
+    fn new() -> Self {
+        let (requests, request_rx) = std::sync::mpsc::channel::<()>();
+        let (line_tx, lines) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            for () in request_rx {
+                let mut line = String::new();
+                if io::stdin().read_line(&mut line).unwrap_or(0) == 0
+                    || line_tx.send(line.trim().to_lowercase()).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            lines: tokio::sync::Mutex::new(lines),
+        }
+    }
+
+    /// Ask for the next line; it arrives on `lines`
+    fn request(&self) {
+        let _ = self.requests.send(());
+    }
 }
 
 /// State for live updating interface
@@ -47,6 +84,7 @@ impl InteractiveCli {
             config_manager,
             config,
             download_service,
+            commands: CommandReader::new(),
         })
     }
 
@@ -523,11 +561,10 @@ impl InteractiveCli {
         // Create a test API client
         use crate::{
             infrastructure::api::{EnhancedArchiveApiClient, get_archive_servers},
-            utilities::common::get_user_agent,
+            infrastructure::http::HttpClientFactory,
         };
 
-        let client = reqwest::Client::builder()
-            .user_agent(get_user_agent())
+        let client = HttpClientFactory::builder()?
             .timeout(std::time::Duration::from_secs(10))
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {}", e))?;
@@ -665,6 +702,7 @@ impl InteractiveCli {
                 self.show_error(&format!("Failed to fetch archive metadata: {}", error));
                 self.wait_for_keypress();
             }
+            DownloadResult::Cancelled(_) => {}
         }
 
         Ok(())
@@ -1285,27 +1323,81 @@ impl InteractiveCli {
             })
         };
 
-        // Execute download
-        let result = self
-            .download_service
-            .download(request, Some(progress_callback))
-            .await?;
-
+        // Typed commands arrive a line at a time; exactly one line is requested
+        // at any moment, and the last one dismisses the final summary
+        let mut commands = self.commands.lines.lock().await;
+        self.commands.request();
+
+        // Execute download, reacting to pause/resume/cancel commands meanwhile
+        let control = DownloadControl::new();
+        let download = self.download_service.download_with_control(
+            request,
+            Some(progress_callback),
+            control.clone(),
+        );
+        tokio::pin!(download);
+        let result = loop {
+            tokio::select! {
+                result = &mut download => break result,
+                Some(command) = commands.recv() => {
+                    self.commands.request();
+                    let status = match command.as_str() {
+                        "p" | "pause" => {
+                            control.pause();
+                            "Paused - type r to resume"
+                        }
+                        "r" | "resume" => {
+                            control.resume();
+                            "Resuming..."
+                        }
+                        "c" | "cancel" => {
+                            control.cancel();
+                            "Cancelling - saving session..."
+                        }
+                        _ => continue,
+                    };
+                    download_state
+                        .lock()
+                        .expect("Progress state mutex poisoned")
+                        .status = status.to_string();
+                }
+            }
+        };
         // Stop progress display
         progress_task.abort();
 
         // Show final result
         self.clear_screen();
         match result {
-            DownloadResult::Success(session, _stats, _is_dry_run) => {
+            Ok(DownloadResult::Success(session, _stats, _is_dry_run)) => {
                 self.show_success_summary(&session);
             }
-            DownloadResult::Error(error) => {
+            Ok(DownloadResult::Cancelled(session)) => {
+                let progress = session.get_progress_summary();
+                println!(
+                    "{} Download cancelled after {} of {} files; run it again to resume",
+                    "⏸️".yellow(),
+                    progress.completed_files,
+                    progress.total_files
+                );
+            }
+            Ok(DownloadResult::Error(error)) => {
+                self.show_error(&format!("Download failed: {}", error));
+            }
+            Err(error) => {
                 self.show_error(&format!("Download failed: {}", error));
             }
         }
 
-        self.wait_for_keypress();
+        // The line requested last is the one that continues
+        println!();
+        print!(
+            "{} {}",
+            "⏸️".bright_blue(),
+            "Press Enter to continue...".dimmed()
+        );
+        let _ = io::stdout().flush();
+        let _ = commands.recv().await;
         Ok(())
     }
 
@@ -1405,7 +1497,11 @@ impl InteractiveCli {
         let _ = writeln!(buffer);
 
         // 4. Footer (Controls)
-        let _ = writeln!(buffer, "{}", "Press Ctrl+C to cancel".dimmed());
+        let _ = writeln!(
+            buffer,
+            "{}",
+            "Type p to pause, r to resume or c to cancel, then press Enter".dimmed()
+        );
 
         // Single print to reduce flickering
         // Move to top-left (H) and clear everything below (J)
@@ -0,0 +1,53 @@
+//! OpenMetrics endpoint
+//!
+//! `--metrics-addr ADDR` serves the process-wide [`DownloadMetrics`] at
+//! `GET /metrics` for Prometheus and other OpenMetrics scrapers, using the
+//! daemon's HTTP framing. The endpoint is read-only, so unlike the daemon
+//! API it may listen on any address, but it has no authentication: anyone
+//! who can reach a non-loopback address sees server names and transfer
+//! volumes.
+
+use crate::{
+    IaGetError, Result,
+    core::download::DownloadMetrics,
+    interface::daemon::{read_request, write_response},
+    utilities::common::OPENMETRICS_CONTENT_TYPE,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::BufReader;
+
+async fn handle_scrape(metrics: Arc<DownloadMetrics>, connection: tokio::net::TcpStream) {
+    let mut connection = BufReader::new(connection);
+    let (status, content_type, body) = match read_request(&mut connection).await {
+        Ok(request) if request.method == "GET" && request.segments() == ["metrics"] => (
+            200,
+            OPENMETRICS_CONTENT_TYPE,
+            metrics.render_openmetrics().await,
+        ),
+        Ok(request) if request.method == "GET" => {
+            (404, "text/plain", "Metrics are at /metrics\n".to_string())
+        }
+        Ok(_) => (405, "text/plain", "Only GET is supported\n".to_string()),
+        Err(e) => (400, "text/plain", format!("{}\n", e)),
+    };
+    let _ = write_response(&mut connection, status, content_type, body.as_bytes()).await;
+}
+
+/// Listen on `addr` and serve `metrics` in the background, returning the
+/// bound address
+pub async fn start_metrics_server(metrics: Arc<DownloadMetrics>, addr: &str) -> Result<SocketAddr> {
+    let addr: SocketAddr = addr
+        .parse()
+        .map_err(|_| IaGetError::Config(format!("Invalid metrics address '{}'", addr)))?;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| IaGetError::Network(format!("Cannot listen on {}: {}", addr, e)))?;
+    let bound = listener.local_addr()?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_scrape(metrics.clone(), stream));
+        }
+    });
+    Ok(bound)
+}
@@ -5,10 +5,12 @@
 //! The Rust implementation provides CLI and optional GUI desktop applications.
 
 pub mod cli;
+pub mod daemon;
 
 #[cfg(feature = "gui")]
 pub mod gui;
 pub mod interactive;
+pub mod metrics;
 
 // Re-export commonly used interface types
 pub use cli::*;
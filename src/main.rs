@@ -7,17 +7,30 @@ use anyhow::{Context, Result};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use colored::Colorize;
 use std::path::PathBuf;
+use std::sync::Arc;
 #[cfg(feature = "gui")]
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::signal;
 
 use ia_get::{
     DownloadRequest, DownloadResult, DownloadService,
     core::archive::AdvancedMetadataProcessor,
-    core::session::DownloadState,
+    core::download::{
+        DownloadControl, DownloadMetrics, HookSettings, ProgressEventWriter, set_download_metrics,
+        set_global_hook_settings, set_progress_event_writer,
+    },
     core::session::sanitize_filename_for_filesystem,
+    core::session::{ChecksumPolicy, DownloadOrder, DownloadState},
     infrastructure::api::{EnhancedArchiveApiClient, get_archive_servers},
+    infrastructure::config::{Credentials, set_global_credentials},
+    infrastructure::http::{
+        BandwidthSchedule, HttpClientFactory, NetworkSettings, RetryPolicy,
+        set_global_bandwidth_limit, set_global_network_settings, set_global_retry_policy,
+    },
+    infrastructure::persistence::config_persistence::ConfigPersistence,
     interface::cli::SourceType,
+    interface::metrics::start_metrics_server,
     utilities::common::get_user_agent,
     utilities::filters::format_size,
 };
@@ -201,12 +214,45 @@ async fn show_interactive_menu() -> Result<()> {
 /// Entry point for the ia-get CLI application  
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Set up signal handling for graceful shutdown
-    tokio::spawn(async {
-        signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
-        println!("\n{} Download interrupted by user", "⚠️".yellow());
-        std::process::exit(0);
-    });
+    // Set up signal handling for graceful shutdown. While a download is running
+    // the first Ctrl+C stops it and saves the session; a second one quits at once.
+    static DOWNLOAD_RUNNING: AtomicBool = AtomicBool::new(false);
+    let interrupt = DownloadControl::new();
+    {
+        let interrupt = interrupt.clone();
+        tokio::spawn(async move {
+            signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+            if DOWNLOAD_RUNNING.load(Ordering::SeqCst) {
+                println!(
+                    "\n{} Stopping downloads and saving the session (Ctrl+C again to quit)",
+                    "⏸️".yellow()
+                );
+                interrupt.cancel();
+                signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+            }
+            println!("\n{} Download interrupted by user", "⚠️".yellow());
+            std::process::exit(0);
+        });
+    }
+
+    // Every client and request path follows the saved retry, proxy and TLS
+    // preferences and signs in with the stored credentials; downloads run the
+    // saved hooks and share the saved bandwidth limit
+    if let Ok(config) = ConfigPersistence::new().and_then(|persistence| persistence.load_config()) {
+        set_global_retry_policy(RetryPolicy::from_config(&config));
+        set_global_network_settings(NetworkSettings::from_config(&config));
+        set_global_hook_settings(HookSettings::from_config(&config));
+        if let Some(ref limit) = config.bandwidth_limit {
+            match BandwidthSchedule::parse(limit) {
+                Ok(schedule) => set_global_bandwidth_limit(Some(schedule)),
+                Err(e) => eprintln!("{} Ignoring saved bandwidth_limit: {}", "⚠️".yellow(), e),
+            }
+        }
+    }
+    match Credentials::default_path().and_then(|path| Credentials::load(&path)) {
+        Ok(credentials) => set_global_credentials(credentials),
+        Err(e) => eprintln!("{} Ignoring stored credentials: {}", "⚠️".yellow(), e),
+    }
 
     // Parse command line arguments
     let matches = build_cli().try_get_matches();
@@ -256,6 +302,56 @@ async fn main() -> Result<()> {
         }
     };
 
+    // A single limit covers every transfer in the process, daemon and queue jobs included
+    if let Some(schedule) = matches.get_one::<BandwidthSchedule>("limit-rate") {
+        set_global_bandwidth_limit(Some(schedule.clone()));
+    }
+
+    // Progress events for scripts; status messages move to stderr when they take stdout
+    if matches.contains_id("progress-fd")
+        || matches
+            .get_one::<String>("progress")
+            .is_some_and(|mode| mode == "json")
+    {
+        let writer = match matches.get_one::<i32>("progress-fd") {
+            Some(&fd) => ProgressEventWriter::from_fd(fd),
+            None => ProgressEventWriter::stdout(),
+        };
+        match writer {
+            Ok(writer) => set_progress_event_writer(Some(writer)),
+            Err(e) => {
+                eprintln!("{} Cannot write progress events: {}", "❌".red(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Metrics for Prometheus and friends, gathered while the process runs
+    if let Some(addr) = matches.get_one::<String>("metrics-addr") {
+        let metrics = Arc::new(DownloadMetrics::new());
+        match start_metrics_server(metrics.clone(), addr).await {
+            Ok(bound) => {
+                set_download_metrics(Some(metrics));
+                eprintln!(
+                    "{} Serving metrics at http://{}/metrics",
+                    "📈".bright_blue(),
+                    bound
+                );
+                if !bound.ip().is_loopback() {
+                    eprintln!(
+                        "{} Metrics are served without authentication to anyone who can reach {}",
+                        "⚠️".yellow(),
+                        bound
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("{} {}", "❌".red(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Check for subcommands first
     match matches.subcommand() {
         Some(("search", search_matches)) => {
@@ -340,6 +436,350 @@ async fn main() -> Result<()> {
             }
             return Ok(());
         }
+        Some(("cat", cat_matches)) => {
+            use ia_get::interface::cli::advanced_commands;
+
+            let reference = cat_matches
+                .get_one::<String>("file")
+                .expect("File argument is required");
+            let verify_md5 = !cat_matches.get_flag("no-verify");
+
+            if let Err(e) = advanced_commands::cat_file(reference, verify_md5).await {
+                eprintln!("{} {:#}", "❌".red(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(("members", members_matches)) => {
+            use ia_get::interface::cli::advanced_commands;
+
+            let (action, action_matches) = members_matches
+                .subcommand()
+                .expect("members requires a subcommand");
+            let reference = action_matches
+                .get_one::<String>("archive")
+                .expect("Archive argument is required");
+            let filter = match member_filter(action_matches) {
+                Ok(filter) => filter,
+                Err(e) => {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            };
+
+            let result = match action {
+                "extract" => {
+                    let output_dir = action_matches
+                        .get_one::<String>("output")
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+                    advanced_commands::extract_archive_members(reference, &filter, &output_dir)
+                        .await
+                        .map(|failed| failed == 0)
+                }
+                _ => advanced_commands::list_archive_members(reference, &filter)
+                    .await
+                    .map(|_| true),
+            };
+            match result {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(("mirror", mirror_matches)) => {
+            use ia_get::interface::cli::advanced_commands;
+
+            let config = advanced_commands::MirrorConfig {
+                collection: mirror_matches
+                    .get_one::<String>("collection")
+                    .expect("Collection argument is required")
+                    .clone(),
+                query: mirror_matches.get_one::<String>("query").cloned(),
+                output_dir: mirror_matches
+                    .get_one::<String>("output")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| std::env::current_dir().unwrap_or_default()),
+                page_size: mirror_matches
+                    .get_one::<String>("page-size")
+                    .and_then(|s| s.parse::<u32>().ok())
+                    .unwrap_or(1000),
+                concurrent_downloads: mirror_matches
+                    .get_one::<String>("concurrent")
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .unwrap_or(4)
+                    .clamp(1, 16),
+                restart: mirror_matches.get_flag("restart"),
+            };
+
+            match advanced_commands::mirror_collection(config).await {
+                Ok(checkpoint) if !checkpoint.failed.is_empty() => std::process::exit(1),
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(("sync", sync_matches)) => {
+            use ia_get::{core::download::RemovalMode, interface::cli::advanced_commands};
+
+            let removal = if sync_matches.get_flag("delete") {
+                RemovalMode::Delete
+            } else if sync_matches.get_flag("quarantine") {
+                RemovalMode::Quarantine
+            } else {
+                RemovalMode::Keep
+            };
+            let config = advanced_commands::SyncConfig {
+                identifier: sync_matches
+                    .get_one::<String>("identifier")
+                    .expect("Identifier argument is required")
+                    .clone(),
+                dir: PathBuf::from(
+                    sync_matches
+                        .get_one::<String>("dir")
+                        .expect("Directory argument is required"),
+                ),
+                removal,
+                verify_all: sync_matches.get_flag("verify"),
+                dry_run: sync_matches.get_flag("dry-run"),
+                concurrent_downloads: sync_matches
+                    .get_one::<String>("concurrent")
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .unwrap_or(4)
+                    .clamp(1, 16),
+            };
+
+            match advanced_commands::sync_item(config).await {
+                Ok(summary) if summary.failed > 0 => std::process::exit(1),
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(("store", store_matches)) => {
+            use ia_get::interface::cli::advanced_commands;
+
+            if let Some(("gc", gc_matches)) = store_matches.subcommand() {
+                let store_dir = gc_matches.get_one::<String>("store").cloned().or_else(|| {
+                    ConfigPersistence::new()
+                        .and_then(|persistence| persistence.load_config())
+                        .ok()
+                        .and_then(|config| config.store_dir)
+                });
+                let Some(store_dir) = store_dir else {
+                    eprintln!(
+                        "{} No store configured: pass --store DIR or run 'ia-get config set store_dir DIR'",
+                        "❌".red()
+                    );
+                    std::process::exit(1);
+                };
+                if let Err(e) = advanced_commands::store_gc(&store_dir) {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(("verify-pieces", verify_matches)) => {
+            use ia_get::interface::cli::advanced_commands::{self, PieceVerifyConfig};
+
+            let config = PieceVerifyConfig {
+                identifier: verify_matches
+                    .get_one::<String>("identifier")
+                    .expect("identifier is required")
+                    .clone(),
+                dir: PathBuf::from(
+                    verify_matches
+                        .get_one::<String>("dir")
+                        .expect("dir is required"),
+                ),
+                repair: verify_matches.get_flag("repair"),
+                offline: verify_matches.get_flag("offline"),
+            };
+            match advanced_commands::verify_item_pieces(config).await {
+                Ok(report) if report.is_clean() => {}
+                Ok(_) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("{} {:#}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(("queue", queue_matches)) => {
+            use ia_get::{
+                infrastructure::persistence::download_queue::get_default_queue_path,
+                interface::cli::advanced_commands::{self, QueueAction},
+            };
+
+            let path = match get_default_queue_path() {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("{} {}", "❌".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let result = match queue_matches.subcommand() {
+                Some(("run", run_matches)) => {
+                    let parallel = *run_matches
+                        .get_one::<u64>("parallel")
+                        .expect("parallel has a default")
+                        as usize;
+                    DOWNLOAD_RUNNING.store(true, Ordering::SeqCst);
+                    let summary = advanced_commands::run_queue(
+                        &path,
+                        parallel,
+                        run_matches.get_flag("retry-failed"),
+                        interrupt.clone(),
+                    )
+                    .await;
+                    DOWNLOAD_RUNNING.store(false, Ordering::SeqCst);
+                    match summary {
+                        Ok(summary) if summary.failed > 0 => std::process::exit(1),
+                        Ok(summary) if summary.interrupted > 0 => std::process::exit(130),
+                        Ok(_) => Ok(()),
+                        Err(e) => Err(e),
+                    }
+                }
+                Some((action, matches)) => {
+                    let action = match action {
+                        "add" => QueueAction::Add(
+                            matches
+                                .get_many::<String>("identifiers")
+                                .expect("Identifiers are required")
+                                .map(|identifier| queued_request(matches, identifier))
+                                .collect(),
+                            *matches
+                                .get_one::<i32>("priority")
+                                .expect("priority has a default"),
+                        ),
+                        "remove" if matches.get_flag("finished") => QueueAction::RemoveFinished,
+                        "remove" => QueueAction::Remove(
+                            matches
+                                .get_many::<u64>("ids")
+                                .expect("Entry numbers are required")
+                                .copied()
+                                .collect(),
+                        ),
+                        "reorder" => {
+                            let id = *matches.get_one::<u64>("id").expect("id is required");
+                            match matches.get_one::<i32>("priority") {
+                                Some(&priority) => QueueAction::SetPriority(id, priority),
+                                None => QueueAction::Move(
+                                    id,
+                                    *matches
+                                        .get_one::<u64>("position")
+                                        .expect("position is required")
+                                        as usize,
+                                ),
+                            }
+                        }
+                        _ => QueueAction::List,
+                    };
+                    advanced_commands::queue_command(&path, action)
+                }
+                None => unreachable!("queue requires a subcommand"),
+            };
+            if let Err(e) = result {
+                eprintln!("{} {:#}", "❌".red(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(("daemon", daemon_matches)) => {
+            use ia_get::{
+                core::download::QueueRunner,
+                infrastructure::persistence::download_queue::get_default_queue_path,
+                interface::daemon::{
+                    DaemonToken, JobManager, get_default_daemon_token_path, serve,
+                },
+            };
+
+            let result = async {
+                let endpoint = daemon_endpoint(daemon_matches, "listen")?;
+                let parallel = *daemon_matches
+                    .get_one::<u64>("parallel")
+                    .expect("parallel has a default") as usize;
+                let manager = JobManager::new(
+                    QueueRunner::new(get_default_queue_path()?, parallel).keep_running(),
+                );
+                let token = DaemonToken::generate()?;
+                token.save(&get_default_daemon_token_path()?)?;
+                set_progress_event_writer(Some(manager.event_writer()));
+                serve(manager, &endpoint, token).await
+            }
+            .await;
+            if let Err(e) = result {
+                eprintln!("{} {:#}", "❌".red(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(("remote", remote_matches)) => {
+            use ia_get::interface::{
+                cli::advanced_commands::{self, RemoteAction},
+                daemon::{DaemonClient, DaemonToken, get_default_daemon_token_path},
+            };
+
+            let job_id = |matches: &ArgMatches| {
+                *matches
+                    .get_one::<u64>("job")
+                    .expect("Job argument is required")
+            };
+            let action = match remote_matches.subcommand() {
+                Some(("add", add_matches)) => RemoteAction::Add(Box::new(queued_request(
+                    add_matches,
+                    add_matches
+                        .get_one::<String>("identifier")
+                        .expect("Identifier argument is required"),
+                ))),
+                Some(("status", matches)) => RemoteAction::Status(job_id(matches)),
+                Some(("pause", matches)) => RemoteAction::Pause(job_id(matches)),
+                Some(("resume", matches)) => RemoteAction::Resume(job_id(matches)),
+                Some(("cancel", matches)) => RemoteAction::Cancel(job_id(matches)),
+                Some(("events", _)) => RemoteAction::Events,
+                _ => RemoteAction::List,
+            };
+
+            let client = daemon_endpoint(remote_matches, "addr").and_then(|endpoint| {
+                let token = DaemonToken::load(&get_default_daemon_token_path()?)?;
+                Ok(DaemonClient::new(endpoint, token))
+            });
+            let result = match client {
+                Ok(client) => advanced_commands::remote_command(&client, action).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = result {
+                eprintln!("{} {:#}", "❌".red(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(("auth", auth_matches)) => {
+            use ia_get::interface::cli::advanced_commands::{self, AuthAction};
+
+            let action = match auth_matches.subcommand() {
+                Some(("keys", _)) => AuthAction::Keys,
+                Some(("cookies", _)) => AuthAction::Cookies,
+                Some(("logout", _)) => AuthAction::Logout,
+                _ => AuthAction::Status,
+            };
+            if let Err(e) = advanced_commands::handle_auth(action) {
+                eprintln!("{} {:#}", "❌".red(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
         Some(("config", config_matches)) => {
             use ia_get::interface::cli::commands;
             match config_matches.subcommand() {
@@ -550,44 +990,58 @@ async fn main() -> Result<()> {
         .unwrap_or(4)
         .min(16); // Cap at 16 concurrent downloads
 
+    let segments_per_file = matches
+        .get_one::<String>("segments")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 16); // Cap at 16 connections per file
+    let segment_min_size = matches
+        .get_one::<String>("segment-threshold")
+        .map(|s| s.to_string());
+
+    let checksum_policy = matches
+        .get_one::<String>("checksum")
+        .and_then(|s| s.parse::<ChecksumPolicy>().ok())
+        .unwrap_or_default();
+
+    let download_order = matches
+        .get_one::<DownloadOrder>("order")
+        .cloned()
+        .unwrap_or_default();
+
+    let saved_config = ConfigPersistence::new()
+        .and_then(|persistence| persistence.load_config())
+        .ok();
+
+    let store_dir = matches
+        .get_one::<String>("store")
+        .cloned()
+        .or_else(|| {
+            saved_config
+                .as_ref()
+                .and_then(|config| config.store_dir.clone())
+        })
+        .map(PathBuf::from);
+
     let mut include_formats = matches
         .get_many::<String>("include")
         .map(|values| values.map(|s| s.to_string()).collect::<Vec<_>>())
         .unwrap_or_default();
 
     // Add formats from format categories
-    if let Some(format_categories) = matches.get_many::<String>("include-formats") {
-        use ia_get::utilities::filters::{FileFormats, FormatCategory};
-        let file_formats = FileFormats::new();
-
-        for category_name in format_categories {
-            let category_name_lower = category_name.to_lowercase();
-            for category in FormatCategory::all() {
-                if category.display_name().to_lowercase() == category_name_lower {
-                    include_formats.extend(file_formats.get_formats(&category));
-                    break;
-                }
-            }
-        }
-    }
-
-    let mut exclude_formats = Vec::new();
-
-    // Add exclude formats from format categories
-    if let Some(exclude_format_categories) = matches.get_many::<String>("exclude-formats") {
-        use ia_get::utilities::filters::{FileFormats, FormatCategory};
-        let file_formats = FileFormats::new();
-
-        for category_name in exclude_format_categories {
-            let category_name_lower = category_name.to_lowercase();
-            for category in FormatCategory::all() {
-                if category.display_name().to_lowercase() == category_name_lower {
-                    exclude_formats.extend(file_formats.get_formats(&category));
-                    break;
-                }
-            }
-        }
-    }
+    include_formats.extend(formats_in_categories(
+        matches
+            .get_many::<String>("include-formats")
+            .into_iter()
+            .flatten(),
+    ));
+
+    let exclude_formats = formats_in_categories(
+        matches
+            .get_many::<String>("exclude-formats")
+            .into_iter()
+            .flatten(),
+    );
 
     let max_file_size = matches.get_one::<String>("max-size").map(|s| s.to_string());
 
@@ -617,6 +1071,17 @@ async fn main() -> Result<()> {
         verbose,
         resume: true,
         source_types: get_source_types_from_matches(&matches),
+        segments_per_file,
+        segment_min_size,
+        checksum_policy,
+        output_container: matches
+            .get_one::<String>("output-archive")
+            .map(PathBuf::from),
+        store_dir,
+        download_order,
+        // Installed from the saved configuration at startup
+        max_retries: None,
+        redownload: Vec::new(),
     };
 
     println!(
@@ -636,7 +1101,13 @@ async fn main() -> Result<()> {
     let service = DownloadService::new().context("Failed to create download service")?;
 
     // Execute download using unified API
-    match service.download(request.clone(), None).await {
+    DOWNLOAD_RUNNING.store(true, Ordering::SeqCst);
+    let result = service
+        .download_with_control(request.clone(), None, interrupt)
+        .await;
+    DOWNLOAD_RUNNING.store(false, Ordering::SeqCst);
+
+    match result {
         Ok(DownloadResult::Success(session, api_stats, _is_dry_run)) => {
             if !dry_run {
                 println!("\n{} Download completed successfully!", "✅".green().bold());
@@ -719,6 +1190,17 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Ok(DownloadResult::Cancelled(session)) => {
+            let progress = session.get_progress_summary();
+            println!(
+                "\n{} Download stopped after {} of {} files",
+                "⏸️".yellow().bold(),
+                progress.completed_files,
+                progress.total_files
+            );
+            println!("💡 Run the same command again to resume");
+            std::process::exit(130);
+        }
         Ok(DownloadResult::Error(error)) => {
             eprintln!("{} Error: {}", "✘".red().bold(), error);
             std::process::exit(1);
@@ -738,8 +1220,7 @@ async fn display_api_health() -> Result<()> {
     println!();
 
     // Create a test API client
-    let client = reqwest::Client::builder()
-        .user_agent(get_user_agent())
+    let client = HttpClientFactory::builder()?
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .context("Failed to create HTTP client")?;
@@ -923,6 +1404,189 @@ fn get_source_types_from_matches(matches: &ArgMatches) -> Vec<SourceType> {
     types
 }
 
+/// Formats in the named categories (documents, images, ...), matched case-insensitively
+fn formats_in_categories<'a>(categories: impl Iterator<Item = &'a String>) -> Vec<String> {
+    use ia_get::utilities::filters::{FileFormats, FormatCategory};
+    let file_formats = FileFormats::new();
+
+    let mut formats = Vec::new();
+    for category_name in categories {
+        if let Some(category) = FormatCategory::all()
+            .into_iter()
+            .find(|category| category.display_name().eq_ignore_ascii_case(category_name))
+        {
+            formats.extend(file_formats.get_formats(&category));
+        }
+    }
+    formats
+}
+
+/// Output, concurrency and format arguments of `queue add` and `remote add`
+fn queued_request_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Output directory (defaults to the current directory)")
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::new("concurrent")
+                .short('c')
+                .long("concurrent")
+                .help("Number of concurrent downloads (1-16)")
+                .value_name("NUM")
+                .value_parser(clap::value_parser!(u64).range(1..=16)),
+        )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .help("Only download files of these formats (e.g., --include pdf,txt)")
+                .value_name("FORMATS")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+}
+
+/// Download request for `identifier` from the saved settings and the
+/// arguments added by `queued_request_args`
+fn queued_request(matches: &ArgMatches, identifier: &str) -> DownloadRequest {
+    let config = ConfigPersistence::new()
+        .and_then(|persistence| persistence.load_config())
+        .unwrap_or_default();
+    let output_dir = matches
+        .get_one::<String>("output")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let mut request = DownloadRequest::from_config(
+        &config,
+        identifier.to_string(),
+        std::path::absolute(&output_dir).unwrap_or(output_dir),
+    );
+    if let Some(&concurrent) = matches.get_one::<u64>("concurrent") {
+        request.concurrent_downloads = concurrent as usize;
+    }
+    if let Some(formats) = matches.get_many::<String>("include") {
+        request.include_formats = formats.cloned().collect();
+    }
+    request
+}
+
+/// `--socket` argument shared by `daemon` and `remote`
+fn daemon_socket_arg() -> Arg {
+    Arg::new("socket")
+        .long("socket")
+        .help("Unix socket of the daemon API instead of a TCP address")
+        .value_name("PATH")
+}
+
+/// A `remote` subcommand acting on one job
+fn daemon_job_command(name: &'static str, about: &'static str) -> Command {
+    Command::new(name).about(about).arg(
+        Arg::new("job")
+            .help("Job number, as shown by 'ia-get remote list'")
+            .required(true)
+            .value_parser(clap::value_parser!(u64))
+            .index(1),
+    )
+}
+
+/// Daemon endpoint from the address argument `addr_arg` or `--socket`
+fn daemon_endpoint(
+    matches: &ArgMatches,
+    addr_arg: &str,
+) -> ia_get::Result<ia_get::interface::daemon::DaemonEndpoint> {
+    ia_get::interface::daemon::DaemonEndpoint::from_args(
+        matches.get_one::<String>(addr_arg).map(String::as_str),
+        matches.get_one::<String>("socket").map(String::as_str),
+    )
+}
+
+/// Filter arguments shared by `members list` and `members extract`
+fn member_filter_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("archive")
+                .help("Zip or tar file as <identifier>/<path> or an archive.org download URL")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("glob")
+                .short('g')
+                .long("glob")
+                .help("Only members whose path matches this pattern, e.g. 'docs/*.pdf' (can be used multiple times)")
+                .value_name("PATTERN")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("include")
+                .short('i')
+                .long("include")
+                .help("Include only members with these extensions (can be used multiple times)")
+                .value_name("FORMAT")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("include-formats")
+                .long("include-formats")
+                .help("Include members by format category")
+                .value_name("CATEGORIES")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("exclude-formats")
+                .long("exclude-formats")
+                .help("Exclude members by format category")
+                .value_name("CATEGORIES")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("max-size")
+                .long("max-size")
+                .help("Skip members larger than this (e.g., 100MB, 1GB)")
+                .value_name("SIZE"),
+        )
+}
+
+/// Member filter from `members list`/`members extract` arguments
+fn member_filter(matches: &ArgMatches) -> Result<ia_get::core::archive::MemberFilter> {
+    let strings = |id: &str| {
+        matches
+            .get_many::<String>(id)
+            .map(|values| values.cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    let mut include_formats = strings("include");
+    include_formats.extend(formats_in_categories(
+        matches
+            .get_many::<String>("include-formats")
+            .into_iter()
+            .flatten(),
+    ));
+    let max_size = matches
+        .get_one::<String>("max-size")
+        .map(|size| ia_get::utilities::filters::parse_size_string(size))
+        .transpose()
+        .context("Invalid --max-size")?;
+
+    Ok(ia_get::core::archive::MemberFilter {
+        patterns: strings("glob"),
+        include_formats,
+        exclude_formats: formats_in_categories(
+            matches
+                .get_many::<String>("exclude-formats")
+                .into_iter()
+                .flatten(),
+        ),
+        max_size,
+    })
+}
+
 /// Build the CLI interface
 fn build_cli() -> Command {
     Command::new("ia-get")
@@ -964,6 +1628,76 @@ fn build_cli() -> Command {
                 .value_name("NUM")
                 .default_value("4")
         )
+        .arg(
+            Arg::new("progress")
+                .long("progress")
+                .help("How to report progress: bars for people, json for newline-delimited JSON events on stdout")
+                .value_name("MODE")
+                .value_parser(["bars", "json"])
+                .default_value("bars")
+        )
+        .arg(
+            Arg::new("progress-fd")
+                .long("progress-fd")
+                .help("Write JSON progress events to this open file descriptor instead of stdout (e.g., 3 with 3>events.ndjson); implies --progress=json")
+                .value_name("FD")
+                .value_parser(clap::value_parser!(i32).range(3..))
+        )
+        .arg(
+            Arg::new("metrics-addr")
+                .long("metrics-addr")
+                .help("Serve download metrics in OpenMetrics format at http://ADDR/metrics (e.g., 127.0.0.1:9184); unauthenticated, so keep it on loopback unless the network is trusted")
+                .value_name("ADDR")
+                .global(true)
+        )
+        .arg(
+            Arg::new("limit-rate")
+                .long("limit-rate")
+                .help("Cap total bandwidth, optionally by time of day (e.g., 5MB/s or \"2MB/s 09:00-18:00, unlimited\")")
+                .value_name("RATE")
+                .value_parser(|s: &str| BandwidthSchedule::parse(s).map_err(|e| e.to_string()))
+                .global(true)
+        )
+        .arg(
+            Arg::new("segments")
+                .long("segments")
+                .help("Split large files into NUM byte ranges fetched in parallel (1 disables)")
+                .value_name("NUM")
+                .default_value("1")
+        )
+        .arg(
+            Arg::new("segment-threshold")
+                .long("segment-threshold")
+                .help("Minimum file size for segmented downloads (e.g., 100MB, 1GB)")
+                .value_name("SIZE")
+        )
+        .arg(
+            Arg::new("output-archive")
+                .long("output-archive")
+                .help("Write files straight into a .tar, .tar.gz, .tar.zst or .zip (resumes by skipping members already present)")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("store")
+                .long("store")
+                .help("Link files already in this content-addressed store instead of downloading them, and add verified downloads to it")
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::new("checksum")
+                .long("checksum")
+                .help("Checksums to verify: md5, sha1, crc32, strongest available, or all published; a file lacking the one chosen fails verification")
+                .value_name("POLICY")
+                .value_parser(["md5", "sha1", "crc32", "strongest", "all"])
+                .default_value("md5")
+        )
+        .arg(
+            Arg::new("order")
+                .long("order")
+                .help("Download order: metadata, smallest, largest, format[:CATEGORY,...] or priority:PATTERN,... (e.g. 'priority:*.xml,*.pdf')")
+                .value_name("STRATEGY")
+                .value_parser(|s: &str| s.parse::<DownloadOrder>().map_err(|e| e.to_string()))
+        )
         .arg(
             Arg::new("include")
                 .short('i')
@@ -1162,6 +1896,349 @@ fn build_cli() -> Command {
                         .action(ArgAction::SetTrue)
                 )
         )
+        .subcommand(
+            Command::new("cat")
+                .about("Stream a single file to stdout")
+                .long_about("Write one file from an archive to stdout without touching disk, e.g. `ia-get cat <identifier>/<path> | tar x`. Exits non-zero if the MD5 does not match.")
+                .arg(
+                    Arg::new("file")
+                        .help("File to stream as <identifier>/<path> or an archive.org download URL")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("no-verify")
+                        .long("no-verify")
+                        .help("Skip MD5 verification of the streamed bytes")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("members")
+                .about("List or extract single files inside a remote zip or tar")
+                .long_about("Work with the contents of a zip or tar file in an item without downloading all of it. Extracted members are written where extracting the whole archive in OUTPUT would put them.")
+                .subcommand_required(true)
+                .subcommand(member_filter_args(
+                    Command::new("list").about("List the files inside an archive"),
+                ))
+                .subcommand(member_filter_args(
+                    Command::new("extract")
+                        .about("Download matching files from inside an archive")
+                        .arg(
+                            Arg::new("output")
+                                .short('o')
+                                .long("output")
+                                .help("Directory the archive would be extracted in (defaults to the current directory)")
+                                .value_name("DIR"),
+                        ),
+                ))
+        )
+        .subcommand(
+            Command::new("mirror")
+                .about("Download every item in a collection")
+                .long_about("Page through all items of a collection with the Scrape API and download each into OUTPUT/<identifier>. Progress is saved to OUTPUT/.ia-get-mirror.json so an interrupted mirror resumes where it stopped; items already downloaded successfully according to the download history are skipped.")
+                .arg(
+                    Arg::new("collection")
+                        .help("Collection identifier")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Directory to mirror into (defaults to the current directory)")
+                        .value_name("DIR")
+                )
+                .arg(
+                    Arg::new("query")
+                        .short('q')
+                        .long("query")
+                        .help("Extra search terms to narrow the collection, e.g. 'mediatype:texts'")
+                        .value_name("QUERY")
+                )
+                .arg(
+                    Arg::new("page-size")
+                        .long("page-size")
+                        .help("Identifiers fetched per Scrape API request (100-10000)")
+                        .value_name("N")
+                        .default_value("1000")
+                )
+                .arg(
+                    Arg::new("concurrent")
+                        .short('j')
+                        .long("concurrent")
+                        .help("Concurrent file downloads within each item (1-16)")
+                        .value_name("N")
+                )
+                .arg(
+                    Arg::new("restart")
+                        .long("restart")
+                        .help("Ignore the saved checkpoint and page through the collection from the start")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("sync")
+                .about("Keep a local directory in step with an item")
+                .long_about("Compare fresh metadata with the files in DIR by size, mtime and MD5, download new or changed files and report files removed upstream.")
+                .arg(
+                    Arg::new("identifier")
+                        .help("Archive.org identifier")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("dir")
+                        .help("Local mirror directory")
+                        .required(true)
+                        .index(2)
+                )
+                .arg(
+                    Arg::new("delete")
+                        .long("delete")
+                        .help("Delete local files that were removed upstream")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("quarantine")
+                )
+                .arg(
+                    Arg::new("quarantine")
+                        .long("quarantine")
+                        .help("Move local files that were removed upstream to DIR/.ia-get-quarantine")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("verify")
+                        .long("verify")
+                        .help("Check MD5s even for files whose size and mtime match")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Only print the changes")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("concurrent")
+                        .short('j')
+                        .long("concurrent")
+                        .help("Number of concurrent downloads (1-16)")
+                        .value_name("N")
+                )
+        )
+        .subcommand(
+            Command::new("store")
+                .about("Manage the content-addressed file store")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("gc")
+                        .about("Remove stored files no download refers to any more")
+                        .arg(
+                            Arg::new("store")
+                                .long("store")
+                                .help("Store directory (defaults to the store_dir setting)")
+                                .value_name("DIR")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("verify-pieces")
+                .about("Check downloaded files against the item's torrent piece hashes")
+                .long_about("Check the files of an item in DIR against the SHA1 piece hashes in <identifier>_archive.torrent and report the exact byte ranges that are corrupt. The torrent is cached in DIR, so later checks can run with --offline. With --repair only the corrupt pieces are downloaded again, using HTTP range requests.")
+                .arg(
+                    Arg::new("identifier")
+                        .help("Archive.org identifier")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::new("dir")
+                        .help("Directory holding the downloaded files")
+                        .required(true)
+                        .index(2)
+                )
+                .arg(
+                    Arg::new("repair")
+                        .long("repair")
+                        .help("Re-fetch corrupt pieces with HTTP range requests")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .help("Use only the cached torrent; do not contact archive.org")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("repair")
+                )
+        )
+        .subcommand(
+            Command::new("queue")
+                .about("Manage the persistent download queue")
+                .long_about("Queue items with their own download settings in ia-get-queue.json, next to the download history, and download them several at a time with 'queue run'. Higher priorities run first; equal priorities run in the order they were added. The GUI shows and edits the same queue.")
+                .subcommand_required(true)
+                .subcommand(queued_request_args(
+                    Command::new("add")
+                        .about("Add items to the queue")
+                        .arg(
+                            Arg::new("identifiers")
+                                .help("Archive.org identifiers or URLs")
+                                .required(true)
+                                .num_args(1..)
+                                .index(1)
+                        )
+                        .arg(
+                            Arg::new("priority")
+                                .short('p')
+                                .long("priority")
+                                .help("Higher priorities run first (default 0)")
+                                .value_name("NUM")
+                                .allow_negative_numbers(true)
+                                .value_parser(clap::value_parser!(i32))
+                                .default_value("0")
+                        ),
+                ))
+                .subcommand(Command::new("list").about("Show the queue in run order"))
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove entries from the queue")
+                        .arg(
+                            Arg::new("ids")
+                                .help("Entry numbers, as shown by 'ia-get queue list'")
+                                .num_args(1..)
+                                .value_parser(clap::value_parser!(u64))
+                                .required_unless_present("finished")
+                                .index(1)
+                        )
+                        .arg(
+                            Arg::new("finished")
+                                .long("finished")
+                                .help("Remove every completed or cancelled entry")
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with("ids")
+                        )
+                )
+                .subcommand(
+                    Command::new("reorder")
+                        .about("Move an entry in the run order or change its priority")
+                        .arg(
+                            Arg::new("id")
+                                .help("Entry number")
+                                .required(true)
+                                .value_parser(clap::value_parser!(u64))
+                                .index(1)
+                        )
+                        .arg(
+                            Arg::new("position")
+                                .help("New position in the run order, 1 being next")
+                                .value_parser(clap::value_parser!(u64).range(1..))
+                                .required_unless_present("priority")
+                                .index(2)
+                        )
+                        .arg(
+                            Arg::new("priority")
+                                .short('p')
+                                .long("priority")
+                                .help("Set the entry's priority instead")
+                                .value_name("NUM")
+                                .allow_negative_numbers(true)
+                                .value_parser(clap::value_parser!(i32))
+                                .conflicts_with("position")
+                        )
+                )
+                .subcommand(
+                    Command::new("run")
+                        .about("Download queued items until the queue is empty")
+                        .arg(
+                            Arg::new("parallel")
+                                .short('j')
+                                .long("parallel")
+                                .help("Items downloaded at the same time (1-8)")
+                                .value_name("NUM")
+                                .value_parser(clap::value_parser!(u64).range(1..=8))
+                                .default_value("2")
+                        )
+                        .arg(
+                            Arg::new("retry-failed")
+                                .long("retry-failed")
+                                .help("Queue failed entries again before starting")
+                                .action(ArgAction::SetTrue)
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("daemon")
+                .about("Run a shared download queue controlled over a local JSON API")
+                .long_about("Work through the download queue shared with 'ia-get queue' and the GUI, taking new items from 'ia-get remote' or scripts. The API listens on a loopback TCP address or a Unix socket. Clients must present the random token written to ia-get-daemon.token in the configuration directory (readable only by you) on every start; 'ia-get remote' reads it from there. A restarted daemon picks up unfinished items and resumes them from their session files.")
+                .arg(
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Loopback address to listen on (default 127.0.0.1:8765)")
+                        .value_name("ADDR")
+                        .conflicts_with("socket")
+                )
+                .arg(
+                    Arg::new("parallel")
+                        .short('j')
+                        .long("parallel")
+                        .help("Items downloaded at the same time (1-8)")
+                        .value_name("NUM")
+                        .value_parser(clap::value_parser!(u64).range(1..=8))
+                        .default_value("1")
+                )
+                .arg(daemon_socket_arg())
+        )
+        .subcommand(
+            Command::new("remote")
+                .about("Queue and control downloads on a running ia-get daemon")
+                .long_about("Talk to an 'ia-get daemon'. Without a subcommand the daemon's jobs are listed.")
+                .arg(
+                    Arg::new("addr")
+                        .long("addr")
+                        .help("Address of the daemon (default 127.0.0.1:8765)")
+                        .value_name("ADDR")
+                        .conflicts_with("socket")
+                        .global(true)
+                )
+                .arg(daemon_socket_arg().global(true))
+                .subcommand(queued_request_args(
+                    Command::new("add")
+                        .about("Queue an item for download")
+                        .arg(
+                            Arg::new("identifier")
+                                .help("Archive.org identifier or URL")
+                                .required(true)
+                                .index(1)
+                        ),
+                ))
+                .subcommand(Command::new("list").about("List the daemon's jobs"))
+                .subcommand(daemon_job_command("status", "Show one job"))
+                .subcommand(daemon_job_command("pause", "Hold a queued job or stop a running one, keeping partial files"))
+                .subcommand(daemon_job_command("resume", "Queue a paused or failed job again"))
+                .subcommand(daemon_job_command("cancel", "Stop a job for good"))
+                .subcommand(Command::new("events").about("Print progress events as NDJSON until interrupted"))
+        )
+        .subcommand(
+            Command::new("auth")
+                .about("Sign in to an archive.org account for restricted items")
+                .long_about("Store IA S3 keys or the cookies of a logged-in browser session. Secrets are read from standard input and kept in credentials.toml in the configuration directory, readable only by you. Without a subcommand the stored credentials are summarised.")
+                .subcommand(
+                    Command::new("keys")
+                        .about("Store IA S3 access and secret keys (sent as 'Authorization: LOW')")
+                )
+                .subcommand(
+                    Command::new("cookies")
+                        .about("Store the cookies of a logged-in archive.org session")
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("Show which credentials are stored")
+                )
+                .subcommand(
+                    Command::new("logout")
+                        .about("Delete the stored credentials")
+                )
+        )
         .subcommand(
             Command::new("config")
                 .about("Configuration and preference management")
@@ -1266,8 +2343,7 @@ async fn analyze_archive_metadata(identifier: &str) -> Result<()> {
     println!();
 
     // Create HTTP client
-    let client = reqwest::Client::builder()
-        .user_agent(get_user_agent())
+    let client = HttpClientFactory::builder()?
         .timeout(std::time::Duration::from_secs(60))
         .build()
         .context("Failed to create HTTP client")?;
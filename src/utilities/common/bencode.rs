@@ -0,0 +1,199 @@
+//! Minimal bencode decoder and encoder
+//!
+//! Just enough of the BitTorrent encoding to read `.torrent` files and
+//! recompute their info hash. Dictionaries are kept in a `BTreeMap`, so
+//! re-encoding a canonical torrent reproduces its original bytes.
+
+use crate::{IaGetError, Result};
+use std::collections::BTreeMap;
+
+/// Deepest nesting accepted, to keep hostile input from exhausting the stack
+const MAX_DEPTH: usize = 64;
+
+/// A decoded bencode value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bencode {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Bencode>),
+    Dict(BTreeMap<Vec<u8>, Bencode>),
+}
+
+impl Bencode {
+    /// Decode a complete bencoded document
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut pos = 0;
+        let value = decode_value(data, &mut pos, 0)?;
+        if pos != data.len() {
+            return Err(parse_error(pos, "trailing data after value"));
+        }
+        Ok(value)
+    }
+
+    /// Encode the value back into bencode
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Bencode::Int(i) => out.extend_from_slice(format!("i{}e", i).as_bytes()),
+            Bencode::Bytes(bytes) => {
+                out.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+                out.extend_from_slice(bytes);
+            }
+            Bencode::List(items) => {
+                out.push(b'l');
+                for item in items {
+                    item.encode_into(out);
+                }
+                out.push(b'e');
+            }
+            Bencode::Dict(entries) => {
+                out.push(b'd');
+                for (key, value) in entries {
+                    out.extend_from_slice(format!("{}:", key.len()).as_bytes());
+                    out.extend_from_slice(key);
+                    value.encode_into(out);
+                }
+                out.push(b'e');
+            }
+        }
+    }
+
+    /// Look up `key` in a dictionary
+    pub fn get(&self, key: &str) -> Option<&Bencode> {
+        match self {
+            Bencode::Dict(entries) => entries.get(key.as_bytes()),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Bencode::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Bencode::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Byte string as UTF-8 text (lossy)
+    pub fn as_str(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    pub fn as_list(&self) -> Option<&[Bencode]> {
+        match self {
+            Bencode::List(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+fn parse_error(pos: usize, message: &str) -> IaGetError {
+    IaGetError::Parse(format!("Invalid bencode at byte {}: {}", pos, message))
+}
+
+fn decode_value(data: &[u8], pos: &mut usize, depth: usize) -> Result<Bencode> {
+    if depth > MAX_DEPTH {
+        return Err(parse_error(*pos, "nesting too deep"));
+    }
+
+    match data.get(*pos) {
+        Some(b'i') => {
+            *pos += 1;
+            let end = find(data, *pos, b'e')?;
+            let text = std::str::from_utf8(&data[*pos..end])
+                .map_err(|_| parse_error(*pos, "integer is not ASCII"))?;
+            let value = text
+                .parse::<i64>()
+                .map_err(|_| parse_error(*pos, "malformed integer"))?;
+            *pos = end + 1;
+            Ok(Bencode::Int(value))
+        }
+        Some(b'l') => {
+            *pos += 1;
+            let mut items = Vec::new();
+            while data.get(*pos) != Some(&b'e') {
+                items.push(decode_value(data, pos, depth + 1)?);
+            }
+            *pos += 1;
+            Ok(Bencode::List(items))
+        }
+        Some(b'd') => {
+            *pos += 1;
+            let mut entries = BTreeMap::new();
+            while data.get(*pos) != Some(&b'e') {
+                let key = decode_bytes(data, pos)?;
+                let value = decode_value(data, pos, depth + 1)?;
+                entries.insert(key, value);
+            }
+            *pos += 1;
+            Ok(Bencode::Dict(entries))
+        }
+        Some(b'0'..=b'9') => decode_bytes(data, pos).map(Bencode::Bytes),
+        Some(_) => Err(parse_error(*pos, "unexpected byte")),
+        None => Err(parse_error(*pos, "unexpected end of data")),
+    }
+}
+
+fn decode_bytes(data: &[u8], pos: &mut usize) -> Result<Vec<u8>> {
+    let colon = find(data, *pos, b':')?;
+    let len = std::str::from_utf8(&data[*pos..colon])
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or_else(|| parse_error(*pos, "malformed string length"))?;
+    let start = colon + 1;
+    let end = start
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| parse_error(*pos, "string runs past end of data"))?;
+    *pos = end;
+    Ok(data[start..end].to_vec())
+}
+
+fn find(data: &[u8], from: usize, byte: u8) -> Result<usize> {
+    data.get(from..)
+        .and_then(|rest| rest.iter().position(|b| *b == byte))
+        .map(|offset| from + offset)
+        .ok_or_else(|| parse_error(from, "unterminated value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let data = b"d4:infod6:lengthi42e4:name4:teste4:listl1:ai-3eee";
+        let value = Bencode::decode(data).unwrap();
+        assert_eq!(
+            value.get("info").unwrap().get("length").unwrap().as_int(),
+            Some(42)
+        );
+        assert_eq!(
+            value.get("info").unwrap().get("name").unwrap().as_str(),
+            Some("test".to_string())
+        );
+        assert_eq!(value.get("list").unwrap().as_list().unwrap().len(), 2);
+        assert_eq!(value.encode(), data.to_vec());
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        assert!(Bencode::decode(b"i42").is_err());
+        assert!(Bencode::decode(b"10:short").is_err());
+        assert!(Bencode::decode(b"i1ei2e").is_err());
+        assert!(Bencode::decode(b"x").is_err());
+        assert!(Bencode::decode(&[b'l'; 200]).is_err());
+    }
+}
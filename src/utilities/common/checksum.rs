@@ -0,0 +1,194 @@
+//! Streaming checksum calculation
+//!
+//! Computes MD5, SHA1 and CRC32 in a single pass as bytes arrive from the
+//! network, so a finished download can be verified without re-reading it from
+//! disk. When a `.tmp` file is resumed, the existing prefix is hashed once to
+//! restore the hasher state before new bytes are appended.
+
+use crate::{IaGetError, Result};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::io::Read;
+use std::path::Path;
+
+/// Buffer size used when hashing data already on disk
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Checksums of a complete file, as lowercase hex strings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDigests {
+    pub md5: String,
+    pub sha1: String,
+    pub crc32: String,
+    /// Number of bytes that were hashed
+    pub bytes: u64,
+}
+
+impl FileDigests {
+    /// Compare against an expected MD5 (case-insensitive)
+    pub fn matches_md5(&self, expected: &str) -> bool {
+        self.md5.eq_ignore_ascii_case(expected.trim())
+    }
+
+    /// Compare against an expected SHA1 (case-insensitive)
+    pub fn matches_sha1(&self, expected: &str) -> bool {
+        self.sha1.eq_ignore_ascii_case(expected.trim())
+    }
+
+    /// Compare against an expected CRC32, ignoring case and leading zeros
+    pub fn matches_crc32(&self, expected: &str) -> bool {
+        let normalize = |s: &str| s.trim().trim_start_matches('0').to_lowercase();
+        normalize(&self.crc32) == normalize(expected)
+    }
+}
+
+/// Incremental MD5/SHA1/CRC32 hasher fed with each downloaded chunk
+#[derive(Clone)]
+pub struct StreamingHasher {
+    md5: md5::Context,
+    sha1: Sha1,
+    crc32: crc32fast::Hasher,
+    bytes: u64,
+}
+
+impl StreamingHasher {
+    /// Create an empty hasher
+    pub fn new() -> Self {
+        Self {
+            md5: md5::Context::new(),
+            sha1: Sha1::new(),
+            crc32: crc32fast::Hasher::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Restore hasher state from the first `len` bytes of a partially downloaded file
+    pub fn resume_from_file<P: AsRef<Path>>(file_path: P, len: u64) -> Result<Self> {
+        let file = std::fs::File::open(file_path).map_err(|e| {
+            IaGetError::FileSystem(format!("Failed to open file for checksum resume: {}", e))
+        })?;
+
+        let mut hasher = Self::new();
+        hasher.consume_reader(file.take(len))?;
+
+        if hasher.bytes != len {
+            return Err(IaGetError::FileSystem(format!(
+                "Partial file is shorter than expected: {} of {} bytes",
+                hasher.bytes, len
+            )));
+        }
+
+        Ok(hasher)
+    }
+
+    /// Hash an entire file on disk
+    pub fn hash_file<P: AsRef<Path>>(file_path: P) -> Result<FileDigests> {
+        let file = std::fs::File::open(file_path).map_err(|e| {
+            IaGetError::FileSystem(format!("Failed to open file for checksum: {}", e))
+        })?;
+
+        let mut hasher = Self::new();
+        hasher.consume_reader(file)?;
+        Ok(hasher.finalize())
+    }
+
+    fn consume_reader<R: Read>(&mut self, mut reader: R) -> Result<()> {
+        let mut buffer = vec![0; HASH_BUFFER_SIZE];
+        loop {
+            let bytes_read = reader.read(&mut buffer).map_err(|e| {
+                IaGetError::FileSystem(format!("Failed to read file for checksum: {}", e))
+            })?;
+            if bytes_read == 0 {
+                return Ok(());
+            }
+            self.update(&buffer[..bytes_read]);
+        }
+    }
+
+    /// Feed the next chunk of file data
+    pub fn update(&mut self, data: &[u8]) {
+        self.md5.consume(data);
+        self.sha1.update(data);
+        self.crc32.update(data);
+        self.bytes += data.len() as u64;
+    }
+
+    /// Number of bytes hashed so far
+    pub fn bytes_hashed(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Finish hashing and return the digests
+    pub fn finalize(self) -> FileDigests {
+        FileDigests {
+            md5: format!("{:x}", self.md5.finalize()),
+            sha1: format!("{:x}", self.sha1.finalize()),
+            crc32: format!("{:08x}", self.crc32.finalize()),
+            bytes: self.bytes,
+        }
+    }
+}
+
+impl Default for StreamingHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_known_digests() {
+        let mut hasher = StreamingHasher::new();
+        hasher.update(b"ab");
+        hasher.update(b"c");
+        let digests = hasher.finalize();
+
+        assert_eq!(digests.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(digests.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(digests.crc32, "352441c2");
+        assert_eq!(digests.bytes, 3);
+        assert!(digests.matches_md5("900150983CD24FB0D6963F7D28E17F72"));
+        assert!(digests.matches_crc32("352441C2"));
+    }
+
+    #[test]
+    fn test_resume_matches_single_pass() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data[..120_000]).unwrap();
+        file.flush().unwrap();
+
+        let mut resumed = StreamingHasher::resume_from_file(file.path(), 120_000).unwrap();
+        assert_eq!(resumed.bytes_hashed(), 120_000);
+        resumed.update(&data[120_000..]);
+
+        let mut single = StreamingHasher::new();
+        single.update(&data);
+
+        assert_eq!(resumed.finalize(), single.finalize());
+    }
+
+    #[test]
+    fn test_resume_from_short_file_fails() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"short").unwrap();
+        file.flush().unwrap();
+
+        assert!(StreamingHasher::resume_from_file(file.path(), 100).is_err());
+    }
+
+    #[test]
+    fn test_hash_file_matches_calculate_md5() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"Internet Archive").unwrap();
+        file.flush().unwrap();
+
+        let digests = StreamingHasher::hash_file(file.path()).unwrap();
+        let md5 = crate::utilities::common::calculate_md5(file.path()).unwrap();
+        assert_eq!(digests.md5, md5);
+    }
+}
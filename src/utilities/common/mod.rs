@@ -2,13 +2,19 @@
 //!
 //! Contains common utility functions, constants, and helper functions used throughout the application.
 
+pub use bencode::*;
+pub use checksum::*;
 pub use constants::*;
+pub use openmetrics::*;
 pub use performance::*;
 pub use progress::*;
 pub use url_processing::*;
 pub use utils::*;
 
+pub mod bencode;
+pub mod checksum;
 pub mod constants;
+pub mod openmetrics;
 pub mod performance;
 pub mod progress;
 pub mod url_processing;
@@ -0,0 +1,90 @@
+//! OpenMetrics text encoding
+//!
+//! Just enough of the OpenMetrics 1.0 text format for ia-get's counters and
+//! gauges: metric families with `# TYPE` and `# HELP` lines, labelled
+//! samples and the closing `# EOF`.
+
+use std::fmt::Write;
+
+/// Content type of an OpenMetrics text exposition
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Kind of a metric family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    /// Only ever goes up; samples get a `_total` suffix
+    Counter,
+    /// Goes up and down
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+/// Builds an OpenMetrics exposition one family at a time
+#[derive(Debug, Default)]
+pub struct OpenMetricsEncoder {
+    out: String,
+    family: String,
+    kind: Option<MetricType>,
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl OpenMetricsEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a metric family; samples written next belong to it
+    pub fn family(&mut self, name: &str, kind: MetricType, help: &str) -> &mut Self {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind.as_str());
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        self.family = name.to_string();
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Write a sample of the current family
+    pub fn sample(&mut self, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(&self.family);
+        if self.kind == Some(MetricType::Counter) {
+            self.out.push_str("_total");
+        }
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        if value.is_nan() {
+            self.out.push_str(" NaN\n");
+        } else if value.is_infinite() {
+            self.out
+                .push_str(if value > 0.0 { " +Inf\n" } else { " -Inf\n" });
+        } else {
+            let _ = writeln!(self.out, " {}", value);
+        }
+        self
+    }
+
+    /// Close the exposition with `# EOF`
+    pub fn finish(mut self) -> String {
+        self.out.push_str("# EOF\n");
+        self.out
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides performance monitoring, optimization strategies,
 //! and adaptive algorithms to improve download speeds and resource usage.
 
+use super::{MetricType, OpenMetricsEncoder};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -55,6 +56,63 @@ pub struct ConnectionStats {
     pub connection_timeouts: u64,
 }
 
+impl PerformanceMetrics {
+    /// Add the transfer, retry and connection totals to an OpenMetrics exposition
+    pub fn encode_openmetrics(&self, encoder: &mut OpenMetricsEncoder) {
+        encoder
+            .family(
+                "ia_get_downloaded_bytes",
+                MetricType::Counter,
+                "Bytes received by completed transfers",
+            )
+            .sample(&[], self.total_bytes as f64)
+            .family(
+                "ia_get_transfers",
+                MetricType::Counter,
+                "Finished transfers by result",
+            )
+            .sample(&[("result", "success")], self.successful_downloads as f64)
+            .sample(&[("result", "failure")], self.failed_downloads as f64)
+            .family(
+                "ia_get_retries",
+                MetricType::Counter,
+                "Transfers retried against the next server",
+            )
+            .sample(&[], self.retry_count as f64)
+            .family(
+                "ia_get_connections",
+                MetricType::Counter,
+                "Connections opened to download servers",
+            )
+            .sample(
+                &[("reused", "false")],
+                self.connection_stats.connections_established as f64,
+            )
+            .sample(
+                &[("reused", "true")],
+                self.connection_stats.connections_reused as f64,
+            )
+            .family(
+                "ia_get_connection_timeouts",
+                MetricType::Counter,
+                "Transfers that failed with a timeout",
+            )
+            .sample(&[], self.connection_stats.connection_timeouts as f64)
+            .family(
+                "ia_get_connection_time_seconds",
+                MetricType::Gauge,
+                "Average time until response headers arrived",
+            )
+            .sample(&[], self.connection_stats.avg_connection_time.as_secs_f64())
+            .family(
+                "ia_get_peak_speed_bytes_per_second",
+                MetricType::Gauge,
+                "Fastest single transfer so far",
+            )
+            .sample(&[], self.peak_speed);
+    }
+}
+
 /// Performance monitor for tracking download metrics
 pub struct PerformanceMonitor {
     metrics: Arc<Mutex<PerformanceMetrics>>,
@@ -98,6 +98,53 @@ pub fn extract_identifier_from_url(url: &str) -> Result<String> {
     }
 }
 
+/// Splits a file reference into its identifier and file path
+///
+/// Accepts `<identifier>/<path>` as well as archive.org `/download/` and
+/// `/details/` URLs that point at a file inside an item.
+///
+/// # Examples
+///
+/// ```rust
+/// use ia_get::url_processing::split_file_reference;
+///
+/// let (identifier, path) = split_file_reference("mario/disk 1/readme.txt").unwrap();
+/// assert_eq!(identifier, "mario");
+/// assert_eq!(path, "disk 1/readme.txt");
+///
+/// let (identifier, path) =
+///     split_file_reference("https://archive.org/download/mario/disk%201/readme.txt").unwrap();
+/// assert_eq!((identifier.as_str(), path.as_str()), ("mario", "disk 1/readme.txt"));
+/// ```
+pub fn split_file_reference(input: &str) -> Result<(String, String)> {
+    let reference = if is_archive_url(input) {
+        let parsed_url = Url::parse(input)
+            .map_err(|_| IaGetError::UrlFormat("Invalid URL format".to_string()))?;
+        let path = parsed_url.path();
+        let item_path = path
+            .strip_prefix("/download/")
+            .or_else(|| path.strip_prefix("/details/"))
+            .ok_or_else(|| {
+                IaGetError::UrlFormat("URL must contain a /download/ or /details/ path".to_string())
+            })?;
+        urlencoding::decode(item_path)
+            .map_err(|_| IaGetError::UrlFormat("Invalid URL encoding".to_string()))?
+            .into_owned()
+    } else {
+        input.to_string()
+    };
+
+    match reference.trim_matches('/').split_once('/') {
+        Some((identifier, path)) if !identifier.is_empty() && !path.is_empty() => {
+            Ok((identifier.to_string(), path.to_string()))
+        }
+        _ => Err(IaGetError::UrlFormat(format!(
+            "Expected <identifier>/<path>, got '{}'",
+            input
+        ))),
+    }
+}
+
 /// Constructs metadata URL from identifier
 pub fn construct_metadata_url(identifier: &str) -> String {
     format!("https://archive.org/metadata/{}", identifier)
@@ -181,36 +181,85 @@ pub fn calculate_md5<P: AsRef<std::path::Path>>(file_path: P) -> crate::Result<S
     Ok(format!("{:x}", digest))
 }
 
-/// Get available disk space for a given path
+/// Get available disk space on the filesystem holding `path`
+///
+/// `path` need not exist yet; its nearest existing ancestor is queried, so a
+/// download directory about to be created on a mounted volume reports that
+/// volume. The figure is the space available to unprivileged users.
 ///
 /// # Arguments
 /// * `path` - Path to check available space for
 ///
 /// # Returns
 /// Available space in bytes, or None if the information cannot be retrieved
-pub fn get_available_disk_space<P: AsRef<std::path::Path>>(_path: P) -> Option<u64> {
-    #[cfg(not(target_os = "android"))]
+pub fn get_available_disk_space<P: AsRef<std::path::Path>>(path: P) -> Option<u64> {
+    #[cfg(unix)]
     {
-        use sys_info::disk_info;
-
-        // Try to get disk info for the path
-        match disk_info() {
-            Ok(info) => {
-                // Available space in bytes = free blocks * block size
-                Some(info.free * 1024) // sys_info returns free space in KB
-            }
-            Err(_) => None,
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = nearest_existing_ancestor(path.as_ref())?;
+        let c_path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+        // SAFETY: statvfs is plain old data, so all-zero is a valid value
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: c_path is NUL-terminated and stat is a valid out-pointer
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
         }
+        // Field widths differ between platforms
+        #[allow(clippy::unnecessary_cast)]
+        Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+    }
+
+    #[cfg(not(unix))]
+    {
+        // sys-info only reports the system disk here
+        let _ = path;
+        sys_info::disk_info().ok().map(|info| info.free * 1024)
+    }
+}
+
+/// Whether two paths (existing or not) live on the same filesystem
+///
+/// Returns None when this cannot be determined on the current platform.
+pub fn same_filesystem<P: AsRef<std::path::Path>, Q: AsRef<std::path::Path>>(
+    a: P,
+    b: Q,
+) -> Option<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let dev = |path: &std::path::Path| {
+            nearest_existing_ancestor(path)
+                .and_then(|dir| std::fs::metadata(dir).ok())
+                .map(|m| m.dev())
+        };
+        Some(dev(a.as_ref())? == dev(b.as_ref())?)
     }
 
-    #[cfg(target_os = "android")]
+    #[cfg(not(unix))]
     {
-        // On Android, sys-info is not available due to NDK limitations
-        // Return None to skip disk space checks
+        let _ = (a, b);
         None
     }
 }
 
+/// `path` itself if it exists, otherwise its closest existing parent
+#[cfg(unix)]
+fn nearest_existing_ancestor(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    path.ancestors()
+        .map(|dir| {
+            if dir.as_os_str().is_empty() {
+                std::path::Path::new(".")
+            } else {
+                dir
+            }
+        })
+        .find(|dir| dir.exists())
+        .map(|dir| dir.to_path_buf())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,6 +278,17 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_space_of_missing_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("not/created/yet");
+
+        // Reported for the filesystem that will hold the directory
+        assert!(get_available_disk_space(&missing).is_some());
+        assert_eq!(same_filesystem(&missing, temp_dir.path()), Some(true));
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(0), "0B");
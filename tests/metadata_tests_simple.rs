@@ -206,6 +206,14 @@ async fn test_mario_archive_dry_run() {
         verbose: true,
         resume: true,
         source_types: vec![ia_get::cli::SourceType::Original], // Default to original files
+        segments_per_file: 1,
+        segment_min_size: None,
+        checksum_policy: Default::default(),
+        output_container: None,
+        store_dir: None,
+        download_order: Default::default(),
+        max_retries: None,
+        redownload: Vec::new(),
     };
 
     // Execute the dry-run request
@@ -270,6 +278,9 @@ async fn test_mario_archive_dry_run() {
         DownloadResult::Error(error) => {
             panic!("Expected successful dry-run, got error: {}", error);
         }
+        DownloadResult::Cancelled(_) => {
+            panic!("Expected successful dry-run, got a cancelled download");
+        }
     }
 }
 
@@ -329,5 +340,8 @@ async fn test_luigi_archive_dry_run() {
         DownloadResult::Error(error) => {
             panic!("Expected successful Luigi dry-run, got error: {}", error);
         }
+        DownloadResult::Cancelled(_) => {
+            panic!("Expected successful Luigi dry-run, got a cancelled download");
+        }
     }
 }
@@ -0,0 +1,127 @@
+//! Credentials Support Layer Tests
+//!
+//! Tests for storing archive.org credentials and sending them, run against
+//! a local mock of the metadata API.
+
+use super::{MockResponse, MockServer};
+use ia_get::IaGetError;
+use ia_get::infrastructure::config::Credentials;
+use ia_get::metadata::fetch_json_metadata;
+use indicatif::ProgressBar;
+
+const METADATA: &str = r#"{"created":1,"d1":"d1","d2":"d2","dir":"/1/items/restricted",
+    "files":[{"name":"file.txt","source":"original"}],"files_count":1,
+    "item_last_updated":1,"item_size":1,"metadata":{},"server":"d1","uniq":1,
+    "workable_servers":["d1"]}"#;
+
+/// Serve the metadata API on a local port; returns its base URL
+///
+/// `restricted` needs the test keys or session cookie, `missing` answers an
+/// empty object like archive.org does, and everything else is a 404.
+fn mock_archive() -> String {
+    MockServer::start(|request| {
+        let authorized = request.header("Authorization") == Some("LOW key:secret")
+            || request
+                .header("Cookie")
+                .is_some_and(|cookie| cookie.contains("logged-in-sig=sig"));
+        match request.path() {
+            "/metadata/restricted" if authorized => MockResponse::new("200 OK", METADATA),
+            "/metadata/restricted" => MockResponse::new("403 Forbidden", "restricted"),
+            "/metadata/missing" => MockResponse::new("200 OK", "{}"),
+            _ => MockResponse::new("404 Not Found", "not found"),
+        }
+    })
+    .base
+}
+
+fn client(credentials: &Credentials) -> reqwest::Client {
+    credentials
+        .apply(reqwest::Client::builder())
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+async fn fetch(base: &str, identifier: &str, credentials: &Credentials) -> ia_get::Result<()> {
+    let url = format!("{}/details/{}", base, identifier);
+    fetch_json_metadata(&url, &client(credentials), &ProgressBar::hidden(), None)
+        .await
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn test_restricted_item_needs_login() {
+    let base = mock_archive();
+
+    let err = fetch(&base, "restricted", &Credentials::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, IaGetError::AuthRequired(_)), "{:?}", err);
+    assert!(err.is_permanent());
+
+    let keys = Credentials {
+        access_key: Some("key".to_string()),
+        secret_key: Some("secret".to_string()),
+        ..Default::default()
+    };
+    fetch(&base, "restricted", &keys).await.unwrap();
+
+    let cookies = Credentials {
+        cookies: Some("logged-in-user=me%40example.org; logged-in-sig=sig".to_string()),
+        cookie_domains: vec!["127.0.0.1".to_string()],
+        ..Default::default()
+    };
+    fetch(&base, "restricted", &cookies).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cookies_stay_within_their_domains() {
+    let base = mock_archive();
+    let cookies = Credentials {
+        cookies: Some("logged-in-sig=sig".to_string()),
+        ..Default::default()
+    };
+    let err = fetch(&base, "restricted", &cookies).await.unwrap_err();
+    assert!(matches!(err, IaGetError::AuthRequired(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_missing_item_is_not_found() {
+    let base = mock_archive();
+    for identifier in ["missing", "gone"] {
+        let err = fetch(&base, identifier, &Credentials::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, IaGetError::NotFound(_)), "{:?}", err);
+    }
+}
+
+#[test]
+fn test_credentials_file_is_private() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("credentials.toml");
+    assert!(!Credentials::load(&path).unwrap().is_configured());
+
+    let credentials = Credentials {
+        access_key: Some("key".to_string()),
+        secret_key: Some("hunter2".to_string()),
+        ..Default::default()
+    };
+    credentials.save(&path).unwrap();
+    assert_eq!(Credentials::load(&path).unwrap(), credentials);
+    assert_eq!(
+        credentials.authorization().as_deref(),
+        Some("LOW key:hunter2")
+    );
+    assert!(!format!("{:?}", credentials).contains("hunter2"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(Credentials::load(&path).is_err());
+    }
+}
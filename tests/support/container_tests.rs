@@ -0,0 +1,237 @@
+//! Output Container Tests
+//!
+//! Tests for writing downloads into tar and zip containers and resuming them.
+
+use ia_get::core::download::{ContainerFormat, ContainerWriter};
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+
+const MTIME: u64 = 1_600_000_000;
+
+fn write_source(dir: &Path, name: &str, contents: &[u8]) -> std::path::PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Read every tar member as (name, mtime, contents)
+fn read_tar(path: &Path, format: ContainerFormat) -> Vec<(String, u64, Vec<u8>)> {
+    let file = std::fs::File::open(path).unwrap();
+    let reader: Box<dyn Read> = match format {
+        ContainerFormat::TarGz => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        ContainerFormat::TarZst => Box::new(zstd::Decoder::new(file).unwrap()),
+        _ => Box::new(file),
+    };
+    let mut archive = tar::Archive::new(reader);
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let mtime = entry.header().mtime().unwrap();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            (name, mtime, contents)
+        })
+        .collect()
+}
+
+#[test]
+fn test_container_format_from_path() {
+    assert_eq!(
+        ContainerFormat::from_path("out.tar"),
+        Some(ContainerFormat::Tar)
+    );
+    assert_eq!(
+        ContainerFormat::from_path("out.tar.gz"),
+        Some(ContainerFormat::TarGz)
+    );
+    assert_eq!(
+        ContainerFormat::from_path("out.tgz"),
+        Some(ContainerFormat::TarGz)
+    );
+    assert_eq!(
+        ContainerFormat::from_path("out.tar.zst"),
+        Some(ContainerFormat::TarZst)
+    );
+    assert_eq!(
+        ContainerFormat::from_path("OUT.ZIP"),
+        Some(ContainerFormat::Zip)
+    );
+    assert_eq!(ContainerFormat::from_path("out.gz"), None);
+    assert!(ContainerWriter::open("out.rar").is_err());
+}
+
+#[test]
+fn test_tar_containers_round_trip_and_resume() {
+    for name in ["item.tar", "item.tar.gz", "item.tar.zst"] {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(name);
+        let format = ContainerFormat::from_path(&path).unwrap();
+        let first = write_source(temp_dir.path(), "a.txt", b"first file");
+        let second = write_source(temp_dir.path(), "b.txt", b"second file");
+
+        let mut writer = ContainerWriter::open(&path).unwrap();
+        writer.add_file("a.txt", &first, Some(MTIME)).unwrap();
+        writer.finish().unwrap();
+
+        // Reopening picks up existing members and appends after them
+        let mut writer = ContainerWriter::open(&path).unwrap();
+        assert!(writer.contains("a.txt"), "{}", name);
+        assert!(!writer.contains("b.txt"), "{}", name);
+        writer
+            .add_file("sub/b.txt", &second, Some(MTIME + 1))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let entries = read_tar(&path, format);
+        assert_eq!(
+            entries,
+            vec![
+                ("a.txt".to_string(), MTIME, b"first file".to_vec()),
+                ("sub/b.txt".to_string(), MTIME + 1, b"second file".to_vec()),
+            ],
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_tar_resumes_after_interruption() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("item.tar");
+    let source = write_source(temp_dir.path(), "a.txt", b"payload");
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    writer.add_file("a.txt", &source, Some(MTIME)).unwrap();
+    drop(writer); // no terminator, as if the process was killed
+
+    // Simulate a member cut off halfway through
+    let committed = std::fs::metadata(&path).unwrap().len();
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.extend_from_slice(&bytes.clone()[..600]);
+    std::fs::write(&path, bytes).unwrap();
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), committed);
+    assert!(writer.contains("a.txt"));
+    writer.add_file("b.txt", &source, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    let names: Vec<String> = read_tar(&path, ContainerFormat::Tar)
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    assert_eq!(names, vec!["a.txt", "b.txt"]);
+}
+
+#[test]
+fn test_tar_long_member_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("item.tar.gz");
+    let source = write_source(temp_dir.path(), "long.bin", b"long name");
+    let long_name = format!("{}/{}.bin", "d".repeat(80), "f".repeat(60));
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    writer.add_file(&long_name, &source, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    assert!(ContainerWriter::open(&path).unwrap().contains(&long_name));
+    let entries = read_tar(&path, ContainerFormat::TarGz);
+    assert_eq!(entries[0].0, long_name);
+}
+
+#[test]
+fn test_zip_round_trip_and_resume() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("item.zip");
+    let source = write_source(temp_dir.path(), "a.txt", b"zip contents");
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    writer.add_file("a.txt", &source, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    assert!(writer.contains("a.txt"));
+    writer.add_file("b.txt", &source, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(archive.len(), 2);
+    let mut entry = archive.by_name("b.txt").unwrap();
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"zip contents");
+    let modified = entry.last_modified().unwrap();
+    assert_eq!(
+        (modified.year(), modified.month(), modified.day()),
+        (2020, 9, 13)
+    );
+}
+
+#[test]
+fn test_zip_stores_incompressible_members() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("item.zip");
+    let text = write_source(temp_dir.path(), "text", &b"squeeze me ".repeat(1000));
+    // xorshift output does not deflate
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let noise: Vec<u8> = (0..64 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let noise = write_source(temp_dir.path(), "noise", &noise);
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    writer.add_file("notes.txt", &text, Some(MTIME)).unwrap();
+    writer.add_file("photo.JPG", &text, Some(MTIME)).unwrap();
+    writer.add_file("noise.bin", &noise, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    for (name, method) in [
+        ("notes.txt", zip::CompressionMethod::Deflated),
+        ("photo.JPG", zip::CompressionMethod::Stored),
+        ("noise.bin", zip::CompressionMethod::Stored),
+    ] {
+        let mut entry = archive.by_name(name).unwrap();
+        assert_eq!(entry.compression(), method, "{}", name);
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents.len() as u64, entry.size());
+    }
+}
+
+#[test]
+fn test_zip_recovers_unfinished_archive() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("item.zip");
+    let source = write_source(temp_dir.path(), "a.txt", b"recover me");
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    writer.add_file("a.txt", &source, Some(MTIME)).unwrap();
+    writer.add_file("b.txt", &source, Some(MTIME)).unwrap();
+    drop(writer);
+
+    // Cut off the central directory, as if the process was killed mid-write
+    let len = std::fs::metadata(&path).unwrap().len();
+    let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(len - 30).unwrap();
+    drop(file);
+
+    let mut writer = ContainerWriter::open(&path).unwrap();
+    assert!(writer.contains("a.txt"));
+    writer.add_file("c.txt", &source, Some(MTIME)).unwrap();
+    writer.finish().unwrap();
+
+    let archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert!(archive.file_names().any(|n| n == "a.txt"));
+    assert!(archive.file_names().any(|n| n == "c.txt"));
+}
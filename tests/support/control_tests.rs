@@ -0,0 +1,150 @@
+//! Download Control Tests
+//!
+//! Tests for pausing and resuming a running download, run against a local
+//! file server that sends no `ETag` or `Last-Modified` validators.
+
+use super::{MockResponse, MockServer, archive_file, download_config, item_metadata};
+use ia_get::core::download::{ArchiveDownloader, DownloadControl};
+use ia_get::metadata_storage::{ArchiveFile, ArchiveMetadata, DownloadState};
+use indicatif::ProgressBar;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const FILE_LEN: usize = 512 * 1024;
+const FIRST_CHUNK: usize = 64 * 1024;
+
+fn contents() -> Vec<u8> {
+    (0..FILE_LEN).map(|i| (i % 251) as u8).collect()
+}
+
+/// A local file server and the handles a test needs to steer it
+struct MockFileServer {
+    server: MockServer,
+    /// Signalled once the first chunk of a full transfer was sent
+    sent: mpsc::Receiver<()>,
+    /// Dropping it lets the stalled full transfer end
+    release: mpsc::Sender<()>,
+}
+
+/// Serve `/1/items/example/data.bin` on a local port, without validators
+fn mock_file_server() -> MockFileServer {
+    let (sent_tx, sent) = mpsc::channel();
+    let (release, release_rx) = mpsc::channel::<()>();
+    let (sent_tx, release_rx) = (Mutex::new(sent_tx), Arc::new(Mutex::new(release_rx)));
+    let server = MockServer::start(move |request| {
+        let data = contents();
+        let start = request
+            .header("Range")
+            .and_then(|r| r.strip_prefix("bytes="))
+            .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+        match start {
+            Some(start) => MockResponse::new("206 Partial Content", &data[start..]).header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, FILE_LEN - 1, FILE_LEN),
+            ),
+            None => {
+                // Send a first chunk, then stall until the test lets go
+                let sent_tx = sent_tx.lock().unwrap().clone();
+                let release_rx = release_rx.clone();
+                MockResponse::new("200 OK", data).stall_after(FIRST_CHUNK, move || {
+                    let _ = sent_tx.send(());
+                    let _ = release_rx.lock().unwrap().recv();
+                })
+            }
+        }
+    });
+    MockFileServer {
+        server,
+        sent,
+        release,
+    }
+}
+
+fn metadata(server: &str) -> ArchiveMetadata {
+    let md5 = format!("{:x}", md5::compute(contents()));
+    let file = ArchiveFile {
+        md5: Some(md5),
+        ..archive_file("data.bin", FILE_LEN as u64)
+    };
+    item_metadata("example", server, vec![file])
+}
+
+#[tokio::test]
+async fn test_pause_flushes_partial_and_resume_continues_from_it() {
+    let MockFileServer {
+        server,
+        sent,
+        release,
+    } = mock_file_server();
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let control = DownloadControl::new();
+    let downloader = ArchiveDownloader::new(
+        reqwest::Client::new(),
+        1,
+        true,
+        false,
+        temp_dir.path().join("sessions"),
+        false,
+        false,
+    )
+    .with_control(control.clone());
+
+    let download = tokio::spawn({
+        let metadata = metadata(&server.base);
+        let config = download_config(&output_dir);
+        async move {
+            downloader
+                .download_with_metadata(
+                    "https://archive.org/details/example".to_string(),
+                    "example".to_string(),
+                    metadata,
+                    config,
+                    vec!["data.bin".to_string()],
+                    &ProgressBar::hidden(),
+                    None,
+                )
+                .await
+        }
+    });
+
+    // Pause once the first chunk is on its way
+    tokio::task::spawn_blocking(move || sent.recv_timeout(Duration::from_secs(10)))
+        .await
+        .unwrap()
+        .expect("first chunk was never sent");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    control.pause();
+
+    // The partial file holds everything received before the pause
+    let partial = output_dir.join("data.tmp");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0) != FIRST_CHUNK as u64 {
+        assert!(Instant::now() < deadline, "partial file was not flushed");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    drop(release);
+
+    control.resume();
+    let session = tokio::time::timeout(Duration::from_secs(30), download)
+        .await
+        .expect("download did not finish")
+        .unwrap()
+        .unwrap();
+
+    let ranges: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|request| request.header("Range").map(str::to_string))
+        .collect();
+    assert_eq!(ranges, vec![None, Some(format!("bytes={}-", FIRST_CHUNK))]);
+    assert_eq!(
+        std::fs::read(output_dir.join("data.bin")).unwrap(),
+        contents()
+    );
+    assert!(!partial.exists());
+    let status = &session.file_status["data.bin"];
+    assert_eq!(status.status, DownloadState::Completed);
+}
@@ -0,0 +1,230 @@
+//! Daemon Support Layer Tests
+//!
+//! Tests for the daemon's HTTP framing, its access checks and the job
+//! control API behind `ia-get daemon` and `ia-get remote`.
+
+use ia_get::core::download::QueueRunner;
+use ia_get::infrastructure::persistence::{DownloadQueue, JobState};
+use ia_get::interface::daemon::{
+    DaemonEndpoint, DaemonToken, HttpRequest, JobManager, authorize, handle_request, read_request,
+    write_json_response,
+};
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, BufReader};
+
+fn api(method: &str, path: &str, body: &str) -> HttpRequest {
+    HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers: vec![],
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn test_endpoint_must_be_loopback() {
+    assert_eq!(
+        DaemonEndpoint::from_args(None, None).unwrap(),
+        DaemonEndpoint::Tcp("127.0.0.1:8765".parse().unwrap())
+    );
+    assert_eq!(
+        DaemonEndpoint::from_args(Some("[::1]:9000"), None).unwrap(),
+        DaemonEndpoint::Tcp("[::1]:9000".parse().unwrap())
+    );
+    assert_eq!(
+        DaemonEndpoint::from_args(None, Some("/run/ia-get.sock")).unwrap(),
+        DaemonEndpoint::Unix(PathBuf::from("/run/ia-get.sock"))
+    );
+    assert!(DaemonEndpoint::from_args(Some("0.0.0.0:8765"), None).is_err());
+    assert!(DaemonEndpoint::from_args(Some("localhost"), None).is_err());
+}
+
+#[tokio::test]
+async fn test_http_request_and_response_framing() {
+    let raw =
+        "POST /jobs/3/pause?verbose=1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 2\r\n\r\n{}";
+    let mut reader = BufReader::new(raw.as_bytes());
+    let parsed = read_request(&mut reader).await.unwrap();
+    assert_eq!(parsed.method, "POST");
+    assert_eq!(parsed.segments(), ["jobs", "3", "pause"]);
+    assert_eq!(parsed.header("Host"), Some("localhost"));
+    assert_eq!(parsed.body, b"{}");
+
+    let mut truncated = BufReader::new("GET /jobs HTTP/1.1\r\nHost: x\r\n".as_bytes());
+    assert!(read_request(&mut truncated).await.is_err());
+
+    let (mut client, mut server) = tokio::io::duplex(4096);
+    write_json_response(
+        &mut server,
+        404,
+        &serde_json::json!({ "error": "No job 7" }),
+    )
+    .await
+    .unwrap();
+    drop(server);
+    let mut response = String::new();
+    client.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    assert!(response.contains("Content-Length: 20\r\n"));
+    assert!(response.ends_with("\r\n\r\n{\"error\":\"No job 7\"}"));
+}
+
+#[test]
+fn test_api_queues_and_controls_jobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let manager = JobManager::new(QueueRunner::new(path.clone(), 1));
+    let output = dir.path().to_string_lossy().replace('\\', "/");
+
+    // Fields left out of the request take their defaults
+    let body = format!(
+        r#"{{"identifier": "example", "output_dir": "{}", "resume": false}}"#,
+        output
+    );
+    let (status, job) = handle_request(&manager, &api("POST", "/jobs", &body));
+    assert_eq!(status, 201);
+    assert_eq!(job["id"], 1);
+    assert_eq!(job["state"], "queued");
+    assert_eq!(job["request"]["resume"], true);
+    assert_eq!(job["request"]["concurrent_downloads"], 4);
+
+    let (status, job) = handle_request(&manager, &api("POST", "/jobs/1/pause", ""));
+    assert_eq!((status, job["state"].as_str()), (200, Some("paused")));
+    let (status, job) = handle_request(&manager, &api("POST", "/jobs/1/resume", ""));
+    assert_eq!((status, job["state"].as_str()), (200, Some("queued")));
+    let (status, _) = handle_request(&manager, &api("POST", "/jobs/1/cancel", ""));
+    assert_eq!(status, 200);
+    let (status, error) = handle_request(&manager, &api("POST", "/jobs/1/resume", ""));
+    assert_eq!(status, 409);
+    assert_eq!(error["error"], "Cannot resume job 1: it is cancelled");
+
+    let (status, jobs) = handle_request(&manager, &api("GET", "/jobs", ""));
+    assert_eq!(status, 200);
+    assert_eq!(jobs.as_array().unwrap().len(), 1);
+
+    assert_eq!(handle_request(&manager, &api("GET", "/jobs/9", "")).0, 404);
+    assert_eq!(
+        handle_request(&manager, &api("DELETE", "/jobs/1", "")).0,
+        405
+    );
+    assert_eq!(
+        handle_request(&manager, &api("POST", "/jobs", "not json")).0,
+        400
+    );
+    let relative = r#"{"identifier": "example", "output_dir": "downloads"}"#;
+    assert_eq!(
+        handle_request(&manager, &api("POST", "/jobs", relative)).0,
+        400
+    );
+
+    // Every change went to the shared queue
+    let saved = DownloadQueue::load(&path).unwrap();
+    assert_eq!(saved.get(1).unwrap().state, JobState::Cancelled);
+}
+
+#[test]
+fn test_requests_need_token_json_and_localhost() {
+    let token = DaemonToken::new("secret");
+    let with_headers = |headers: &[(&str, &str)]| HttpRequest {
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..api("POST", "/jobs", "{}")
+    };
+    let valid = [
+        ("host", "localhost:8765"),
+        ("authorization", "Bearer secret"),
+        ("content-type", "application/json; charset=utf-8"),
+    ];
+    assert_eq!(authorize(&with_headers(&valid), &token), Ok(()));
+
+    let status =
+        |headers: &[(&str, &str)]| authorize(&with_headers(headers), &token).unwrap_err().0;
+    assert_eq!(status(&valid[1..]), 403);
+    assert_eq!(
+        status(&[("host", "evil.example:8765"), valid[1], valid[2]]),
+        403
+    );
+    assert_eq!(
+        status(&[valid[0], valid[1], valid[2], ("origin", "null")]),
+        403
+    );
+    assert_eq!(status(&[valid[0], valid[2]]), 401);
+    assert_eq!(
+        status(&[valid[0], ("authorization", "Bearer secreT"), valid[2]]),
+        401
+    );
+    assert_eq!(
+        status(&[valid[0], valid[1], ("content-type", "text/plain")]),
+        415
+    );
+    assert_eq!(status(&valid[..2]), 415);
+}
+
+#[test]
+fn test_tokens_are_random_and_private() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-daemon.token");
+    let token = DaemonToken::generate().unwrap();
+    assert_eq!(token.as_str().len(), 64);
+    assert_ne!(token, DaemonToken::generate().unwrap());
+
+    token.save(&path).unwrap();
+    assert_eq!(DaemonToken::load(&path).unwrap(), token);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(DaemonToken::load(&path).is_err());
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unix_socket_is_private_and_checks_the_token() {
+    use ia_get::interface::daemon::{DaemonClient, serve};
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("daemon.sock");
+    let endpoint = DaemonEndpoint::Unix(socket.clone());
+    let manager = JobManager::new(QueueRunner::new(dir.path().join("ia-get-queue.json"), 1));
+    let token = DaemonToken::generate().unwrap();
+    let server = tokio::spawn({
+        let endpoint = endpoint.clone();
+        let token = token.clone();
+        async move { serve(manager, &endpoint, token).await }
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !socket.exists() {
+        assert!(Instant::now() < deadline, "daemon did not start");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    // The private directory it was bound in is gone
+    let staging = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            name.to_string_lossy().starts_with(".daemon.sock")
+        })
+        .count();
+    assert_eq!(staging, 0);
+
+    let client = DaemonClient::new(endpoint.clone(), token);
+    assert!(client.list().await.unwrap().is_empty());
+    let stranger = DaemonClient::new(endpoint, DaemonToken::new("guess"));
+    let error = stranger.list().await.unwrap_err().to_string();
+    assert!(
+        error.contains("Missing or invalid bearer token"),
+        "{}",
+        error
+    );
+    server.abort();
+}
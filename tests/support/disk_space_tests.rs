@@ -0,0 +1,107 @@
+//! Disk Space Preflight Tests
+//!
+//! Tests for estimating the room a download needs before it starts.
+
+use super::archive_file;
+use ia_get::core::download::{
+    DECOMPRESSION_RATIO, PreflightOptions, SpaceEstimate, check_disk_space, estimate_disk_space,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn options(output_dir: &Path) -> PreflightOptions<'_> {
+    PreflightOptions {
+        output_dir,
+        output_container: None,
+        decompress_formats: None,
+        concurrent_downloads: 2,
+    }
+}
+
+#[test]
+fn test_estimate_counts_remaining_bytes() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = vec![
+        archive_file("done.bin", 100),
+        archive_file("partial.bin", 1000),
+        archive_file("new.bin", 500),
+        archive_file("small.bin", 10),
+    ];
+    std::fs::write(temp_dir.path().join("done.bin"), vec![0; 100]).unwrap();
+    std::fs::write(temp_dir.path().join("partial.tmp"), vec![0; 400]).unwrap();
+
+    let estimates = estimate_disk_space(&files, &options(temp_dir.path()));
+    assert_eq!(estimates.len(), 1);
+    let (path, estimate) = &estimates[0];
+    assert_eq!(path, temp_dir.path());
+    assert_eq!(
+        *estimate,
+        SpaceEstimate {
+            download_bytes: 600 + 500 + 10,
+            decompressed_bytes: 0,
+            // The two largest remaining downloads
+            temp_bytes: 600 + 500,
+        }
+    );
+    assert_eq!(estimate.required(), estimate.total() + 100 * 1024 * 1024);
+}
+
+#[test]
+fn test_estimate_includes_decompressed_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = vec![
+        archive_file("log.txt.gz", 1000),
+        archive_file("disc.zip", 1000),
+    ];
+    let formats = vec!["gzip".to_string()];
+    let options = PreflightOptions {
+        decompress_formats: Some(&formats),
+        ..options(temp_dir.path())
+    };
+
+    let estimates = estimate_disk_space(&files, &options);
+    assert_eq!(
+        estimates[0].1.decompressed_bytes,
+        1000 * DECOMPRESSION_RATIO
+    );
+}
+
+#[test]
+fn test_container_on_same_filesystem_is_merged() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("staging");
+    let container = temp_dir.path().join("item.tar");
+    let files = vec![
+        archive_file("a.bin", 300),
+        archive_file("b.bin", 200),
+        archive_file("c.bin", 100),
+    ];
+    let options = PreflightOptions {
+        output_container: Some(&container),
+        concurrent_downloads: 1,
+        ..options(&output_dir)
+    };
+
+    let estimates = estimate_disk_space(&files, &options);
+    assert_eq!(estimates.len(), 1);
+    assert_eq!(estimates[0].0, output_dir);
+    // Every file lands in the container; only one is staged at a time
+    assert_eq!(estimates[0].1.download_bytes, 600);
+    assert_eq!(estimates[0].1.temp_bytes, 300);
+}
+
+#[test]
+fn test_check_reports_shortage() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = vec![archive_file("huge.iso", u64::MAX / 4)];
+
+    let checks = check_disk_space(&files, &options(&temp_dir.path().join("out")));
+    assert_eq!(checks.len(), 1);
+    if let Some(available) = checks[0].available {
+        assert!(checks[0].shortage() > 0);
+        assert_eq!(
+            checks[0].shortage(),
+            checks[0].estimate.required() - available
+        );
+    }
+}
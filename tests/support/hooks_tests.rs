@@ -0,0 +1,256 @@
+//! Post-Download Hook Support Layer Tests
+//!
+//! Tests for hook configuration, the environment handed to hook commands,
+//! timeouts and concurrency limits.
+
+use super::{MockResponse, MockServer, archive_file, item_metadata, seed_metadata};
+use ia_get::core::download::{
+    DownloadRequest, DownloadResult, DownloadService, HookContext, HookEvent, HookOutcome,
+    HookRunner, HookSettings, run_hook,
+};
+use ia_get::infrastructure::config::Config;
+use ia_get::infrastructure::persistence::DownloadHistory;
+use ia_get::metadata_storage::ArchiveFile;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn settings() -> HookSettings {
+    HookSettings {
+        file_success: None,
+        file_failure: None,
+        item_success: None,
+        item_failure: None,
+        timeout: Duration::from_secs(10),
+        max_concurrent: 2,
+    }
+}
+
+fn file_context() -> HookContext {
+    HookContext {
+        identifier: "example".to_string(),
+        file: Some("disk 1.img".to_string()),
+        path: "/data/example/disk 1.img".to_string(),
+        md5: Some("d41d8cd98f00b204e9800998ecf8427e".to_string()),
+        size: Some(1474560),
+        format: Some("ISO Image".to_string()),
+        error: None,
+    }
+}
+
+#[test]
+fn test_settings_from_config() {
+    let config = Config {
+        hook_file_success: Some("ingest \"$IA_GET_PATH\"".to_string()),
+        hook_concurrency: 0,
+        ..Default::default()
+    };
+    let settings = HookSettings::from_config(&config);
+    assert!(!settings.is_empty());
+    assert_eq!(
+        settings.command(HookEvent::FileSuccess),
+        Some("ingest \"$IA_GET_PATH\"")
+    );
+    assert_eq!(settings.command(HookEvent::ItemFailure), None);
+    assert_eq!(settings.timeout, Duration::from_secs(300));
+    assert_eq!(settings.max_concurrent, 1);
+    assert!(HookSettings::from_config(&Config::default()).is_empty());
+}
+
+#[test]
+fn test_context_environment() {
+    let env = file_context().env(HookEvent::FileSuccess);
+    let get = |name: &str| {
+        env.iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(get("IA_GET_HOOK"), Some("file_success"));
+    assert_eq!(get("IA_GET_IDENTIFIER"), Some("example"));
+    assert_eq!(get("IA_GET_FILE"), Some("disk 1.img"));
+    assert_eq!(get("IA_GET_PATH"), Some("/data/example/disk 1.img"));
+    assert_eq!(get("IA_GET_MD5"), Some("d41d8cd98f00b204e9800998ecf8427e"));
+    assert_eq!(get("IA_GET_SIZE"), Some("1474560"));
+    assert_eq!(get("IA_GET_FORMAT"), Some("ISO Image"));
+    assert_eq!(get("IA_GET_STATUS"), Some("success"));
+    assert_eq!(get("IA_GET_ERROR"), None);
+
+    let item = HookContext {
+        identifier: "example".to_string(),
+        path: "/data/example".to_string(),
+        error: Some("2 file(s) failed".to_string()),
+        ..Default::default()
+    };
+    let env = item.env(HookEvent::ItemFailure);
+    assert!(env.contains(&("IA_GET_STATUS", "failed".to_string())));
+    assert!(env.contains(&("IA_GET_ERROR", "2 file(s) failed".to_string())));
+    assert!(!env.iter().any(|(key, _)| *key == "IA_GET_FILE"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hook_sees_environment_and_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("env.txt");
+    let command = format!(
+        "printf '%s|%s|%s' \"$IA_GET_FILE\" \"$IA_GET_SIZE\" \"$IA_GET_STATUS\" > '{}'; exit 3",
+        out.display()
+    );
+
+    let outcome = run_hook(
+        &command,
+        HookEvent::FileSuccess,
+        &file_context(),
+        Duration::from_secs(10),
+    )
+    .await;
+    assert_eq!(outcome.exit_code, Some(3));
+    assert!(!outcome.succeeded());
+    assert!(!outcome.timed_out);
+    assert_eq!(outcome.file.as_deref(), Some("disk 1.img"));
+    assert_eq!(
+        std::fs::read_to_string(&out).unwrap(),
+        "disk 1.img|1474560|success"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hook_is_killed_after_timeout() {
+    let started = Instant::now();
+    let outcome = run_hook(
+        "sleep 30",
+        HookEvent::ItemSuccess,
+        &HookContext::default(),
+        Duration::from_millis(200),
+    )
+    .await;
+    assert!(outcome.timed_out);
+    assert_eq!(outcome.exit_code, None);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_runner_limits_concurrency_and_collects_outcomes() {
+    let runner = HookRunner::new(HookSettings {
+        file_success: Some("sleep 0.3".to_string()),
+        item_success: Some("exit 0".to_string()),
+        max_concurrent: 1,
+        ..settings()
+    });
+
+    let started = Instant::now();
+    runner.run(HookEvent::FileSuccess, file_context());
+    runner.run(HookEvent::FileSuccess, file_context());
+    // Not configured: nothing runs
+    runner.run(HookEvent::FileFailure, file_context());
+    runner.run(HookEvent::ItemSuccess, HookContext::default());
+    let outcomes: Vec<HookOutcome> = runner.finish().await;
+
+    // One at a time, so the two sleeps ran back to back
+    assert!(started.elapsed() >= Duration::from_millis(600));
+    assert_eq!(outcomes.len(), 3);
+    assert!(outcomes.iter().all(HookOutcome::succeeded));
+    assert_eq!(
+        outcomes
+            .iter()
+            .filter(|outcome| outcome.hook == HookEvent::FileSuccess)
+            .count(),
+        2
+    );
+    assert!(runner.finish().await.is_empty());
+}
+
+const ITEM_CONTENT: &[u8] = b"hello hooks";
+
+/// Serve `ITEM_CONTENT` for every request; returns the base URL
+fn mock_file_server() -> String {
+    MockServer::start(|_| MockResponse::new("200 OK", ITEM_CONTENT)).base
+}
+
+/// Cache the metadata of the `example` item, holding one file of `size` bytes
+fn seed_item(output_dir: &Path, server: &str, size: u64) {
+    let file = ArchiveFile {
+        format: Some("Text".to_string()),
+        md5: Some(format!("{:x}", md5::compute(ITEM_CONTENT))),
+        ..archive_file("hello.txt", size)
+    };
+    seed_metadata(
+        output_dir,
+        "example",
+        &item_metadata("example", server, vec![file]),
+    );
+}
+
+fn request(output_dir: &Path) -> DownloadRequest {
+    DownloadRequest {
+        identifier: "example".to_string(),
+        output_dir: output_dir.to_path_buf(),
+        ..Default::default()
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_item_failure_hook_runs_for_early_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("out");
+    let out = dir.path().join("error.txt");
+    // Far more than any test machine has free
+    seed_item(&output_dir, "http://127.0.0.1:9", 1 << 60);
+
+    let service = DownloadService::new()
+        .unwrap()
+        .with_history_path(dir.path().join("history.json"))
+        .with_hook_settings(HookSettings {
+            item_failure: Some(format!(
+                "printf '%s|%s' \"$IA_GET_IDENTIFIER\" \"$IA_GET_ERROR\" > '{}'",
+                out.display()
+            )),
+            ..settings()
+        });
+    let result = service.download(request(&output_dir), None).await.unwrap();
+
+    assert!(matches!(result, DownloadResult::Error(_)));
+    let recorded = std::fs::read_to_string(&out).unwrap();
+    assert!(recorded.starts_with("example|Insufficient disk space"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hook_outcomes_are_kept_in_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("out");
+    let history_path = dir.path().join("history.json");
+    seed_item(&output_dir, &mock_file_server(), ITEM_CONTENT.len() as u64);
+
+    let service = DownloadService::new()
+        .unwrap()
+        .with_history_path(history_path.clone())
+        .with_hook_settings(HookSettings {
+            file_success: Some("true".to_string()),
+            item_success: Some("exit 4".to_string()),
+            ..settings()
+        });
+    let result = service.download(request(&output_dir), None).await.unwrap();
+
+    assert!(matches!(result, DownloadResult::Success(..)));
+    assert_eq!(
+        std::fs::read(output_dir.join("hello.txt")).unwrap(),
+        ITEM_CONTENT
+    );
+    let history = DownloadHistory::load_from_file(&history_path).unwrap();
+    let outcomes: Vec<HookOutcome> =
+        serde_json::from_value(history.entries[0].metadata["hooks"].clone()).unwrap();
+    let file_hook = outcomes
+        .iter()
+        .find(|outcome| outcome.hook == HookEvent::FileSuccess)
+        .unwrap();
+    assert_eq!(file_hook.file.as_deref(), Some("hello.txt"));
+    assert!(file_hook.succeeded());
+    let item_hook = outcomes
+        .iter()
+        .find(|outcome| outcome.hook == HookEvent::ItemSuccess)
+        .unwrap();
+    assert_eq!(item_hook.exit_code, Some(4));
+}
@@ -0,0 +1,111 @@
+//! Archive Member Support Layer Tests
+//!
+//! Tests for reading `view_archive.php` listings, choosing members and
+//! placing them where extracting the archive would (`ia-get members`).
+
+use ia_get::core::archive::{
+    ArchiveMember, MemberFilter, is_browsable_archive, member_path, parse_member_listing,
+    view_archive_url,
+};
+use std::path::Path;
+
+const LISTING: &str = r#"<table class="archext">
+<thead><tr><th>Name</th><th>Timestamp</th><th>Size</th></tr></thead>
+<tr><td><a href="//ia800200.us.archive.org/view_archive.php?archive=/1/items/example/disks.zip&amp;file=disks%2F">disks/</a></td><td id="timestamp">2001-02-03 04:05</td><td id="size"></td></tr>
+<tr><td><a href="//ia800200.us.archive.org/view_archive.php?archive=/1/items/example/disks.zip&amp;file=disks%2Fdisk%201.img">disks/disk 1.img</a></td><td id="timestamp">2001-02-03 04:05</td><td id="size">1474560</td></tr>
+<tr><td><a href="//ia800200.us.archive.org/view_archive.php?archive=/1/items/example/disks.zip&amp;file=README.TXT">README.TXT</a></td><td id="timestamp"></td><td id="size">812</td></tr>
+</table>"#;
+
+fn member(name: &str, size: u64) -> ArchiveMember {
+    ArchiveMember {
+        name: name.to_string(),
+        size: Some(size),
+        modified: None,
+    }
+}
+
+#[test]
+fn test_parse_member_listing_skips_directories() {
+    assert_eq!(
+        parse_member_listing(LISTING),
+        vec![
+            ArchiveMember {
+                name: "disks/disk 1.img".to_string(),
+                size: Some(1474560),
+                modified: Some("2001-02-03 04:05".to_string()),
+            },
+            ArchiveMember {
+                name: "README.TXT".to_string(),
+                size: Some(812),
+                modified: None,
+            },
+        ]
+    );
+    assert!(parse_member_listing("<html>No such archive</html>").is_empty());
+}
+
+#[test]
+fn test_view_archive_url_encodes_paths() {
+    assert_eq!(
+        view_archive_url(
+            "ia800200.us.archive.org",
+            "/1/items/example",
+            "disks.zip",
+            Some("disks/disk 1.img")
+        ),
+        "https://ia800200.us.archive.org/view_archive.php?archive=%2F1%2Fitems%2Fexample%2Fdisks.zip&file=disks%2Fdisk%201.img"
+    );
+    assert!(is_browsable_archive("backup.tar.gz"));
+    assert!(is_browsable_archive("disks.ZIP"));
+    assert!(!is_browsable_archive("scan.pdf"));
+    assert!(!is_browsable_archive("log.gz"));
+}
+
+#[test]
+fn test_member_path_matches_extracted_layout() {
+    let output = Path::new("out");
+    assert_eq!(
+        member_path(output, "disks.zip", "disks/disk 1.img"),
+        Some(output.join("disks").join("disks").join("disk 1.img"))
+    );
+    assert_eq!(
+        member_path(output, "backup.tar.gz", "./etc/hosts"),
+        Some(output.join("backup.tar").join("etc").join("hosts"))
+    );
+    // Archives in item subdirectories are flattened like the archive itself
+    assert_eq!(
+        member_path(output, "cds/one.zip", "a.txt"),
+        Some(output.join("cds_one").join("a.txt"))
+    );
+}
+
+#[test]
+fn test_member_path_rejects_escaping_names() {
+    let output = Path::new("out");
+    assert_eq!(member_path(output, "disks.zip", "../../.bashrc"), None);
+    assert_eq!(member_path(output, "disks.zip", "/etc/passwd"), None);
+    assert_eq!(member_path(output, "disks.zip", "a/../../b"), None);
+    assert_eq!(member_path(output, "disks.zip", "."), None);
+}
+
+#[test]
+fn test_member_filter_combines_patterns_formats_and_size() {
+    let filter = MemberFilter {
+        patterns: vec!["disks/*".to_string()],
+        include_formats: vec!["img".to_string()],
+        exclude_formats: vec![],
+        max_size: Some(2_000_000),
+    };
+    assert!(filter.matches(&member("disks/disk 1.img", 1_474_560)));
+    assert!(filter.matches(&member("DISKS/DISK2.IMG", 1_474_560)));
+    assert!(!filter.matches(&member("disks/notes.txt", 10)));
+    assert!(!filter.matches(&member("other/disk3.img", 10)));
+    assert!(!filter.matches(&member("disks/huge.img", 700_000_000)));
+
+    let exclude = MemberFilter {
+        exclude_formats: vec!["txt".to_string()],
+        ..Default::default()
+    };
+    assert!(!exclude.matches(&member("README.TXT", 812)));
+    assert!(exclude.matches(&member("disks/disk 1.img", 1_474_560)));
+}
@@ -3,9 +3,10 @@
 //! Tests for metadata storage functionality including filename sanitization,
 //! path validation, session management, and file metadata handling.
 
+use super::archive_file;
 use ia_get::metadata_storage::{
-    ArchiveFile, DownloadState, generate_session_filename, sanitize_filename_for_filesystem,
-    validate_path_length,
+    ArchiveFile, ChecksumAlgorithm, ChecksumPolicy, DownloadState, generate_session_filename,
+    sanitize_filename_for_filesystem, validate_path_length,
 };
 use std::io::Write;
 use tempfile::Builder;
@@ -395,3 +396,117 @@ fn test_download_state_enum() {
         assert_eq!(state, state_clone);
     }
 }
+
+fn checksummed_file(md5: Option<&str>, sha1: Option<&str>, crc32: Option<&str>) -> ArchiveFile {
+    ArchiveFile {
+        format: Some("Text".to_string()),
+        md5: md5.map(String::from),
+        crc32: crc32.map(String::from),
+        sha1: sha1.map(String::from),
+        ..archive_file("abc.txt", 3)
+    }
+}
+
+const ABC_MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+const ABC_SHA1: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+const ABC_CRC32: &str = "352441c2";
+
+#[test]
+fn test_checksum_policy_selects_algorithms() {
+    let mut hasher = ia_get::utilities::common::StreamingHasher::new();
+    hasher.update(b"abc");
+    let digests = hasher.finalize();
+    let file_info = checksummed_file(Some(ABC_MD5), Some(ABC_SHA1), Some(ABC_CRC32));
+
+    let algorithms = |policy| {
+        file_info
+            .verify_checksums(&digests, policy)
+            .iter()
+            .map(|r| r.algorithm)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        algorithms(ChecksumPolicy::Md5),
+        vec![ChecksumAlgorithm::Md5]
+    );
+    assert_eq!(
+        algorithms(ChecksumPolicy::Crc32),
+        vec![ChecksumAlgorithm::Crc32]
+    );
+    assert_eq!(
+        algorithms(ChecksumPolicy::Strongest),
+        vec![ChecksumAlgorithm::Sha1]
+    );
+    assert_eq!(
+        algorithms(ChecksumPolicy::All),
+        vec![
+            ChecksumAlgorithm::Sha1,
+            ChecksumAlgorithm::Md5,
+            ChecksumAlgorithm::Crc32
+        ]
+    );
+
+    let results = file_info.verify_checksums(&digests, ChecksumPolicy::All);
+    assert!(results.iter().all(|r| r.matched));
+    assert_eq!(results[0].actual, ABC_SHA1);
+}
+
+#[test]
+fn test_checksum_policy_missing_algorithm_and_mismatch() {
+    let mut hasher = ia_get::utilities::common::StreamingHasher::new();
+    hasher.update(b"abc");
+    let digests = hasher.finalize();
+
+    // No SHA1 published: the requested check fails rather than using MD5
+    let file_info = checksummed_file(Some(ABC_MD5), None, Some(ABC_CRC32));
+    let results = file_info.verify_checksums(&digests, ChecksumPolicy::Sha1);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].algorithm, ChecksumAlgorithm::Sha1);
+    assert!(results[0].is_missing());
+    assert!(!results[0].matched);
+    assert_eq!(results[0].actual, ABC_SHA1);
+
+    let file_info = checksummed_file(None, Some(&"0".repeat(40)), None);
+    let results = file_info.verify_checksums(&digests, ChecksumPolicy::Strongest);
+    assert!(!results[0].matched);
+
+    assert!(
+        checksummed_file(None, None, None)
+            .verify_checksums(&digests, ChecksumPolicy::All)
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_checksum_policy_parsing() {
+    assert_eq!(
+        "SHA1".parse::<ChecksumPolicy>().unwrap(),
+        ChecksumPolicy::Sha1
+    );
+    assert_eq!(
+        "strongest".parse::<ChecksumPolicy>().unwrap(),
+        ChecksumPolicy::Strongest
+    );
+    assert!("sha256".parse::<ChecksumPolicy>().is_err());
+    assert_eq!(
+        serde_json::to_string(&ChecksumPolicy::All).unwrap(),
+        "\"all\""
+    );
+}
+
+#[test]
+fn test_validate_checksums_hashes_xml_files() {
+    let mut temp_file = Builder::new().suffix(".xml").tempfile().unwrap();
+    temp_file.write_all(b"abc").unwrap();
+    temp_file.flush().unwrap();
+
+    let mut file_info = checksummed_file(None, Some(ABC_SHA1), None);
+    file_info.name = "abc_files.xml".to_string();
+
+    let results = file_info
+        .validate_checksums(temp_file.path(), ChecksumPolicy::Sha1)
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].matched);
+}
@@ -0,0 +1,142 @@
+//! Metrics Support Layer Tests
+//!
+//! Tests for the counters behind `--metrics-addr`, their OpenMetrics
+//! encoding and the endpoint serving them.
+
+use ia_get::core::download::{
+    DownloadMetrics, ServerFailure, ServerHealthTracker, download_metrics, set_download_metrics,
+};
+use ia_get::interface::metrics::start_metrics_server;
+use ia_get::utilities::common::{MetricType, OpenMetricsEncoder};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_encoder_families_samples_and_escaping() {
+    let mut encoder = OpenMetricsEncoder::new();
+    encoder
+        .family("ia_get_things", MetricType::Counter, "Things seen")
+        .sample(&[("name", "a \"quoted\"\\path\n")], 3.0)
+        .family("ia_get_level", MetricType::Gauge, "Current level")
+        .sample(&[], 0.5)
+        .sample(&[("kind", "peak")], f64::INFINITY);
+
+    assert_eq!(
+        encoder.finish(),
+        "# TYPE ia_get_things counter\n\
+         # HELP ia_get_things Things seen\n\
+         ia_get_things_total{name=\"a \\\"quoted\\\"\\\\path\\n\"} 3\n\
+         # TYPE ia_get_level gauge\n\
+         # HELP ia_get_level Current level\n\
+         ia_get_level 0.5\n\
+         ia_get_level{kind=\"peak\"} +Inf\n\
+         # EOF\n"
+    );
+}
+
+#[tokio::test]
+async fn test_exposition_covers_servers_transfers_and_checksums() {
+    let metrics = Arc::new(DownloadMetrics::new());
+    metrics.record_server_success("ia800100.us.archive.org", 4096, Duration::from_secs(2));
+    metrics.record_server_success("ia800100.us.archive.org", 1024, Duration::from_secs(1));
+    metrics.record_server_failure("ia800100.us.archive.org", ServerFailure::RateLimited);
+    metrics.record_server_failure("ia600200.us.archive.org", ServerFailure::ServerError);
+    metrics.record_checksum_failure();
+    metrics.set_queue_depth(7);
+
+    let active = metrics.start_transfer();
+    let waiting = [metrics.wait_for_slot(), metrics.wait_for_slot()];
+    let text = metrics.render_openmetrics().await;
+    for line in [
+        "ia_get_server_bytes_total{server=\"ia800100.us.archive.org\"} 5120",
+        "ia_get_server_transfer_seconds_total{server=\"ia800100.us.archive.org\"} 3",
+        "ia_get_server_transfers_total{server=\"ia800100.us.archive.org\"} 2",
+        "ia_get_server_errors_total{server=\"ia800100.us.archive.org\",kind=\"rate_limited\"} 1",
+        "ia_get_server_errors_total{server=\"ia600200.us.archive.org\",kind=\"server_error\"} 1",
+        "ia_get_active_transfers 1",
+        "ia_get_waiting_files 2",
+        "ia_get_queue_depth 7",
+        "ia_get_checksum_failures_total 1",
+        "# TYPE ia_get_retries counter",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing {:?} in\n{}",
+            line,
+            text
+        );
+    }
+    assert!(text.ends_with("# EOF\n"));
+
+    // Guards lower their gauge again when dropped
+    drop(active);
+    drop(waiting);
+    let text = metrics.render_openmetrics().await;
+    assert!(text.lines().any(|l| l == "ia_get_active_transfers 0"));
+    assert!(text.lines().any(|l| l == "ia_get_waiting_files 0"));
+}
+
+#[tokio::test]
+async fn test_health_tracker_feeds_installed_metrics_and_endpoint_serves_them() {
+    let metrics = Arc::new(DownloadMetrics::new());
+    set_download_metrics(Some(metrics.clone()));
+    assert!(download_metrics().is_some());
+
+    let tracker = ServerHealthTracker::new();
+    tracker
+        .record_success("metrics-test.archive.org", 2048, Duration::from_millis(500))
+        .await;
+    tracker
+        .record_failure("metrics-test.archive.org", ServerFailure::Timeout)
+        .await;
+
+    let server = &metrics.servers()["metrics-test.archive.org"];
+    assert_eq!((server.bytes, server.transfers), (2048, 1));
+    assert_eq!(server.timeouts, 1);
+    // The exporter's monitor adds up every tracker, while each tracker's own
+    // monitor only reports its session
+    let other = ServerHealthTracker::new();
+    other
+        .record_success("metrics-test.archive.org", 1024, Duration::from_millis(500))
+        .await;
+    let totals = metrics.performance_monitor().get_metrics().await;
+    assert!(totals.total_bytes >= 3072);
+    assert!(totals.connection_stats.connection_timeouts >= 1);
+    let session = tracker.performance_monitor().get_metrics().await;
+    assert_eq!(session.total_bytes, 2048);
+    assert_eq!(session.connection_stats.connection_timeouts, 1);
+    assert_eq!(
+        other.performance_monitor().get_metrics().await.total_bytes,
+        1024
+    );
+
+    let addr = start_metrics_server(metrics.clone(), "127.0.0.1:0")
+        .await
+        .unwrap();
+    let scrape = |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    };
+
+    let response = scrape("/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: application/openmetrics-text; version=1.0.0"));
+    assert!(response.contains(
+        "ia_get_server_errors_total{server=\"metrics-test.archive.org\",kind=\"timeout\"} 1"
+    ));
+    assert!(response.ends_with("# EOF\n"));
+    assert!(scrape("/").await.starts_with("HTTP/1.1 404"));
+
+    assert!(
+        start_metrics_server(metrics, "not an address")
+            .await
+            .is_err()
+    );
+}
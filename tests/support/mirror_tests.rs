@@ -0,0 +1,150 @@
+//! Collection Mirror Tests
+//!
+//! Tests for paging the Scrape API with cursors and for resuming an
+//! interrupted mirror from its checkpoint, run against a local mock of the
+//! Scrape API.
+
+use super::{MockResponse, MockServer};
+use ia_get::IaGetError;
+use ia_get::infrastructure::api::EnhancedArchiveApiClient;
+use ia_get::infrastructure::persistence::MirrorCheckpoint;
+use ia_get::interface::cli::advanced_commands::{MirrorConfig, mirror_pages};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// Serve a four-item collection in pages of two; returns the endpoint URL
+/// and the server, which records every request
+fn mock_scrape_api() -> (String, MockServer) {
+    let server = MockServer::start(|request| {
+        let query = request.query();
+        let body = if query.contains("cursor=page3") {
+            r#"{"items":[],"count":0}"#
+        } else if query.contains("cursor=page2") {
+            r#"{"items":[{"identifier":"c"},{"identifier":"d"}],"count":2,"cursor":"page3"}"#
+        } else {
+            r#"{"items":[{"identifier":"a"},{"identifier":"b"}],"count":2,"total":4,"cursor":"page2"}"#
+        };
+        MockResponse::new("200 OK", body).header("Content-Type", "application/json")
+    });
+    let endpoint = format!("{}/services/search/v1/scrape", server.base);
+    (endpoint, server)
+}
+
+fn api() -> EnhancedArchiveApiClient {
+    EnhancedArchiveApiClient::new(reqwest::Client::new())
+}
+
+fn config(output_dir: &std::path::Path) -> MirrorConfig {
+    MirrorConfig {
+        collection: "example".to_string(),
+        query: None,
+        output_dir: output_dir.to_path_buf(),
+        page_size: 100,
+        concurrent_downloads: 1,
+        restart: false,
+    }
+}
+
+#[tokio::test]
+async fn test_scrape_pages_follow_cursor() {
+    let (endpoint, server) = mock_scrape_api();
+    let mut api = api();
+
+    let mut identifiers = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = api
+            .scrape_items_at(&endpoint, "collection:(example)", 5, cursor.as_deref())
+            .await
+            .unwrap();
+        identifiers.extend(page.items.into_iter().map(|item| item.identifier));
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(identifiers, ["a", "b", "c", "d"]);
+    let requests: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|request| request.query().to_string())
+        .collect();
+    assert_eq!(requests.len(), 3);
+    assert!(!requests[0].contains("cursor="));
+    // The page size is clamped to the API's minimum
+    assert!(requests[0].contains("count=100"));
+    assert!(requests[0].contains("q=collection%3A%28example%29"));
+    assert!(requests[1].contains("cursor=page2"));
+    assert!(requests[2].contains("cursor=page3"));
+}
+
+#[tokio::test]
+async fn test_mirror_resumes_from_checkpoint() {
+    let (endpoint, _) = mock_scrape_api();
+    let temp_dir = TempDir::new().unwrap();
+    let config = config(temp_dir.path());
+    let checkpoint_path = MirrorCheckpoint::path_for(temp_dir.path());
+    let downloaded = Arc::new(Mutex::new(Vec::new()));
+    let download = async |identifier: &str, _: &str| {
+        downloaded.lock().unwrap().push(identifier.to_string());
+        identifier != "b"
+    };
+
+    // The listing breaks down before the last page, like a killed process
+    let mut scrape = api();
+    let result = mirror_pages(
+        &config,
+        &HashSet::new(),
+        async |query, cursor| {
+            if cursor == Some("page3") {
+                return Err(IaGetError::Network("connection reset".to_string()));
+            }
+            scrape.scrape_items_at(&endpoint, query, 100, cursor).await
+        },
+        download,
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!(*downloaded.lock().unwrap(), ["a", "b", "c", "d"]);
+    let saved = MirrorCheckpoint::load_or_new(&checkpoint_path, "collection:(example)").unwrap();
+    assert_eq!(saved.cursor.as_deref(), Some("page3"));
+    assert_eq!((saved.mirrored, saved.total_items), (3, Some(4)));
+    assert_eq!(saved.failed, ["b"]);
+
+    // The next run starts on the page it stopped at
+    let mut scrape = api();
+    let checkpoint = mirror_pages(
+        &config,
+        &HashSet::new(),
+        async |query, cursor| scrape.scrape_items_at(&endpoint, query, 100, cursor).await,
+        download,
+    )
+    .await
+    .unwrap();
+    assert!(checkpoint.finished);
+    assert_eq!(downloaded.lock().unwrap().len(), 4);
+
+    // Stopped halfway through a page: only the rest of it is fetched, and
+    // items the history already has are skipped
+    let mut interrupted = MirrorCheckpoint::new("collection:(example)");
+    interrupted.cursor = Some("page2".to_string());
+    interrupted.mark_done("c");
+    interrupted.save_to_file(&checkpoint_path).unwrap();
+    downloaded.lock().unwrap().clear();
+
+    let mut scrape = api();
+    let completed = HashSet::from(["d".to_string()]);
+    let checkpoint = mirror_pages(
+        &config,
+        &completed,
+        async |query, cursor| scrape.scrape_items_at(&endpoint, query, 100, cursor).await,
+        download,
+    )
+    .await
+    .unwrap();
+    assert!(downloaded.lock().unwrap().is_empty());
+    assert!(checkpoint.finished);
+    assert_eq!((checkpoint.mirrored, checkpoint.skipped), (0, 1));
+}
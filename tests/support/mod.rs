@@ -1,10 +1,275 @@
 //! Support Layer Test Module
 //!
-//! This module includes all support layer tests organized by functionality.
+//! This module includes all support layer tests organized by functionality,
+//! plus the local HTTP server and item fixtures they share.
 
+mod auth_tests;
 mod compression_tests;
+mod container_tests;
+mod control_tests;
+mod daemon_tests;
+mod disk_space_tests;
 mod filters_tests;
+mod hooks_tests;
+mod members_tests;
 mod metadata_storage_tests;
+mod metrics_tests;
+mod mirror_tests;
+mod progress_events_tests;
 mod progress_tests;
+mod queue_tests;
+mod server_health_tests;
 mod session_tests;
+mod store_tests;
+mod stream_tests;
+mod sync_tests;
+mod torrent_tests;
 mod url_processing_tests;
+mod verification_tests;
+
+use ia_get::metadata_storage::{ArchiveFile, ArchiveMetadata, ChecksumPolicy, DownloadConfig};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A request received by a [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// Path and query string
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl MockRequest {
+    /// Target without its query string
+    pub fn path(&self) -> &str {
+        self.target
+            .split_once('?')
+            .map_or(&self.target, |(path, _)| path)
+    }
+
+    /// Value of header `name`, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Query string, empty when there is none
+    pub fn query(&self) -> &str {
+        self.target.split_once('?').map_or("", |(_, query)| query)
+    }
+}
+
+/// Answer of a [`MockServer`]; the body is sent with a `Content-Length`
+pub struct MockResponse {
+    status: &'static str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Wait before answering
+    delay: Duration,
+    /// Bytes of the body sent before `wait` runs and the connection closes
+    stall: Option<(usize, Box<dyn FnOnce() + Send>)>,
+}
+
+impl MockResponse {
+    pub fn new(status: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+            stall: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Hold the response back for `delay`, keeping the request active
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Send only the first `sent` bytes of the body, then call `wait` and
+    /// close the connection, like a transfer that stalls and breaks off
+    pub fn stall_after(mut self, sent: usize, wait: impl FnOnce() + Send + 'static) -> Self {
+        self.stall = Some((sent, Box::new(wait)));
+        self
+    }
+}
+
+/// A local HTTP/1.1 server answering each connection on its own thread
+pub struct MockServer {
+    /// `http://127.0.0.1:<port>`
+    pub base: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    most_active: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    /// Serve on a free local port, answering every request with `handler`
+    pub fn start(
+        handler: impl Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let active = Arc::new(AtomicUsize::new(0));
+        let most_active = Arc::new(AtomicUsize::new(0));
+        let most = most_active.clone();
+        let handler = Arc::new(handler);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (handler, seen) = (handler.clone(), seen.clone());
+                let (active, most) = (active.clone(), most.clone());
+                std::thread::spawn(move || {
+                    let Some(request) = read_request(&stream) else {
+                        return;
+                    };
+                    seen.lock().unwrap().push(request.clone());
+                    most.fetch_max(active.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    write_response(stream, handler(&request));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        MockServer {
+            base,
+            requests,
+            most_active,
+        }
+    }
+
+    /// Most requests answered at the same time so far
+    pub fn most_active(&self) -> usize {
+        self.most_active.load(Ordering::SeqCst)
+    }
+
+    /// Every request received so far, in arrival order
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<MockRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let target = request_line.split_whitespace().nth(1)?.to_string();
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Some(MockRequest { target, headers })
+}
+
+fn write_response(mut stream: TcpStream, response: MockResponse) {
+    std::thread::sleep(response.delay);
+    let mut head = format!("HTTP/1.1 {}\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    let _ = stream.write_all(head.as_bytes());
+    match response.stall {
+        Some((sent, wait)) => {
+            let _ = stream.write_all(&response.body[..sent]);
+            let _ = stream.flush();
+            wait();
+        }
+        None => {
+            let _ = stream.write_all(&response.body);
+        }
+    }
+}
+
+/// A file of `size` bytes publishing no checksums; set other fields with
+/// `ArchiveFile { md5: .., ..archive_file(name, size) }`
+pub fn archive_file(name: &str, size: u64) -> ArchiveFile {
+    ArchiveFile {
+        name: name.to_string(),
+        source: "original".to_string(),
+        format: None,
+        mtime: None,
+        size: Some(size),
+        md5: None,
+        crc32: None,
+        sha1: None,
+        btih: None,
+        summation: None,
+        original: None,
+        rotation: None,
+    }
+}
+
+/// Metadata of item `identifier` holding `files`, served only by `server`
+pub fn item_metadata(identifier: &str, server: &str, files: Vec<ArchiveFile>) -> ArchiveMetadata {
+    ArchiveMetadata {
+        created: 0,
+        d1: String::new(),
+        d2: String::new(),
+        dir: format!("/1/items/{}", identifier),
+        files_count: files.len() as u32,
+        item_size: files.iter().filter_map(|file| file.size).sum(),
+        files,
+        item_last_updated: 0,
+        metadata: serde_json::json!({}),
+        server: server.to_string(),
+        uniq: 0,
+        workable_servers: vec![server.to_string()],
+        reviews: vec![],
+    }
+}
+
+/// Settings for downloading one file at a time into `output_dir`, verified by MD5
+pub fn download_config(output_dir: &Path) -> DownloadConfig {
+    DownloadConfig {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        max_concurrent: 1,
+        format_filters: vec![],
+        min_size: None,
+        max_size: None,
+        verify_md5: true,
+        preserve_mtime: false,
+        user_agent: "ia-get test".to_string(),
+        enable_compression: false,
+        auto_decompress: false,
+        decompress_formats: vec![],
+        segments_per_file: 1,
+        segment_min_size: None,
+        checksum_policy: ChecksumPolicy::Md5,
+        output_container: None,
+        store_dir: None,
+        download_order: Default::default(),
+    }
+}
+
+/// Cache `metadata` in `output_dir` so the download service never asks
+/// archive.org about item `identifier`
+pub fn seed_metadata(output_dir: &Path, identifier: &str, metadata: &ArchiveMetadata) {
+    let session_dir = output_dir.join(".ia-get-sessions");
+    std::fs::create_dir_all(&session_dir).unwrap();
+    std::fs::write(
+        session_dir.join(format!("metadata_{}.json", identifier)),
+        serde_json::to_string(metadata).unwrap(),
+    )
+    .unwrap();
+}
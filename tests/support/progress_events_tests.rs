@@ -0,0 +1,144 @@
+//! Progress Event Support Layer Tests
+//!
+//! Tests for the versioned NDJSON event stream written by `--progress=json`.
+
+use ia_get::IaGetError;
+use ia_get::core::download::{
+    DownloadRequest, DownloadResult, DownloadService, PROGRESS_SCHEMA_VERSION, ProgressEvent,
+    ProgressEventRecord, ProgressEventWriter, set_progress_event_writer,
+};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Writer that keeps everything written to it
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_record_is_one_versioned_json_line() {
+    let record = ProgressEventRecord::new(ProgressEvent::FileStarted {
+        file: "disk 1.img".to_string(),
+        size: Some(1474560),
+    });
+    let line = record.to_json_line();
+    assert!(line.ends_with('\n'));
+    assert_eq!(line.matches('\n').count(), 1);
+
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["schema"], PROGRESS_SCHEMA_VERSION);
+    assert_eq!(value["event"], "file_started");
+    assert_eq!(value["file"], "disk 1.img");
+    assert_eq!(value["size"], 1474560);
+    assert!(value["timestamp"].as_str().unwrap().ends_with('Z'));
+
+    let parsed: ProgressEventRecord = serde_json::from_str(&line).unwrap();
+    assert_eq!(parsed, record);
+}
+
+#[test]
+fn test_file_failed_carries_error_kind() {
+    let event = ProgressEvent::file_failed(
+        "missing.pdf",
+        &IaGetError::NotFound("https://archive.org/download/x/missing.pdf".to_string()),
+    );
+    let value = serde_json::to_value(ProgressEventRecord::new(event)).unwrap();
+    assert_eq!(value["event"], "file_failed");
+    assert_eq!(value["error_kind"], "not_found");
+    assert!(value["error"].as_str().unwrap().starts_with("Not found: "));
+    assert_eq!(IaGetError::Interrupted.kind(), "interrupted");
+}
+
+#[test]
+fn test_writer_emits_events_in_order() {
+    let captured = Captured::default();
+    let writer = ProgressEventWriter::new(Box::new(captured.clone()));
+    writer.emit(ProgressEvent::FileVerified {
+        file: "a.txt".to_string(),
+        bytes: 12,
+        checksums: vec!["md5".to_string()],
+    });
+    writer.emit(ProgressEvent::SessionCompleted {
+        identifier: "example".to_string(),
+        completed: 1,
+        failed: 0,
+        interrupted: 0,
+    });
+
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<ProgressEventRecord> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        &events[0].event,
+        ProgressEvent::FileVerified { checksums, .. } if checksums == &["md5"]
+    ));
+    assert!(matches!(
+        events[1].event,
+        ProgressEvent::SessionCompleted { completed: 1, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_early_failure_emits_session_failed() {
+    let captured = Captured::default();
+    set_progress_event_writer(Some(ProgressEventWriter::new(Box::new(captured.clone()))));
+
+    let service = DownloadService::new().unwrap();
+    let request = DownloadRequest {
+        identifier: "not a valid identifier!".to_string(),
+        ..Default::default()
+    };
+    let result = service.download(request, None).await.unwrap();
+    assert!(matches!(result, DownloadResult::Error(_)));
+
+    // Other tests may emit events meanwhile; only this item's matter
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let failures: Vec<ProgressEvent> = output
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<ProgressEventRecord>(line)
+                .unwrap()
+                .event
+        })
+        .filter(|event| {
+            matches!(event, ProgressEvent::SessionFailed { identifier, .. }
+                if identifier == "not a valid identifier!")
+        })
+        .collect();
+    assert_eq!(failures.len(), 1);
+    assert!(matches!(
+        &failures[0],
+        ProgressEvent::SessionFailed { error_kind, .. } if !error_kind.is_empty()
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_event_fd_is_close_on_exec() {
+    use std::os::fd::IntoRawFd;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let fd = std::fs::File::create(temp_dir.path().join("events.ndjson"))
+        .unwrap()
+        .into_raw_fd();
+    // Hand it over the way a shell redirect would: inheritable
+    unsafe { libc::fcntl(fd, libc::F_SETFD, 0) };
+
+    let writer = ProgressEventWriter::from_fd(fd).unwrap();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    assert_ne!(flags & libc::FD_CLOEXEC, 0);
+    drop(writer);
+}
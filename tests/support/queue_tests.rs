@@ -0,0 +1,401 @@
+//! Download Queue Support Layer Tests
+//!
+//! Tests for the persistent queue behind `ia-get queue`: run order,
+//! reordering, saving and working through it with `QueueRunner`, partly
+//! against a local file server.
+
+use super::{MockResponse, MockServer, archive_file, item_metadata, seed_metadata};
+use ia_get::DownloadRequest;
+use ia_get::core::download::{DownloadControl, DownloadService, QueueRunSummary, QueueRunner};
+use ia_get::infrastructure::persistence::{DownloadQueue, JobState};
+use ia_get::interface::cli::advanced_commands::{QueueAction, queue_command};
+use ia_get::metadata_storage::ArchiveFile;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::Duration;
+
+fn request(identifier: &str) -> DownloadRequest {
+    DownloadRequest {
+        identifier: identifier.to_string(),
+        ..Default::default()
+    }
+}
+
+fn order(queue: &DownloadQueue) -> Vec<&str> {
+    queue
+        .entries
+        .iter()
+        .map(|entry| entry.request.identifier.as_str())
+        .collect()
+}
+
+#[test]
+fn test_priority_then_insertion_order() {
+    let mut queue = DownloadQueue::default();
+    queue.add(request("low"), -1);
+    queue.add(request("first"), 0);
+    queue.add(request("urgent"), 10);
+    queue.add(request("second"), 0);
+    assert_eq!(order(&queue), ["urgent", "first", "second", "low"]);
+
+    let low = queue.entries[3].id;
+    assert!(queue.set_priority(low, 5));
+    assert_eq!(order(&queue), ["urgent", "low", "first", "second"]);
+    assert!(!queue.set_priority(99, 1));
+}
+
+#[test]
+fn test_move_takes_neighbour_priority() {
+    let mut queue = DownloadQueue::default();
+    let a = queue.add(request("a"), 5).id;
+    queue.add(request("b"), 0);
+    let c = queue.add(request("c"), 0).id;
+
+    assert!(queue.move_to(c, 0));
+    assert_eq!(order(&queue), ["c", "a", "b"]);
+    assert_eq!(queue.get(c).unwrap().priority, 5);
+
+    // Past the end means last
+    assert!(queue.move_to(a, 10));
+    assert_eq!(order(&queue), ["c", "b", "a"]);
+    assert_eq!(queue.get(a).unwrap().priority, 0);
+
+    // Later additions of the same priority still go behind
+    queue.add(request("d"), 0);
+    assert_eq!(order(&queue), ["c", "b", "a", "d"]);
+    assert!(!queue.move_to(99, 0));
+}
+
+#[test]
+fn test_update_saves_and_requeue_recovers_interrupted_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+
+    let (running, failed, done) = DownloadQueue::update(&path, |queue| {
+        let ids = (
+            queue.add(request("running"), 0).id,
+            queue.add(request("failed"), 0).id,
+            queue.add(request("done"), 0).id,
+        );
+        queue.get_mut(ids.0).unwrap().set_state(JobState::Running);
+        queue.get_mut(ids.1).unwrap().set_state(JobState::Failed);
+        queue.get_mut(ids.2).unwrap().set_state(JobState::Completed);
+        ids
+    })
+    .unwrap();
+
+    let mut queue = DownloadQueue::load(&path).unwrap();
+    assert_eq!(queue.entries.len(), 3);
+    assert_eq!(queue.requeue(false), 1);
+    assert_eq!(queue.get(running).unwrap().state, JobState::Queued);
+    assert_eq!(queue.get(failed).unwrap().state, JobState::Failed);
+    assert_eq!(queue.requeue(true), 1);
+    assert_eq!(queue.get(failed).unwrap().state, JobState::Queued);
+
+    assert_eq!(queue.remove_finished(), 1);
+    assert!(queue.get(done).is_none());
+    assert_eq!(queue.remove(running).unwrap().request.identifier, "running");
+    // Numbers are never reused
+    assert_eq!(queue.add(request("next"), 0).id, done + 1);
+}
+
+#[tokio::test]
+async fn test_runner_records_outcomes_and_skips_held_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let held = DownloadQueue::update(&path, |queue| {
+        // Invalid identifiers fail before any request is made
+        queue.add(request("bad id!"), 0);
+        queue.add(request("x"), 1);
+        let held = queue.add(request("held-item"), 2).id;
+        queue.get_mut(held).unwrap().set_state(JobState::Paused);
+        held
+    })
+    .unwrap();
+
+    let summary = QueueRunner::new(path.clone(), 2).run(false).await.unwrap();
+    assert_eq!(
+        summary,
+        QueueRunSummary {
+            completed: 0,
+            failed: 2,
+            ..Default::default()
+        }
+    );
+
+    let queue = DownloadQueue::load(&path).unwrap();
+    assert_eq!(queue.get(held).unwrap().state, JobState::Paused);
+    for entry in queue.entries.iter().filter(|entry| entry.id != held) {
+        assert_eq!(entry.state, JobState::Failed);
+        assert!(
+            entry
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("Invalid Archive.org identifier")
+        );
+    }
+}
+
+const CONTENT: &[u8] = b"queued item";
+
+/// A local file server for queue items and the handles a test needs to steer it
+struct MockItemServer {
+    server: MockServer,
+    /// Names of files whose transfer stalled after the first bytes
+    stalled: mpsc::Receiver<String>,
+    /// Dropping it lets stalled transfers end
+    release: mpsc::Sender<()>,
+}
+
+/// Serve `CONTENT` for every file, holding each response for a moment;
+/// files named `stall*` send a few bytes and then wait for the release
+fn mock_item_server() -> MockItemServer {
+    let (stalled_tx, stalled) = mpsc::channel();
+    let (release, release_rx) = mpsc::channel::<()>();
+    let (stalled_tx, release_rx) = (Mutex::new(stalled_tx), Arc::new(Mutex::new(release_rx)));
+    let server = MockServer::start(move |request| {
+        let name = request
+            .path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if name.starts_with("stall") {
+            let stalled_tx = stalled_tx.lock().unwrap().clone();
+            let release_rx = release_rx.clone();
+            MockResponse::new("200 OK", CONTENT).stall_after(4, move || {
+                let _ = stalled_tx.send(name);
+                let _ = release_rx.lock().unwrap().recv();
+            })
+        } else {
+            MockResponse::new("200 OK", CONTENT).delay(Duration::from_millis(500))
+        }
+    });
+    MockItemServer {
+        server,
+        stalled,
+        release,
+    }
+}
+
+/// Request for item `identifier` holding the file `file`, with the item's
+/// metadata cached so that nothing is asked of archive.org
+fn served_request(server: &str, dir: &Path, identifier: &str, file: &str) -> DownloadRequest {
+    let output_dir = dir.join(identifier);
+    let file = ArchiveFile {
+        md5: Some(format!("{:x}", md5::compute(CONTENT))),
+        ..archive_file(file, CONTENT.len() as u64)
+    };
+    seed_metadata(
+        &output_dir,
+        identifier,
+        &item_metadata(identifier, server, vec![file]),
+    );
+    DownloadRequest {
+        identifier: identifier.to_string(),
+        output_dir,
+        ..Default::default()
+    }
+}
+
+/// Runner over the queue at `path` that keeps its history in `dir`
+fn runner(path: &Path, dir: &Path, parallel: usize) -> QueueRunner {
+    let service = DownloadService::new()
+        .unwrap()
+        .with_history_path(dir.join("history.json"));
+    QueueRunner::new(path.to_path_buf(), parallel).with_service(service)
+}
+
+fn state_of(path: &Path, id: u64) -> JobState {
+    DownloadQueue::load(path).unwrap().get(id).unwrap().state
+}
+
+/// Wait until `count` transfers have stalled, returning their file names
+async fn wait_for_stalls(stalled: mpsc::Receiver<String>, count: usize) -> Vec<String> {
+    tokio::task::spawn_blocking(move || {
+        (0..count)
+            .map(|_| {
+                stalled
+                    .recv_timeout(Duration::from_secs(20))
+                    .expect("transfer never started")
+            })
+            .collect()
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_runner_downloads_items_in_parallel() {
+    let MockItemServer { server, .. } = mock_item_server();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let ids = DownloadQueue::update(&path, |queue| {
+        [("alpha", "a.txt"), ("beta", "b.txt")].map(|(identifier, file)| {
+            let request = served_request(&server.base, dir.path(), identifier, file);
+            queue.add(request, 0).id
+        })
+    })
+    .unwrap();
+
+    let summary = tokio::time::timeout(
+        Duration::from_secs(60),
+        runner(&path, dir.path(), 2).run(false),
+    )
+    .await
+    .expect("queue run did not finish")
+    .unwrap();
+
+    assert_eq!(summary.completed, 2);
+    assert_eq!(server.most_active(), 2);
+    for id in ids {
+        assert_eq!(state_of(&path, id), JobState::Completed);
+    }
+    assert_eq!(
+        std::fs::read(dir.path().join("beta").join("b.txt")).unwrap(),
+        CONTENT
+    );
+}
+
+#[tokio::test]
+async fn test_cancelling_a_run_queues_its_items_again() {
+    let MockItemServer {
+        server,
+        stalled,
+        release,
+    } = mock_item_server();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let ids = DownloadQueue::update(&path, |queue| {
+        [("alpha", "stall-a.bin"), ("beta", "stall-b.bin")].map(|(identifier, file)| {
+            let request = served_request(&server.base, dir.path(), identifier, file);
+            queue.add(request, 0).id
+        })
+    })
+    .unwrap();
+
+    let control = DownloadControl::new();
+    let run = tokio::spawn({
+        let runner = runner(&path, dir.path(), 2).with_control(control.clone());
+        async move { runner.run(false).await }
+    });
+    wait_for_stalls(stalled, 2).await;
+    control.cancel();
+
+    let summary = tokio::time::timeout(Duration::from_secs(30), run)
+        .await
+        .expect("cancelled run did not stop")
+        .unwrap()
+        .unwrap();
+    drop(release);
+
+    assert_eq!(
+        summary,
+        QueueRunSummary {
+            interrupted: 2,
+            ..Default::default()
+        }
+    );
+    for id in ids {
+        assert_eq!(state_of(&path, id), JobState::Queued);
+    }
+}
+
+#[tokio::test]
+async fn test_edits_from_cli_and_gui_reach_a_running_queue() {
+    let MockItemServer {
+        server,
+        stalled,
+        release,
+    } = mock_item_server();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let (stuck, next) = DownloadQueue::update(&path, |queue| {
+        let stuck = queue.add(
+            served_request(&server.base, dir.path(), "alpha", "stall.bin"),
+            1,
+        );
+        let next = queue.add(served_request(&server.base, dir.path(), "beta", "b.txt"), 0);
+        (stuck.id, next.id)
+    })
+    .unwrap();
+
+    let run = tokio::spawn({
+        let runner = runner(&path, dir.path(), 1);
+        async move { runner.run(false).await }
+    });
+    wait_for_stalls(stalled, 1).await;
+
+    // `queue add` from another process is picked up by the same run
+    let late = served_request(&server.base, dir.path(), "gamma", "c.txt");
+    queue_command(&path, QueueAction::Add(vec![late], 0)).unwrap();
+    // Pausing the running item in the GUI stops its download
+    DownloadQueue::update(&path, |queue| {
+        queue.get_mut(stuck).unwrap().set_state(JobState::Paused);
+    })
+    .unwrap();
+
+    let summary = tokio::time::timeout(Duration::from_secs(60), run)
+        .await
+        .expect("queue run did not finish")
+        .unwrap()
+        .unwrap();
+    drop(release);
+
+    assert_eq!(
+        summary,
+        QueueRunSummary {
+            completed: 2,
+            withdrawn: 1,
+            ..Default::default()
+        }
+    );
+    let queue = DownloadQueue::load(&path).unwrap();
+    assert_eq!(queue.get(stuck).unwrap().state, JobState::Paused);
+    assert_eq!(queue.get(next).unwrap().state, JobState::Completed);
+    let late = queue
+        .entries
+        .iter()
+        .find(|entry| entry.request.identifier == "gamma")
+        .unwrap();
+    assert_eq!(late.state, JobState::Completed);
+    assert!(
+        PathBuf::from(&late.request.output_dir)
+            .join("c.txt")
+            .exists()
+    );
+}
+
+#[test]
+fn test_concurrent_updates_are_not_lost() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ia-get-queue.json");
+    let writers: Vec<_> = (0..8)
+        .map(|writer| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for item in 0..10 {
+                    DownloadQueue::update(&path, |queue| {
+                        queue.add(request(&format!("item-{}-{}", writer, item)), 0);
+                    })
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    let queue = DownloadQueue::load(&path).unwrap();
+    assert_eq!(queue.entries.len(), 80);
+    // No temporary file is left behind
+    assert_eq!(
+        std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("tmp".as_ref()))
+            .count(),
+        0
+    );
+}
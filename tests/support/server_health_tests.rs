@@ -0,0 +1,103 @@
+//! Server Health Support Layer Tests
+//!
+//! Tests for per-server health scoring, mirror ranking and the
+//! circuit breaker used when selecting download servers.
+
+use ia_get::IaGetError;
+use ia_get::core::download::{ServerFailure, ServerHealthTracker, server_from_url};
+use std::time::Duration;
+
+fn servers() -> Vec<String> {
+    vec![
+        "ia800100.us.archive.org".to_string(),
+        "ia800200.us.archive.org".to_string(),
+        "ia800300.us.archive.org".to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn test_untried_servers_keep_archive_order() {
+    let tracker = ServerHealthTracker::new();
+    assert_eq!(tracker.ranked_servers(&servers()).await, servers());
+}
+
+#[tokio::test]
+async fn test_faster_server_ranked_first() {
+    let tracker = ServerHealthTracker::new();
+    let servers = servers();
+
+    tracker
+        .record_success(&servers[0], 1_000_000, Duration::from_secs(10))
+        .await;
+    tracker
+        .record_success(&servers[2], 1_000_000, Duration::from_secs(1))
+        .await;
+
+    let ranked = tracker.ranked_servers(&servers).await;
+    assert_eq!(ranked[0], servers[2]);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_opens_after_repeated_failures() {
+    let tracker = ServerHealthTracker::new();
+    let servers = servers();
+
+    for _ in 0..3 {
+        tracker
+            .record_failure(&servers[0], ServerFailure::ServerError)
+            .await;
+    }
+
+    assert!(tracker.is_circuit_open(&servers[0]).await);
+    let ranked = tracker.ranked_servers(&servers).await;
+    assert_eq!(ranked.last(), Some(&servers[0]));
+
+    let snapshot = tracker.snapshot().await;
+    assert_eq!(snapshot[&servers[0]].server_errors, 3);
+    assert_eq!(snapshot[&servers[0]].circuit_trips, 1);
+
+    // A success closes the circuit again
+    tracker
+        .record_success(&servers[0], 1024, Duration::from_millis(10))
+        .await;
+    assert!(!tracker.is_circuit_open(&servers[0]).await);
+}
+
+#[test]
+fn test_failure_classification() {
+    let rate_limited = IaGetError::RateLimited {
+        message: "throttled".to_string(),
+        retry_after: None,
+    };
+    let unavailable = IaGetError::Http {
+        status: 503,
+        message: "Service Unavailable".to_string(),
+    };
+    let stalled = IaGetError::Timeout("No data received for 45s".to_string());
+    // Only the error kind counts, not words in the message
+    let other = IaGetError::Network("Connection reset after timeout (HTTP 503)".to_string());
+    let not_server = IaGetError::Http {
+        status: 416,
+        message: "Range Not Satisfiable".to_string(),
+    };
+
+    assert_eq!(
+        ServerFailure::from_error(&rate_limited),
+        ServerFailure::RateLimited
+    );
+    assert_eq!(
+        ServerFailure::from_error(&unavailable),
+        ServerFailure::ServerError
+    );
+    assert_eq!(ServerFailure::from_error(&stalled), ServerFailure::Timeout);
+    assert_eq!(ServerFailure::from_error(&other), ServerFailure::Other);
+    assert_eq!(ServerFailure::from_error(&not_server), ServerFailure::Other);
+}
+
+#[test]
+fn test_server_from_url() {
+    assert_eq!(
+        server_from_url("https://ia800100.us.archive.org/12/items/x/file.iso"),
+        "ia800100.us.archive.org"
+    );
+}
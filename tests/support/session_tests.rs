@@ -3,9 +3,10 @@
 //! Tests for download session functionality including session creation,
 //! persistence, file status tracking, and progress monitoring.
 
+use ia_get::core::session::journal_path;
 use ia_get::metadata_storage::{
-    ArchiveFile, ArchiveMetadata, DownloadConfig, DownloadSession, DownloadState,
-    generate_session_filename,
+    ArchiveFile, ArchiveMetadata, ChecksumPolicy, DownloadConfig, DownloadOrder, DownloadSession,
+    DownloadState, ResumeValidator, generate_session_filename, plan_segments,
 };
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -75,6 +76,12 @@ fn create_test_config() -> DownloadConfig {
         enable_compression: true,
         auto_decompress: false,
         decompress_formats: vec!["gz".to_string(), "zip".to_string()],
+        segments_per_file: 1,
+        segment_min_size: None,
+        checksum_policy: ChecksumPolicy::Md5,
+        output_container: None,
+        store_dir: None,
+        download_order: Default::default(),
     }
 }
 
@@ -365,3 +372,328 @@ fn test_session_with_nonexistent_requested_files() {
     assert!(session.file_status.contains_key("test-file.txt"));
     assert!(!session.file_status.contains_key("nonexistent-file.txt"));
 }
+
+#[test]
+fn test_plan_segments_covers_whole_file() {
+    let segments = plan_segments(1000, 3);
+
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[0].start, 0);
+    assert_eq!(segments[2].end, 999);
+    for pair in segments.windows(2) {
+        assert_eq!(pair[0].end + 1, pair[1].start);
+    }
+    assert_eq!(segments.iter().map(|s| s.len()).sum::<u64>(), 1000);
+    assert!(
+        segments
+            .iter()
+            .all(|s| !s.completed && s.bytes_downloaded == 0)
+    );
+}
+
+#[test]
+fn test_plan_segments_edge_cases() {
+    assert!(plan_segments(0, 4).is_empty());
+    assert!(plan_segments(100, 0).is_empty());
+
+    // Never more segments than bytes
+    let tiny = plan_segments(2, 8);
+    assert_eq!(tiny.len(), 2);
+    assert_eq!(tiny[1].len(), 1);
+}
+
+#[test]
+fn test_segment_progress_serialization() {
+    let metadata = create_test_metadata();
+    let config = create_test_config();
+    let mut session = DownloadSession::new(
+        "https://archive.org/details/test-archive".to_string(),
+        "test-archive".to_string(),
+        metadata,
+        config,
+        vec!["test-file.txt".to_string()],
+    );
+
+    let mut segments = plan_segments(1024, 2);
+    segments[0].bytes_downloaded = 512;
+    segments[0].completed = true;
+    session
+        .file_status
+        .get_mut("test-file.txt")
+        .unwrap()
+        .segments = segments.clone();
+
+    let json = serde_json::to_string(&session).unwrap();
+    let restored: DownloadSession = serde_json::from_str(&json).unwrap();
+    let status = &restored.file_status["test-file.txt"];
+    assert_eq!(status.segments, segments);
+    assert_eq!(status.segments[1].remaining(), 512);
+}
+
+#[test]
+fn test_resume_validator_prefers_strong_etag() {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("etag", "\"5f2b-1a2b3c\"".parse().unwrap());
+    headers.insert(
+        "last-modified",
+        "Tue, 01 Oct 2024 10:00:00 GMT".parse().unwrap(),
+    );
+    let validator = ResumeValidator::from_headers(&headers).unwrap();
+    assert_eq!(validator.if_range(), Some("\"5f2b-1a2b3c\""));
+
+    // Weak ETags are not allowed in If-Range
+    let weak = ResumeValidator {
+        etag: Some("W/\"5f2b\"".to_string()),
+        last_modified: Some("Tue, 01 Oct 2024 10:00:00 GMT".to_string()),
+    };
+    assert_eq!(weak.if_range(), Some("Tue, 01 Oct 2024 10:00:00 GMT"));
+
+    assert!(ResumeValidator::from_headers(&reqwest::header::HeaderMap::new()).is_none());
+}
+
+#[test]
+fn test_apply_item_update_resets_changed_files() {
+    let mut session = DownloadSession::new(
+        "https://archive.org/details/test-archive".to_string(),
+        "test-archive".to_string(),
+        create_test_metadata(),
+        create_test_config(),
+        vec!["test-file.txt".to_string(), "image.jpg".to_string()],
+    );
+    session.update_file_status("image.jpg", DownloadState::Completed);
+    for status in session.file_status.values_mut() {
+        status.resume_validator = Some(ResumeValidator {
+            etag: Some("\"old\"".to_string()),
+            last_modified: None,
+        });
+    }
+
+    let mut updated = create_test_metadata();
+    assert!(!session.item_changed(&updated));
+    updated.item_last_updated += 60;
+    updated.files[1].md5 = Some("rederived".to_string());
+    assert!(session.item_changed(&updated));
+
+    let reset = session.apply_item_update(updated);
+    assert_eq!(reset, vec!["image.jpg".to_string()]);
+
+    let image = &session.file_status["image.jpg"];
+    assert_eq!(image.status, DownloadState::Pending);
+    assert_eq!(image.file_info.md5.as_deref(), Some("rederived"));
+    assert!(image.resume_validator.is_none());
+
+    // Unchanged files keep their partial progress
+    assert!(
+        session.file_status["test-file.txt"]
+            .resume_validator
+            .is_some()
+    );
+    assert_eq!(session.archive_metadata.item_last_updated, 1234567950);
+}
+
+fn ordered_session(order: DownloadOrder) -> DownloadSession {
+    let mut metadata = create_test_metadata();
+    metadata.files.push(ArchiveFile {
+        name: "movie.mp4".to_string(),
+        size: Some(4096),
+        format: Some("MPEG4".to_string()),
+        ..metadata.files[0].clone()
+    });
+    metadata.files.push(ArchiveFile {
+        name: "notes.txt".to_string(),
+        size: Some(10),
+        ..metadata.files[0].clone()
+    });
+    let mut config = create_test_config();
+    config.download_order = order;
+
+    // Requested in a different order than the metadata lists them
+    let requested_files = vec![
+        "notes.txt".to_string(),
+        "movie.mp4".to_string(),
+        "image.jpg".to_string(),
+        "test-file.txt".to_string(),
+    ];
+    DownloadSession::new(
+        "https://archive.org/details/test-archive".to_string(),
+        "test-archive".to_string(),
+        metadata,
+        config,
+        requested_files,
+    )
+}
+
+#[test]
+fn test_pending_files_follow_download_order() {
+    let session = ordered_session(DownloadOrder::Metadata);
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["test-file.txt", "image.jpg", "movie.mp4", "notes.txt"]
+    );
+
+    let session = ordered_session(DownloadOrder::SmallestFirst);
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["notes.txt", "test-file.txt", "image.jpg", "movie.mp4"]
+    );
+
+    let session = ordered_session(DownloadOrder::LargestFirst);
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["movie.mp4", "image.jpg", "test-file.txt", "notes.txt"]
+    );
+
+    // Default category ranking puts documents before images before video
+    let session = ordered_session(DownloadOrder::FormatPriority(Vec::new()));
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["test-file.txt", "notes.txt", "image.jpg", "movie.mp4"]
+    );
+
+    let session = ordered_session("format:video,images".parse().unwrap());
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["movie.mp4", "image.jpg", "test-file.txt", "notes.txt"]
+    );
+
+    // Unmatched files keep metadata order after the matched ones
+    let session = ordered_session("priority:*.JPG,notes.*".parse().unwrap());
+    assert_eq!(
+        session.get_pending_files(),
+        vec!["image.jpg", "notes.txt", "test-file.txt", "movie.mp4"]
+    );
+}
+
+#[test]
+fn test_download_order_survives_session_round_trip() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("session.json");
+    let mut session = ordered_session("priority:*.mp4,*.jpg".parse().unwrap());
+    session.save_to_file(&path).unwrap();
+
+    let mut loaded = DownloadSession::load_from_file(&path).unwrap();
+    assert_eq!(
+        loaded.download_config.download_order,
+        DownloadOrder::Priority(vec!["*.mp4".to_string(), "*.jpg".to_string()])
+    );
+    loaded.update_file_status("movie.mp4", DownloadState::Completed);
+    assert_eq!(loaded.get_pending_files()[0], "image.jpg");
+
+    session.download_config.download_order = DownloadOrder::LargestFirst;
+    session.save_to_file(&path).unwrap();
+    let loaded = DownloadSession::load_from_file(&path).unwrap();
+    assert_eq!(
+        loaded.download_config.download_order,
+        DownloadOrder::LargestFirst
+    );
+}
+
+#[test]
+fn test_download_order_parsing() {
+    assert_eq!(
+        "smallest".parse::<DownloadOrder>().unwrap(),
+        DownloadOrder::SmallestFirst
+    );
+    assert_eq!(
+        "Largest".parse::<DownloadOrder>().unwrap(),
+        DownloadOrder::LargestFirst
+    );
+    assert_eq!(
+        "format".parse::<DownloadOrder>().unwrap(),
+        DownloadOrder::FormatPriority(Vec::new())
+    );
+    for text in [
+        "metadata",
+        "format:audio,documents",
+        "priority:*.xml,cover.jpg",
+    ] {
+        assert_eq!(text.parse::<DownloadOrder>().unwrap().to_string(), text);
+    }
+    assert!("format:podcasts".parse::<DownloadOrder>().is_err());
+    assert!("priority".parse::<DownloadOrder>().is_err());
+    assert!("priority:".parse::<DownloadOrder>().is_err());
+    assert!("random".parse::<DownloadOrder>().is_err());
+}
+
+fn journaled_session(dir: &std::path::Path) -> (std::path::PathBuf, DownloadSession) {
+    let path = dir.join("session.json");
+    let session = DownloadSession::new(
+        "https://archive.org/details/test-archive".to_string(),
+        "test-archive".to_string(),
+        create_test_metadata(),
+        create_test_config(),
+        vec!["test-file.txt".to_string(), "image.jpg".to_string()],
+    );
+    session.save_to_file(&path).unwrap();
+    (path, session)
+}
+
+#[test]
+fn test_journal_replayed_on_load() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let (path, mut session) = journaled_session(temp_dir.path());
+
+    session.update_file_status("test-file.txt", DownloadState::Completed);
+    session.journal_file_state(&path, "test-file.txt").unwrap();
+    session.update_file_status("image.jpg", DownloadState::Paused);
+    session.journal_file_state(&path, "image.jpg").unwrap();
+    assert_eq!(session.journal_seq, 2);
+
+    // The snapshot is untouched; the states come from the journal
+    let loaded = DownloadSession::load_from_file(&path).unwrap();
+    assert_eq!(loaded.journal_seq, 2);
+    assert_eq!(
+        loaded.file_status["test-file.txt"].status,
+        DownloadState::Completed
+    );
+    assert_eq!(loaded.get_pending_files(), vec!["image.jpg"]);
+}
+
+#[test]
+fn test_journal_skips_torn_line() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let (path, mut session) = journaled_session(temp_dir.path());
+
+    session.update_file_status("test-file.txt", DownloadState::Completed);
+    session.journal_file_state(&path, "test-file.txt").unwrap();
+
+    // Simulate a crash in the middle of the next append
+    let journal = journal_path(&path);
+    let mut content = std::fs::read(&journal).unwrap();
+    let torn = content.clone();
+    content.extend_from_slice(&torn[..torn.len() / 2]);
+    std::fs::write(&journal, content).unwrap();
+
+    let loaded = DownloadSession::load_from_file(&path).unwrap();
+    assert_eq!(loaded.journal_seq, 1);
+    assert_eq!(
+        loaded.file_status["test-file.txt"].status,
+        DownloadState::Completed
+    );
+}
+
+#[test]
+fn test_compaction_folds_journal_into_snapshot() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let (path, mut session) = journaled_session(temp_dir.path());
+    let journal = journal_path(&path);
+
+    session.update_file_status("test-file.txt", DownloadState::Completed);
+    session.journal_file_state(&path, "test-file.txt").unwrap();
+    let stale_journal = std::fs::read(&journal).unwrap();
+
+    session.save_to_file(&path).unwrap();
+    assert!(!journal.exists());
+    assert!(!path.with_extension("json.tmp").exists());
+
+    // A journal left behind by a crash after the rename must not be replayed
+    session.update_file_status("test-file.txt", DownloadState::Failed);
+    session.save_to_file(&path).unwrap();
+    std::fs::write(&journal, stale_journal).unwrap();
+
+    let loaded = DownloadSession::load_from_file(&path).unwrap();
+    assert_eq!(
+        loaded.file_status["test-file.txt"].status,
+        DownloadState::Failed
+    );
+}
@@ -0,0 +1,102 @@
+//! Content Store Tests
+//!
+//! Tests for the content-addressed store that deduplicates identical files.
+
+use super::archive_file;
+use ia_get::core::download::ContentStore;
+use ia_get::core::session::{ArchiveFile, ChecksumAlgorithm};
+use tempfile::TempDir;
+
+const CONTENTS: &[u8] = b"hello store";
+const MD5: &str = "0b8ed0e4b4c0b3e5d0c2d1b7a5c5c7d9";
+const SHA1: &str = "2AAE6C35C94FCFB415DBE95F408B9CE91EE846ED";
+
+fn file_info(name: &str) -> ArchiveFile {
+    ArchiveFile {
+        md5: Some(MD5.to_string()),
+        sha1: Some(SHA1.to_string()),
+        ..archive_file(name, CONTENTS.len() as u64)
+    }
+}
+
+#[test]
+fn test_store_links_inserted_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = ContentStore::open(temp_dir.path().join("store")).unwrap();
+    let first = temp_dir.path().join("item1/cover.jpg");
+    std::fs::create_dir_all(first.parent().unwrap()).unwrap();
+    std::fs::write(&first, CONTENTS).unwrap();
+
+    let info = file_info("cover.jpg");
+    assert!(store.lookup(&info).is_none());
+    store.insert(&info, &first).unwrap();
+    assert!(store.lookup(&info).is_some());
+
+    let second = temp_dir.path().join("item2/sub/cover.jpg");
+    let evidence = store.link_into(&info, &second).unwrap().unwrap();
+    // The stored copy was verified against the strongest published checksum
+    assert_eq!(evidence.algorithm, ChecksumAlgorithm::Sha1);
+    assert!(evidence.actual.eq_ignore_ascii_case(SHA1));
+    assert!(evidence.matched);
+    assert_eq!(std::fs::read(&second).unwrap(), CONTENTS);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let a = std::fs::metadata(&first).unwrap();
+        let b = std::fs::metadata(&second).unwrap();
+        assert_eq!(a.ino(), b.ino());
+    }
+}
+
+#[test]
+fn test_store_ignores_mismatched_or_malformed_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = ContentStore::open(temp_dir.path().join("store")).unwrap();
+    let source = temp_dir.path().join("file.bin");
+    std::fs::write(&source, CONTENTS).unwrap();
+    store.insert(&file_info("file.bin"), &source).unwrap();
+
+    // Same checksum but a different size is not trusted
+    let mut resized = file_info("file.bin");
+    resized.size = Some(1);
+    assert!(store.lookup(&resized).is_none());
+    assert!(
+        store
+            .link_into(&resized, &temp_dir.path().join("other.bin"))
+            .unwrap()
+            .is_none()
+    );
+
+    // Digests that are not hex never become paths
+    let mut malformed = file_info("file.bin");
+    malformed.sha1 = Some("../../etc/passwd".to_string());
+    malformed.md5 = None;
+    store.insert(&malformed, &source).unwrap();
+    assert!(store.lookup(&malformed).is_none());
+}
+
+#[test]
+fn test_store_gc_removes_unreferenced_blobs() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = ContentStore::open(temp_dir.path().join("store")).unwrap();
+    let info = file_info("a.txt");
+    let first = temp_dir.path().join("a.txt");
+    std::fs::write(&first, CONTENTS).unwrap();
+    store.insert(&info, &first).unwrap();
+    let second = temp_dir.path().join("b.txt");
+    assert!(store.link_into(&info, &second).unwrap().is_some());
+
+    // Still referenced by one output file
+    std::fs::remove_file(&first).unwrap();
+    let report = store.gc().unwrap();
+    assert_eq!(report.blobs_removed, 0);
+    assert_eq!(report.blobs_kept, 2); // one blob per published checksum
+
+    // Nothing refers to it any more
+    std::fs::remove_file(&second).unwrap();
+    let report = store.gc().unwrap();
+    assert_eq!(report.blobs_removed, 2);
+    assert_eq!(report.blobs_kept, 0);
+    assert!(store.lookup(&info).is_none());
+}
@@ -0,0 +1,143 @@
+//! Streaming Output Support Layer Tests
+//!
+//! Tests for server selection and error handling when streaming a single
+//! file to a writer (`ia-get cat`).
+
+use super::{MockResponse, MockServer, archive_file, item_metadata};
+use ia_get::IaGetError;
+use ia_get::core::download::{StreamOutcome, candidate_servers, stream_file, stream_from_urls};
+use ia_get::metadata_storage::{ArchiveFile, ArchiveMetadata};
+
+/// Serve `/good` with "abc", `/busy` with a 503 and everything else with a
+/// 404 on a local port; returns its base URL
+fn mock_server() -> String {
+    MockServer::start(|request| match request.path() {
+        "/good" => MockResponse::new("200 OK", "abc"),
+        "/busy" => MockResponse::new("503 Service Unavailable", "busy"),
+        _ => MockResponse::new("404 Not Found", "not found"),
+    })
+    .base
+}
+
+fn abc_file(md5: &str) -> ArchiveFile {
+    ArchiveFile {
+        md5: Some(md5.to_string()),
+        ..archive_file("abc.txt", 3)
+    }
+}
+
+const ABC_MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+
+fn metadata() -> ArchiveMetadata {
+    ArchiveMetadata {
+        d1: "ia800200.us.archive.org".to_string(),
+        d2: "ia900200.us.archive.org".to_string(),
+        workable_servers: vec![
+            "ia900200.us.archive.org".to_string(),
+            "ia800300.us.archive.org".to_string(),
+        ],
+        ..item_metadata("example", "ia800200.us.archive.org", vec![])
+    }
+}
+
+#[test]
+fn test_candidate_servers_prefer_workable_servers() {
+    assert_eq!(
+        candidate_servers(&metadata()),
+        vec![
+            "ia900200.us.archive.org".to_string(),
+            "ia800300.us.archive.org".to_string(),
+            "ia800200.us.archive.org".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_without_servers_fails() {
+    let file_info = archive_file("file.txt", 3);
+    let mut output = Vec::new();
+
+    let result = stream_file(
+        &reqwest::Client::new(),
+        &file_info,
+        &[],
+        "/1/items/example",
+        true,
+        &mut output,
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(output.is_empty());
+}
+
+#[tokio::test]
+async fn test_stream_retries_on_next_server() {
+    let base = mock_server();
+    let urls = vec![format!("{}/busy", base), format!("{}/good", base)];
+    let mut output = Vec::new();
+
+    let outcome = stream_from_urls(
+        &reqwest::Client::new(),
+        &urls,
+        &abc_file(ABC_MD5),
+        true,
+        &mut output,
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(outcome, StreamOutcome::Complete(_)));
+    assert_eq!(output, b"abc");
+}
+
+#[tokio::test]
+async fn test_stream_md5_mismatch_fails() {
+    let base = mock_server();
+    let urls = vec![format!("{}/good", base)];
+    let mut output = Vec::new();
+
+    let err = stream_from_urls(
+        &reqwest::Client::new(),
+        &urls,
+        &abc_file("00000000000000000000000000000000"),
+        true,
+        &mut output,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, IaGetError::HashMismatch(_)), "{:?}", err);
+    // The bytes were already written; only the exit status reports the mismatch
+    assert_eq!(output, b"abc");
+
+    // Without verification the same transfer succeeds
+    let outcome = stream_from_urls(
+        &reqwest::Client::new(),
+        &urls,
+        &abc_file("00000000000000000000000000000000"),
+        false,
+        &mut Vec::new(),
+    )
+    .await
+    .unwrap();
+    assert!(matches!(outcome, StreamOutcome::Complete(_)));
+}
+
+#[tokio::test]
+async fn test_stream_missing_file_is_not_retried() {
+    let base = mock_server();
+    let urls = vec![format!("{}/missing", base), format!("{}/good", base)];
+
+    let err = stream_from_urls(
+        &reqwest::Client::new(),
+        &urls,
+        &abc_file(ABC_MD5),
+        true,
+        &mut Vec::new(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, IaGetError::NotFound(_)), "{:?}", err);
+}
@@ -0,0 +1,185 @@
+//! Sync Support Layer Tests
+//!
+//! Tests for comparing item metadata with a local mirror (`ia-get sync`).
+
+use super::{MockResponse, MockServer, archive_file, download_config, item_metadata};
+use ia_get::core::download::{
+    ArchiveDownloader, QUARANTINE_DIR_NAME, RemovalMode, apply_removals, plan_sync,
+};
+use ia_get::core::session::{DownloadConfig, DownloadSession, DownloadState};
+use ia_get::metadata_storage::{ArchiveFile, ArchiveMetadata};
+use indicatif::ProgressBar;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tempfile::TempDir;
+
+const HELLO_MD5: &str = "5d41402abc4b2a76b9719d911017c592";
+
+fn file(name: &str, size: u64, md5: &str, mtime: Option<u64>) -> ArchiveFile {
+    ArchiveFile {
+        mtime,
+        md5: Some(md5.to_string()),
+        ..archive_file(name, size)
+    }
+}
+
+fn metadata(files: Vec<ArchiveFile>) -> ArchiveMetadata {
+    item_metadata("example", "ia800200.us.archive.org", files)
+}
+
+fn session(dir: &Path, metadata: ArchiveMetadata) -> DownloadSession {
+    let requested = metadata.files.iter().map(|f| f.name.clone()).collect();
+    DownloadSession::new(
+        "https://archive.org/details/example".to_string(),
+        "example".to_string(),
+        metadata,
+        DownloadConfig {
+            preserve_mtime: true,
+            ..download_config(dir)
+        },
+        requested,
+    )
+}
+
+#[test]
+fn test_plan_sync_classifies_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    std::fs::write(dir.join("same.txt"), "hello").unwrap();
+    std::fs::write(dir.join("resized.txt"), "hello!").unwrap();
+    std::fs::write(dir.join("corrupt.txt"), "jello").unwrap();
+    std::fs::write(dir.join("gone.txt"), "old").unwrap();
+
+    let current = metadata(vec![
+        file("same.txt", 5, HELLO_MD5, None),
+        file("resized.txt", 5, HELLO_MD5, None),
+        file("corrupt.txt", 5, HELLO_MD5, None),
+        file("new.txt", 5, HELLO_MD5, None),
+    ]);
+    let previous = session(
+        dir,
+        metadata(vec![
+            file("same.txt", 5, HELLO_MD5, None),
+            file("gone.txt", 3, HELLO_MD5, None),
+            file("gone-and-deleted.txt", 3, HELLO_MD5, None),
+        ]),
+    );
+
+    let plan = plan_sync(&current, dir, Some(&previous), false).unwrap();
+    assert_eq!(plan.added, vec!["new.txt"]);
+    assert_eq!(plan.changed, vec!["corrupt.txt", "resized.txt"]);
+    assert_eq!(plan.removed, vec!["gone.txt"]);
+    assert_eq!(plan.unchanged, vec!["same.txt"]);
+    assert!(!plan.is_up_to_date());
+}
+
+#[test]
+fn test_plan_sync_trusts_matching_mtime() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("file.txt");
+    std::fs::write(&path, "jello").unwrap();
+    let mtime = 1_600_000_000;
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime))
+        .unwrap();
+
+    let current = metadata(vec![file("file.txt", 5, HELLO_MD5, Some(mtime))]);
+
+    let plan = plan_sync(&current, temp_dir.path(), None, false).unwrap();
+    assert_eq!(plan.unchanged, vec!["file.txt"]);
+    assert!(plan.is_up_to_date());
+
+    // --verify checks the MD5 anyway
+    let plan = plan_sync(&current, temp_dir.path(), None, true).unwrap();
+    assert_eq!(plan.changed, vec!["file.txt"]);
+}
+
+#[test]
+fn test_apply_removals_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let removed = vec!["a.txt".to_string()];
+
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    apply_removals(dir, &removed, RemovalMode::Keep).unwrap();
+    assert!(dir.join("a.txt").exists());
+
+    let moved = apply_removals(dir, &removed, RemovalMode::Quarantine).unwrap();
+    assert!(!dir.join("a.txt").exists());
+    assert!(moved[0].starts_with(dir.join(QUARANTINE_DIR_NAME)));
+    assert_eq!(std::fs::read_to_string(&moved[0]).unwrap(), "a");
+
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    apply_removals(dir, &removed, RemovalMode::Delete).unwrap();
+    assert!(!dir.join("a.txt").exists());
+}
+
+#[tokio::test]
+async fn test_redownload_replaces_copy_only_when_complete() {
+    let available = Arc::new(AtomicBool::new(false));
+    let serving = available.clone();
+    let server = MockServer::start(move |_| {
+        if serving.load(Ordering::SeqCst) {
+            MockResponse::new("200 OK", "hello")
+        } else {
+            MockResponse::new("404 Not Found", "")
+        }
+    });
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    // Same size as the published file, so only an explicit request refetches it
+    std::fs::write(dir.join("file.txt"), "jello").unwrap();
+    let metadata = item_metadata(
+        "example",
+        &server.base,
+        vec![file("file.txt", 5, HELLO_MD5, None)],
+    );
+    let download = || async {
+        ArchiveDownloader::new(
+            reqwest::Client::new(),
+            1,
+            true,
+            false,
+            dir.join(".ia-get-sessions"),
+            false,
+            false,
+        )
+        .with_redownload(vec!["file.txt".to_string()])
+        .download_with_metadata(
+            "https://archive.org/details/example".to_string(),
+            "example".to_string(),
+            metadata.clone(),
+            download_config(dir),
+            vec!["file.txt".to_string()],
+            &ProgressBar::hidden(),
+            None,
+        )
+        .await
+        .unwrap()
+    };
+
+    let session = download().await;
+    assert_eq!(
+        session.file_status["file.txt"].status,
+        DownloadState::Failed
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("file.txt")).unwrap(),
+        "jello"
+    );
+
+    available.store(true, Ordering::SeqCst);
+    let session = download().await;
+    assert_eq!(
+        session.file_status["file.txt"].status,
+        DownloadState::Completed
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("file.txt")).unwrap(),
+        "hello"
+    );
+}
@@ -0,0 +1,202 @@
+//! Torrent Piece Verification Tests
+//!
+//! Tests for parsing item torrents and locating corrupt byte ranges.
+
+use ia_get::core::archive::{CorruptRange, TorrentInfo, verify_pieces};
+use ia_get::utilities::common::Bencode;
+use indicatif::ProgressBar;
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+const PIECE_LENGTH: usize = 16;
+
+fn bytes(s: &str) -> Bencode {
+    Bencode::Bytes(s.as_bytes().to_vec())
+}
+
+fn dict(entries: Vec<(&str, Bencode)>) -> Bencode {
+    Bencode::Dict(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.as_bytes().to_vec(), v))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+/// Two files, 20 and 30 bytes, laid over four 16-byte pieces
+fn sample_files() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("a.txt", (0u8..20).collect()),
+        ("sub/b.txt", (100u8..130).collect()),
+    ]
+}
+
+fn build_torrent(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let data: Vec<u8> = files.iter().flat_map(|(_, d)| d.clone()).collect();
+    let pieces = data
+        .chunks(PIECE_LENGTH)
+        .flat_map(|chunk| Sha1::digest(chunk).to_vec())
+        .collect();
+
+    let file_list = files
+        .iter()
+        .map(|(name, d)| {
+            dict(vec![
+                ("length", Bencode::Int(d.len() as i64)),
+                ("path", Bencode::List(name.split('/').map(bytes).collect())),
+            ])
+        })
+        .collect();
+    let info = dict(vec![
+        ("files", Bencode::List(file_list)),
+        ("name", bytes("example_item")),
+        ("piece length", Bencode::Int(PIECE_LENGTH as i64)),
+        ("pieces", Bencode::Bytes(pieces)),
+    ]);
+    dict(vec![
+        ("announce", bytes("http://bt1.archive.org/announce")),
+        ("info", info),
+    ])
+    .encode()
+}
+
+fn write_files(dir: &Path, files: &[(&str, Vec<u8>)]) {
+    for (name, data) in files {
+        let path = ia_get::core::download::mirror_path(dir, name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+}
+
+fn corrupt_byte(path: &Path, offset: usize) {
+    let mut data = std::fs::read(path).unwrap();
+    data[offset] ^= 0xff;
+    std::fs::write(path, data).unwrap();
+}
+
+#[test]
+fn test_parse_torrent() {
+    let files = sample_files();
+    let torrent = build_torrent(&files);
+    let info = TorrentInfo::parse(&torrent).unwrap();
+
+    assert_eq!(info.name, "example_item");
+    assert_eq!(info.piece_length, PIECE_LENGTH as u64);
+    assert_eq!(info.pieces.len(), 4);
+    assert_eq!(info.total_length(), 50);
+    assert_eq!(info.files[1].path, "sub/b.txt");
+    assert_eq!(info.files[1].offset, 20);
+
+    let root = Bencode::decode(&torrent).unwrap();
+    let expected_hash = format!("{:x}", Sha1::digest(root.get("info").unwrap().encode()));
+    assert_eq!(info.info_hash, expected_hash);
+
+    // Piece 1 straddles the two files
+    assert_eq!(info.piece_segments(1), vec![(0, 16, 4), (1, 0, 12)]);
+}
+
+#[test]
+fn test_parse_rejects_inconsistent_piece_count() {
+    let info = dict(vec![
+        ("length", Bencode::Int(100)),
+        ("name", bytes("single.bin")),
+        ("piece length", Bencode::Int(16)),
+        ("pieces", Bencode::Bytes(vec![0; 20])),
+    ]);
+    let torrent = dict(vec![("info", info)]).encode();
+    assert!(TorrentInfo::parse(&torrent).is_err());
+}
+
+#[test]
+fn test_parse_rejects_oversized_piece_length() {
+    let info = dict(vec![
+        ("length", Bencode::Int(100)),
+        ("name", bytes("single.bin")),
+        ("piece length", Bencode::Int(1 << 40)),
+        ("pieces", Bencode::Bytes(vec![0; 20])),
+    ]);
+    let torrent = dict(vec![("info", info)]).encode();
+    let err = TorrentInfo::parse(&torrent).unwrap_err();
+    assert!(err.to_string().contains("piece length"), "{}", err);
+}
+
+#[test]
+fn test_verify_clean_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = sample_files();
+    write_files(temp_dir.path(), &files);
+    let info = TorrentInfo::parse(&build_torrent(&files)).unwrap();
+
+    let report = verify_pieces(&info, temp_dir.path(), &ProgressBar::hidden()).unwrap();
+    assert!(report.is_clean());
+    assert_eq!(report.pieces_ok, 4);
+    assert!(report.corrupt_ranges.is_empty());
+    assert!(report.missing_files.is_empty());
+}
+
+#[test]
+fn test_verify_reports_corrupt_ranges() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = sample_files();
+    write_files(temp_dir.path(), &files);
+    let info = TorrentInfo::parse(&build_torrent(&files)).unwrap();
+
+    // Byte 18 of a.txt lies in piece 1, which also covers b.txt[0..12]
+    corrupt_byte(&temp_dir.path().join("a.txt"), 18);
+    // Bytes 15 and 29 of b.txt are in pieces 2 and 3, reported as one range
+    let b_path = ia_get::core::download::mirror_path(temp_dir.path(), "sub/b.txt");
+    corrupt_byte(&b_path, 15);
+    corrupt_byte(&b_path, 29);
+
+    let report = verify_pieces(&info, temp_dir.path(), &ProgressBar::hidden()).unwrap();
+    assert!(!report.is_clean());
+    assert_eq!(report.pieces_ok, 1);
+    assert_eq!(report.corrupt_pieces, vec![1, 2, 3]);
+    assert_eq!(
+        report.corrupt_ranges,
+        vec![
+            CorruptRange {
+                file: "a.txt".to_string(),
+                start: 16,
+                end: 20,
+                first_piece: 1,
+                last_piece: 1,
+            },
+            CorruptRange {
+                file: "sub/b.txt".to_string(),
+                start: 0,
+                end: 30,
+                first_piece: 1,
+                last_piece: 3,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_verify_truncated_and_missing_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = sample_files();
+    let info = TorrentInfo::parse(&build_torrent(&files)).unwrap();
+
+    // Only a.txt, cut short: piece 0 is fine, everything else touches b.txt
+    std::fs::write(temp_dir.path().join("a.txt"), &files[0].1[..18]).unwrap();
+
+    let report = verify_pieces(&info, temp_dir.path(), &ProgressBar::hidden()).unwrap();
+    assert_eq!(report.missing_files, vec!["sub/b.txt"]);
+    assert_eq!(report.pieces_ok, 1);
+    assert_eq!(report.unverifiable_pieces, 3);
+    assert!(report.is_clean());
+
+    // Once b.txt exists, the truncated tail of a.txt shows up as corrupt
+    write_files(temp_dir.path(), &files[1..]);
+    let report = verify_pieces(&info, temp_dir.path(), &ProgressBar::hidden()).unwrap();
+    assert_eq!(report.corrupt_pieces, vec![1]);
+    assert_eq!(report.corrupt_ranges[0].file, "a.txt");
+    assert_eq!(
+        (report.corrupt_ranges[0].start, report.corrupt_ranges[0].end),
+        (16, 20)
+    );
+}
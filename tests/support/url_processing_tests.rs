@@ -5,7 +5,7 @@
 
 use ia_get::url_processing::{
     construct_download_url, construct_metadata_url, extract_identifier_from_url, is_archive_url,
-    normalize_archive_identifier, validate_and_process_url,
+    normalize_archive_identifier, split_file_reference, validate_and_process_url,
 };
 
 #[test]
@@ -148,3 +148,23 @@ fn test_normalize_archive_identifier_invalid() {
     // Note: Non-archive URLs like "https://example.com/test" are treated as plain identifiers,
     // which is valid behavior - they get returned as-is
 }
+
+#[test]
+fn test_split_file_reference() {
+    let (identifier, path) = split_file_reference("example/sub/dir/file.tar").unwrap();
+    assert_eq!(identifier, "example");
+    assert_eq!(path, "sub/dir/file.tar");
+
+    let (identifier, path) =
+        split_file_reference("https://archive.org/details/example/file%20name.mp4").unwrap();
+    assert_eq!(identifier, "example");
+    assert_eq!(path, "file name.mp4");
+}
+
+#[test]
+fn test_split_file_reference_requires_path() {
+    assert!(split_file_reference("example").is_err());
+    assert!(split_file_reference("example/").is_err());
+    assert!(split_file_reference("https://archive.org/download/example").is_err());
+    assert!(split_file_reference("https://archive.org/search?query=x").is_err());
+}
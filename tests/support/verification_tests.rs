@@ -0,0 +1,173 @@
+//! Download Verification Tests
+//!
+//! Tests for how downloaded and already present files are checked against
+//! the item's published checksums, run against a local file server.
+
+use super::{MockResponse, MockServer, archive_file, download_config, item_metadata};
+use ia_get::core::download::ArchiveDownloader;
+use ia_get::metadata_storage::{
+    ArchiveFile, ArchiveMetadata, ChecksumPolicy, DownloadConfig, DownloadSession, DownloadState,
+};
+use indicatif::ProgressBar;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const CONTENT: &[u8] = b"verified content";
+
+fn downloader(sessions: &Path, verify: bool) -> ArchiveDownloader {
+    ArchiveDownloader::new(
+        reqwest::Client::new(),
+        1,
+        verify,
+        false,
+        sessions.to_path_buf(),
+        false,
+        false,
+    )
+}
+
+async fn download(
+    sessions: &Path,
+    metadata: &ArchiveMetadata,
+    config: &DownloadConfig,
+) -> DownloadSession {
+    downloader(sessions, config.verify_md5)
+        .download_with_metadata(
+            "https://archive.org/details/example".to_string(),
+            "example".to_string(),
+            metadata.clone(),
+            config.clone(),
+            vec!["data.bin".to_string()],
+            &ProgressBar::hidden(),
+            None,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_missing_checksum_fails_once_and_keeps_the_file() {
+    let server = MockServer::start(|_| MockResponse::new("200 OK", CONTENT));
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let sessions = temp_dir.path().join("sessions");
+    // Two names for the same server, so another server is there to try
+    let mut metadata = item_metadata(
+        "example",
+        &server.base,
+        vec![ArchiveFile {
+            md5: Some(format!("{:x}", md5::compute(CONTENT))),
+            ..archive_file("data.bin", CONTENT.len() as u64)
+        }],
+    );
+    metadata
+        .workable_servers
+        .push(server.base.replace("127.0.0.1", "localhost"));
+    let config = DownloadConfig {
+        checksum_policy: ChecksumPolicy::Sha1,
+        ..download_config(&output_dir)
+    };
+
+    let session = download(&sessions, &metadata, &config).await;
+    let status = &session.file_status["data.bin"];
+    assert_eq!(status.status, DownloadState::Failed);
+    assert!(
+        status
+            .error_message
+            .as_deref()
+            .is_some_and(|e| e.contains("publishes no SHA1 checksum")),
+        "{:?}",
+        status.error_message
+    );
+    // Fetched once: no other server was tried and the file was kept
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(std::fs::read(output_dir.join("data.bin")).unwrap(), CONTENT);
+
+    // A rerun fails the same way without fetching the file again
+    let session = download(&sessions, &metadata, &config).await;
+    assert_eq!(
+        session.file_status["data.bin"].status,
+        DownloadState::Failed
+    );
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unverified_partial_is_discarded() {
+    // Answers byte ranges, so a kept partial would be completed, not replaced
+    let server = MockServer::start(|request| match request.header("Range") {
+        Some(range) => {
+            let start: usize = range["bytes=".len()..range.len() - 1].parse().unwrap();
+            MockResponse::new("206 Partial Content", &CONTENT[start..])
+        }
+        None => MockResponse::new("200 OK", CONTENT),
+    });
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    std::fs::create_dir_all(&output_dir).unwrap();
+    std::fs::write(output_dir.join("data.tmp"), b"stale").unwrap();
+    let metadata = item_metadata(
+        "example",
+        &server.base,
+        vec![ArchiveFile {
+            md5: Some(format!("{:x}", md5::compute(CONTENT))),
+            ..archive_file("data.bin", CONTENT.len() as u64)
+        }],
+    );
+    let config = DownloadConfig {
+        verify_md5: false,
+        ..download_config(&output_dir)
+    };
+
+    let session = download(&temp_dir.path().join("sessions"), &metadata, &config).await;
+    assert_eq!(
+        session.file_status["data.bin"].status,
+        DownloadState::Completed
+    );
+    assert!(server.requests()[0].header("Range").is_none());
+    assert_eq!(std::fs::read(output_dir.join("data.bin")).unwrap(), CONTENT);
+}
+
+#[tokio::test]
+async fn test_changed_file_replaces_completed_copy_without_verification() {
+    let served: Arc<Mutex<&'static [u8]>> = Arc::new(Mutex::new(b"old revision"));
+    let body = served.clone();
+    let server = MockServer::start(move |_| MockResponse::new("200 OK", *body.lock().unwrap()));
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let sessions = temp_dir.path().join("sessions");
+    let config = DownloadConfig {
+        verify_md5: false,
+        ..download_config(&output_dir)
+    };
+    let old = item_metadata(
+        "example",
+        &server.base,
+        vec![archive_file("data.bin", b"old revision".len() as u64)],
+    );
+    download(&sessions, &old, &config).await;
+    assert_eq!(
+        std::fs::read(output_dir.join("data.bin")).unwrap(),
+        b"old revision"
+    );
+
+    *served.lock().unwrap() = CONTENT;
+    let new = ArchiveMetadata {
+        item_last_updated: 1,
+        ..item_metadata(
+            "example",
+            &server.base,
+            vec![archive_file("data.bin", CONTENT.len() as u64)],
+        )
+    };
+    let session = download(&sessions, &new, &config).await;
+    let status = &session.file_status["data.bin"];
+    assert_eq!(status.status, DownloadState::Completed);
+    assert!(!status.replace_local_copy);
+    assert_eq!(server.requests().len(), 2);
+    assert_eq!(std::fs::read(output_dir.join("data.bin")).unwrap(), CONTENT);
+
+    // Once replaced, the copy is current again
+    download(&sessions, &new, &config).await;
+    assert_eq!(server.requests().len(), 2);
+}